@@ -0,0 +1,286 @@
+use crate::commands::job;
+use crate::errors::Result;
+use crate::models::{
+    AttributeJoin, AttributeJoinReport, AttributeJoinRequest, AttributeJoinResult, DissolveRequest, ExportResult,
+    GeometryAttributesRequest, GeometryAttributesResult, GridRequest, GridResult, MergeRequest, MergeResult,
+    NearRequest, NearResult, OverlayRequest, RepairRequest, RepairResult, SamplePointsRequest, SamplePointsResult,
+    SpatialJoinRequest, SpatialJoinResult, SplitRequest, SplitResult, TopologyRequest, TopologyResult,
+    ValidationReport, ValidationRequest,
+};
+use crate::services::overlay_service::{self, OverlayMode};
+use crate::services::{
+    attribute_join_service, dissolve_service, export_service, generate_service, geometry_attribute_service,
+    join_service, merge_service, near_service, topology_service, validation_service, worker_pool,
+};
+use tauri::AppHandle;
+
+/// 空间连接：按空间关系将连接图层的属性附加到待连接图层并写入新图层，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_spatial_join(
+    app: AppHandle,
+    request: SpatialJoinRequest,
+    job_id: Option<String>,
+) -> Result<SpatialJoinResult> {
+    log::info!("空间连接: {} <- {} -> {}", request.target.path, request.join.path, request.output.path);
+    let job = job::start_job(&app, "spatial_join", job_id)?;
+    let task_job = job.clone();
+    let inputs = vec![request.target.path.clone(), request.join.path.clone()];
+    let result = run_with_inputs(request.output.path.clone(), inputs, move |_| {
+        join_service::spatial_join(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 检查属性连接的匹配情况（两侧未匹配的关键字、表格中重复的关键字），用于添加虚拟连接前预览
+#[tauri::command]
+pub async fn analysis_attribute_join_check(
+    path: String,
+    layer_index: Option<usize>,
+    join: AttributeJoin,
+) -> Result<AttributeJoinReport> {
+    log::info!("检查属性连接: {} ({}) <- {} ({})", path, join.layer_key, join.table_path, join.table_key);
+    worker_pool::read(path, move |path| {
+        attribute_join_service::check_join(path, layer_index.unwrap_or(0), &join)
+    }).await
+}
+
+/// 属性连接：按关键字段将表格字段连接到图层并写入新图层，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_attribute_join(
+    app: AppHandle,
+    request: AttributeJoinRequest,
+    job_id: Option<String>,
+) -> Result<AttributeJoinResult> {
+    log::info!("属性连接: {} <- {} -> {}", request.input.path, request.join.table_path, request.output.path);
+    let job = job::start_job(&app, "attribute_join", job_id)?;
+    let task_job = job.clone();
+    let inputs = vec![request.input.path.clone(), request.join.table_path.clone()];
+    let result = run_with_inputs(request.output.path.clone(), inputs, move |_| {
+        attribute_join_service::attribute_join(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 裁剪：保留输入图层在掩膜面内的部分，属性不变，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_clip(app: AppHandle, request: OverlayRequest, job_id: Option<String>) -> Result<ExportResult> {
+    run_overlay(app, request, OverlayMode::Clip, job_id).await
+}
+
+/// 擦除：去掉输入图层在掩膜面内的部分，属性不变，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_erase(app: AppHandle, request: OverlayRequest, job_id: Option<String>) -> Result<ExportResult> {
+    run_overlay(app, request, OverlayMode::Erase, job_id).await
+}
+
+async fn run_overlay(app: AppHandle, request: OverlayRequest, mode: OverlayMode, job_id: Option<String>) -> Result<ExportResult> {
+    log::info!("叠加分析 ({:?}): {} / {} -> {}", mode, request.input.path, request.mask.path, request.output.path);
+    let kind = match mode {
+        OverlayMode::Clip => "clip",
+        OverlayMode::Erase => "erase",
+    };
+    let job = job::start_job(&app, kind, job_id)?;
+    let task_job = job.clone();
+    let inputs = vec![request.input.path.clone(), request.mask.path.clone()];
+    let result = run_with_inputs(request.output.path.clone(), inputs, move |_| {
+        overlay_service::overlay(&request, mode, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 按掩膜面分割图层：每个掩膜面输出一个以命名字段值为文件名的文件，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_split(app: AppHandle, request: SplitRequest, job_id: Option<String>) -> Result<SplitResult> {
+    log::info!("分割图层: {} 按 {} -> {}", request.input.path, request.mask.path, request.output_dir);
+    let job = job::start_job(&app, "split", job_id)?;
+    let task_job = job.clone();
+    let inputs = vec![request.input.path.clone(), request.mask.path.clone()];
+    let result = run_with_inputs(request.output_dir.clone(), inputs, move |_| {
+        overlay_service::split(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 融合：按融合字段合并要素并汇总其他字段，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_dissolve(app: AppHandle, request: DissolveRequest, job_id: Option<String>) -> Result<ExportResult> {
+    log::info!("融合: {} -> {}", request.input.path, request.output.path);
+    let job = job::start_job(&app, "dissolve", job_id)?;
+    let task_job = job.clone();
+    let inputs = vec![request.input.path.clone()];
+    let result = run_with_inputs(request.output.path.clone(), inputs, move |_| {
+        dissolve_service::dissolve(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 合并：多个图层合并为一个输出，字段按名称合并并放宽类型，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_merge(app: AppHandle, request: MergeRequest, job_id: Option<String>) -> Result<MergeResult> {
+    log::info!("合并 {} 个图层 -> {}", request.inputs.len(), request.output.path);
+    let job = job::start_job(&app, "merge", job_id)?;
+    let task_job = job.clone();
+    let inputs = request.inputs.iter().map(|input| input.input.path.clone()).collect();
+    let result = run_with_inputs(request.output.path.clone(), inputs, move |_| {
+        merge_service::merge(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 检查图层几何（自相交、环方向、重复顶点、未闭合环等），进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_validate_geometries(
+    app: AppHandle,
+    request: ValidationRequest,
+    job_id: Option<String>,
+) -> Result<ValidationReport> {
+    log::info!("检查几何: {}", request.input.path);
+    let job = job::start_job(&app, "validate_geometries", job_id)?;
+    let task_job = job.clone();
+    let result = worker_pool::read(request.input.path.clone(), move |_| {
+        validation_service::validate_geometries(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 修复图层几何：指定输出时写入新图层，否则原位写回输入图层，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_repair_geometries(
+    app: AppHandle,
+    request: RepairRequest,
+    job_id: Option<String>,
+) -> Result<RepairResult> {
+    log::info!("修复几何: {} ({:?})", request.input.path, request.method);
+    let job = job::start_job(&app, "repair_geometries", job_id)?;
+    let task_job = job.clone();
+    let input_path = request.input.path.clone();
+    let output_path = request.output.as_ref().map(|output| output.path.clone());
+    let task = move |_: &str| validation_service::repair_geometries(&request, &task_job);
+    // 原位修改时独占输入数据集，否则读取输入、独占输出
+    let result = match output_path {
+        Some(output_path) => run_with_inputs(output_path, vec![input_path], task).await,
+        None => worker_pool::write(input_path, task).await,
+    };
+    job.finish(&result);
+    result
+}
+
+/// 拓扑检查：按规则检查图层并将错误几何写入错误图层，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_check_topology(
+    app: AppHandle,
+    request: TopologyRequest,
+    job_id: Option<String>,
+) -> Result<TopologyResult> {
+    log::info!("拓扑检查: {} 条规则 -> {}", request.rules.len(), request.output.path);
+    let job = job::start_job(&app, "check_topology", job_id)?;
+    let task_job = job.clone();
+    let inputs = topology_inputs(&request);
+    let result = run_with_inputs(request.output.path.clone(), inputs, move |_| {
+        topology_service::check_topology(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 计算几何属性（面积、周长、长度、中心点、顶点数、外包矩形）并写入字段，
+/// 指定输出时写入新图层，否则直接修改输入图层，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_add_geometry_attributes(
+    app: AppHandle,
+    request: GeometryAttributesRequest,
+    job_id: Option<String>,
+) -> Result<GeometryAttributesResult> {
+    log::info!("计算几何属性: {} ({:?}, {} 个字段)", request.input.path, request.method, request.fields.len());
+    let job = job::start_job(&app, "geometry_attributes", job_id)?;
+    let task_job = job.clone();
+    let input_path = request.input.path.clone();
+    let output_path = request.output.as_ref().map(|output| output.path.clone());
+    let task = move |_: &str| geometry_attribute_service::add_geometry_attributes(&request, &task_job);
+    // 原位修改时独占输入数据集，否则读取输入、独占输出
+    let result = match output_path {
+        Some(output_path) => run_with_inputs(output_path, vec![input_path], task).await,
+        None => worker_pool::write(input_path, task).await,
+    };
+    job.finish(&result);
+    result
+}
+
+/// 邻近分析：查找输入要素最近的 k 个目标要素，或输出起点-终点距离表，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_near(
+    app: AppHandle,
+    request: NearRequest,
+    job_id: Option<String>,
+) -> Result<NearResult> {
+    log::info!("邻近分析: {} -> {} ({:?})", request.input.path, request.near.path, request.mode);
+    let job = job::start_job(&app, "near", job_id)?;
+    let task_job = job.clone();
+    let inputs = vec![request.input.path.clone(), request.near.path.clone()];
+    let result = run_with_inputs(request.output.path.clone(), inputs, move |_| {
+        near_service::near(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 生成矩形或六边形格网，可统计每个格网内的点数，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_create_grid(
+    app: AppHandle,
+    request: GridRequest,
+    job_id: Option<String>,
+) -> Result<GridResult> {
+    log::info!("生成格网: {:?} {} -> {}", request.shape, request.cell_size, request.output.path);
+    let job = job::start_job(&app, "create_grid", job_id)?;
+    let task_job = job.clone();
+    let inputs = request.layer.iter().chain(&request.count_points).map(|input| input.path.clone()).collect();
+    let result = run_with_inputs(request.output.path.clone(), inputs, move |_| {
+        generate_service::create_grid(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 在面要素内生成随机或规则采样点，进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn analysis_sample_points(
+    app: AppHandle,
+    request: SamplePointsRequest,
+    job_id: Option<String>,
+) -> Result<SamplePointsResult> {
+    log::info!("生成采样点: {} ({:?}) -> {}", request.input.path, request.method, request.output.path);
+    let job = job::start_job(&app, "sample_points", job_id)?;
+    let task_job = job.clone();
+    let inputs = vec![request.input.path.clone()];
+    let result = run_with_inputs(request.output.path.clone(), inputs, move |_| {
+        generate_service::sample_points(&request, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 拓扑检查读取的全部图层
+fn topology_inputs(request: &TopologyRequest) -> Vec<String> {
+    request.rules.iter()
+        .flat_map(|rule| std::iter::once(&rule.layer).chain(&rule.cover_layer))
+        .map(|input| input.path.clone())
+        .collect()
+}
+
+/// 读取输入、写入输出的分析任务，输出不能与任何输入为同一数据源
+async fn run_with_inputs<F, T>(output: String, inputs: Vec<String>, task: F) -> Result<T>
+where
+    F: FnOnce(&str) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    export_service::check_output_path(&output, &inputs)?;
+    worker_pool::write_with_inputs(output, inputs, task).await
+}
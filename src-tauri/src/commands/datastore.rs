@@ -0,0 +1,107 @@
+use crate::errors::Result;
+use crate::models::{DataStoreInfo, DataStoreKind, DataStoreTable, LayerStyle, StoredLayerStyle, TableSchema};
+use crate::services::{datastore_service, worker_pool};
+
+/// 新建空的 GeoPackage 或 SpatiaLite 数据库
+#[tauri::command]
+pub async fn datastore_create(path: String, kind: DataStoreKind, overwrite: Option<bool>) -> Result<()> {
+    log::info!("新建数据库: {} ({:?})", path, kind);
+    worker_pool::write(path, move |path| {
+        datastore_service::create_datastore(path, kind, overwrite.unwrap_or(false))
+    }).await
+}
+
+/// 在数据库中新建表
+#[tauri::command]
+pub async fn datastore_create_table(path: String, schema: TableSchema) -> Result<DataStoreTable> {
+    log::info!("新建表: {} -> {}", schema.name, path);
+    worker_pool::write(path, move |path| datastore_service::create_table(path, &schema)).await
+}
+
+/// 列出数据库内容（含属性表）
+#[tauri::command]
+pub async fn datastore_list_contents(path: String) -> Result<DataStoreInfo> {
+    log::info!("读取数据库内容: {}", path);
+    worker_pool::read(path, datastore_service::list_contents).await
+}
+
+/// 将矢量图层复制到数据库，返回表名
+#[tauri::command]
+pub async fn datastore_import_layer(
+    source_path: String,
+    layer_index: Option<usize>,
+    store_path: String,
+    table_name: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<String> {
+    log::info!("导入图层到数据库: {} -> {}", source_path, store_path);
+    let inputs = vec![source_path.clone()];
+    worker_pool::write_with_inputs(store_path, inputs, move |store_path| {
+        datastore_service::import_layer(
+            &source_path,
+            layer_index,
+            store_path,
+            table_name.as_deref(),
+            overwrite.unwrap_or(false),
+        )
+    }).await
+}
+
+/// 将数据库中的表导出为文件
+#[tauri::command]
+pub async fn datastore_export_table(
+    store_path: String,
+    table_name: String,
+    output_path: String,
+    format: String,
+) -> Result<()> {
+    log::info!("导出数据库表: {}:{} -> {} ({})", store_path, table_name, output_path, format);
+    let inputs = vec![store_path.clone()];
+    worker_pool::write_with_inputs(output_path, inputs, move |output_path| {
+        datastore_service::export_table(&store_path, &table_name, output_path, &format)
+    }).await
+}
+
+/// 保存图层样式到 layer_styles
+#[tauri::command]
+pub async fn datastore_save_style(
+    path: String,
+    table_name: String,
+    style_name: String,
+    style: LayerStyle,
+    use_as_default: Option<bool>,
+    description: Option<String>,
+) -> Result<StoredLayerStyle> {
+    log::info!("保存样式: {} -> {}:{}", style_name, path, table_name);
+    worker_pool::write(path, move |path| {
+        datastore_service::save_layer_style(
+            path,
+            &table_name,
+            &style_name,
+            &style,
+            use_as_default.unwrap_or(true),
+            description.as_deref(),
+        )
+    }).await
+}
+
+/// 列出保存的图层样式
+#[tauri::command]
+pub async fn datastore_list_styles(path: String, table_name: Option<String>) -> Result<Vec<StoredLayerStyle>> {
+    log::info!("列出样式: {} (table={:?})", path, table_name);
+    worker_pool::read(path, move |path| datastore_service::list_layer_styles(path, table_name.as_deref())).await
+}
+
+/// 获取表的默认样式
+#[tauri::command]
+pub async fn datastore_get_default_style(path: String, table_name: String) -> Result<Option<StoredLayerStyle>> {
+    log::info!("读取默认样式: {}:{}", path, table_name);
+    worker_pool::read(path, move |path| datastore_service::get_default_style(path, &table_name)).await
+}
+
+/// 删除保存的样式
+#[tauri::command]
+pub async fn datastore_delete_style(path: String, style_id: u64) -> Result<()> {
+    log::info!("删除样式: {} (id={})", path, style_id);
+    worker_pool::write(path, move |path| datastore_service::delete_layer_style(path, style_id)).await
+}
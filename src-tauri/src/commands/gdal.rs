@@ -1,29 +1,51 @@
-use crate::errors::Result;
-use crate::models::{VectorInfo, MultiLayerVectorInfo};
+use crate::errors::{AppError, Result};
+use crate::models::{AttributeJoin, VectorInfo, MultiLayerVectorInfo, DimensionMode, GdbMetadata, ExportDriver, ExportOptions, ExportResult, BatchConvertRequest, BatchConvertReport, GeoJsonStreamEvent, GeoJsonStreamSummary, FieldStatistics};
+use crate::services::{attribute_join_service, export_service, gdb_service, selection_service, worker_pool};
 use crate::services::gdal_service;
+use crate::commands::job;
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Emitter};
 
 /// 使用GDAL打开矢量文件
 #[tauri::command]
 pub async fn gdal_open_vector(path: String) -> Result<VectorInfo> {
     log::info!("使用GDAL打开矢量文件: {}", path);
-    gdal_service::read_vector_info(&path).await
+    worker_pool::read(path, gdal_service::read_vector_info).await
 }
 
 /// 使用GDAL读取属性表
+/// decode_domains: 编码值显示为属性域描述（默认开启）
+/// joins: 图层的虚拟属性连接，连接字段附加在图层字段之后
 #[tauri::command]
 pub async fn gdal_get_attribute_table(
     path: String,
     offset: Option<usize>,
-    limit: Option<usize>
+    limit: Option<usize>,
+    decode_domains: Option<bool>,
+    joins: Option<Vec<AttributeJoin>>,
 ) -> Result<serde_json::Value> {
     log::info!("使用GDAL读取属性表: {} (offset={:?}, limit={:?})", path, offset, limit);
     
-    // 先获取总数
-    let total = gdal_service::get_feature_count(&path).await?;
-    log::info!("要素总数: {}", total);
-    
-    // 读取指定范围的要素（包含几何信息）
-    let features = gdal_service::read_vector_features_with_geometry(&path, offset, limit).await?;
+    let (total, fields, features) = worker_pool::read(path, move |path| {
+        // 先获取总数
+        let total = gdal_service::get_feature_count(path)?;
+        log::info!("要素总数: {}", total);
+        
+        // 字段定义（类型、别名、宽度等，用于显示和校验）
+        let mut fields = gdal_service::read_layer_fields(path, None)?;
+        let joiners = attribute_join_service::load_joiners(path, 0, joins.as_deref().unwrap_or_default())?;
+        fields.extend(joiners.iter().flat_map(|joiner| joiner.fields().iter().cloned()));
+        
+        // 读取指定范围的要素（包含几何信息）
+        let features = gdal_service::read_vector_features_with_geometry(
+            path,
+            offset,
+            limit,
+            decode_domains.unwrap_or(true),
+            &joiners,
+        )?;
+        Ok((total, fields, features))
+    }).await?;
     
     // 转换为属性表格式
     let rows: Vec<serde_json::Value> = features
@@ -42,26 +64,46 @@ pub async fn gdal_get_attribute_table(
     
     Ok(serde_json::json!({
         "features": rows,
+        "fields": fields,
         "total": total
     }))
 }
 
+/// 统计字段值，指定 selection（图层ID）时只统计该图层选择集中的要素
+#[tauri::command]
+pub async fn gdal_field_statistics(
+    path: String,
+    layer_index: Option<usize>,
+    field: String,
+    selection: Option<String>,
+) -> Result<FieldStatistics> {
+    log::info!("统计字段: {} {} (选择集: {:?})", path, field, selection);
+    let fids = selection.as_deref()
+        .map(|layer_id| selection_service::selection_fids(layer_id, &path, layer_index.unwrap_or(0)))
+        .transpose()?;
+    worker_pool::read(path, move |path| {
+        gdal_service::field_statistics(path, layer_index.unwrap_or(0), &field, fids.as_deref())
+    }).await
+}
+
 /// 使用GDAL读取GeoJSON
 #[tauri::command]
-pub async fn gdal_get_geojson(path: String) -> Result<serde_json::Value> {
+pub async fn gdal_get_geojson(path: String, dimension: Option<DimensionMode>) -> Result<serde_json::Value> {
     log::info!("使用GDAL读取GeoJSON: {}", path);
-    gdal_service::read_vector_as_geojson(&path).await
+    worker_pool::read(path, move |path| {
+        gdal_service::read_vector_as_geojson(path, dimension.unwrap_or_default())
+    }).await
 }
 
-/// 坐标转换
+/// 坐标转换（支持 [x, y]、[x, y, z]、[x, y, z, m]）
 #[tauri::command]
 pub async fn gdal_transform_coordinates(
     from_srs: String,
     to_srs: String,
-    coordinates: Vec<(f64, f64)>
-) -> Result<Vec<(f64, f64)>> {
+    coordinates: Vec<Vec<f64>>
+) -> Result<Vec<Vec<f64>>> {
     log::info!("坐标转换: {} -> {}", from_srs, to_srs);
-    gdal_service::transform_coordinates(&from_srs, &to_srs, coordinates).await
+    worker_pool::run(move || gdal_service::transform_coordinates(&from_srs, &to_srs, coordinates)).await
 }
 
 /// 获取支持的格式
@@ -115,32 +157,161 @@ pub fn gdal_diagnose() -> Result<serde_json::Value> {
     }))
 }
 
-/// 导出矢量数据
+/// 导出矢量数据（支持任意可写入的GDAL矢量驱动）
+/// 导出作为任务运行：通过 job-progress 事件推送进度，可用 job_cancel 取消
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn gdal_export_vector(
+    app: AppHandle,
     input_path: String,
     output_path: String,
     format: String,
     layer_index: Option<usize>,
-) -> Result<()> {
+    dimension: Option<DimensionMode>,
+    options: Option<ExportOptions>,
+    job_id: Option<String>,
+) -> Result<ExportResult> {
     if let Some(idx) = layer_index {
         log::info!("导出矢量数据 (图层索引: {}): {} -> {} (格式: {})", idx, input_path, output_path, format);
     } else {
         log::info!("导出矢量数据: {} -> {} (格式: {})", input_path, output_path, format);
     }
-    gdal_service::export_vector(&input_path, &output_path, &format, layer_index).await
+    let mut options = options.unwrap_or_default();
+    if let Some(dimension) = dimension {
+        options.dimension = dimension;
+    }
+    let job = job::start_job(&app, "export", job_id)?;
+    let task_job = job.clone();
+    let inputs = vec![input_path.clone()];
+    let result = worker_pool::write_with_inputs(output_path, inputs, move |output_path| {
+        export_service::export_vector(&input_path, output_path, &format, layer_index, &options, &task_job)
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 批量转换文件夹或多图层数据源（如 GDB 全部要素类转 Shapefile、多个 KML 合并到一个 GPKG）
+/// 每完成一个图层通过 batch-progress 事件推送结果，总进度通过 job-progress 事件推送
+#[tauri::command]
+pub async fn gdal_batch_convert(
+    app: AppHandle,
+    request: BatchConvertRequest,
+    job_id: Option<String>,
+) -> Result<BatchConvertReport> {
+    log::info!("批量转换: {} -> {} (格式: {})", request.source, request.output, request.format);
+    let job = job::start_job(&app, "batch_convert", job_id)?;
+    let task_job = job.clone();
+    let inputs = vec![request.source.clone()];
+    let result = worker_pool::write_with_inputs(request.output.clone(), inputs, move |_| {
+        export_service::batch_convert(&request, &task_job, move |progress| {
+            let _ = app.emit("batch-progress", progress);
+        })
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 获取可用于导出的矢量驱动
+#[tauri::command]
+pub fn gdal_get_export_drivers() -> Result<Vec<ExportDriver>> {
+    Ok(export_service::get_export_drivers())
 }
 
 /// 读取多图层矢量文件信息（用于KML、GDB等格式）
 #[tauri::command]
 pub async fn gdal_open_multi_layer_vector(path: String) -> Result<MultiLayerVectorInfo> {
     log::info!("读取多图层矢量文件: {}", path);
-    gdal_service::read_multi_layer_vector_info(&path).await
+    worker_pool::read(path, gdal_service::read_multi_layer_vector_info).await
 }
 
 /// 读取指定图层的GeoJSON数据
+/// decode_domains: 编码值显示为属性域描述（用于属性表，默认保留原始编码）
+/// joins: 图层的虚拟属性连接，连接字段附加到要素属性
+/// 读取作为任务运行：通过 job-progress 事件推送进度，可用 job_cancel 取消
 #[tauri::command]
-pub async fn gdal_get_layer_geojson(path: String, layer_index: usize) -> Result<serde_json::Value> {
+pub async fn gdal_get_layer_geojson(
+    app: AppHandle,
+    path: String,
+    layer_index: usize,
+    dimension: Option<DimensionMode>,
+    decode_domains: Option<bool>,
+    joins: Option<Vec<AttributeJoin>>,
+    job_id: Option<String>,
+) -> Result<serde_json::Value> {
     log::info!("读取图层 {} 的GeoJSON: {}", layer_index, path);
-    gdal_service::read_layer_as_geojson(&path, layer_index).await
+    let job = job::start_job(&app, "read_layer", job_id)?;
+    let task_job = job.clone();
+    let result = worker_pool::read(path, move |path| {
+        let joiners = attribute_join_service::load_joiners(path, layer_index, joins.as_deref().unwrap_or_default())?;
+        gdal_service::read_layer_as_geojson(
+            path,
+            layer_index,
+            dimension.unwrap_or_default(),
+            decode_domains.unwrap_or(false),
+            &joiners,
+            &task_job,
+        )
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 分批读取的默认批次大小（要素数）
+const DEFAULT_STREAM_BATCH: usize = 5_000;
+
+/// 分批读取指定图层的GeoJSON要素，通过 on_event 通道依次推送 batch 消息，最后推送 finished 消息
+/// 前端可边接收边渲染，前后端内存只保留一批要素；读取作为任务运行，可用 job_cancel 取消
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn gdal_stream_layer_geojson(
+    app: AppHandle,
+    path: String,
+    layer_index: Option<usize>,
+    dimension: Option<DimensionMode>,
+    decode_domains: Option<bool>,
+    joins: Option<Vec<AttributeJoin>>,
+    batch_size: Option<usize>,
+    on_event: Channel<GeoJsonStreamEvent>,
+    job_id: Option<String>,
+) -> Result<GeoJsonStreamSummary> {
+    let layer_index = layer_index.unwrap_or(0);
+    let batch_size = batch_size.unwrap_or(DEFAULT_STREAM_BATCH).clamp(100, 100_000);
+    log::info!("分批读取图层 {} 的GeoJSON: {} (每批 {} 个要素)", layer_index, path, batch_size);
+
+    let job = job::start_job(&app, "read_layer", job_id)?;
+    let task_job = job.clone();
+    let result = worker_pool::read(path, move |path| {
+        let send = |event: GeoJsonStreamEvent| {
+            on_event.send(event)
+                .map_err(|e| AppError::Unknown(format!("推送GeoJSON数据失败: {}", e)))
+        };
+
+        let joiners = attribute_join_service::load_joiners(path, layer_index, joins.as_deref().unwrap_or_default())?;
+        let mut batches = 0;
+        let features = gdal_service::stream_layer_as_geojson(
+            path,
+            layer_index,
+            dimension.unwrap_or_default(),
+            decode_domains.unwrap_or(false),
+            &joiners,
+            batch_size,
+            &task_job,
+            |features, read, total| {
+                send(GeoJsonStreamEvent::Batch { index: batches, features, read, total })?;
+                batches += 1;
+                Ok(())
+            },
+        )?;
+        send(GeoJsonStreamEvent::Finished { features, batches })?;
+        Ok(GeoJsonStreamSummary { features, batches })
+    }).await;
+    job.finish(&result);
+    result
+}
+
+/// 读取文件地理数据库元数据（要素集、属性域、子类型、别名、关系类）
+#[tauri::command]
+pub async fn gdal_get_gdb_metadata(path: String) -> Result<GdbMetadata> {
+    log::info!("读取GDB元数据: {}", path);
+    worker_pool::read(path, gdb_service::read_gdb_metadata).await
 }
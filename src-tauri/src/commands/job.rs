@@ -0,0 +1,38 @@
+use crate::errors::Result;
+use crate::models::{JobInfo, JobProgress, WorkerPoolStatus};
+use crate::services::job_service::{self, JobContext};
+use crate::services::worker_pool;
+use tauri::{AppHandle, Emitter};
+
+/// 登记长时间运行的任务，进度和最终状态通过 job-progress 事件推送
+pub(crate) fn start_job(app: &AppHandle, kind: &str, job_id: Option<String>) -> Result<JobContext> {
+    let app = app.clone();
+    job_service::start(kind, job_id, Box::new(move |progress: JobProgress| {
+        let _ = app.emit("job-progress", progress);
+    }))
+}
+
+/// 取消任务（任务停止后删除不完整的输出）
+#[tauri::command]
+pub fn job_cancel(job_id: String) -> Result<bool> {
+    log::info!("请求取消任务: {}", job_id);
+    Ok(job_service::cancel(&job_id))
+}
+
+/// 列出正在运行的任务
+#[tauri::command]
+pub fn job_list() -> Result<Vec<JobInfo>> {
+    Ok(job_service::list_jobs())
+}
+
+/// 获取GDAL工作线程池状态（并发数、执行中和排队的任务数）
+#[tauri::command]
+pub fn worker_get_status() -> Result<WorkerPoolStatus> {
+    Ok(worker_pool::status())
+}
+
+/// 设置GDAL工作线程池并发数
+#[tauri::command]
+pub fn worker_set_concurrency(concurrency: usize) -> Result<WorkerPoolStatus> {
+    worker_pool::set_concurrency(concurrency)
+}
@@ -1,6 +1,13 @@
 pub mod layer;
 pub mod project;
 pub mod gdal;
+pub mod postgis;
+pub mod datastore;
+pub mod job;
+pub mod tiles;
+pub mod spatial;
+pub mod selection;
+pub mod analysis;
 pub mod file;
 pub mod window;
 pub mod fs;
@@ -0,0 +1,91 @@
+use crate::errors::Result;
+use crate::models::{EditResult, FeatureEdits, PostgisConnection, PostgisTableInfo};
+use crate::services::{postgis_service, spatial_index_service, tile_service, worker_pool};
+
+/// 列出已保存的PostGIS连接
+#[tauri::command]
+pub async fn postgis_list_connections() -> Result<Vec<PostgisConnection>> {
+    log::info!("列出PostGIS连接");
+    postgis_service::list_connections().await
+}
+
+/// 保存PostGIS连接（新建或更新）
+#[tauri::command]
+pub async fn postgis_save_connection(connection: PostgisConnection) -> Result<PostgisConnection> {
+    log::info!("保存PostGIS连接: {}", connection.name);
+    postgis_service::save_connection(connection).await
+}
+
+/// 删除PostGIS连接
+#[tauri::command]
+pub async fn postgis_delete_connection(connection_id: String) -> Result<()> {
+    log::info!("删除PostGIS连接: {}", connection_id);
+    postgis_service::delete_connection(&connection_id).await
+}
+
+/// 测试PostGIS连接，返回数据库版本
+#[tauri::command]
+pub async fn postgis_test_connection(connection: PostgisConnection) -> Result<String> {
+    log::info!("测试PostGIS连接: {}@{}:{}", connection.username, connection.host, connection.port);
+    worker_pool::run(move || postgis_service::test_connection(&connection)).await
+}
+
+/// 列出数据库模式
+#[tauri::command]
+pub async fn postgis_list_schemas(connection_id: String) -> Result<Vec<String>> {
+    log::info!("列出PostGIS模式: {}", connection_id);
+    worker_pool::run(move || postgis_service::list_schemas(&connection_id)).await
+}
+
+/// 列出空间表和视图
+#[tauri::command]
+pub async fn postgis_list_tables(
+    connection_id: String,
+    schema: Option<String>,
+) -> Result<Vec<PostgisTableInfo>> {
+    log::info!("列出PostGIS空间表: {} (schema={:?})", connection_id, schema);
+    worker_pool::run(move || postgis_service::list_tables(&connection_id, schema.as_deref())).await
+}
+
+/// 将要素编辑写回PostGIS表
+#[tauri::command]
+pub async fn postgis_apply_edits(
+    connection_id: String,
+    schema: String,
+    table: String,
+    edits: FeatureEdits,
+) -> Result<EditResult> {
+    log::info!(
+        "写入PostGIS编辑: {}.{} (新增={}, 修改={}, 删除={})",
+        schema, table, edits.inserts.len(), edits.updates.len(), edits.deletes.len()
+    );
+    let prefix = postgis_service::layer_path(&connection_id, &schema, &table);
+    let result = worker_pool::run(move || postgis_service::apply_edits(&connection_id, &schema, &table, &edits)).await;
+    // 数据库表没有修改时间可比较，编辑后主动丢弃空间索引和瓦片缓存
+    spatial_index_service::invalidate_prefix(&prefix);
+    tile_service::clear_prefix_cache(&prefix);
+    result
+}
+
+/// 将矢量图层导出到PostGIS，返回新表的图层引用
+#[tauri::command]
+pub async fn postgis_export_layer(
+    input_path: String,
+    layer_index: Option<usize>,
+    connection_id: String,
+    schema: String,
+    table: String,
+    overwrite: Option<bool>,
+) -> Result<String> {
+    log::info!("导出到PostGIS: {} -> {}.{}", input_path, schema, table);
+    worker_pool::read(input_path, move |input_path| {
+        postgis_service::export_layer(
+            input_path,
+            layer_index,
+            &connection_id,
+            &schema,
+            &table,
+            overwrite.unwrap_or(false),
+        )
+    }).await
+}
@@ -1,21 +1,27 @@
 use crate::errors::Result;
 use crate::models::Project;
-use crate::services::project_service;
+use crate::services::{project_service, selection_service};
 
 #[tauri::command]
 pub async fn new_project(name: String) -> Result<Project> {
     log::info!("Creating new project: {}", name);
-    project_service::create_project(name).await
+    let project = project_service::create_project(name).await?;
+    // 新项目不继承上一个项目的选择集
+    selection_service::clear(None);
+    Ok(project)
 }
 
 #[tauri::command]
 pub async fn open_project(path: String) -> Result<Project> {
     log::info!("Opening project: {}", path);
-    project_service::load_project(&path).await
+    let project = project_service::load_project(&path).await?;
+    selection_service::restore(&project.selections);
+    Ok(project)
 }
 
 #[tauri::command]
-pub async fn save_project(project: Project, path: Option<String>) -> Result<String> {
+pub async fn save_project(mut project: Project, path: Option<String>) -> Result<String> {
     log::info!("Saving project: {}", project.name);
+    project.selections = selection_service::snapshot();
     project_service::save_project(&project, path.as_deref()).await
 }
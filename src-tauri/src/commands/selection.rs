@@ -0,0 +1,53 @@
+use crate::errors::Result;
+use crate::models::{SelectionMode, SelectionQuery, SelectionSummary};
+use crate::services::{selection_service, worker_pool};
+use std::collections::BTreeSet;
+
+/// 按条件（矩形、多边形、属性条件、与参照图层的空间关系）选择要素
+/// mode: 新建（默认）、添加、移除、相交
+#[tauri::command]
+pub async fn selection_select(
+    layer_id: String,
+    path: String,
+    layer_index: Option<usize>,
+    query: SelectionQuery,
+    mode: Option<SelectionMode>,
+) -> Result<SelectionSummary> {
+    log::info!("选择要素: 图层 {} ({}) {:?}", layer_id, path, query);
+    worker_pool::read(path, move |path| {
+        selection_service::select(&layer_id, path, layer_index.unwrap_or(0), &query, mode.unwrap_or_default())
+    }).await
+}
+
+/// 用指定要素更新选择集（如地图点选、属性表勾选）
+#[tauri::command]
+pub fn selection_set_fids(
+    layer_id: String,
+    path: String,
+    layer_index: Option<usize>,
+    fids: Vec<u64>,
+    mode: Option<SelectionMode>,
+) -> Result<SelectionSummary> {
+    let fids: BTreeSet<u64> = fids.into_iter().collect();
+    Ok(selection_service::apply(&layer_id, &path, layer_index.unwrap_or(0), fids, mode.unwrap_or_default()))
+}
+
+/// 获取图层选择集中的要素 FID
+#[tauri::command]
+pub fn selection_get_fids(layer_id: String) -> Result<Vec<u64>> {
+    selection_service::selected_fids(&layer_id)
+}
+
+/// 列出所有非空选择集
+#[tauri::command]
+pub fn selection_list() -> Result<Vec<SelectionSummary>> {
+    Ok(selection_service::list())
+}
+
+/// 清除选择集，不指定图层时清除全部
+#[tauri::command]
+pub fn selection_clear(layer_id: Option<String>) -> Result<()> {
+    log::info!("清除选择集: {:?}", layer_id);
+    selection_service::clear(layer_id.as_deref());
+    Ok(())
+}
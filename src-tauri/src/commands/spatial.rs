@@ -0,0 +1,68 @@
+use crate::errors::Result;
+use crate::models::{Extent, IdentifyLayerResult, IdentifyRequest, MeasureRequest, MeasureResult, SpatialIndexInfo};
+use crate::services::{identify_service, measure_service, spatial_index_service, worker_pool};
+use std::sync::Arc;
+
+/// 构建图层空间索引（已构建且数据源未修改时直接返回）
+/// 索引在首次查询时也会自动构建，打开大图层后可提前调用以免首次识别等待
+#[tauri::command]
+pub async fn spatial_index_build(path: String, layer_index: Option<usize>) -> Result<SpatialIndexInfo> {
+    log::info!("构建空间索引: {} (图层 {:?})", path, layer_index);
+    worker_pool::read(path, move |path| {
+        spatial_index_service::build_index(path, layer_index.unwrap_or(0))
+    }).await
+}
+
+/// 丢弃空间索引（图层移除或外部修改数据后调用），不指定图层时丢弃数据源的全部图层索引
+#[tauri::command]
+pub fn spatial_index_invalidate(path: String, layer_index: Option<usize>) -> Result<()> {
+    log::info!("丢弃空间索引: {} (图层 {:?})", path, layer_index);
+    spatial_index_service::invalidate(&path, layer_index);
+    Ok(())
+}
+
+/// 按空间范围（经纬度）查询要素 FID
+/// exact: 精确判断几何相交（默认），为 false 时只比较外包矩形
+#[tauri::command]
+pub async fn spatial_query_extent(
+    path: String,
+    layer_index: Option<usize>,
+    extent: Extent,
+    exact: Option<bool>,
+) -> Result<Vec<u64>> {
+    worker_pool::read(path, move |path| {
+        spatial_index_service::query_extent(path, layer_index.unwrap_or(0), &extent, exact.unwrap_or(true))
+    }).await
+}
+
+/// 要素识别：查询多个图层在地图坐标处（含像素容差）的要素及全部属性
+/// 结果按请求中的图层顺序返回，单个图层失败时在其结果中记录错误
+#[tauri::command]
+pub async fn spatial_identify(request: IdentifyRequest) -> Result<Vec<IdentifyLayerResult>> {
+    log::info!("要素识别: ({}, {}) {} 个图层", request.x, request.y, request.layers.len());
+    let request = Arc::new(request);
+    let mut results = Vec::with_capacity(request.layers.len());
+    for (i, layer) in request.layers.iter().enumerate() {
+        let task_request = request.clone();
+        let result = worker_pool::read(layer.path.clone(), move |_| {
+            identify_service::identify_layer(&task_request.layers[i], &task_request)
+        }).await;
+        results.push(result.unwrap_or_else(|e| {
+            log::warn!("图层识别失败 {}: {}", layer.path, e);
+            IdentifyLayerResult {
+                layer_id: layer.layer_id.clone(),
+                layer_name: String::new(),
+                features: Vec::new(),
+                truncated: false,
+                error: Some(e.to_string()),
+            }
+        }));
+    }
+    Ok(results)
+}
+
+/// 量算几何的长度、面积和周长：椭球面（CGCS2000/WGS84）测地线量算，可选投影平面量算，按指定单位返回
+#[tauri::command]
+pub async fn spatial_measure(request: MeasureRequest) -> Result<MeasureResult> {
+    worker_pool::run(move || measure_service::measure(&request)).await
+}
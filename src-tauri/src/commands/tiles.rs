@@ -0,0 +1,92 @@
+use crate::errors::{AppError, Result};
+use crate::models::{TileSourceInfo, TileSourceOptions};
+use crate::services::{tile_service, worker_pool};
+use std::sync::Arc;
+use tauri::http::{header, Response, StatusCode};
+
+/// 注册矢量瓦片源，返回可供 OpenLayers MVT 源使用的 URL 模板
+#[tauri::command]
+pub async fn tiles_register_source(
+    path: String,
+    layer_index: Option<usize>,
+    options: Option<TileSourceOptions>,
+) -> Result<TileSourceInfo> {
+    log::info!("注册矢量瓦片源: {} (图层 {:?})", path, layer_index);
+    worker_pool::read(path, move |path| {
+        tile_service::register_source(path, layer_index.unwrap_or(0), options.unwrap_or_default())
+    }).await
+}
+
+/// 注销矢量瓦片源
+#[tauri::command]
+pub fn tiles_unregister_source(source_id: String) -> Result<()> {
+    tile_service::unregister_source(&source_id);
+    Ok(())
+}
+
+/// 清除瓦片缓存（数据编辑后调用），不指定瓦片源时清除全部
+#[tauri::command]
+pub fn tiles_clear_cache(source_id: Option<String>) -> Result<()> {
+    log::info!("清除瓦片缓存: {:?}", source_id);
+    tile_service::clear_cache(source_id.as_deref());
+    Ok(())
+}
+
+/// 列出已注册的瓦片源
+#[tauri::command]
+pub fn tiles_list_sources() -> Result<Vec<TileSourceInfo>> {
+    Ok(tile_service::list_sources())
+}
+
+/// 解析瓦片请求路径 /{瓦片源ID}/{z}/{x}/{y}.pbf
+fn parse_tile_path(path: &str) -> Result<(String, u8, u32, u32)> {
+    let invalid = || AppError::InvalidFormat(format!("瓦片路径无效: {}", path));
+    let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let [source_id, z, x, y] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    let y = y.strip_suffix(".pbf").or_else(|| y.strip_suffix(".mvt")).unwrap_or(y);
+    Ok((
+        source_id.to_string(),
+        z.parse().map_err(|_| invalid())?,
+        x.parse().map_err(|_| invalid())?,
+        y.parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// 读取或生成瓦片
+async fn load_tile(path: &str) -> Result<Arc<Vec<u8>>> {
+    let (source_id, z, x, y) = parse_tile_path(path)?;
+    let source = tile_service::get_source(&source_id)?;
+    if let Some(tile) = tile_service::cached_tile(&source, z, x, y) {
+        return Ok(tile);
+    }
+
+    let generation = source.generation();
+    let task_source = source.clone();
+    let tile = Arc::new(worker_pool::read(source.info.path.clone(), move |_| {
+        tile_service::render_tile(&task_source, z, x, y)
+    }).await?);
+    tile_service::store_tile(&source, z, x, y, tile.clone(), generation);
+    Ok(tile)
+}
+
+/// 处理 mvt:// 协议请求
+pub async fn tile_response(path: &str) -> Response<Vec<u8>> {
+    let (status, body, content_type) = match load_tile(path).await {
+        Ok(tile) => (StatusCode::OK, tile.as_ref().clone(), "application/vnd.mapbox-vector-tile"),
+        Err(AppError::FileNotFound(message)) => (StatusCode::NOT_FOUND, message.into_bytes(), "text/plain"),
+        Err(AppError::InvalidFormat(message)) => (StatusCode::BAD_REQUEST, message.into_bytes(), "text/plain"),
+        Err(e) => {
+            log::error!("生成瓦片失败 {}: {}", path, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string().into_bytes(), "text/plain")
+        }
+    };
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(body)
+        .unwrap_or_default()
+}
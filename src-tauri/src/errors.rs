@@ -24,6 +24,9 @@ pub enum AppError {
     #[error("Database error: {0}")]
     DatabaseError(String),
     
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+    
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -1 +1,376 @@
-// Geometry operations will be implemented here
+use crate::errors::{AppError, Result};
+use crate::models::{CoordinateDimension, DimensionMode};
+use gdal::vector::{Geometry, OGRwkbGeometryType};
+
+/// 获取几何对象的坐标维度
+pub fn coordinate_dimension(geom: &Geometry) -> CoordinateDimension {
+    let (has_z, has_m) = unsafe {
+        let h = geom.c_geometry();
+        (gdal_sys::OGR_G_Is3D(h) != 0, gdal_sys::OGR_G_IsMeasured(h) != 0)
+    };
+    CoordinateDimension::from_flags(has_z, has_m)
+}
+
+/// 从几何类型（如图层声明的类型）推断坐标维度
+pub fn dimension_of_type(geom_type: OGRwkbGeometryType::Type) -> CoordinateDimension {
+    let (has_z, has_m) = unsafe {
+        (gdal_sys::OGR_GT_HasZ(geom_type) != 0, gdal_sys::OGR_GT_HasM(geom_type) != 0)
+    };
+    CoordinateDimension::from_flags(has_z, has_m)
+}
+
+/// 按指定方式处理几何的Z值/M值
+pub fn apply_dimension_mode(geom: &mut Geometry, mode: DimensionMode) {
+    match mode {
+        DimensionMode::Preserve => {}
+        DimensionMode::DropM => unsafe {
+            gdal_sys::OGR_G_SetMeasured(geom.c_geometry(), 0);
+        },
+        DimensionMode::Force2D => geom.flatten_to_2d(),
+    }
+}
+
+/// 按指定方式处理几何类型的Z值/M值（用于创建导出图层）
+pub fn apply_dimension_mode_to_type(
+    geom_type: OGRwkbGeometryType::Type,
+    mode: DimensionMode,
+) -> OGRwkbGeometryType::Type {
+    unsafe {
+        match mode {
+            DimensionMode::Preserve => geom_type,
+            DimensionMode::DropM => {
+                gdal_sys::OGR_GT_SetModifier(geom_type, gdal_sys::OGR_GT_HasZ(geom_type), 0)
+            }
+            DimensionMode::Force2D => gdal_sys::OGR_GT_Flatten(geom_type),
+        }
+    }
+}
+
+/// 单部件几何类型对应的多部件类型（保留Z/M），其他类型保持不变
+pub fn multi_geometry_type(geom_type: OGRwkbGeometryType::Type) -> OGRwkbGeometryType::Type {
+    match unsafe { gdal_sys::OGR_GT_Flatten(geom_type) } {
+        OGRwkbGeometryType::wkbPoint | OGRwkbGeometryType::wkbLineString | OGRwkbGeometryType::wkbPolygon => unsafe {
+            gdal_sys::OGR_GT_GetCollection(geom_type)
+        },
+        _ => geom_type,
+    }
+}
+
+/// 几何能否写入声明为 layer_type 的图层而不丢失部件：多部件和未声明类型的图层总是可以，
+/// 单部件图层只接受同类单部件几何或只有一个同类部件的集合
+pub fn fits_layer_type(geom: &Geometry, layer_type: OGRwkbGeometryType::Type) -> bool {
+    let flatten = |ty| unsafe { gdal_sys::OGR_GT_Flatten(ty) };
+    let layer_flat = flatten(layer_type);
+    if !matches!(
+        layer_flat,
+        OGRwkbGeometryType::wkbPoint | OGRwkbGeometryType::wkbLineString | OGRwkbGeometryType::wkbPolygon
+    ) {
+        return true;
+    }
+    let geom_flat = flatten(geom.geometry_type());
+    if geom_flat == layer_flat {
+        return true;
+    }
+    let is_collection = unsafe {
+        gdal_sys::OGR_GT_IsSubClassOf(geom_flat, OGRwkbGeometryType::wkbGeometryCollection) != 0
+    };
+    is_collection && geom.geometry_count() == 1 && flatten(geom.get_geometry(0).geometry_type()) == layer_flat
+}
+
+/// 将几何转换为指定类型后设置为要素几何（如单部件提升为多部件）
+pub fn set_feature_geometry_as(
+    feature: &gdal::vector::Feature,
+    geom: Geometry,
+    geom_type: OGRwkbGeometryType::Type,
+) -> Result<()> {
+    unsafe {
+        let forced = gdal_sys::OGR_G_ForceTo(geom.into_c_geometry(), geom_type, std::ptr::null_mut());
+        if gdal_sys::OGR_F_SetGeometryDirectly(feature.c_feature(), forced) != gdal_sys::OGRErr::OGRERR_NONE {
+            return Err(AppError::InvalidFormat("设置要素几何失败".to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// 曲线几何（圆弧、曲线多边形等）转为线性几何，线性几何原样返回
+pub fn to_linear(geom: Geometry) -> Result<Geometry> {
+    let ty = geom.geometry_type();
+    if unsafe { gdal_sys::OGR_GT_IsNonLinear(ty) } == 0 {
+        return Ok(geom);
+    }
+    let wkb = unsafe {
+        let linear = gdal_sys::OGR_G_ForceTo(geom.into_c_geometry(), gdal_sys::OGR_GT_GetLinear(ty), std::ptr::null_mut());
+        let mut wkb = vec![0u8; gdal_sys::OGR_G_WkbSize(linear).max(0) as usize];
+        let err = gdal_sys::OGR_G_ExportToWkb(linear, gdal_sys::OGRwkbByteOrder::wkbNDR, wkb.as_mut_ptr());
+        gdal_sys::OGR_G_DestroyGeometry(linear);
+        if err != gdal_sys::OGRErr::OGRERR_NONE {
+            return Err(AppError::InvalidFormat("曲线几何转换失败".to_string()));
+        }
+        wkb
+    };
+    Geometry::from_wkb(&wkb).map_err(|e| AppError::InvalidFormat(format!("曲线几何转换失败: {}", e)))
+}
+
+/// 接管 OGR 新建的几何句柄（经 WKB 转为 Geometry 后销毁原句柄），空句柄返回 None
+unsafe fn take_c_geometry(handle: gdal_sys::OGRGeometryH) -> Result<Option<Geometry>> {
+    if handle.is_null() {
+        return Ok(None);
+    }
+    let mut wkb = vec![0u8; gdal_sys::OGR_G_WkbSize(handle).max(0) as usize];
+    let err = gdal_sys::OGR_G_ExportToIsoWkb(handle, gdal_sys::OGRwkbByteOrder::wkbNDR, wkb.as_mut_ptr());
+    gdal_sys::OGR_G_DestroyGeometry(handle);
+    if err != gdal_sys::OGRErr::OGRERR_NONE {
+        return Err(AppError::InvalidFormat("几何运算结果转换失败".to_string()));
+    }
+    Geometry::from_wkb(&wkb)
+        .map(Some)
+        .map_err(|e| AppError::InvalidFormat(format!("几何运算结果转换失败: {}", e)))
+}
+
+/// 几何的拓扑维度：点 0、线 1、面 2
+pub fn topological_dimension(geom: &Geometry) -> i32 {
+    unsafe { gdal_sys::OGR_G_GetDimension(geom.c_geometry()) }
+}
+
+/// 收集几何中指定拓扑维度的非空单部件几何（展开多部件几何和几何集合）
+pub fn collect_parts(geom: &Geometry, dimension: i32, parts: &mut Vec<Geometry>) {
+    let flat_type = unsafe { gdal_sys::OGR_GT_Flatten(geom.geometry_type()) };
+    if unsafe { gdal_sys::OGR_GT_IsSubClassOf(flat_type, OGRwkbGeometryType::wkbGeometryCollection) } != 0 {
+        for i in 0..geom.geometry_count() {
+            collect_parts(&geom.get_geometry(i), dimension, parts);
+        }
+    } else if !geom.is_empty() && topological_dimension(geom) == dimension {
+        parts.push(geom.clone());
+    }
+}
+
+/// 由单部件几何组成多部件几何（点 → MultiPoint、线 → MultiLineString、面 → MultiPolygon）
+fn multi_from_parts(parts: Vec<Geometry>, dimension: i32) -> Result<Option<Geometry>> {
+    if parts.is_empty() {
+        return Ok(None);
+    }
+    let multi_type = match dimension {
+        0 => OGRwkbGeometryType::wkbMultiPoint,
+        1 => OGRwkbGeometryType::wkbMultiLineString,
+        _ => OGRwkbGeometryType::wkbMultiPolygon,
+    };
+    let mut multi = Geometry::empty(multi_type)
+        .map_err(|e| AppError::InvalidFormat(format!("创建多部件几何失败: {}", e)))?;
+    for part in parts {
+        multi.add_geometry(part)
+            .map_err(|e| AppError::InvalidFormat(format!("创建多部件几何失败: {}", e)))?;
+    }
+    Ok(Some(multi))
+}
+
+/// 叠加运算结果中与输入同维度的部分，组成多部件几何
+/// 如面与面相交时去掉只在边界接触产生的线和点；没有同维度部分时返回 None
+pub fn overlay_parts(result: &Geometry, dimension: i32) -> Result<Option<Geometry>> {
+    let mut parts = Vec::new();
+    collect_parts(result, dimension, &mut parts);
+    multi_from_parts(parts, dimension)
+}
+
+/// 合并多个面几何（级联合并），只有一个时直接返回其副本
+pub fn union_polygons(polygons: &[Geometry]) -> Result<Option<Geometry>> {
+    match polygons {
+        [] => Ok(None),
+        [single] => Ok(Some(single.clone())),
+        _ => {
+            let mut parts = Vec::new();
+            for polygon in polygons {
+                collect_parts(polygon, 2, &mut parts);
+            }
+            let Some(multi) = multi_from_parts(parts, 2)? else {
+                return Ok(None);
+            };
+            unsafe { take_c_geometry(gdal_sys::OGR_G_UnionCascaded(multi.c_geometry())) }
+        }
+    }
+}
+
+/// 合并多个几何（融合）：面级联合并，线和点组成多部件几何
+/// 维度不同时只保留其中最高维度的部分
+pub fn dissolve_geometries(geoms: &[Geometry]) -> Result<Option<Geometry>> {
+    let Some(dimension) = geoms.iter().filter(|g| !g.is_empty()).map(topological_dimension).max() else {
+        return Ok(None);
+    };
+    let mut parts = Vec::new();
+    for geom in geoms {
+        collect_parts(geom, dimension, &mut parts);
+    }
+    if dimension == 2 {
+        return union_polygons(&parts);
+    }
+    multi_from_parts(parts, dimension)
+}
+
+/// 几何运算结果的图层类型：线性化并去掉M值（GEOS 运算不保留M值），
+/// multipart 为 true 时为多部件类型，否则为单部件类型
+pub fn result_geometry_type(input_type: OGRwkbGeometryType::Type, multipart: bool) -> OGRwkbGeometryType::Type {
+    unsafe {
+        let linear = gdal_sys::OGR_GT_GetLinear(input_type);
+        let has_z = gdal_sys::OGR_GT_HasZ(linear);
+        let flat_type = match gdal_sys::OGR_GT_Flatten(linear) {
+            OGRwkbGeometryType::wkbPoint | OGRwkbGeometryType::wkbMultiPoint if multipart => OGRwkbGeometryType::wkbMultiPoint,
+            OGRwkbGeometryType::wkbPoint | OGRwkbGeometryType::wkbMultiPoint => OGRwkbGeometryType::wkbPoint,
+            OGRwkbGeometryType::wkbLineString | OGRwkbGeometryType::wkbMultiLineString if multipart => {
+                OGRwkbGeometryType::wkbMultiLineString
+            }
+            OGRwkbGeometryType::wkbLineString | OGRwkbGeometryType::wkbMultiLineString => OGRwkbGeometryType::wkbLineString,
+            OGRwkbGeometryType::wkbPolygon | OGRwkbGeometryType::wkbMultiPolygon if multipart => OGRwkbGeometryType::wkbMultiPolygon,
+            OGRwkbGeometryType::wkbPolygon | OGRwkbGeometryType::wkbMultiPolygon => OGRwkbGeometryType::wkbPolygon,
+            other => other,
+        };
+        gdal_sys::OGR_GT_SetModifier(flat_type, has_z, 0)
+    }
+}
+
+/// 几何转GeoJSON
+/// OGR自带的GeoJSON输出会丢弃M值，带M值的几何在这里手动输出，
+/// 坐标顺序为 [x, y, z, m]（无Z值时为 [x, y, m]）
+pub fn to_geojson_value(geom: &Geometry) -> Result<serde_json::Value> {
+    let ty = geom.geometry_type();
+    let is_linear = unsafe { gdal_sys::OGR_GT_IsNonLinear(ty) } == 0;
+
+    if !coordinate_dimension(geom).has_m() || !is_linear {
+        let geojson = geom.json()
+            .map_err(|e| AppError::InvalidFormat(format!("几何转换失败: {}", e)))?;
+        return serde_json::from_str(&geojson)
+            .map_err(|e| AppError::InvalidFormat(format!("JSON解析失败: {}", e)));
+    }
+
+    measured_geometry_to_geojson(geom)
+}
+
+fn measured_geometry_to_geojson(geom: &Geometry) -> Result<serde_json::Value> {
+    let flat_type = unsafe { gdal_sys::OGR_GT_Flatten(geom.geometry_type()) };
+
+    let (type_name, coordinates) = match flat_type {
+        OGRwkbGeometryType::wkbPoint => {
+            let coords = if geom.is_empty() {
+                serde_json::json!([])
+            } else {
+                position(geom, 0)
+            };
+            ("Point", coords)
+        }
+        OGRwkbGeometryType::wkbLineString | OGRwkbGeometryType::wkbLinearRing => {
+            ("LineString", positions(geom))
+        }
+        OGRwkbGeometryType::wkbPolygon => ("Polygon", sub_coordinates(geom, positions)),
+        OGRwkbGeometryType::wkbMultiPoint => ("MultiPoint", sub_coordinates(geom, |g| position(g, 0))),
+        OGRwkbGeometryType::wkbMultiLineString => ("MultiLineString", sub_coordinates(geom, positions)),
+        OGRwkbGeometryType::wkbMultiPolygon => {
+            ("MultiPolygon", sub_coordinates(geom, |g| sub_coordinates(g, positions)))
+        }
+        OGRwkbGeometryType::wkbGeometryCollection => {
+            let mut geometries = Vec::new();
+            for i in 0..geom.geometry_count() {
+                geometries.push(to_geojson_value(&geom.get_geometry(i))?);
+            }
+            return Ok(serde_json::json!({
+                "type": "GeometryCollection",
+                "geometries": geometries
+            }));
+        }
+        _ => {
+            return Err(AppError::InvalidFormat(format!(
+                "不支持的几何类型: {}",
+                geom.geometry_name()
+            )))
+        }
+    };
+
+    Ok(serde_json::json!({
+        "type": type_name,
+        "coordinates": coordinates
+    }))
+}
+
+fn sub_coordinates<F>(geom: &Geometry, f: F) -> serde_json::Value
+where
+    F: Fn(&Geometry) -> serde_json::Value,
+{
+    let parts: Vec<serde_json::Value> = (0..geom.geometry_count())
+        .map(|i| f(&geom.get_geometry(i)))
+        .collect();
+    serde_json::Value::Array(parts)
+}
+
+fn positions(geom: &Geometry) -> serde_json::Value {
+    let points: Vec<serde_json::Value> = (0..geom.point_count())
+        .map(|i| position(geom, i))
+        .collect();
+    serde_json::Value::Array(points)
+}
+
+fn position(geom: &Geometry, index: usize) -> serde_json::Value {
+    let (mut x, mut y, mut z, mut m) = (0.0, 0.0, 0.0, 0.0);
+    let (has_z, has_m) = unsafe {
+        let h = geom.c_geometry();
+        gdal_sys::OGR_G_GetPointZM(h, index as i32, &mut x, &mut y, &mut z, &mut m);
+        (gdal_sys::OGR_G_Is3D(h) != 0, gdal_sys::OGR_G_IsMeasured(h) != 0)
+    };
+
+    // GeoJSON 第三个坐标按 Z 解读，M 值固定作为第四个坐标输出，XYM 几何的 Z 位置为 null
+    match (has_z, has_m) {
+        (true, true) => serde_json::json!([x, y, z, m]),
+        (true, false) => serde_json::json!([x, y, z]),
+        (false, true) => serde_json::json!([x, y, null, m]),
+        (false, false) => serde_json::json!([x, y]),
+    }
+}
+
+const GEOMETRY_TYPE_NAMES: [(&str, OGRwkbGeometryType::Type); 7] = [
+    ("Point", OGRwkbGeometryType::wkbPoint),
+    ("LineString", OGRwkbGeometryType::wkbLineString),
+    ("Polygon", OGRwkbGeometryType::wkbPolygon),
+    ("MultiPoint", OGRwkbGeometryType::wkbMultiPoint),
+    ("MultiLineString", OGRwkbGeometryType::wkbMultiLineString),
+    ("MultiPolygon", OGRwkbGeometryType::wkbMultiPolygon),
+    ("GeometryCollection", OGRwkbGeometryType::wkbGeometryCollection),
+];
+
+/// 解析几何类型名称（如 "Point"、"MultiPolygonZ"、"LineString ZM"、"Geometry"），不区分大小写
+pub fn parse_geometry_type(name: &str) -> Result<OGRwkbGeometryType::Type> {
+    let normalized: String = name.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+
+    let (base, has_z, has_m) = if let Some(base) = normalized.strip_suffix("zm") {
+        (base, true, true)
+    } else if let Some(base) = normalized.strip_suffix('z') {
+        (base, true, false)
+    } else if let Some(base) = normalized.strip_suffix('m') {
+        (base, false, true)
+    } else {
+        (normalized.as_str(), false, false)
+    };
+
+    let flat_type = if base == "geometry" || base == "unknown" {
+        OGRwkbGeometryType::wkbUnknown
+    } else {
+        GEOMETRY_TYPE_NAMES.iter()
+            .find(|(type_name, _)| type_name.to_lowercase() == base)
+            .map(|(_, ty)| *ty)
+            .ok_or_else(|| AppError::InvalidFormat(format!("不支持的几何类型: {}", name)))?
+    };
+
+    Ok(unsafe { gdal_sys::OGR_GT_SetModifier(flat_type, has_z as i32, has_m as i32) })
+}
+
+/// 几何类型名称（与 parse_geometry_type 对应，如 "MultiPolygonZ"）
+pub fn geometry_type_name(geom_type: OGRwkbGeometryType::Type) -> String {
+    let flat_type = unsafe { gdal_sys::OGR_GT_Flatten(geom_type) };
+    let base = GEOMETRY_TYPE_NAMES.iter()
+        .find(|(_, ty)| *ty == flat_type)
+        .map(|(type_name, _)| *type_name)
+        .unwrap_or("Geometry");
+
+    let suffix = match dimension_of_type(geom_type) {
+        CoordinateDimension::Xy => "",
+        CoordinateDimension::Xyz => "Z",
+        CoordinateDimension::Xym => "M",
+        CoordinateDimension::Xyzm => "ZM",
+    };
+    format!("{}{}", base, suffix)
+}
@@ -0,0 +1,141 @@
+use crate::errors::{AppError, Result};
+use crate::models::{Extent, GridShape};
+use gdal::vector::{Geometry, OGRwkbGeometryType};
+
+/// 规则格网：矩形或尖顶六边形（奇数行右移半个格网），行号自上而下、列号自左而右
+/// 六边形的 width 为相邻格网中心的距离（对边距离）
+pub struct Grid {
+    shape: GridShape,
+    extent: Extent,
+    width: f64,  // 列间距
+    height: f64, // 行间距
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl Grid {
+    /// 覆盖范围的格网，格网数超过 max_cells 时报错
+    pub fn new(shape: GridShape, extent: &Extent, cell_width: f64, cell_height: f64, max_cells: u64) -> Result<Grid> {
+        if !(cell_width > 0.0 && cell_height > 0.0) {
+            return Err(AppError::InvalidFormat(format!("格网大小无效: {} x {}", cell_width, cell_height)));
+        }
+        let span_x = extent.max_x - extent.min_x;
+        let span_y = extent.max_y - extent.min_y;
+        if !(span_x > 0.0 && span_y > 0.0) {
+            return Err(AppError::InvalidFormat("格网范围无效: 宽度和高度必须大于 0".to_string()));
+        }
+
+        let (width, height, rows, cols) = match shape {
+            GridShape::Rectangle => {
+                (cell_width, cell_height, (span_y / cell_height).ceil(), (span_x / cell_width).ceil())
+            }
+            // 六边形多一行一列，保证范围边缘被完整覆盖
+            GridShape::Hexagon => {
+                let height = cell_width * 3f64.sqrt() / 2.0;
+                (cell_width, height, (span_y / height).ceil() + 1.0, (span_x / cell_width).ceil() + 1.0)
+            }
+        };
+        if rows * cols > max_cells as f64 {
+            return Err(AppError::InvalidFormat(format!(
+                "格网数量过多（约 {} 个，最多 {} 个），请增大格网大小", (rows * cols) as u64, max_cells
+            )));
+        }
+        Ok(Grid {
+            shape,
+            extent: extent.clone(),
+            width,
+            height,
+            rows: rows as usize,
+            cols: cols as usize,
+        })
+    }
+
+    pub fn cell_count(&self) -> u64 {
+        (self.rows * self.cols) as u64
+    }
+
+    /// 六边形中心
+    fn center(&self, row: usize, col: usize) -> (f64, f64) {
+        let offset = if row % 2 == 1 { self.width / 2.0 } else { 0.0 };
+        (self.extent.min_x + col as f64 * self.width + offset, self.extent.max_y - row as f64 * self.height)
+    }
+
+    /// 格网外环顶点（逆时针，首尾相同）
+    pub fn cell_ring(&self, row: usize, col: usize) -> Vec<(f64, f64)> {
+        match self.shape {
+            GridShape::Rectangle => {
+                let min_x = self.extent.min_x + col as f64 * self.width;
+                let max_y = self.extent.max_y - row as f64 * self.height;
+                let (max_x, min_y) = (min_x + self.width, max_y - self.height);
+                vec![(min_x, min_y), (max_x, min_y), (max_x, max_y), (min_x, max_y), (min_x, min_y)]
+            }
+            GridShape::Hexagon => {
+                let (cx, cy) = self.center(row, col);
+                let radius = self.width / 3f64.sqrt();
+                let mut ring: Vec<(f64, f64)> = (0..6)
+                    .map(|i| {
+                        let angle = (30.0 + 60.0 * i as f64).to_radians();
+                        (cx + radius * angle.cos(), cy + radius * angle.sin())
+                    })
+                    .collect();
+                ring.push(ring[0]);
+                ring
+            }
+        }
+    }
+
+    /// 格网面几何
+    pub fn cell_geometry(&self, row: usize, col: usize) -> Result<Geometry> {
+        let map_err = |e: gdal::errors::GdalError| AppError::InvalidFormat(format!("创建格网几何失败: {}", e));
+        let mut ring = Geometry::empty(OGRwkbGeometryType::wkbLinearRing).map_err(map_err)?;
+        for point in self.cell_ring(row, col) {
+            ring.add_point_2d(point);
+        }
+        let mut polygon = Geometry::empty(OGRwkbGeometryType::wkbPolygon).map_err(map_err)?;
+        polygon.add_geometry(ring).map_err(map_err)?;
+        Ok(polygon)
+    }
+
+    /// 格网与范围是否相交（六边形格网边缘的行列可能完全在范围之外）
+    pub fn intersects_extent(&self, row: usize, col: usize) -> bool {
+        let ring = self.cell_ring(row, col);
+        let (min_x, max_x) = ring.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+        let (min_y, max_y) = ring.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+        min_x < self.extent.max_x && max_x > self.extent.min_x && min_y < self.extent.max_y && max_y > self.extent.min_y
+    }
+
+    /// 点所在的格网 (行, 列)，范围之外返回 None
+    pub fn locate(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let extent = &self.extent;
+        if x < extent.min_x || x > extent.max_x || y < extent.min_y || y > extent.max_y {
+            return None;
+        }
+        match self.shape {
+            GridShape::Rectangle => {
+                let col = (((x - extent.min_x) / self.width) as usize).min(self.cols - 1);
+                let row = (((extent.max_y - y) / self.height) as usize).min(self.rows - 1);
+                Some((row, col))
+            }
+            // 点落在最近的六边形中心所在的格网，只需比较上下相邻两行
+            GridShape::Hexagon => {
+                let first_row = ((extent.max_y - y) / self.height).floor() as usize;
+                let mut best: Option<((usize, usize), f64)> = None;
+                for row in first_row..=(first_row + 1).min(self.rows - 1) {
+                    let offset = if row % 2 == 1 { self.width / 2.0 } else { 0.0 };
+                    let nearest = ((x - extent.min_x - offset) / self.width).round();
+                    for col in [nearest - 1.0, nearest, nearest + 1.0] {
+                        if col < 0.0 || col >= self.cols as f64 {
+                            continue;
+                        }
+                        let (cx, cy) = self.center(row, col as usize);
+                        let distance = (x - cx).hypot(y - cy);
+                        if best.is_none_or(|(_, d)| distance < d) {
+                            best = Some(((row, col as usize), distance));
+                        }
+                    }
+                }
+                best.map(|(cell, _)| cell)
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::models::{AreaUnit, LengthUnit, MeasureValues};
+use geo::orient::Direction;
+use geo::{
+    Area, BoundingRect, Centroid, Closest, ClosestPoint, CoordsIter, EuclideanLength, GeodesicArea, GeodesicDistance,
+    GeodesicLength, Intersects, Orient,
+};
+use geo_types::{Geometry, LineString, Point, Polygon};
+
+/// 1 亩的平方米数
+const SQUARE_METERS_PER_MU: f64 = 10000.0 / 15.0;
+
+/// 米转为指定长度单位
+pub fn convert_length(meters: f64, unit: LengthUnit) -> f64 {
+    match unit {
+        LengthUnit::Meter => meters,
+        LengthUnit::Kilometer => meters / 1000.0,
+    }
+}
+
+/// 平方米转为指定面积单位
+pub fn convert_area(square_meters: f64, unit: AreaUnit) -> f64 {
+    match unit {
+        AreaUnit::SquareMeter => square_meters,
+        AreaUnit::SquareKilometer => square_meters / 1.0e6,
+        AreaUnit::Hectare => square_meters / 1.0e4,
+        AreaUnit::Mu => square_meters / SQUARE_METERS_PER_MU,
+    }
+}
+
+/// 量算值转为指定单位（输入为米、平方米）
+pub fn convert_values(values: &MeasureValues, length_unit: LengthUnit, area_unit: AreaUnit) -> MeasureValues {
+    MeasureValues {
+        length: convert_length(values.length, length_unit),
+        area: convert_area(values.area, area_unit),
+        perimeter: convert_length(values.perimeter, length_unit),
+    }
+}
+
+/// 椭球面量算（坐标为经纬度），结果为米、平方米
+/// 使用 Karney 测地线算法（WGS84 椭球，与 CGCS2000 椭球的差异可忽略），面先统一为外环逆时针、内环顺时针
+pub fn geodesic(geom: &Geometry<f64>) -> MeasureValues {
+    let mut values = MeasureValues::default();
+    accumulate(geom, &mut values, &|line| line.geodesic_length(), &|polygon| {
+        let (perimeter, area) = polygon.orient(Direction::Default).geodesic_perimeter_area_signed();
+        (perimeter, area.abs())
+    });
+    values
+}
+
+/// 平面量算（坐标为投影坐标），按坐标系长度单位（每单位的米数）换算为米、平方米
+pub fn planar(geom: &Geometry<f64>, meters_per_unit: f64) -> MeasureValues {
+    let mut values = MeasureValues::default();
+    accumulate(geom, &mut values, &|line| line.euclidean_length(), &|polygon| {
+        let perimeter = polygon.exterior().euclidean_length()
+            + polygon.interiors().iter().map(|ring| ring.euclidean_length()).sum::<f64>();
+        (perimeter, polygon.unsigned_area())
+    });
+    MeasureValues {
+        length: values.length * meters_per_unit,
+        area: values.area * meters_per_unit * meters_per_unit,
+        perimeter: values.perimeter * meters_per_unit,
+    }
+}
+
+/// 几何中心（面按面积加权，线按长度加权），空几何返回 None
+pub fn centroid(geom: &Geometry<f64>) -> Option<(f64, f64)> {
+    geom.centroid().map(|point| (point.x(), point.y()))
+}
+
+/// 顶点数（面的环计入闭合点）
+pub fn vertex_count(geom: &Geometry<f64>) -> usize {
+    geom.coords_count()
+}
+
+/// 外包矩形 (min_x, min_y, max_x, max_y)，空几何返回 None
+pub fn bounds(geom: &Geometry<f64>) -> Option<[f64; 4]> {
+    geom.bounding_rect().map(|rect| [rect.min().x, rect.min().y, rect.max().x, rect.max().y])
+}
+
+/// 两个几何（坐标为经纬度）之间的最短椭球面距离（米），相交时为 0，空几何返回 None
+/// 取每个几何的顶点到另一几何最近点的测地线距离的最小值；最近点在经纬度平面上求取，
+/// 在几十公里内与严格的测地线最近点差异可忽略
+pub fn geodesic_distance(a: &Geometry<f64>, b: &Geometry<f64>) -> Option<f64> {
+    if a.intersects(b) {
+        return Some(0.0);
+    }
+    let vertex_distance = |from: &Geometry<f64>, to: &Geometry<f64>| {
+        from.coords_iter()
+            .filter_map(|coord| {
+                let point = Point::from(coord);
+                match to.closest_point(&point) {
+                    Closest::Intersection(closest) | Closest::SinglePoint(closest) => {
+                        Some(point.geodesic_distance(&closest))
+                    }
+                    Closest::Indeterminate => None,
+                }
+            })
+            .reduce(f64::min)
+    };
+    match (vertex_distance(a, b), vertex_distance(b, a)) {
+        (Some(ab), Some(ba)) => Some(ab.min(ba)),
+        (ab, ba) => ab.or(ba),
+    }
+}
+
+/// 累加几何中各部件的长度、面积和周长（点不计）
+fn accumulate(
+    geom: &Geometry<f64>,
+    values: &mut MeasureValues,
+    line_length: &dyn Fn(&LineString<f64>) -> f64,
+    polygon_measure: &dyn Fn(&Polygon<f64>) -> (f64, f64),
+) {
+    let add_polygon = |polygon: &Polygon<f64>, values: &mut MeasureValues| {
+        let (perimeter, area) = polygon_measure(polygon);
+        values.perimeter += perimeter;
+        values.area += area;
+    };
+    match geom {
+        Geometry::Point(_) | Geometry::MultiPoint(_) => {}
+        Geometry::Line(line) => values.length += line_length(&LineString::from(*line)),
+        Geometry::LineString(line) => values.length += line_length(line),
+        Geometry::MultiLineString(lines) => values.length += lines.iter().map(line_length).sum::<f64>(),
+        Geometry::Polygon(polygon) => add_polygon(polygon, values),
+        Geometry::MultiPolygon(polygons) => {
+            for polygon in polygons {
+                add_polygon(polygon, values);
+            }
+        }
+        Geometry::Rect(rect) => add_polygon(&rect.to_polygon(), values),
+        Geometry::Triangle(triangle) => add_polygon(&triangle.to_polygon(), values),
+        Geometry::GeometryCollection(collection) => {
+            for part in collection {
+                accumulate(part, values, line_length, polygon_measure);
+            }
+        }
+    }
+}
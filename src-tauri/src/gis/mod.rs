@@ -1,3 +1,8 @@
 pub mod projection;
 pub mod geometry;
 pub mod gdal_init;
+pub mod mvt;
+pub mod validation;
+pub mod measure;
+pub mod grid;
+pub mod sampling;
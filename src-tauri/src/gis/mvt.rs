@@ -0,0 +1,364 @@
+use gdal::vector::{Geometry, OGRwkbGeometryType};
+use std::collections::HashMap;
+
+/// 瓦片坐标范围（Mapbox Vector Tile 标准值）
+pub const EXTENT: u32 = 4096;
+
+/// 要素几何类型（MVT GeomType）
+const GEOM_POINT: u32 = 1;
+const GEOM_LINESTRING: u32 = 2;
+const GEOM_POLYGON: u32 = 3;
+
+/// 几何绘制命令
+const CMD_MOVE_TO: u32 = 1;
+const CMD_LINE_TO: u32 = 2;
+const CMD_CLOSE_PATH: u32 = 7;
+
+/// 瓦片在切片坐标系（EPSG:3857 或经纬度）中的范围，用于将坐标换算为瓦片坐标
+#[derive(Debug, Clone, Copy)]
+pub struct TileBounds {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl TileBounds {
+    fn to_tile(self, x: f64, y: f64) -> [i32; 2] {
+        let scale = EXTENT as f64 / (self.max_x - self.min_x);
+        [
+            ((x - self.min_x) * scale).round() as i32,
+            ((self.max_y - y) * scale).round() as i32,
+        ]
+    }
+}
+
+/// 瓦片坐标下的几何（一个要素只能有一种几何类型）
+#[derive(Debug, Clone)]
+pub enum TileGeometry {
+    Points(Vec<[i32; 2]>),
+    Lines(Vec<Vec<[i32; 2]>>),
+    /// 环已按 MVT 规则调整方向：外环面积为正，内环面积为负
+    Polygons(Vec<Vec<[i32; 2]>>),
+}
+
+/// 要素属性值
+#[derive(Debug, Clone)]
+pub enum TileValue {
+    String(String),
+    Double(f64),
+    Int(i64),
+    Bool(bool),
+}
+
+/// 属性值去重键（浮点数按位比较）
+#[derive(PartialEq, Eq, Hash)]
+enum ValueKey {
+    String(String),
+    Double(u64),
+    Int(i64),
+    Bool(bool),
+}
+
+impl From<&TileValue> for ValueKey {
+    fn from(value: &TileValue) -> Self {
+        match value {
+            TileValue::String(s) => ValueKey::String(s.clone()),
+            TileValue::Double(d) => ValueKey::Double(d.to_bits()),
+            TileValue::Int(i) => ValueKey::Int(*i),
+            TileValue::Bool(b) => ValueKey::Bool(*b),
+        }
+    }
+}
+
+/// 将投影坐标系下的几何转换为瓦片几何
+/// 几何集合按类型拆分为多个瓦片几何；退化的线、环（量化后点数不足或面积为零）被丢弃
+pub fn tile_geometries(geom: &Geometry, bounds: TileBounds) -> Vec<TileGeometry> {
+    let mut points = Vec::new();
+    let mut lines = Vec::new();
+    let mut rings = Vec::new();
+    collect_parts(geom, bounds, &mut points, &mut lines, &mut rings);
+
+    let mut result = Vec::new();
+    if !points.is_empty() {
+        result.push(TileGeometry::Points(points));
+    }
+    if !lines.is_empty() {
+        result.push(TileGeometry::Lines(lines));
+    }
+    if !rings.is_empty() {
+        result.push(TileGeometry::Polygons(rings));
+    }
+    result
+}
+
+fn collect_parts(
+    geom: &Geometry,
+    bounds: TileBounds,
+    points: &mut Vec<[i32; 2]>,
+    lines: &mut Vec<Vec<[i32; 2]>>,
+    rings: &mut Vec<Vec<[i32; 2]>>,
+) {
+    let geom_type = unsafe { gdal_sys::OGR_GT_Flatten(geom.geometry_type()) };
+    match geom_type {
+        OGRwkbGeometryType::wkbPoint => {
+            if let Some(&(x, y, _)) = geom.get_point_vec().first() {
+                points.push(bounds.to_tile(x, y));
+            }
+        }
+        OGRwkbGeometryType::wkbLineString | OGRwkbGeometryType::wkbLinearRing => {
+            let line = quantize(geom, bounds);
+            if line.len() >= 2 {
+                lines.push(line);
+            }
+        }
+        OGRwkbGeometryType::wkbPolygon => {
+            for i in 0..geom.geometry_count() {
+                let mut ring = quantize(&geom.get_geometry(i), bounds);
+                if ring.len() > 1 && ring.first() == ring.last() {
+                    ring.pop();
+                }
+                let area = ring_area(&ring);
+                if ring.len() < 3 || area == 0 {
+                    if i == 0 {
+                        // 外环退化则整个多边形不可见
+                        return;
+                    }
+                    continue;
+                }
+                // 外环面积为正（y轴向下时顺时针），内环为负
+                if (i == 0) != (area > 0) {
+                    ring.reverse();
+                }
+                rings.push(ring);
+            }
+        }
+        _ => {
+            for i in 0..geom.geometry_count() {
+                collect_parts(&geom.get_geometry(i), bounds, points, lines, rings);
+            }
+        }
+    }
+}
+
+/// 量化为瓦片坐标并去除连续重复点
+fn quantize(geom: &Geometry, bounds: TileBounds) -> Vec<[i32; 2]> {
+    let mut result: Vec<[i32; 2]> = Vec::with_capacity(geom.point_count());
+    for (x, y, _) in geom.get_point_vec() {
+        let point = bounds.to_tile(x, y);
+        if result.last() != Some(&point) {
+            result.push(point);
+        }
+    }
+    result
+}
+
+/// 环的有向面积的两倍（测量员公式，y轴向下）
+fn ring_area(ring: &[[i32; 2]]) -> i64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let [x1, y1] = ring[i];
+            let [x2, y2] = ring[(i + 1) % n];
+            x1 as i64 * y2 as i64 - x2 as i64 * y1 as i64
+        })
+        .sum()
+}
+
+/// 单个图层的编码器
+pub struct LayerBuilder {
+    name: String,
+    keys: Vec<String>,
+    key_index: HashMap<String, u32>,
+    values: Vec<TileValue>,
+    value_index: HashMap<ValueKey, u32>,
+    features: Vec<Vec<u8>>,
+}
+
+impl LayerBuilder {
+    pub fn new(name: &str) -> Self {
+        LayerBuilder {
+            name: name.to_string(),
+            keys: Vec::new(),
+            key_index: HashMap::new(),
+            values: Vec::new(),
+            value_index: HashMap::new(),
+            features: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// 添加要素
+    pub fn add_feature(&mut self, id: Option<u64>, geometry: &TileGeometry, properties: &[(String, TileValue)]) {
+        let (geom_type, commands) = encode_geometry(geometry);
+        if commands.is_empty() {
+            return;
+        }
+
+        let mut tags = Vec::with_capacity(properties.len() * 2);
+        for (key, value) in properties {
+            tags.push(self.key(key));
+            tags.push(self.value(value));
+        }
+
+        let mut feature = Vec::new();
+        if let Some(id) = id {
+            write_varint_field(&mut feature, 1, id);
+        }
+        write_packed(&mut feature, 2, &tags);
+        write_varint_field(&mut feature, 3, geom_type as u64);
+        write_packed(&mut feature, 4, &commands);
+        self.features.push(feature);
+    }
+
+    fn key(&mut self, key: &str) -> u32 {
+        if let Some(&index) = self.key_index.get(key) {
+            return index;
+        }
+        let index = self.keys.len() as u32;
+        self.keys.push(key.to_string());
+        self.key_index.insert(key.to_string(), index);
+        index
+    }
+
+    fn value(&mut self, value: &TileValue) -> u32 {
+        let key = ValueKey::from(value);
+        if let Some(&index) = self.value_index.get(&key) {
+            return index;
+        }
+        let index = self.values.len() as u32;
+        self.values.push(value.clone());
+        self.value_index.insert(key, index);
+        index
+    }
+
+    /// 编码为 Tile.Layer 消息
+    fn encode(&self) -> Vec<u8> {
+        let mut layer = Vec::new();
+        write_varint_field(&mut layer, 15, 2); // version
+        write_bytes_field(&mut layer, 1, self.name.as_bytes());
+        for feature in &self.features {
+            write_bytes_field(&mut layer, 2, feature);
+        }
+        for key in &self.keys {
+            write_bytes_field(&mut layer, 3, key.as_bytes());
+        }
+        for value in &self.values {
+            write_bytes_field(&mut layer, 4, &encode_value(value));
+        }
+        write_varint_field(&mut layer, 5, EXTENT as u64);
+        layer
+    }
+}
+
+/// 编码瓦片（空图层不写入）
+pub fn encode_tile(layers: &[LayerBuilder]) -> Vec<u8> {
+    let mut tile = Vec::new();
+    for layer in layers.iter().filter(|l| !l.is_empty()) {
+        write_bytes_field(&mut tile, 3, &layer.encode());
+    }
+    tile
+}
+
+fn encode_value(value: &TileValue) -> Vec<u8> {
+    let mut buf = Vec::new();
+    match value {
+        TileValue::String(s) => write_bytes_field(&mut buf, 1, s.as_bytes()),
+        TileValue::Double(d) => {
+            write_key(&mut buf, 3, 1);
+            buf.extend_from_slice(&d.to_le_bytes());
+        }
+        TileValue::Int(i) => write_varint_field(&mut buf, 4, *i as u64),
+        TileValue::Bool(b) => write_varint_field(&mut buf, 7, *b as u64),
+    }
+    buf
+}
+
+/// 编码几何绘制命令，返回 (几何类型, 命令序列)
+fn encode_geometry(geometry: &TileGeometry) -> (u32, Vec<u32>) {
+    let mut commands = Vec::new();
+    let mut cursor = [0i32; 2];
+    let mut push_point = |commands: &mut Vec<u32>, point: [i32; 2]| {
+        commands.push(zigzag(point[0] - cursor[0]));
+        commands.push(zigzag(point[1] - cursor[1]));
+        cursor = point;
+    };
+
+    match geometry {
+        TileGeometry::Points(points) => {
+            commands.push(command(CMD_MOVE_TO, points.len() as u32));
+            for &point in points {
+                push_point(&mut commands, point);
+            }
+            (GEOM_POINT, commands)
+        }
+        TileGeometry::Lines(lines) => {
+            for line in lines {
+                commands.push(command(CMD_MOVE_TO, 1));
+                push_point(&mut commands, line[0]);
+                commands.push(command(CMD_LINE_TO, line.len() as u32 - 1));
+                for &point in &line[1..] {
+                    push_point(&mut commands, point);
+                }
+            }
+            (GEOM_LINESTRING, commands)
+        }
+        TileGeometry::Polygons(rings) => {
+            for ring in rings {
+                commands.push(command(CMD_MOVE_TO, 1));
+                push_point(&mut commands, ring[0]);
+                commands.push(command(CMD_LINE_TO, ring.len() as u32 - 1));
+                for &point in &ring[1..] {
+                    push_point(&mut commands, point);
+                }
+                commands.push(command(CMD_CLOSE_PATH, 1));
+            }
+            (GEOM_POLYGON, commands)
+        }
+    }
+}
+
+fn command(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+fn zigzag(n: i32) -> u32 {
+    ((n << 1) ^ (n >> 31)) as u32
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_key(buf: &mut Vec<u8>, field: u32, wire_type: u32) {
+    write_varint(buf, ((field << 3) | wire_type) as u64);
+}
+
+fn write_varint_field(buf: &mut Vec<u8>, field: u32, value: u64) {
+    write_key(buf, field, 0);
+    write_varint(buf, value);
+}
+
+fn write_bytes_field(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_key(buf, field, 2);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn write_packed(buf: &mut Vec<u8>, field: u32, values: &[u32]) {
+    if values.is_empty() {
+        return;
+    }
+    let mut packed = Vec::with_capacity(values.len() * 2);
+    for &value in values {
+        write_varint(&mut packed, value as u64);
+    }
+    write_bytes_field(buf, field, &packed);
+}
@@ -0,0 +1,97 @@
+use geo::{Area, BoundingRect, Intersects, TriangulateEarcut};
+use geo_types::{Geometry, Point, Polygon};
+
+/// 可复现的伪随机数（SplitMix64），相同种子在各平台、各版本生成相同的序列
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        SeededRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// [0, 1) 之间的均匀分布
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// 几何中的面（展开多面和几何集合），其他类型忽略
+pub fn polygons(geom: &Geometry<f64>) -> Vec<Polygon<f64>> {
+    match geom {
+        Geometry::Polygon(polygon) => vec![polygon.clone()],
+        Geometry::MultiPolygon(multi) => multi.0.clone(),
+        Geometry::Rect(rect) => vec![rect.to_polygon()],
+        Geometry::Triangle(triangle) => vec![triangle.to_polygon()],
+        Geometry::GeometryCollection(collection) => collection.iter().flat_map(polygons).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 在面内均匀随机生成 count 个点：面剖分为三角形，按面积选择三角形后在其中取点
+pub fn random_points(polygons: &[Polygon<f64>], count: usize, rng: &mut SeededRng) -> Vec<(f64, f64)> {
+    let triangles: Vec<_> = polygons.iter()
+        .flat_map(|polygon| polygon.earcut_triangles())
+        .filter(|triangle| triangle.unsigned_area() > 0.0)
+        .collect();
+    let mut cumulative = Vec::with_capacity(triangles.len());
+    let mut total = 0.0;
+    for triangle in &triangles {
+        total += triangle.unsigned_area();
+        cumulative.push(total);
+    }
+    if triangles.is_empty() {
+        return Vec::new();
+    }
+
+    (0..count)
+        .map(|_| {
+            let target = rng.next_f64() * total;
+            let index = cumulative.partition_point(|&area| area <= target).min(triangles.len() - 1);
+            let [a, b, c] = triangles[index].to_array();
+            let (mut u, mut v) = (rng.next_f64(), rng.next_f64());
+            if u + v > 1.0 {
+                (u, v) = (1.0 - u, 1.0 - v);
+            }
+            (a.x + u * (b.x - a.x) + v * (c.x - a.x), a.y + u * (b.y - a.y) + v * (c.y - a.y))
+        })
+        .collect()
+}
+
+/// 在面内按间距生成规则点阵，点位为间距的整数倍加半个间距（相邻面的点阵对齐），自上而下逐行排列
+pub fn regular_points(polygons: &[Polygon<f64>], spacing: f64) -> Vec<(f64, f64)> {
+    let mut points = Vec::new();
+    for polygon in polygons {
+        let Some(rect) = polygon.bounding_rect() else { continue };
+        let first_col = (rect.min().x / spacing - 0.5).ceil() as i64;
+        let last_col = (rect.max().x / spacing - 0.5).floor() as i64;
+        let first_row = (rect.min().y / spacing - 0.5).ceil() as i64;
+        let last_row = (rect.max().y / spacing - 0.5).floor() as i64;
+        for row in (first_row..=last_row).rev() {
+            for col in first_col..=last_col {
+                let point = Point::new((col as f64 + 0.5) * spacing, (row as f64 + 0.5) * spacing);
+                if polygon.intersects(&point) {
+                    points.push((point.x(), point.y()));
+                }
+            }
+        }
+    }
+    points
+}
+
+/// 按间距生成点阵时需要检查的点位数（各面外包矩形内的点位数之和）
+pub fn regular_candidates(polygons: &[Polygon<f64>], spacing: f64) -> f64 {
+    polygons.iter()
+        .filter_map(|polygon| polygon.bounding_rect())
+        .map(|rect| (rect.width() / spacing + 1.0) * (rect.height() / spacing + 1.0))
+        .sum()
+}
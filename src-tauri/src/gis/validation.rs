@@ -0,0 +1,438 @@
+use crate::errors::{AppError, Result};
+use crate::gis::geometry;
+use crate::models::{GeometryIssueKind, RepairMethod};
+use gdal::cpl::CslStringList;
+use gdal::vector::{Geometry, OGRwkbGeometryType};
+use gdal_sys::OGRGeometryH;
+
+/// 坐标 [x, y, z, m]
+type Coord = [f64; 4];
+
+/// 几何问题，位置为几何所在坐标系的坐标
+pub struct Issue {
+    pub kind: GeometryIssueKind,
+    pub reason: String,
+    pub location: Option<(f64, f64)>,
+}
+
+impl Issue {
+    fn new(kind: GeometryIssueKind, reason: String, location: Option<&Coord>) -> Issue {
+        Issue { kind, reason, location: location.map(|c| (c[0], c[1])) }
+    }
+}
+
+fn flat_type(handle: OGRGeometryH) -> OGRwkbGeometryType::Type {
+    unsafe { gdal_sys::OGR_GT_Flatten(gdal_sys::OGR_G_GetGeometryType(handle)) }
+}
+
+fn is_collection(handle: OGRGeometryH) -> bool {
+    unsafe { gdal_sys::OGR_GT_IsSubClassOf(flat_type(handle), OGRwkbGeometryType::wkbGeometryCollection) != 0 }
+}
+
+fn read_coords(handle: OGRGeometryH) -> Vec<Coord> {
+    let count = unsafe { gdal_sys::OGR_G_GetPointCount(handle) };
+    (0..count)
+        .map(|i| {
+            let mut c = [0.0; 4];
+            unsafe { gdal_sys::OGR_G_GetPointZM(handle, i, &mut c[0], &mut c[1], &mut c[2], &mut c[3]) };
+            c
+        })
+        .collect()
+}
+
+/// 替换线或环的全部坐标，保持原有的Z/M维度
+fn write_coords(handle: OGRGeometryH, coords: &[Coord]) {
+    unsafe {
+        let has_z = gdal_sys::OGR_G_Is3D(handle) != 0;
+        let has_m = gdal_sys::OGR_G_IsMeasured(handle) != 0;
+        gdal_sys::OGR_G_SetPointCount(handle, coords.len() as i32);
+        for (i, c) in coords.iter().enumerate() {
+            let i = i as i32;
+            match (has_z, has_m) {
+                (true, true) => gdal_sys::OGR_G_SetPointZM(handle, i, c[0], c[1], c[2], c[3]),
+                (true, false) => gdal_sys::OGR_G_SetPoint(handle, i, c[0], c[1], c[2]),
+                (false, true) => gdal_sys::OGR_G_SetPointM(handle, i, c[0], c[1], c[3]),
+                (false, false) => gdal_sys::OGR_G_SetPoint_2D(handle, i, c[0], c[1]),
+            }
+        }
+    }
+}
+
+fn same_xy(a: &Coord, b: &Coord) -> bool {
+    a[0] == b[0] && a[1] == b[1]
+}
+
+/// 环的有向面积（逆时针为正）
+fn signed_area(ring: &[Coord]) -> f64 {
+    ring.windows(2).map(|w| w[0][0] * w[1][1] - w[1][0] * w[0][1]).sum::<f64>() / 2.0
+}
+
+/// 顶点 b 是否为尖刺：前后两段共线且方向相反（路径原路折返）
+fn is_spike(a: &Coord, b: &Coord, c: &Coord) -> bool {
+    let (ux, uy) = (b[0] - a[0], b[1] - a[1]);
+    let (vx, vy) = (c[0] - b[0], c[1] - b[1]);
+    let scale = ux.hypot(uy) * vx.hypot(vy);
+    scale > 0.0 && (ux * vy - uy * vx).abs() <= 1e-10 * scale && ux * vx + uy * vy < 0.0
+}
+
+/// 去掉连续重复顶点，closed 时同时去掉与首点重复的末尾顶点（结果不含闭合点）
+fn dedup(coords: &[Coord], closed: bool) -> Vec<Coord> {
+    let mut result: Vec<Coord> = Vec::with_capacity(coords.len());
+    for c in coords {
+        if result.last().is_none_or(|last| !same_xy(last, c)) {
+            result.push(*c);
+        }
+    }
+    if closed {
+        while result.len() > 1 && same_xy(&result[0], &result[result.len() - 1]) {
+            result.pop();
+        }
+    }
+    result
+}
+
+/// 第一个尖刺顶点的序号，closed 时首尾相连判断
+fn find_spike(points: &[Coord], closed: bool) -> Option<usize> {
+    let n = points.len();
+    if n < 3 {
+        return None;
+    }
+    if closed {
+        (0..n).find(|&i| is_spike(&points[(i + n - 1) % n], &points[i], &points[(i + 1) % n]))
+    } else {
+        (1..n - 1).find(|&i| is_spike(&points[i - 1], &points[i], &points[i + 1]))
+    }
+}
+
+/// 反复去掉尖刺顶点（去掉后可能形成新的尖刺或重复顶点）
+fn remove_spikes(mut points: Vec<Coord>, closed: bool) -> Vec<Coord> {
+    while let Some(i) = find_spike(&points, closed) {
+        points.remove(i);
+        points = dedup(&points, closed);
+    }
+    points
+}
+
+/// 两条线段的交点，proper 表示在线段内部相交或共线重叠（不只是端点接触）
+fn segment_intersection(p1: &Coord, p2: &Coord, q1: &Coord, q2: &Coord) -> Option<((f64, f64), bool)> {
+    let (rx, ry) = (p2[0] - p1[0], p2[1] - p1[1]);
+    let (sx, sy) = (q2[0] - q1[0], q2[1] - q1[1]);
+    let (qpx, qpy) = (q1[0] - p1[0], q1[1] - p1[1]);
+    let denom = rx * sy - ry * sx;
+    if denom == 0.0 {
+        // 平行：共线时按投影判断重叠
+        if qpx * ry - qpy * rx != 0.0 {
+            return None;
+        }
+        let len2 = rx * rx + ry * ry;
+        let t0 = (qpx * rx + qpy * ry) / len2;
+        let t1 = t0 + (sx * rx + sy * ry) / len2;
+        let (lo, hi) = (t0.min(t1).max(0.0), t0.max(t1).min(1.0));
+        if lo > hi {
+            return None;
+        }
+        return Some(((p1[0] + lo * rx, p1[1] + lo * ry), lo < hi));
+    }
+    let t = (qpx * sy - qpy * sx) / denom;
+    let u = (qpx * ry - qpy * rx) / denom;
+    if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let proper = t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0;
+    Some(((p1[0] + t * rx, p1[1] + t * ry), proper))
+}
+
+/// 环或线自身、以及它们之间的第一个交叉点（坐标已去掉重复顶点）
+/// 同一环或线内不相邻的线段接触即为自相交（首尾闭合处除外），不同环或线之间只有交叉或重叠才算
+fn find_self_intersection(rings: &[Vec<Coord>]) -> Option<(f64, f64)> {
+    struct Segment {
+        ring: usize,
+        index: usize,
+        count: usize,
+        closed: bool,
+        min_x: f64,
+        max_x: f64,
+        min_y: f64,
+        max_y: f64,
+    }
+    let mut segments = Vec::new();
+    for (ring, coords) in rings.iter().enumerate() {
+        let count = coords.len().saturating_sub(1);
+        let closed = count > 1 && same_xy(&coords[0], &coords[count]);
+        for index in 0..count {
+            let (a, b) = (&coords[index], &coords[index + 1]);
+            segments.push(Segment {
+                ring,
+                index,
+                count,
+                closed,
+                min_x: a[0].min(b[0]),
+                max_x: a[0].max(b[0]),
+                min_y: a[1].min(b[1]),
+                max_y: a[1].max(b[1]),
+            });
+        }
+    }
+    segments.sort_by(|a, b| a.min_x.total_cmp(&b.min_x));
+
+    for (i, s) in segments.iter().enumerate() {
+        for t in &segments[i + 1..] {
+            if t.min_x > s.max_x {
+                break;
+            }
+            if t.min_y > s.max_y || t.max_y < s.min_y {
+                continue;
+            }
+            let same_ring = s.ring == t.ring;
+            if same_ring {
+                let (lo, hi) = (s.index.min(t.index), s.index.max(t.index));
+                if hi - lo == 1 || (s.closed && lo == 0 && hi == s.count - 1) {
+                    continue; // 相邻线段共用顶点
+                }
+            }
+            let (a, b) = (&rings[s.ring][s.index], &rings[s.ring][s.index + 1]);
+            let (c, d) = (&rings[t.ring][t.index], &rings[t.ring][t.index + 1]);
+            if let Some((point, proper)) = segment_intersection(a, b, c, d) {
+                if same_ring || proper {
+                    return Some(point);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn ring_label(index: usize) -> String {
+    if index == 0 { "外环".to_string() } else { format!("第 {} 个内环", index) }
+}
+
+/// 检查线或环的顶点（未闭合、重复顶点、点数不足、尖刺），返回去重后的坐标（环为闭合坐标）
+fn check_curve(coords: &[Coord], ring: Option<usize>, issues: &mut Vec<Issue>) -> Vec<Coord> {
+    let label = ring.map_or_else(|| "线".to_string(), ring_label);
+    let closed = ring.is_some();
+    if let (true, Some(first), Some(last)) = (closed, coords.first(), coords.last()) {
+        if !same_xy(first, last) {
+            issues.push(Issue::new(GeometryIssueKind::UnclosedRing, format!("{}首尾不闭合", label), Some(first)));
+        }
+    }
+    let duplicates: Vec<&Coord> = coords.windows(2).filter(|w| same_xy(&w[0], &w[1])).map(|w| &w[1]).collect();
+    if let Some(first) = duplicates.first() {
+        issues.push(Issue::new(
+            GeometryIssueKind::DuplicateVertices,
+            format!("{}有 {} 个连续重复顶点", label, duplicates.len()),
+            Some(first),
+        ));
+    }
+
+    let mut points = dedup(coords, closed);
+    let min_points = if closed { 3 } else { 2 };
+    if points.len() < min_points {
+        issues.push(Issue::new(
+            GeometryIssueKind::TooFewPoints,
+            format!("{}只有 {} 个不同的顶点", label, points.len()),
+            coords.first(),
+        ));
+        return Vec::new();
+    }
+    if let Some(i) = find_spike(&points, closed) {
+        issues.push(Issue::new(GeometryIssueKind::Spike, format!("{}在顶点 {} 处折返", label, i), Some(&points[i])));
+    }
+    if closed {
+        points.push(points[0]);
+    }
+    points
+}
+
+fn check_polygon(handle: OGRGeometryH, issues: &mut Vec<Issue>) {
+    let count = unsafe { gdal_sys::OGR_G_GetGeometryCount(handle) };
+    let mut rings = Vec::new();
+    let mut shell_area = None;
+    for i in 0..count as usize {
+        let ring = unsafe { gdal_sys::OGR_G_GetGeometryRef(handle, i as i32) };
+        let coords = check_curve(&read_coords(ring), Some(i), issues);
+        if coords.is_empty() {
+            continue;
+        }
+        if i == 0 {
+            shell_area = Some(signed_area(&coords));
+        } else if let Some(shell_area) = shell_area {
+            if shell_area.signum() == signed_area(&coords).signum() {
+                issues.push(Issue::new(
+                    GeometryIssueKind::RingOrientation,
+                    format!("{}与外环方向相同", ring_label(i)),
+                    coords.first(),
+                ));
+            }
+        }
+        rings.push(coords);
+    }
+    if let Some((x, y)) = find_self_intersection(&rings) {
+        issues.push(Issue {
+            kind: GeometryIssueKind::SelfIntersection,
+            reason: "环自相交或环之间交叉".to_string(),
+            location: Some((x, y)),
+        });
+    }
+}
+
+fn check_handle(handle: OGRGeometryH, issues: &mut Vec<Issue>) {
+    if is_collection(handle) {
+        for i in 0..unsafe { gdal_sys::OGR_G_GetGeometryCount(handle) } {
+            check_handle(unsafe { gdal_sys::OGR_G_GetGeometryRef(handle, i) }, issues);
+        }
+        return;
+    }
+    match flat_type(handle) {
+        OGRwkbGeometryType::wkbPolygon => check_polygon(handle, issues),
+        OGRwkbGeometryType::wkbLineString => {
+            check_curve(&read_coords(handle), None, issues);
+        }
+        _ => {}
+    }
+}
+
+fn collect_self_intersections(handle: OGRGeometryH, points: &mut Vec<(f64, f64)>) {
+    match flat_type(handle) {
+        OGRwkbGeometryType::wkbPolygon => {
+            let rings: Vec<Vec<Coord>> = (0..unsafe { gdal_sys::OGR_G_GetGeometryCount(handle) })
+                .map(|i| dedup(&read_coords(unsafe { gdal_sys::OGR_G_GetGeometryRef(handle, i) }), false))
+                .filter(|ring| ring.len() >= 4)
+                .collect();
+            points.extend(find_self_intersection(&rings));
+        }
+        OGRwkbGeometryType::wkbLineString => {
+            points.extend(find_self_intersection(&[dedup(&read_coords(handle), false)]));
+        }
+        // 多线的各部件之间交叉也算作自相交
+        OGRwkbGeometryType::wkbMultiLineString => {
+            let lines: Vec<Vec<Coord>> = (0..unsafe { gdal_sys::OGR_G_GetGeometryCount(handle) })
+                .map(|i| dedup(&read_coords(unsafe { gdal_sys::OGR_G_GetGeometryRef(handle, i) }), false))
+                .collect();
+            points.extend(find_self_intersection(&lines));
+        }
+        _ if is_collection(handle) => {
+            for i in 0..unsafe { gdal_sys::OGR_G_GetGeometryCount(handle) } {
+                collect_self_intersections(unsafe { gdal_sys::OGR_G_GetGeometryRef(handle, i) }, points);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 线和面的自相交位置：每个面、线或多线各取第一个交叉点
+pub fn self_intersections(geom: &Geometry) -> Vec<(f64, f64)> {
+    let mut points = Vec::new();
+    collect_self_intersections(unsafe { geom.c_geometry() }, &mut points);
+    points
+}
+
+/// 检查几何（曲线几何需先线性化），返回发现的全部问题
+/// 逐环检查顶点和自相交后，再由 GEOS 判断其他无效情况
+pub fn check_geometry(geom: &Geometry) -> Vec<Issue> {
+    if geom.is_empty() {
+        return vec![Issue::new(GeometryIssueKind::EmptyGeometry, "几何为空".to_string(), None)];
+    }
+    let mut issues = Vec::new();
+    check_handle(unsafe { geom.c_geometry() }, &mut issues);
+
+    // 环未闭合或点数不足时无法转换为 GEOS 几何，自相交已在上面找到
+    let structural = issues.iter().any(|issue| {
+        matches!(
+            issue.kind,
+            GeometryIssueKind::UnclosedRing | GeometryIssueKind::TooFewPoints | GeometryIssueKind::SelfIntersection
+        )
+    });
+    if !structural && !geom.is_valid() {
+        issues.push(Issue {
+            kind: GeometryIssueKind::Invalid,
+            reason: "几何无效（如内环位于外环之外、内环嵌套或多面的部件重叠）".to_string(),
+            location: None,
+        });
+    }
+    issues
+}
+
+/// 清理面：去掉重复顶点和尖刺、闭合环、去掉退化的环，内环方向调整为与外环相反
+/// 外环退化时清空整个面
+fn clean_polygon(handle: OGRGeometryH, spikes: bool) {
+    let count = unsafe { gdal_sys::OGR_G_GetGeometryCount(handle) };
+    let mut shell_area = 0.0;
+    for i in (0..count).rev() {
+        let ring = unsafe { gdal_sys::OGR_G_GetGeometryRef(handle, i) };
+        let mut points = dedup(&read_coords(ring), true);
+        if spikes {
+            points = remove_spikes(points, true);
+        }
+        if points.len() < 3 {
+            if i == 0 {
+                unsafe { gdal_sys::OGR_G_Empty(handle) };
+                return;
+            }
+            unsafe { gdal_sys::OGR_G_RemoveGeometry(handle, i, 1) };
+            continue;
+        }
+        points.push(points[0]);
+        write_coords(ring, &points);
+        if i == 0 {
+            shell_area = signed_area(&points);
+        }
+    }
+    for i in 1..unsafe { gdal_sys::OGR_G_GetGeometryCount(handle) } {
+        let ring = unsafe { gdal_sys::OGR_G_GetGeometryRef(handle, i) };
+        let mut points = read_coords(ring);
+        if signed_area(&points).signum() == shell_area.signum() {
+            points.reverse();
+            write_coords(ring, &points);
+        }
+    }
+}
+
+fn clean_handle(handle: OGRGeometryH, spikes: bool) {
+    if is_collection(handle) {
+        let count = unsafe { gdal_sys::OGR_G_GetGeometryCount(handle) };
+        for i in (0..count).rev() {
+            let part = unsafe { gdal_sys::OGR_G_GetGeometryRef(handle, i) };
+            clean_handle(part, spikes);
+            if unsafe { gdal_sys::OGR_G_IsEmpty(part) } != 0 {
+                unsafe { gdal_sys::OGR_G_RemoveGeometry(handle, i, 1) };
+            }
+        }
+        return;
+    }
+    match flat_type(handle) {
+        OGRwkbGeometryType::wkbPolygon => clean_polygon(handle, spikes),
+        OGRwkbGeometryType::wkbLineString => {
+            let mut points = dedup(&read_coords(handle), false);
+            if spikes {
+                points = remove_spikes(points, false);
+            }
+            if points.len() < 2 {
+                unsafe { gdal_sys::OGR_G_Empty(handle) };
+            } else {
+                write_coords(handle, &points);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 修复几何：先清理顶点（重复顶点、未闭合环、尖刺、内环方向），仍无效时按修复方式处理
+/// 结果只保留与原几何同维度的部分；几何退化为空时返回 None
+pub fn repair_geometry(geom: &Geometry, method: RepairMethod, spikes: bool) -> Result<Option<Geometry>> {
+    let dimension = geometry::topological_dimension(geom);
+    let cleaned = geom.clone();
+    clean_handle(unsafe { cleaned.c_geometry() }, spikes);
+    if cleaned.is_empty() {
+        return Ok(None);
+    }
+    if cleaned.is_valid() {
+        return Ok(Some(cleaned));
+    }
+
+    let fixed = match method {
+        RepairMethod::Buffer if dimension == 2 => cleaned.buffer(0.0, 8),
+        _ => cleaned.make_valid(&CslStringList::new()),
+    }
+    .map_err(|e| AppError::InvalidFormat(format!("几何修复失败: {}", e)))?;
+    geometry::overlay_parts(&fixed, dimension)
+}
@@ -21,9 +21,26 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_process::init())
+        // 矢量瓦片协议：mvt://localhost/{瓦片源ID}/{z}/{x}/{y}.pbf
+        .register_asynchronous_uri_scheme_protocol(services::tile_service::TILE_SCHEME, |_ctx, request, responder| {
+            let path = request.uri().path().to_string();
+            tauri::async_runtime::spawn(async move {
+                responder.respond(commands::tiles::tile_response(&path).await);
+            });
+        })
         .setup(|app| {
             log::info!("应用启动 - 配置窗口状态");
             
+            // 加载已保存的PostGIS连接
+            if let Ok(config_dir) = app.path().app_config_dir() {
+                services::postgis_service::init(config_dir);
+            }
+            
+            // 矢量瓦片磁盘缓存目录
+            if let Ok(cache_dir) = app.path().app_cache_dir() {
+                services::tile_service::init(cache_dir);
+            }
+            
             // 立即强制隐藏主窗口，防止短暂显示
             if let Some(main_window) = app.get_webview_window("main") {
                 log::info!("强制隐藏主窗口");
@@ -69,9 +86,65 @@ pub fn run() {
             commands::gdal::gdal_open_multi_layer_vector,
             commands::gdal::gdal_get_geojson,
             commands::gdal::gdal_get_layer_geojson,
+            commands::gdal::gdal_stream_layer_geojson,
             commands::gdal::gdal_get_attribute_table,
+            commands::gdal::gdal_field_statistics,
             commands::gdal::gdal_diagnose,
             commands::gdal::gdal_export_vector,
+            commands::gdal::gdal_get_export_drivers,
+            commands::gdal::gdal_batch_convert,
+            commands::gdal::gdal_transform_coordinates,
+            commands::gdal::gdal_get_gdb_metadata,
+            commands::postgis::postgis_list_connections,
+            commands::postgis::postgis_save_connection,
+            commands::postgis::postgis_delete_connection,
+            commands::postgis::postgis_test_connection,
+            commands::postgis::postgis_list_schemas,
+            commands::postgis::postgis_list_tables,
+            commands::postgis::postgis_apply_edits,
+            commands::postgis::postgis_export_layer,
+            commands::datastore::datastore_create,
+            commands::datastore::datastore_create_table,
+            commands::datastore::datastore_list_contents,
+            commands::datastore::datastore_import_layer,
+            commands::datastore::datastore_export_table,
+            commands::datastore::datastore_save_style,
+            commands::datastore::datastore_list_styles,
+            commands::datastore::datastore_get_default_style,
+            commands::datastore::datastore_delete_style,
+            commands::job::job_cancel,
+            commands::job::job_list,
+            commands::job::worker_get_status,
+            commands::job::worker_set_concurrency,
+            commands::tiles::tiles_register_source,
+            commands::tiles::tiles_unregister_source,
+            commands::tiles::tiles_clear_cache,
+            commands::tiles::tiles_list_sources,
+            commands::spatial::spatial_index_build,
+            commands::spatial::spatial_index_invalidate,
+            commands::spatial::spatial_query_extent,
+            commands::spatial::spatial_identify,
+            commands::spatial::spatial_measure,
+            commands::selection::selection_select,
+            commands::selection::selection_set_fids,
+            commands::selection::selection_get_fids,
+            commands::selection::selection_list,
+            commands::selection::selection_clear,
+            commands::analysis::analysis_spatial_join,
+            commands::analysis::analysis_attribute_join_check,
+            commands::analysis::analysis_attribute_join,
+            commands::analysis::analysis_clip,
+            commands::analysis::analysis_erase,
+            commands::analysis::analysis_split,
+            commands::analysis::analysis_dissolve,
+            commands::analysis::analysis_merge,
+            commands::analysis::analysis_validate_geometries,
+            commands::analysis::analysis_repair_geometries,
+            commands::analysis::analysis_check_topology,
+            commands::analysis::analysis_add_geometry_attributes,
+            commands::analysis::analysis_near,
+            commands::analysis::analysis_create_grid,
+            commands::analysis::analysis_sample_points,
             commands::file::file_exists,
             commands::window::close_splashscreen,
             commands::fs::read_directory_unrestricted,
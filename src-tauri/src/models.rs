@@ -10,6 +10,8 @@ pub struct Project {
     pub map_config: MapConfig,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub selections: Vec<SelectionSet>, // 各图层的选择集
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +25,8 @@ pub struct Layer {
     pub style: LayerStyle,
     pub extent: Option<Extent>,
     pub attributes: Option<Vec<AttributeField>>,
+    #[serde(default)]
+    pub joins: Vec<AttributeJoin>, // 虚拟属性连接，读取图层时附加表格字段
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +53,10 @@ pub enum SourceType {
     Shapefile,
     GeoJson,
     PostGIS,
+    #[serde(rename = "geopackage")]
+    GeoPackage,
+    #[serde(rename = "spatialite")]
+    SpatiaLite,
     WMS,
     XYZ,
 }
@@ -73,12 +81,70 @@ pub struct Extent {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttributeField {
     pub name: String,
-    pub field_type: String,
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub width: Option<i32>, // 0 或未设置时为空
+    #[serde(default)]
+    pub precision: Option<i32>,
     pub alias: Option<String>,
+    #[serde(default = "default_true")]
+    pub nullable: bool,
+    #[serde(default)]
+    pub unique: bool,
+    #[serde(default)]
+    pub default_value: Option<String>, // OGR 默认值表达式（如 'abc'、CURRENT_TIMESTAMP）
+    #[serde(default)]
+    pub domain: Option<String>, // 属性域名称（GDB、GeoPackage）
     pub editable: bool,
     pub visible: bool,
 }
 
+fn default_true() -> bool {
+    true
+}
+
+// 字段类型（OGR 字段类型及子类型）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    #[serde(alias = "text")]
+    String,
+    #[serde(alias = "int")]
+    Integer,
+    #[serde(alias = "smallint")]
+    Int16,
+    #[serde(alias = "bigint")]
+    Integer64,
+    #[serde(alias = "double", alias = "float")]
+    Real,
+    Float32,
+    #[serde(alias = "bool")]
+    Boolean,
+    Date,
+    Time,
+    #[serde(rename = "datetime")]
+    DateTime,
+    #[serde(alias = "blob")]
+    Binary,
+    Json,
+    Uuid,
+    IntegerList,
+    Integer64List,
+    RealList,
+    StringList,
+    #[serde(other)]
+    Unknown,
+}
+
+impl FieldType {
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            FieldType::Integer | FieldType::Int16 | FieldType::Integer64 | FieldType::Real | FieldType::Float32
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MapConfig {
     pub center: [f64; 2],
@@ -101,15 +167,62 @@ pub struct Geometry {
     pub coordinates: serde_json::Value,
 }
 
+// 坐标维度（是否带Z值/M值）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoordinateDimension {
+    #[serde(rename = "XY")]
+    Xy,
+    #[serde(rename = "XYZ")]
+    Xyz,
+    #[serde(rename = "XYM")]
+    Xym,
+    #[serde(rename = "XYZM")]
+    Xyzm,
+}
+
+impl CoordinateDimension {
+    pub fn from_flags(has_z: bool, has_m: bool) -> Self {
+        match (has_z, has_m) {
+            (false, false) => CoordinateDimension::Xy,
+            (true, false) => CoordinateDimension::Xyz,
+            (false, true) => CoordinateDimension::Xym,
+            (true, true) => CoordinateDimension::Xyzm,
+        }
+    }
+
+    pub fn has_z(&self) -> bool {
+        matches!(self, CoordinateDimension::Xyz | CoordinateDimension::Xyzm)
+    }
+
+    pub fn has_m(&self) -> bool {
+        matches!(self, CoordinateDimension::Xym | CoordinateDimension::Xyzm)
+    }
+}
+
+// 读取/导出时对Z值、M值的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DimensionMode {
+    #[default]
+    #[serde(rename = "preserve")]
+    Preserve, // 保留原始维度
+    #[serde(rename = "drop_m")]
+    DropM, // 去掉M值，保留Z值
+    #[serde(rename = "force_2d")]
+    Force2D, // 强制二维
+}
+
 // 矢量文件信息（由 GDAL 提供）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VectorInfo {
     pub path: String,
     pub feature_count: usize,
     pub geometry_type: String,
+    pub coordinate_dimension: CoordinateDimension,
     pub fields: Vec<AttributeField>,
     pub extent: Extent,
     pub projection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vertical_crs: Option<String>, // 垂直坐标系名称（复合坐标系时）
 }
 
 // 多图层矢量文件信息（用于KML、GDB等多图层格式）
@@ -128,11 +241,1137 @@ pub struct LayerInfo {
     pub index: usize,
     pub feature_count: usize,
     pub geometry_type: String,
+    pub coordinate_dimension: CoordinateDimension,
     pub fields: Vec<AttributeField>,
     pub extent: Extent,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub vertical_crs: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub feature_dataset: Option<String>, // 所属的要素集名称（用于GDB）
 }
 
 // 向后兼容的别名
 pub type ShapefileInfo = VectorInfo;
+
+// PostGIS 数据库连接
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostgisConnection {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub database: String,
+    pub username: String,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub save_password: bool, // 是否将密码保存到系统钥匙串（连接配置文件不含密码）
+    #[serde(default)]
+    pub ssl_mode: Option<String>,
+}
+
+// PostGIS 空间表/视图信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostgisTableInfo {
+    pub schema: String,
+    pub table: String,
+    pub geometry_column: String,
+    pub geometry_type: String,
+    pub coordinate_dimension: CoordinateDimension,
+    pub srid: i32,
+    pub is_view: bool,
+    pub path: String, // 供 gdal_* 命令读取的图层引用（postgis:连接ID/模式.表名）
+}
+
+// 要素编辑集合（几何为 WGS84 GeoJSON）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeatureEdits {
+    #[serde(default)]
+    pub inserts: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub updates: Vec<serde_json::Value>, // 需包含要素 id
+    #[serde(default)]
+    pub deletes: Vec<u64>,
+}
+
+// 编辑结果统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EditResult {
+    pub inserted: usize,
+    pub updated: usize,
+    pub deleted: usize,
+}
+
+// 数据库型数据源类型（GeoPackage / SpatiaLite）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DataStoreKind {
+    #[serde(rename = "geopackage")]
+    GeoPackage,
+    #[serde(rename = "spatialite")]
+    SpatiaLite,
+}
+
+// 新建表的字段定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableFieldSchema {
+    pub name: String,
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub width: Option<i32>,
+    #[serde(default)]
+    pub precision: Option<i32>,
+    #[serde(default)]
+    pub alias: Option<String>,
+    #[serde(default = "default_true")]
+    pub nullable: bool,
+    #[serde(default)]
+    pub unique: bool,
+    #[serde(default)]
+    pub default_value: Option<String>,
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+// 新建表的结构定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSchema {
+    pub name: String,
+    pub geometry_type: Option<String>, // 如 Point、MultiPolygonZ；为空时创建属性表
+    #[serde(default)]
+    pub geometry_column: Option<String>,
+    #[serde(default)]
+    pub crs: Option<String>, // EPSG代码、WKT或PROJ.4
+    #[serde(default)]
+    pub fields: Vec<TableFieldSchema>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+// 数据库中的表（要素表或属性表）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataStoreTable {
+    pub name: String,
+    pub index: usize,
+    pub is_spatial: bool,
+    pub geometry_type: Option<String>,
+    pub geometry_column: Option<String>,
+    pub coordinate_dimension: CoordinateDimension,
+    pub projection: Option<String>,
+    pub feature_count: usize,
+    pub fields: Vec<AttributeField>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+// 数据库内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataStoreInfo {
+    pub path: String,
+    pub kind: DataStoreKind,
+    pub tables: Vec<DataStoreTable>,
+    pub style_count: usize, // layer_styles 中保存的样式数量
+}
+
+// 保存在 layer_styles 表中的图层样式（兼容 QGIS 的表结构）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredLayerStyle {
+    pub id: u64,
+    pub table_name: String,
+    pub geometry_column: Option<String>,
+    pub style_name: String,
+    pub use_as_default: bool,
+    pub description: Option<String>,
+    pub style: Option<LayerStyle>, // MiniGIS 样式（其他软件写入的样式为空）
+    pub update_time: Option<String>,
+}
+
+// 属性域类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldDomainKind {
+    Coded, // 编码值域
+    Range, // 范围域
+    Glob,  // 通配符域（GeoPackage）
+}
+
+// 编码值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodedValue {
+    pub code: String,
+    pub description: Option<String>,
+}
+
+// 属性域（GDB 的编码值域、范围域）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDomain {
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: FieldDomainKind,
+    pub field_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coded_values: Vec<CodedValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub min_inclusive: bool,
+    #[serde(default)]
+    pub max_inclusive: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub glob: Option<String>,
+}
+
+// 子类型
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subtype {
+    pub code: i64,
+    pub name: String,
+    pub field_domains: HashMap<String, String>,  // 字段名 -> 该子类型使用的属性域
+    pub default_values: HashMap<String, String>, // 字段名 -> 默认值
+}
+
+// GDB 要素类/表的元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GdbLayerMetadata {
+    pub name: String,
+    pub index: usize,
+    pub alias: Option<String>,
+    pub feature_dataset: Option<String>,
+    pub fields: Vec<AttributeField>,
+    pub subtype_field: Option<String>,
+    pub default_subtype: Option<i64>,
+    pub subtypes: Vec<Subtype>,
+}
+
+// 要素集
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureDatasetInfo {
+    pub name: String,
+    pub layers: Vec<String>,
+}
+
+// 关系类
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelationshipClass {
+    pub name: String,
+    pub cardinality: String,       // one_to_one / one_to_many / many_to_one / many_to_many
+    pub relationship_type: String, // composite / association / aggregation
+    pub origin_table: String,
+    pub destination_table: String,
+    pub mapping_table: Option<String>, // 多对多关系的中间表
+    pub origin_fields: Vec<String>,
+    pub destination_fields: Vec<String>,
+    pub forward_label: Option<String>,
+    pub backward_label: Option<String>,
+}
+
+// 文件地理数据库元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GdbMetadata {
+    pub path: String,
+    pub feature_datasets: Vec<FeatureDatasetInfo>,
+    pub domains: Vec<FieldDomain>,
+    pub relationships: Vec<RelationshipClass>,
+    pub layers: Vec<GdbLayerMetadata>,
+}
+
+// 导出字段映射（源字段 -> 目标字段）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldMapping {
+    pub source: String,
+    #[serde(default)]
+    pub target: Option<String>, // 目标字段名，为空时沿用源字段名
+    #[serde(default)]
+    pub field_type: Option<FieldType>, // 目标字段类型，为空时沿用源字段类型
+    #[serde(default)]
+    pub width: Option<i32>,
+    #[serde(default)]
+    pub precision: Option<i32>,
+}
+
+// 矢量导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    pub layer_name: Option<String>, // 输出图层名，为空时沿用源图层名
+    pub dimension: DimensionMode,
+    pub dataset_options: Vec<String>, // 数据源创建选项，如 "SPATIALITE=YES"
+    pub layer_options: Vec<String>,   // 图层创建选项，如 "ENCODING=UTF-8"
+    pub field_mapping: Vec<FieldMapping>, // 为空时导出全部字段；可用于选择字段子集并重命名
+    pub target_crs: Option<String>, // 目标坐标系（EPSG代码、WKT或PROJ.4），为空时沿用源坐标系
+    pub where_clause: Option<String>, // 属性过滤条件（OGR SQL WHERE 语法）
+    pub extent: Option<Extent>, // 空间范围（WGS84经纬度），只导出与范围相交的要素
+    pub clip_to_extent: bool, // 按空间范围裁剪几何
+    pub fids: Option<Vec<u64>>, // 只导出指定要素
+    pub selection: Option<String>, // 只导出该图层ID的选择集中的要素（与 fids 同时指定时取交集）
+    pub promote_to_multi: bool, // 单部件几何提升为多部件（Polygon -> MultiPolygon 等）
+}
+
+// 导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportResult {
+    pub output_path: String,
+    pub driver: String,
+    pub layer_name: String,
+    pub features_written: u64,
+    pub features_failed: u64,
+}
+
+// 可写入的矢量驱动
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportDriver {
+    pub name: String,
+    pub long_name: String,
+    pub extensions: Vec<String>,
+}
+
+// 批量转换请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchConvertRequest {
+    pub source: String, // 文件夹或多图层数据源（GDB、GPKG、KML等）
+    pub output: String, // 输出目录；container 为 true 时为输出文件
+    pub format: String,
+    #[serde(default)]
+    pub container: bool, // 所有图层写入同一个数据源（如一个 GPKG）
+    #[serde(default)]
+    pub recursive: bool, // 遍历文件夹时包含子文件夹
+    #[serde(default)]
+    pub layers: Option<Vec<String>>, // 只转换指定名称的图层
+    #[serde(default)]
+    pub options: ExportOptions, // 应用于每个图层（layer_name 除外）
+}
+
+// 批量转换中单个图层的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub source: String,
+    pub layer_index: Option<usize>,
+    pub layer_name: Option<String>,
+    pub output_path: String,
+    pub output_layer: Option<String>,
+    pub success: bool,
+    pub features_written: u64,
+    pub features_failed: u64,
+    pub error: Option<String>,
+}
+
+// 批量转换进度（通过 batch-progress 事件推送）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub item: BatchItemResult,
+}
+
+// 批量转换汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchConvertReport {
+    pub output: String,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub features_written: u64,
+    pub items: Vec<BatchItemResult>,
+}
+
+// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+// 任务进度（通过 job-progress 事件推送）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobProgress {
+    pub job_id: String,
+    pub kind: String, // export / batch_convert / read_layer 等
+    pub status: JobStatus,
+    pub percent: Option<f64>, // 总数未知时为空
+    pub processed: u64,       // 已处理要素数
+    pub total: Option<u64>,
+    pub elapsed_seconds: f64,
+    pub eta_seconds: Option<f64>, // 预计剩余时间
+    pub error: Option<String>,
+}
+
+// 正在运行的任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobInfo {
+    pub job_id: String,
+    pub kind: String,
+    pub elapsed_seconds: f64,
+    pub cancelled: bool,
+}
+
+// GDAL工作线程池状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerPoolStatus {
+    pub concurrency: usize, // 最多同时执行的GDAL任务数
+    pub active: usize,      // 正在执行的任务数
+    pub queued: usize,      // 等待空闲线程的任务数
+}
+
+// 图层空间索引信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpatialIndexInfo {
+    pub path: String,
+    pub layer_index: usize,
+    pub features: usize,         // 已索引的要素数（不含空几何）
+    pub extent: Option<Extent>,  // 图层坐标系下的范围
+    pub build_ms: u64,           // 构建耗时（毫秒）
+}
+
+// 要素识别的图层
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentifyLayer {
+    pub layer_id: String,           // 项目图层ID，原样返回
+    pub path: String,
+    pub layer_index: Option<usize>,
+}
+
+// 要素识别请求（坐标为地图坐标系）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentifyRequest {
+    pub x: f64,
+    pub y: f64,
+    pub crs: String,                 // 地图坐标系，如 "EPSG:4490"
+    pub resolution: f64,             // 地图分辨率（地图单位/像素），用于将像素容差换算为地面距离
+    pub tolerance: Option<f64>,      // 像素容差，默认 3
+    pub layers: Vec<IdentifyLayer>,  // 按图层从上到下的顺序
+    pub max_features: Option<usize>, // 每个图层最多返回的要素数，默认 50
+    pub decode_domains: Option<bool>,
+}
+
+// 单个图层的识别结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentifyLayerResult {
+    pub layer_id: String,
+    pub layer_name: String,
+    pub features: Vec<serde_json::Value>, // WGS84 GeoJSON 要素（全部属性），按与识别点的距离排序
+    pub truncated: bool,                  // 命中要素超过 max_features
+    pub error: Option<String>,            // 该图层识别失败的原因（不影响其他图层）
+}
+
+// 选择方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionMode {
+    #[default]
+    New,       // 新建选择集
+    Add,       // 添加到当前选择集
+    Remove,    // 从当前选择集中移除
+    Intersect, // 与当前选择集取交集
+}
+
+// 空间关系（待选要素 与 参照几何）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpatialRelation {
+    #[default]
+    Intersects,
+    Within,   // 完全位于参照几何内
+    Contains, // 完全包含参照几何
+    Touches,
+    Crosses,
+    Overlaps,
+}
+
+// 选择条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SelectionQuery {
+    // 矩形框选，crs 为范围所在坐标系（默认 WGS84）
+    Rectangle {
+        extent: Extent,
+        crs: Option<String>,
+        relation: Option<SpatialRelation>,
+    },
+    // 多边形选择，geometry 为 GeoJSON 几何
+    Polygon {
+        geometry: serde_json::Value,
+        crs: Option<String>,
+        relation: Option<SpatialRelation>,
+    },
+    // 属性条件（OGR SQL WHERE 语法）
+    Expression {
+        where_clause: String,
+    },
+    // 按与参照图层要素的空间关系选择
+    Spatial {
+        path: String,
+        layer_index: Option<usize>,
+        selection: Option<String>, // 参照图层ID，只使用其选择集中的要素
+        relation: Option<SpatialRelation>,
+    },
+}
+
+// 图层选择集（保存在项目中）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectionSet {
+    pub layer_id: String,
+    pub path: String,
+    pub layer_index: usize,
+    pub fids: Vec<u64>,
+}
+
+// 选择集概要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectionSummary {
+    pub layer_id: String,
+    pub path: String,
+    pub layer_index: usize,
+    pub count: usize,
+}
+
+// 长度单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LengthUnit {
+    #[default]
+    #[serde(rename = "m")]
+    Meter,
+    #[serde(rename = "km")]
+    Kilometer,
+}
+
+// 面积单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AreaUnit {
+    #[default]
+    #[serde(rename = "m2")]
+    SquareMeter,
+    #[serde(rename = "km2")]
+    SquareKilometer,
+    #[serde(rename = "ha")]
+    Hectare,
+    #[serde(rename = "mu")]
+    Mu, // 亩（1 亩 = 10000/15 平方米）
+}
+
+// 量算请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasureRequest {
+    pub geometry: serde_json::Value, // GeoJSON 几何
+    #[serde(default)]
+    pub crs: Option<String>, // 几何的坐标系（如 "EPSG:4527"），默认 WGS84 经纬度
+    #[serde(default)]
+    pub planar_crs: Option<String>, // 平面量算使用的投影坐标系，为空时只做椭球面量算
+    #[serde(default)]
+    pub length_unit: LengthUnit,
+    #[serde(default)]
+    pub area_unit: AreaUnit,
+}
+
+// 量算值：线的长度、面的面积和周长（含内环），多部件几何为各部件之和
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MeasureValues {
+    pub length: f64,
+    pub area: f64,
+    pub perimeter: f64,
+}
+
+// 量算结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasureResult {
+    pub geodesic: MeasureValues,        // CGCS2000/WGS84 椭球面
+    pub planar: Option<MeasureValues>,  // planar_crs 投影平面
+    pub length_unit: LengthUnit,
+    pub area_unit: AreaUnit,
+}
+
+// 字段统计（数值字段计算最小值、最大值、总和、平均值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldStatistics {
+    pub field: String,
+    pub count: u64,      // 非空值个数
+    pub null_count: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub sum: Option<f64>,
+    pub mean: Option<f64>,
+}
+
+// 分析工具的输入图层
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisInput {
+    pub path: String,
+    #[serde(default)]
+    pub layer_index: Option<usize>,
+    #[serde(default)]
+    pub selection: Option<String>, // 图层ID，只处理其选择集中的要素
+}
+
+// 分析工具的输出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisOutput {
+    pub path: String,
+    #[serde(default)]
+    pub format: Option<String>,     // 为空时按扩展名选择驱动
+    #[serde(default)]
+    pub layer_name: Option<String>, // 为空时使用输出文件名
+}
+
+// 空间连接的匹配方式（待连接要素 与 连接要素）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinPredicate {
+    #[default]
+    Intersects,
+    Within,   // 待连接要素位于连接要素内（如点落在区划内）
+    Contains, // 待连接要素包含连接要素（如区划内的点）
+    Nearest,  // 最近的一个连接要素（可限定搜索距离）
+}
+
+// 连接方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinCardinality {
+    #[default]
+    OneToOne,  // 每个待连接要素输出一个要素，多个匹配按规则汇总
+    OneToMany, // 每个匹配输出一个要素
+}
+
+// 字段汇总方式（一对一空间连接、融合）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldAggregation {
+    #[default]
+    First,
+    Count,
+    Sum,
+    Mean,
+    Min,
+    Max,
+}
+
+// 输出字段及其汇总方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregationRule {
+    pub field: String,
+    #[serde(default)]
+    pub aggregation: FieldAggregation,
+    #[serde(default)]
+    pub output_name: Option<String>, // 默认为 字段名（First）或 字段名_汇总方式
+}
+
+// 空间连接请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpatialJoinRequest {
+    pub target: AnalysisInput, // 待连接图层（输出几何和属性来自该图层）
+    pub join: AnalysisInput,   // 连接图层
+    #[serde(default)]
+    pub predicate: JoinPredicate,
+    #[serde(default)]
+    pub distance: Option<f64>, // 最近要素的搜索距离（连接图层坐标系单位），为空时不限距离
+    #[serde(default)]
+    pub cardinality: JoinCardinality,
+    #[serde(default)]
+    pub fields: Vec<AggregationRule>, // 连接字段，为空时连接全部字段（一对一取第一个匹配要素）
+    #[serde(default = "default_true")]
+    pub keep_all: bool, // 保留没有匹配的待连接要素
+    pub output: AnalysisOutput,
+}
+
+// 空间连接结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpatialJoinResult {
+    #[serde(flatten)]
+    pub output: ExportResult,
+    pub matched: u64,   // 有匹配的待连接要素数
+    pub unmatched: u64, // 没有匹配的待连接要素数
+}
+
+// 叠加分析（裁剪、擦除）请求：掩膜图层中的面与输入图层叠加
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayRequest {
+    pub input: AnalysisInput,
+    pub mask: AnalysisInput, // 面图层，只使用其中的面几何
+    pub output: AnalysisOutput,
+}
+
+// 按掩膜面分割图层的请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitRequest {
+    pub input: AnalysisInput,
+    pub mask: AnalysisInput,
+    pub name_field: String, // 掩膜图层中用于命名输出的字段，值相同的掩膜面合并输出
+    pub output_dir: String,
+    pub format: String,
+}
+
+// 分割输出的单个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitOutput {
+    pub name: String, // 掩膜字段值
+    #[serde(flatten)]
+    pub output: ExportResult,
+}
+
+// 分割结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitResult {
+    pub output_dir: String,
+    pub outputs: Vec<SplitOutput>,
+    pub empty: Vec<String>, // 范围内没有要素的掩膜字段值（不生成文件）
+}
+
+// 融合请求：合并融合字段值相同的要素
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DissolveRequest {
+    pub input: AnalysisInput,
+    #[serde(default)]
+    pub fields: Vec<String>, // 融合字段，为空时全部要素合并为一个
+    #[serde(default)]
+    pub statistics: Vec<AggregationRule>, // 其他字段的汇总
+    #[serde(default = "default_true")]
+    pub multipart: bool, // false 时融合结果的每个部件输出为单独的要素
+    pub output: AnalysisOutput,
+}
+
+// 合并的输入图层
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeInput {
+    #[serde(flatten)]
+    pub input: AnalysisInput,
+    #[serde(default)]
+    pub field_map: HashMap<String, String>, // 源字段名 -> 输出字段名，映射为空时不输出该字段
+}
+
+// 合并请求：多个图层合并为一个输出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeRequest {
+    pub inputs: Vec<MergeInput>,
+    #[serde(default)]
+    pub source_field: Option<String>, // 记录要素来源图层名的字段
+    pub output: AnalysisOutput,
+}
+
+// 合并结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeResult {
+    #[serde(flatten)]
+    pub output: ExportResult,
+    pub fields: Vec<TableFieldSchema>, // 合并后的字段定义
+    pub widened: Vec<String>,          // 各输入类型不一致、已放宽类型的字段
+}
+
+// 几何问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeometryIssueKind {
+    SelfIntersection,  // 自相交（环自身交叉或环之间交叉）
+    RingOrientation,   // 内环与外环方向相同
+    DuplicateVertices, // 连续重复顶点
+    UnclosedRing,      // 环首尾不闭合
+    TooFewPoints,      // 点数不足以构成线或环
+    Spike,             // 尖刺（折返的顶点）
+    EmptyGeometry,
+    Invalid, // 其他无效情况（如内环在外环之外、多面的部件重叠）
+}
+
+// 几何问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeometryIssue {
+    pub fid: Option<u64>,
+    pub kind: GeometryIssueKind,
+    pub reason: String,
+    pub location: Option<[f64; 2]>, // 问题位置（经纬度）
+}
+
+// 几何检查请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationRequest {
+    pub input: AnalysisInput,
+    #[serde(default)]
+    pub max_issues: Option<usize>, // 最多返回的问题数，默认 10000
+}
+
+// 几何检查报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub features_checked: u64,
+    pub invalid_features: u64,
+    pub issues: Vec<GeometryIssue>,
+    pub truncated: bool, // 问题数超过 max_issues，未全部返回
+}
+
+// 几何修复方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairMethod {
+    #[default]
+    MakeValid, // 保留全部顶点，自相交处拆分
+    Buffer,    // 零距离缓冲，自相交的面可能丢失部分
+}
+
+// 几何修复请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairRequest {
+    pub input: AnalysisInput,
+    #[serde(default)]
+    pub method: RepairMethod,
+    #[serde(default = "default_true")]
+    pub remove_spikes: bool,
+    #[serde(default)]
+    pub output: Option<AnalysisOutput>, // 为空时原位修复，写回输入图层
+}
+
+// 几何修复结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairResult {
+    pub features_checked: u64,
+    pub repaired: u64,
+    pub unrepaired: Vec<u64>, // 修复失败、退化为空或原位修复时拆分为多个部件（单部件图层）的要素，保持原样
+    pub output: Option<ExportResult>, // 原位修复时为空
+}
+
+// 拓扑规则类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopologyRuleKind {
+    MustNotOverlap,       // 面之间不能重叠
+    MustNotHaveGaps,      // 面之间不能有空隙（图层外边界除外）
+    MustBeCoveredBy,      // 要素必须被覆盖图层的面覆盖
+    MustNotHaveDangles,   // 线的端点必须与其他线相连
+    MustNotSelfIntersect, // 线和面不能自相交
+}
+
+// 拓扑规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyRule {
+    #[serde(default)]
+    pub id: Option<String>, // 规则ID，写入错误图层，默认为 rule_序号
+    pub rule: TopologyRuleKind,
+    pub layer: AnalysisInput,
+    #[serde(default)]
+    pub cover_layer: Option<AnalysisInput>, // must_be_covered_by 的覆盖图层
+    #[serde(default)]
+    pub tolerance: Option<f64>, // 悬挂点判断的容差（输出坐标系单位），默认 0
+}
+
+// 拓扑检查请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyRequest {
+    pub rules: Vec<TopologyRule>,
+    pub output: AnalysisOutput, // 错误图层，坐标系与第一个规则的图层相同
+}
+
+// 单个规则的检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyRuleSummary {
+    pub id: String,
+    pub rule: TopologyRuleKind,
+    pub features_checked: u64,
+    pub errors: u64,
+}
+
+// 拓扑检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyResult {
+    #[serde(flatten)]
+    pub output: ExportResult,
+    pub rules: Vec<TopologyRuleSummary>,
+}
+
+// 几何属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeometryAttribute {
+    Area,
+    Perimeter,
+    Length,
+    CentroidX,
+    CentroidY,
+    VertexCount,
+    MinX,
+    MinY,
+    MaxX,
+    MaxY,
+}
+
+// 量算方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeasureMethod {
+    #[default]
+    Geodesic, // 椭球面量算，中心点和外包矩形为经纬度
+    Planar,   // 投影平面量算，中心点和外包矩形为投影坐标
+}
+
+// 要写入的几何属性字段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeometryAttributeField {
+    pub attribute: GeometryAttribute,
+    #[serde(default)]
+    pub name: Option<String>, // 字段名，默认为属性名（如 area、centroid_x）
+}
+
+// 计算几何属性请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeometryAttributesRequest {
+    pub input: AnalysisInput,
+    pub fields: Vec<GeometryAttributeField>,
+    #[serde(default)]
+    pub method: MeasureMethod,
+    #[serde(default)]
+    pub planar_crs: Option<String>, // 平面量算使用的投影坐标系，默认为图层坐标系（须为投影坐标系）
+    #[serde(default)]
+    pub length_unit: LengthUnit,
+    #[serde(default)]
+    pub area_unit: AreaUnit,
+    #[serde(default)]
+    pub overwrite: bool, // 字段已存在时覆盖其值（字段类型须兼容），否则报错
+    #[serde(default)]
+    pub output: Option<AnalysisOutput>, // 为空时直接写入输入图层
+}
+
+// 计算几何属性结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeometryAttributesResult {
+    pub features_updated: u64,
+    pub fields: Vec<String>, // 写入的字段名
+    pub output: Option<ExportResult>,
+}
+
+// 邻近分析的输出方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NearMode {
+    #[default]
+    Nearest, // 输入要素附加最近的 k 个目标要素（每个邻近要素一行）
+    Matrix,  // 起点-终点距离表（无几何）
+}
+
+// 邻近分析请求：为输入图层的每个要素查找目标图层中最近的要素
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearRequest {
+    pub input: AnalysisInput,  // 起点图层
+    pub near: AnalysisInput,   // 目标图层（使用其空间索引）
+    #[serde(default)]
+    pub mode: NearMode,
+    #[serde(default)]
+    pub k: Option<usize>, // 每个输入要素的邻近要素数，nearest 默认 1，matrix 为空时输出全部组合
+    #[serde(default)]
+    pub max_distance: Option<f64>, // 搜索距离（length_unit 单位），为空时不限距离
+    #[serde(default)]
+    pub length_unit: LengthUnit,
+    #[serde(default)]
+    pub input_id_field: Option<String>, // 输出的起点标识字段（如村名），默认只输出 FID
+    #[serde(default)]
+    pub near_id_field: Option<String>,  // 输出的目标标识字段（如医院名称）
+    pub output: AnalysisOutput,
+}
+
+// 邻近分析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearResult {
+    #[serde(flatten)]
+    pub output: ExportResult,
+    pub matched: u64,   // 找到邻近要素的输入要素数
+    pub unmatched: u64, // 搜索距离内没有目标要素的输入要素数
+    pub geodesic: bool, // 目标图层为地理坐标系时按椭球面计算距离
+}
+
+// 格网形状
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GridShape {
+    #[default]
+    Rectangle,
+    Hexagon, // 尖顶六边形，cell_size 为相邻格网中心的距离
+}
+
+// 生成格网请求：范围取 extent，为空时取 layer 的范围
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridRequest {
+    #[serde(default)]
+    pub extent: Option<Extent>, // 格网坐标系下的范围
+    #[serde(default)]
+    pub layer: Option<AnalysisInput>, // 取图层（或其选择集）的范围
+    #[serde(default)]
+    pub crs: Option<String>, // 格网坐标系，默认为 layer 的坐标系，都为空时为 WGS84 经纬度
+    #[serde(default)]
+    pub shape: GridShape,
+    pub cell_size: f64, // 格网宽度（格网坐标系单位）
+    #[serde(default)]
+    pub cell_height: Option<f64>, // 矩形格网高度，默认与宽度相同
+    #[serde(default)]
+    pub count_points: Option<AnalysisInput>, // 统计每个格网内的点数（写入 point_count）
+    #[serde(default)]
+    pub skip_empty: bool, // 不输出没有点的格网（统计点数时有效）
+    pub output: AnalysisOutput,
+}
+
+// 生成格网结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridResult {
+    #[serde(flatten)]
+    pub output: ExportResult,
+    pub rows: usize,
+    pub cols: usize,
+    pub points_counted: u64, // 落在格网内的点数
+}
+
+// 采样点生成方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleMethod {
+    #[default]
+    Random,  // 每个面内随机生成 count 个点
+    Regular, // 按 spacing 间距生成规则点阵
+}
+
+// 面内采样点请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplePointsRequest {
+    pub input: AnalysisInput, // 面图层
+    #[serde(default)]
+    pub method: SampleMethod,
+    #[serde(default)]
+    pub count: Option<usize>, // random 时每个面的点数
+    #[serde(default)]
+    pub spacing: Option<f64>, // regular 时的点间距（图层坐标系单位）
+    #[serde(default)]
+    pub seed: Option<u64>, // 随机种子，相同种子生成相同的点，为空时随机选择
+    pub output: AnalysisOutput,
+}
+
+// 面内采样点结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplePointsResult {
+    #[serde(flatten)]
+    pub output: ExportResult,
+    pub seed: Option<u64>, // 实际使用的随机种子（random 时）
+}
+
+// 属性连接：按关键字段将表格（CSV/DBF/XLSX 等）的字段连接到图层
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeJoin {
+    pub table_path: String,
+    #[serde(default)]
+    pub table_layer: Option<usize>, // 表格图层序号（如 XLSX 工作表），默认第一个
+    pub table_key: String,
+    pub layer_key: String,
+    #[serde(default = "default_true")]
+    pub coerce_types: bool, // 数值与文本关键字按值比较（如 1、1.0、"1" 视为相同）
+    #[serde(default)]
+    pub ignore_case: bool,  // 文本关键字不区分大小写
+    #[serde(default)]
+    pub fields: Vec<String>, // 连接的表格字段，为空时连接除关键字段外的全部字段
+    #[serde(default)]
+    pub prefix: Option<String>, // 连接字段名前缀
+}
+
+// 属性连接的匹配情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeJoinReport {
+    pub layer_features: u64,
+    pub table_rows: u64,
+    pub matched_features: u64,
+    pub unmatched_features: u64,           // 关键字为空或找不到表格记录的要素数
+    pub unmatched_layer_keys: Vec<String>, // 图层中找不到表格记录的关键字值（去重，最多 MAX_REPORTED_KEYS 个）
+    pub unmatched_table_keys: Vec<String>, // 没有被任何要素匹配的表格关键字值
+    pub duplicate_table_keys: Vec<String>, // 表格中重复的关键字值（只使用第一条记录）
+    pub fields: Vec<AttributeField>,       // 连接到图层的字段（输出字段名）
+}
+
+// 属性连接导出请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeJoinRequest {
+    pub input: AnalysisInput,
+    pub join: AttributeJoin,
+    #[serde(default = "default_true")]
+    pub keep_all: bool, // 保留没有匹配的要素
+    pub output: AnalysisOutput,
+}
+
+// 属性连接导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeJoinResult {
+    #[serde(flatten)]
+    pub output: ExportResult,
+    pub report: AttributeJoinReport,
+}
+
+// 分批读取GeoJSON时通过 Channel 推送的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GeoJsonStreamEvent {
+    // 一批 GeoJSON Feature（已转换到 WGS84），read 为已读取数，total 为图层要素总数
+    Batch { index: usize, features: Vec<serde_json::Value>, read: u64, total: u64 },
+    // 全部读取完成
+    Finished { features: u64, batches: usize },
+}
+
+// 分批读取结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeoJsonStreamSummary {
+    pub features: u64,
+    pub batches: usize,
+}
+
+// 矢量瓦片缓存方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TileCacheMode {
+    None,
+    #[default]
+    Memory, // 内存缓存（应用关闭后失效）
+    Disk,   // 内存 + 应用缓存目录（数据源修改后自动失效）
+}
+
+// 矢量瓦片切片方案
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TileGrid {
+    #[default]
+    WebMercator, // EPSG:3857 标准XYZ瓦片
+    Geographic,  // 经纬度瓦片（0级为东西两个瓦片），用于 EPSG:4326/4490 地图
+}
+
+// 矢量瓦片源选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TileSourceOptions {
+    pub fields: Option<Vec<String>>,  // 写入瓦片的属性字段，为空时写入全部字段
+    pub where_clause: Option<String>, // OGR SQL 属性过滤条件
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    pub simplify: f64,                // 简化容差（屏幕像素），0 表示不简化
+    pub grid: TileGrid,
+    pub cache: TileCacheMode,
+}
+
+impl Default for TileSourceOptions {
+    fn default() -> Self {
+        TileSourceOptions {
+            fields: None,
+            where_clause: None,
+            min_zoom: 0,
+            max_zoom: 18,
+            simplify: 1.0,
+            grid: TileGrid::default(),
+            cache: TileCacheMode::default(),
+        }
+    }
+}
+
+// 已注册的矢量瓦片源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileSourceInfo {
+    pub id: String,
+    pub path: String,
+    pub layer_index: usize,
+    pub layer_name: String, // 瓦片中的图层名
+    pub geometry_type: String,
+    pub fields: Vec<String>,
+    pub tile_url: String,   // OpenLayers MVT 源的 URL 模板（{z}/{x}/{y}）
+    pub options: TileSourceOptions,
+}
@@ -0,0 +1,68 @@
+use crate::errors::{AppError, Result};
+use crate::models::{AttributeField, FieldAggregation, FieldType, TableFieldSchema};
+use crate::services::export_service;
+use gdal::vector::FieldValue;
+use std::collections::HashSet;
+
+fn numeric_value(value: &Option<FieldValue>) -> Option<f64> {
+    match value {
+        Some(FieldValue::IntegerValue(i)) => Some(*i as f64),
+        Some(FieldValue::Integer64Value(i)) => Some(*i as f64),
+        Some(FieldValue::RealValue(r)) => Some(*r),
+        _ => None,
+    }
+}
+
+/// 汇总一组字段值（空值不参与计算，没有值时计数为 0、其他为空）
+pub fn aggregate(values: &[Option<FieldValue>], aggregation: FieldAggregation) -> Option<FieldValue> {
+    let numbers = || values.iter().filter_map(numeric_value);
+    match aggregation {
+        FieldAggregation::First => values.first().cloned().flatten(),
+        FieldAggregation::Count => Some(FieldValue::Integer64Value(values.iter().filter(|v| v.is_some()).count() as i64)),
+        FieldAggregation::Sum => numbers().reduce(|a, b| a + b).map(FieldValue::RealValue),
+        FieldAggregation::Mean => {
+            let (sum, count) = numbers().fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+            (count > 0).then(|| FieldValue::RealValue(sum / count as f64))
+        }
+        FieldAggregation::Min => numbers().reduce(f64::min).map(FieldValue::RealValue),
+        FieldAggregation::Max => numbers().reduce(f64::max).map(FieldValue::RealValue),
+    }
+}
+
+pub fn aggregation_name(aggregation: FieldAggregation) -> &'static str {
+    match aggregation {
+        FieldAggregation::First => "first",
+        FieldAggregation::Count => "count",
+        FieldAggregation::Sum => "sum",
+        FieldAggregation::Mean => "mean",
+        FieldAggregation::Min => "min",
+        FieldAggregation::Max => "max",
+    }
+}
+
+/// 汇总字段的输出定义：计数为 Integer64，求和、平均、最值为 Real（源字段须为数值字段），First 沿用源字段定义
+/// 未指定输出名时为 字段名（First）或 字段名_汇总方式，与 used 中已有字段名重复时加后缀
+pub fn aggregation_schema(
+    field: &AttributeField,
+    aggregation: FieldAggregation,
+    output_name: Option<&str>,
+    used: &mut HashSet<String>,
+) -> Result<TableFieldSchema> {
+    let mut schema = match aggregation {
+        FieldAggregation::First => export_service::field_schema(field),
+        FieldAggregation::Count => export_service::new_field_schema(&field.name, FieldType::Integer64),
+        _ if !field.field_type.is_numeric() => {
+            return Err(AppError::InvalidFormat(format!(
+                "字段 {} 不是数值字段，不能计算 {}", field.name, aggregation_name(aggregation)
+            )));
+        }
+        _ => export_service::new_field_schema(&field.name, FieldType::Real),
+    };
+    let name = match output_name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => name.to_string(),
+        None if aggregation == FieldAggregation::First => field.name.clone(),
+        None => format!("{}_{}", field.name, aggregation_name(aggregation)),
+    };
+    schema.name = export_service::unique_field_name(&name, used);
+    Ok(schema)
+}
@@ -0,0 +1,386 @@
+use crate::errors::{AppError, Result};
+use crate::models::{
+    AttributeField, AttributeJoin, AttributeJoinReport, AttributeJoinRequest, AttributeJoinResult, TableFieldSchema,
+};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::{gdal_service, selection_service};
+use gdal::vector::{Feature, FieldValue, LayerAccess};
+use gdal::{Dataset, DatasetOptions, GdalOpenFlags};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// 报告中最多列出的关键字值个数
+const MAX_REPORTED_KEYS: usize = 1000;
+
+/// 打开连接表格，CSV 自动识别数值和日期字段类型（默认全部为文本）
+fn open_table(path: &str) -> Result<Dataset> {
+    if !path.to_lowercase().ends_with(".csv") {
+        return gdal_service::open_vector_dataset(path);
+    }
+    Dataset::open_ex(
+        path,
+        DatasetOptions {
+            open_flags: GdalOpenFlags::GDAL_OF_VECTOR | GdalOpenFlags::GDAL_OF_READONLY,
+            open_options: Some(&["AUTODETECT_TYPE=YES"]),
+            ..Default::default()
+        },
+    )
+    .map_err(|e| AppError::FileReadError(format!("无法打开表格: {}", e)))
+}
+
+/// 按名称查找字段（优先精确匹配，其次不区分大小写）
+fn find_field(fields: &[AttributeField], name: &str) -> Option<usize> {
+    fields.iter()
+        .position(|f| f.name == name)
+        .or_else(|| fields.iter().position(|f| f.name.eq_ignore_ascii_case(name)))
+}
+
+fn number_key(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 9.0e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// 文本是否为整数（可带正负号）
+fn is_integer_text(s: &str) -> bool {
+    let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// 关键字的比较值，空值和空文本返回 None
+/// 类型前缀区分数值与文本；coerce_types 时数值文本按数值比较，整数与实数按值比较
+/// 整数文本先按 i64 解析（18 位身份证号、地块编码等不经 f64 丢失精度），其他数值文本按 f64 解析
+fn normalize_key(value: &FieldValue, coerce_types: bool, ignore_case: bool) -> Option<String> {
+    let text = |s: &str| if ignore_case { s.to_lowercase() } else { s.to_string() };
+    let key = match value {
+        FieldValue::IntegerValue(i) => format!("n:{}", i),
+        FieldValue::Integer64Value(i) => format!("n:{}", i),
+        FieldValue::RealValue(r) if coerce_types => format!("n:{}", number_key(*r)),
+        FieldValue::RealValue(r) => format!("r:{}", r),
+        FieldValue::StringValue(s) => {
+            let s = s.trim();
+            if s.is_empty() {
+                return None;
+            }
+            if !coerce_types {
+                format!("s:{}", text(s))
+            } else if let Ok(i) = s.parse::<i64>() {
+                format!("n:{}", i)
+            } else if is_integer_text(s) {
+                // 超出 i64 范围的整数按文本比较，经 f64 转换会丢失精度
+                format!("s:{}", s)
+            } else {
+                match s.parse::<f64>() {
+                    Ok(v) if v.is_finite() => format!("n:{}", number_key(v)),
+                    _ => format!("s:{}", text(s)),
+                }
+            }
+        }
+        FieldValue::DateValue(d) => format!("d:{}", d),
+        FieldValue::DateTimeValue(dt) => format!("t:{}", dt),
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// 关键字在报告中的显示文本
+fn display_key(value: &FieldValue) -> String {
+    match value {
+        FieldValue::IntegerValue(i) => i.to_string(),
+        FieldValue::Integer64Value(i) => i.to_string(),
+        FieldValue::RealValue(r) => r.to_string(),
+        FieldValue::StringValue(s) => s.clone(),
+        FieldValue::DateValue(d) => d.to_string(),
+        FieldValue::DateTimeValue(dt) => dt.to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+fn field_value_to_json(value: Option<&FieldValue>) -> serde_json::Value {
+    match value {
+        Some(FieldValue::StringValue(s)) => serde_json::Value::String(s.clone()),
+        Some(FieldValue::IntegerValue(i)) => serde_json::Value::Number((*i).into()),
+        Some(FieldValue::Integer64Value(i)) => serde_json::Value::Number((*i).into()),
+        Some(FieldValue::RealValue(r)) => serde_json::Number::from_f64(*r)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Some(FieldValue::DateValue(d)) => serde_json::Value::String(d.to_string()),
+        Some(FieldValue::DateTimeValue(dt)) => serde_json::Value::String(dt.to_rfc3339()),
+        _ => serde_json::Value::Null,
+    }
+}
+
+/// 已加载到内存的连接表格，按图层要素的关键字查找表格记录
+pub struct AttributeJoiner {
+    layer_key: String,
+    coerce_types: bool,
+    ignore_case: bool,
+    fields: Vec<AttributeField>,
+    rows: HashMap<String, (String, Vec<Option<FieldValue>>)>, // 比较值 -> (显示文本, 连接字段值)
+    table_rows: u64,
+    duplicates: BTreeSet<String>,
+}
+
+impl AttributeJoiner {
+    /// 加载连接表格，连接字段名与 used 中已有的字段名（不区分大小写）不重复
+    pub fn load(join: &AttributeJoin, layer_fields: &[AttributeField], used: &mut HashSet<String>) -> Result<AttributeJoiner> {
+        let layer_key = find_field(layer_fields, &join.layer_key)
+            .map(|i| layer_fields[i].name.clone())
+            .ok_or_else(|| AppError::InvalidFormat(format!("图层关键字段不存在: {}", join.layer_key)))?;
+
+        let dataset = open_table(&join.table_path)?;
+        let table_index = join.table_layer.unwrap_or(0);
+        let mut table = dataset.layer(table_index)
+            .map_err(|e| AppError::FileReadError(format!("无法读取表格 {}: {}", table_index, e)))?;
+        let table_fields = gdal_service::read_attribute_fields(table.defn());
+        let key_index = find_field(&table_fields, &join.table_key)
+            .ok_or_else(|| AppError::InvalidFormat(format!("表格关键字段不存在: {}", join.table_key)))?;
+
+        let columns: Vec<usize> = if join.fields.is_empty() {
+            (0..table_fields.len()).filter(|&i| i != key_index).collect()
+        } else {
+            join.fields.iter()
+                .map(|name| {
+                    find_field(&table_fields, name)
+                        .ok_or_else(|| AppError::InvalidFormat(format!("表格字段不存在: {}", name)))
+                })
+                .collect::<Result<_>>()?
+        };
+
+        let prefix = join.prefix.as_deref().unwrap_or_default();
+        let fields = columns.iter()
+            .map(|&i| {
+                let source = &table_fields[i];
+                AttributeField {
+                    name: export_service::unique_field_name(&format!("{}{}", prefix, source.name), used),
+                    nullable: true,
+                    unique: false,
+                    default_value: None,
+                    domain: None,
+                    editable: false, // 连接字段只读
+                    ..source.clone()
+                }
+            })
+            .collect();
+
+        let mut rows = HashMap::new();
+        let mut duplicates = BTreeSet::new();
+        let mut table_rows = 0;
+        for feature in table.features() {
+            table_rows += 1;
+            let values: Vec<Option<FieldValue>> = feature.fields().map(|(_, value)| value).collect();
+            let Some(raw) = values[key_index].as_ref() else { continue };
+            let Some(key) = normalize_key(raw, join.coerce_types, join.ignore_case) else { continue };
+            match rows.entry(key) {
+                Entry::Occupied(_) => {
+                    duplicates.insert(display_key(raw));
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((display_key(raw), columns.iter().map(|&i| values[i].clone()).collect()));
+                }
+            }
+        }
+        if !duplicates.is_empty() {
+            log::warn!("表格 {} 中有 {} 个重复的关键字值，只使用第一条记录", join.table_path, duplicates.len());
+        }
+        log::info!("已加载连接表格: {} ({} 条记录, {} 个字段)", join.table_path, table_rows, columns.len());
+
+        Ok(AttributeJoiner {
+            layer_key,
+            coerce_types: join.coerce_types,
+            ignore_case: join.ignore_case,
+            fields,
+            rows,
+            table_rows,
+            duplicates,
+        })
+    }
+
+    /// 连接到图层的字段（输出字段名）
+    pub fn fields(&self) -> &[AttributeField] {
+        &self.fields
+    }
+
+    /// 要素关键字的比较值和显示文本
+    fn key(&self, feature: &Feature) -> Option<(String, String)> {
+        let value = feature.field(&self.layer_key).ok().flatten()?;
+        let key = normalize_key(&value, self.coerce_types, self.ignore_case)?;
+        Some((key, display_key(&value)))
+    }
+
+    /// 要素匹配的表格记录（连接字段值）
+    pub fn lookup(&self, feature: &Feature) -> Option<&[Option<FieldValue>]> {
+        let (key, _) = self.key(feature)?;
+        self.rows.get(&key).map(|(_, values)| values.as_slice())
+    }
+
+    /// 将连接字段写入要素属性，没有匹配时为空值
+    pub fn apply<F: FnMut(String, serde_json::Value)>(&self, feature: &Feature, mut insert: F) {
+        let values = self.lookup(feature);
+        for (index, field) in self.fields.iter().enumerate() {
+            insert(field.name.clone(), field_value_to_json(values.and_then(|v| v[index].as_ref())));
+        }
+    }
+}
+
+/// 加载图层的虚拟属性连接（读取图层时附加表格字段）
+pub fn load_joiners(path: &str, layer_index: usize, joins: &[AttributeJoin]) -> Result<Vec<AttributeJoiner>> {
+    if joins.is_empty() {
+        return Ok(Vec::new());
+    }
+    let layer_fields = gdal_service::read_layer_fields(path, Some(layer_index))?;
+    let mut used: HashSet<String> = layer_fields.iter().map(|f| f.name.to_lowercase()).collect();
+    joins.iter()
+        .map(|join| AttributeJoiner::load(join, &layer_fields, &mut used))
+        .collect()
+}
+
+/// 统计要素与表格记录的匹配情况
+struct JoinTally {
+    layer_features: u64,
+    matched_features: u64,
+    unmatched_keys: BTreeSet<String>,
+    matched_keys: HashSet<String>,
+}
+
+impl JoinTally {
+    fn new() -> Self {
+        JoinTally {
+            layer_features: 0,
+            matched_features: 0,
+            unmatched_keys: BTreeSet::new(),
+            matched_keys: HashSet::new(),
+        }
+    }
+
+    fn record<'j>(&mut self, joiner: &'j AttributeJoiner, feature: &Feature) -> Option<&'j [Option<FieldValue>]> {
+        self.layer_features += 1;
+        let (key, display) = joiner.key(feature)?;
+        match joiner.rows.get(&key) {
+            Some((_, values)) => {
+                self.matched_features += 1;
+                self.matched_keys.insert(key);
+                Some(values.as_slice())
+            }
+            None => {
+                self.unmatched_keys.insert(display);
+                None
+            }
+        }
+    }
+
+    fn report(self, joiner: &AttributeJoiner) -> AttributeJoinReport {
+        let unmatched_table: BTreeSet<String> = joiner.rows.iter()
+            .filter(|(key, _)| !self.matched_keys.contains(*key))
+            .map(|(_, (display, _))| display.clone())
+            .collect();
+        let limited = |keys: BTreeSet<String>| keys.into_iter().take(MAX_REPORTED_KEYS).collect();
+        AttributeJoinReport {
+            layer_features: self.layer_features,
+            table_rows: joiner.table_rows,
+            matched_features: self.matched_features,
+            unmatched_features: self.layer_features - self.matched_features,
+            unmatched_layer_keys: limited(self.unmatched_keys),
+            unmatched_table_keys: limited(unmatched_table),
+            duplicate_table_keys: limited(joiner.duplicates.clone()),
+            fields: joiner.fields.clone(),
+        }
+    }
+}
+
+/// 检查属性连接的匹配情况（添加虚拟连接前预览）
+pub fn check_join(path: &str, layer_index: usize, join: &AttributeJoin) -> Result<AttributeJoinReport> {
+    let dataset = gdal_service::open_vector_dataset(path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let layer_fields = gdal_service::read_attribute_fields(layer.defn());
+    let mut used: HashSet<String> = layer_fields.iter().map(|f| f.name.to_lowercase()).collect();
+    let joiner = AttributeJoiner::load(join, &layer_fields, &mut used)?;
+
+    let mut tally = JoinTally::new();
+    for feature in layer.features() {
+        tally.record(&joiner, &feature);
+    }
+    let report = tally.report(&joiner);
+    log::info!(
+        "属性连接检查: {} 个要素, {} 个有匹配, {} 条表格记录未被匹配",
+        report.layer_features, report.matched_features, report.unmatched_table_keys.len()
+    );
+    Ok(report)
+}
+
+/// 属性连接导出：将表格字段连接到图层并写入新图层
+pub fn attribute_join(request: &AttributeJoinRequest, job: &JobContext) -> Result<AttributeJoinResult> {
+    let layer_index = request.input.layer_index.unwrap_or(0);
+    log::info!("属性连接: {} <- {}", request.input.path, request.join.table_path);
+
+    let dataset = gdal_service::open_vector_dataset(&request.input.path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let input_fids = selection_service::input_fids(&request.input)?;
+
+    // 输出字段：图层字段 + 连接字段
+    let layer_fields = gdal_service::read_attribute_fields(layer.defn());
+    let mut used = HashSet::new();
+    let mut schemas: Vec<TableFieldSchema> = layer_fields.iter()
+        .map(|field| {
+            let mut schema = export_service::field_schema(field);
+            schema.name = export_service::unique_field_name(&field.name, &mut used);
+            schema
+        })
+        .collect();
+    let joiner = AttributeJoiner::load(&request.join, &layer_fields, &mut used)?;
+    schemas.extend(joiner.fields().iter().map(export_service::field_schema));
+
+    let geometry_type = unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) };
+    let mut output = OutputLayer::for_analysis(&request.output, layer.spatial_ref().as_ref(), geometry_type, &schemas)?;
+
+    let total = input_fids.as_ref().map_or_else(|| layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut tally = JoinTally::new();
+    job.report(0, Some(total));
+
+    let mut run = || -> Result<()> {
+        for feature in layer.features() {
+            job.check_cancelled()?;
+            if let Some(fids) = &input_fids {
+                if !feature.fid().is_some_and(|fid| fids.contains(&fid)) {
+                    continue;
+                }
+            }
+            let joined = tally.record(&joiner, &feature);
+            if tally.layer_features >= next_report {
+                job.report(tally.layer_features, Some(total));
+                next_report += report_interval;
+            }
+            if joined.is_none() && !request.keep_all {
+                continue;
+            }
+
+            let mut values: Vec<Option<FieldValue>> = feature.fields().map(|(_, value)| value).collect();
+            match joined {
+                Some(joined) => values.extend(joined.iter().cloned()),
+                None => values.resize(schemas.len(), None),
+            }
+            output.write(feature.geometry().cloned(), &values)?;
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        output.discard();
+        return Err(e);
+    }
+    job.report(tally.layer_features, Some(total));
+    let output = output.finish()?;
+    let report = tally.report(&joiner);
+    log::info!(
+        "属性连接完成: {} 个要素有匹配, {} 个无匹配",
+        report.matched_features, report.unmatched_features
+    );
+    Ok(AttributeJoinResult { output, report })
+}
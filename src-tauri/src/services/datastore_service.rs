@@ -0,0 +1,500 @@
+use crate::errors::{AppError, Result};
+use crate::gis::geometry;
+use crate::models::{
+    AttributeField, CoordinateDimension, DataStoreInfo, DataStoreKind, DataStoreTable, FieldType, LayerStyle,
+    StoredLayerStyle, TableFieldSchema, TableSchema,
+};
+use crate::services::job_service::JobContext;
+use crate::services::{export_service, gdal_service};
+use gdal::vector::{LayerAccess, LayerOptions, OGRwkbGeometryType};
+use gdal::{Dataset, DriverManager, Metadata};
+use gdal::spatial_ref::SpatialRef;
+use std::path::Path;
+
+/// 样式表名称（与 QGIS 相同，QGIS 打开时可识别）
+const LAYER_STYLES_TABLE: &str = "layer_styles";
+
+/// 数据库内部表，不在内容列表中显示
+const SYSTEM_TABLES: [&str; 1] = [LAYER_STYLES_TABLE];
+
+/// 根据扩展名判断数据库类型
+pub fn detect_kind(path: &str) -> Option<DataStoreKind> {
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())?;
+    match ext.as_str() {
+        "gpkg" => Some(DataStoreKind::GeoPackage),
+        "sqlite" | "db" | "spatialite" => Some(DataStoreKind::SpatiaLite),
+        _ => None,
+    }
+}
+
+fn driver_name(kind: DataStoreKind) -> &'static str {
+    match kind {
+        DataStoreKind::GeoPackage => "GPKG",
+        DataStoreKind::SpatiaLite => "SQLite",
+    }
+}
+
+/// 以读写方式打开数据库；列出全部表（含属性表）
+fn open_store(path: &str, update: bool) -> Result<(Dataset, DataStoreKind)> {
+    let kind = detect_kind(path)
+        .ok_or_else(|| AppError::InvalidFormat(format!("不是GeoPackage或SpatiaLite数据库: {}", path)))?;
+
+    if !Path::new(path).exists() {
+        return Err(AppError::FileNotFound(path.to_string()));
+    }
+
+    let mut open_flags = gdal::GdalOpenFlags::GDAL_OF_VECTOR;
+    if update {
+        open_flags |= gdal::GdalOpenFlags::GDAL_OF_UPDATE;
+    }
+
+    let dataset = Dataset::open_ex(
+        path,
+        gdal::DatasetOptions {
+            open_flags,
+            allowed_drivers: Some(&[driver_name(kind)]),
+            open_options: Some(&["LIST_ALL_TABLES=YES"]),
+            ..Default::default()
+        },
+    )
+    .map_err(|e| AppError::FileReadError(format!("无法打开数据库: {}", e)))?;
+
+    Ok((dataset, kind))
+}
+
+/// 新建空的 GeoPackage 或 SpatiaLite 数据库
+pub fn create_datastore(path: &str, kind: DataStoreKind, overwrite: bool) -> Result<()> {
+    if Path::new(path).exists() {
+        if !overwrite {
+            return Err(AppError::FileWriteError(format!("文件已存在: {}", path)));
+        }
+        log::info!("删除已存在的数据库: {}", path);
+        std::fs::remove_file(path)?;
+    }
+
+    let driver = DriverManager::get_driver_by_name(driver_name(kind))
+        .map_err(|e| AppError::Unknown(format!("无法获取驱动 {}: {}", driver_name(kind), e)))?;
+
+    let mut options = gdal::cpl::CslStringList::new();
+    if kind == DataStoreKind::SpatiaLite {
+        options.set_name_value("SPATIALITE", "YES")
+            .map_err(|e| AppError::Unknown(format!("设置创建选项失败: {}", e)))?;
+    }
+
+    driver
+        .create_with_band_type_with_options::<u8, _>(path, 0, 0, 0, &options)
+        .map_err(|e| AppError::FileWriteError(format!("创建数据库失败: {}", e)))?;
+
+    log::info!("已创建数据库: {} ({:?})", path, kind);
+    Ok(())
+}
+
+/// 在数据库中新建表（要素表或属性表）
+pub fn create_table(path: &str, schema: &TableSchema) -> Result<DataStoreTable> {
+    let (mut dataset, kind) = open_store(path, true)?;
+
+    if dataset.layer_by_name(&schema.name).is_ok() {
+        return Err(AppError::FileWriteError(format!("表已存在: {}", schema.name)));
+    }
+
+    let geometry_type = match schema.geometry_type.as_deref() {
+        Some(name) if !name.is_empty() && !name.eq_ignore_ascii_case("none") => geometry::parse_geometry_type(name)?,
+        _ => OGRwkbGeometryType::wkbNone,
+    };
+
+    let srs = match schema.crs.as_deref() {
+        Some(crs) if geometry_type != OGRwkbGeometryType::wkbNone => {
+            let mut srs = SpatialRef::from_definition(crs)
+                .map_err(|e| AppError::InvalidFormat(format!("无效的坐标系 {}: {}", crs, e)))?;
+            srs.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
+            Some(srs)
+        }
+        _ => None,
+    };
+
+    let mut options = Vec::new();
+    if let Some(column) = schema.geometry_column.as_deref() {
+        options.push(format!("GEOMETRY_NAME={}", column));
+    }
+    match kind {
+        DataStoreKind::GeoPackage => {
+            if let Some(description) = schema.description.as_deref() {
+                options.push(format!("DESCRIPTION={}", description));
+            }
+        }
+        DataStoreKind::SpatiaLite => {
+            // 保留字段名原样（SQLite 驱动默认转为小写）
+            options.push("LAUNDER=NO".to_string());
+        }
+    }
+    let option_refs: Vec<&str> = options.iter().map(|s| s.as_str()).collect();
+
+    {
+        let layer = dataset
+            .create_layer(LayerOptions {
+                name: &schema.name,
+                srs: srs.as_ref(),
+                ty: geometry_type,
+                options: Some(&option_refs),
+            })
+            .map_err(|e| AppError::FileWriteError(format!("创建表 {} 失败: {}", schema.name, e)))?;
+
+        for field in &schema.fields {
+            gdal_service::create_field(&layer, field)?;
+        }
+    }
+
+    log::info!("已在 {} 中创建表: {} ({} 个字段)", path, schema.name, schema.fields.len());
+
+    let index = table_index(&dataset, &schema.name)?;
+    let mut layer = dataset.layer(index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", index, e)))?;
+    Ok(describe_table(&mut layer, index))
+}
+
+fn describe_table(layer: &mut gdal::vector::Layer, index: usize) -> DataStoreTable {
+    let geom_field = layer.defn().geom_fields().next().map(|f| (f.name(), f.field_type()));
+    let is_spatial = geom_field.is_some();
+
+    let fields = gdal_service::read_attribute_fields(layer.defn())
+        .into_iter()
+        .map(|field| AttributeField { editable: true, ..field })
+        .collect();
+
+    let coordinate_dimension = if is_spatial {
+        gdal_service::detect_coordinate_dimension(layer)
+    } else {
+        CoordinateDimension::Xy
+    };
+
+    let non_empty = |value: Option<String>| value.filter(|v| !v.is_empty());
+
+    DataStoreTable {
+        name: layer.name(),
+        index,
+        is_spatial,
+        geometry_type: geom_field.as_ref().map(|(_, ty)| geometry::geometry_type_name(*ty)),
+        geometry_column: non_empty(geom_field.map(|(name, _)| name)),
+        coordinate_dimension,
+        projection: layer.spatial_ref().and_then(|sr| sr.to_wkt().ok()),
+        feature_count: layer.feature_count() as usize,
+        fields,
+        identifier: non_empty(layer.metadata_item("IDENTIFIER", "")),
+        description: non_empty(layer.metadata_item("DESCRIPTION", "")),
+    }
+}
+
+/// 列出数据库内容（要素表和属性表）
+pub fn list_contents(path: &str) -> Result<DataStoreInfo> {
+    let (dataset, kind) = open_store(path, false)?;
+
+    let mut tables = Vec::new();
+    let mut style_count = 0;
+    for index in 0..dataset.layer_count() {
+        let mut layer = match dataset.layer(index) {
+            Ok(layer) => layer,
+            Err(e) => {
+                log::warn!("无法读取图层 {}: {}", index, e);
+                continue;
+            }
+        };
+
+        let name = layer.name();
+        if name == LAYER_STYLES_TABLE {
+            style_count = layer.feature_count() as usize;
+        }
+        if SYSTEM_TABLES.contains(&name.as_str()) {
+            continue;
+        }
+
+        tables.push(describe_table(&mut layer, index));
+    }
+
+    log::info!("数据库 {} 包含 {} 个表", path, tables.len());
+    Ok(DataStoreInfo {
+        path: path.to_string(),
+        kind,
+        tables,
+        style_count,
+    })
+}
+
+/// 将任意矢量图层复制到数据库
+pub fn import_layer(
+    source_path: &str,
+    layer_index: Option<usize>,
+    store_path: &str,
+    table_name: Option<&str>,
+    overwrite: bool,
+) -> Result<String> {
+    let source = gdal_service::open_vector_dataset(source_path)?;
+    let idx = layer_index.unwrap_or(0);
+    let layer = source.layer(idx)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", idx, e)))?;
+
+    // 目标数据库不存在时按扩展名新建
+    if !Path::new(store_path).exists() {
+        let kind = detect_kind(store_path)
+            .ok_or_else(|| AppError::InvalidFormat(format!("不是GeoPackage或SpatiaLite数据库: {}", store_path)))?;
+        create_datastore(store_path, kind, false)?;
+    }
+
+    let (target, kind) = open_store(store_path, true)?;
+    let name = table_name.map(str::to_string).unwrap_or_else(|| layer.name());
+
+    if !overwrite && target.layer_by_name(&name).is_ok() {
+        return Err(AppError::FileWriteError(format!("表已存在: {}", name)));
+    }
+
+    let mut options = Vec::new();
+    if overwrite {
+        options.push(("OVERWRITE", "YES"));
+    }
+    if kind == DataStoreKind::SpatiaLite {
+        options.push(("LAUNDER", "NO"));
+    }
+
+    log::info!("复制图层到数据库: {} -> {}:{}", source_path, store_path, name);
+    gdal_service::copy_layer_to_dataset(&layer, &target, &name, &options)?;
+
+    Ok(name)
+}
+
+/// 查找表对应的图层索引
+fn table_index(dataset: &Dataset, table_name: &str) -> Result<usize> {
+    (0..dataset.layer_count())
+        .find(|&i| dataset.layer(i).map(|l| l.name() == table_name).unwrap_or(false))
+        .ok_or_else(|| AppError::FileReadError(format!("表不存在: {}", table_name)))
+}
+
+/// 将数据库中的表导出为独立文件（Shapefile、GeoJSON、KML等）
+pub fn export_table(
+    store_path: &str,
+    table_name: &str,
+    output_path: &str,
+    format: &str,
+) -> Result<()> {
+    let index = {
+        let (dataset, _) = open_store(store_path, false)?;
+        table_index(&dataset, table_name)?
+    };
+
+    export_service::export_vector(
+        store_path,
+        output_path,
+        format,
+        Some(index),
+        &Default::default(),
+        &JobContext::untracked("export"),
+    )?;
+    Ok(())
+}
+
+/// 打开（必要时创建）layer_styles 表
+fn ensure_styles_table(dataset: &mut Dataset, kind: DataStoreKind) -> Result<()> {
+    if dataset.layer_by_name(LAYER_STYLES_TABLE).is_ok() {
+        return Ok(());
+    }
+
+    log::info!("创建 {} 表", LAYER_STYLES_TABLE);
+    let options: &[&str] = match kind {
+        DataStoreKind::GeoPackage => &[],
+        DataStoreKind::SpatiaLite => &["LAUNDER=NO"],
+    };
+    let layer = dataset
+        .create_layer(LayerOptions {
+            name: LAYER_STYLES_TABLE,
+            srs: None,
+            ty: OGRwkbGeometryType::wkbNone,
+            options: Some(options),
+        })
+        .map_err(|e| AppError::FileWriteError(format!("创建样式表失败: {}", e)))?;
+
+    // 与 QGIS 相同的字段，另加 styleJSON 保存 MiniGIS 样式
+    let columns = [
+        ("f_table_catalog", FieldType::String),
+        ("f_table_schema", FieldType::String),
+        ("f_table_name", FieldType::String),
+        ("f_geometry_column", FieldType::String),
+        ("styleName", FieldType::String),
+        ("styleQML", FieldType::String),
+        ("styleSLD", FieldType::String),
+        ("useAsDefault", FieldType::Boolean),
+        ("description", FieldType::String),
+        ("owner", FieldType::String),
+        ("ui", FieldType::String),
+        ("update_time", FieldType::DateTime),
+        ("styleJSON", FieldType::String),
+    ];
+    for (name, field_type) in columns {
+        gdal_service::create_field(&layer, &TableFieldSchema {
+            name: name.to_string(),
+            field_type,
+            width: None,
+            precision: None,
+            alias: None,
+            nullable: true,
+            unique: false,
+            default_value: None,
+            domain: None,
+        })?;
+    }
+    Ok(())
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// 保存图层样式到 layer_styles（同名样式覆盖）
+pub fn save_layer_style(
+    path: &str,
+    table_name: &str,
+    style_name: &str,
+    style: &LayerStyle,
+    use_as_default: bool,
+    description: Option<&str>,
+) -> Result<StoredLayerStyle> {
+    let (mut dataset, kind) = open_store(path, true)?;
+
+    let geometry_column = {
+        let table = dataset.layer_by_name(table_name)
+            .map_err(|_| AppError::FileReadError(format!("表不存在: {}", table_name)))?;
+        let column = table.defn().geom_fields().next().map(|f| f.name());
+        column.filter(|c| !c.is_empty())
+    };
+
+    ensure_styles_table(&mut dataset, kind)?;
+    let mut styles = dataset.layer_by_name(LAYER_STYLES_TABLE)
+        .map_err(|e| AppError::FileReadError(format!("无法打开样式表: {}", e)))?;
+
+    // 删除同名样式；设为默认时取消该表其他样式的默认标记
+    styles.set_attribute_filter(&format!("f_table_name = {}", quote_literal(table_name)))
+        .map_err(|e| AppError::InvalidFormat(format!("设置过滤条件失败: {}", e)))?;
+    let mut same_name = Vec::new();
+    let mut other_defaults = Vec::new();
+    for feature in styles.features() {
+        let name = feature.field_as_string_by_name("styleName").ok().flatten().unwrap_or_default();
+        let is_default = feature.field_as_integer_by_name("useAsDefault").ok().flatten().unwrap_or(0) != 0;
+        if let Some(fid) = feature.fid() {
+            if name == style_name {
+                same_name.push(fid);
+            } else if use_as_default && is_default {
+                other_defaults.push(fid);
+            }
+        }
+    }
+    styles.clear_attribute_filter();
+
+    for fid in same_name {
+        let err = unsafe { gdal_sys::OGR_L_DeleteFeature(styles.c_layer(), fid as i64) };
+        if err != gdal_sys::OGRErr::OGRERR_NONE {
+            return Err(AppError::FileWriteError(format!("删除旧样式失败: {}", gdal_service::last_gdal_error())));
+        }
+    }
+    for fid in other_defaults {
+        if let Some(mut feature) = styles.feature(fid) {
+            feature.set_field_integer("useAsDefault", 0)
+                .map_err(|e| AppError::FileWriteError(format!("更新样式失败: {}", e)))?;
+            styles.set_feature(feature)
+                .map_err(|e| AppError::FileWriteError(format!("更新样式失败: {}", e)))?;
+        }
+    }
+
+    let style_json = serde_json::to_string(style)?;
+    let update_time = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    // 写入新样式记录（要素借用图层定义，需在重新读取前释放）
+    {
+        let mut feature = gdal::vector::Feature::new(styles.defn())
+            .map_err(|e| AppError::FileWriteError(format!("创建样式记录失败: {}", e)))?;
+        let set_err = |e: gdal::errors::GdalError| AppError::FileWriteError(format!("写入样式失败: {}", e));
+        feature.set_field_string("f_table_name", table_name).map_err(set_err)?;
+        if let Some(column) = geometry_column.as_deref() {
+            feature.set_field_string("f_geometry_column", column).map_err(set_err)?;
+        }
+        feature.set_field_string("styleName", style_name).map_err(set_err)?;
+        feature.set_field_integer("useAsDefault", use_as_default as i32).map_err(set_err)?;
+        if let Some(description) = description {
+            feature.set_field_string("description", description).map_err(set_err)?;
+        }
+        feature.set_field_string("update_time", &update_time).map_err(set_err)?;
+        feature.set_field_string("styleJSON", &style_json).map_err(set_err)?;
+        feature.create(&styles).map_err(set_err)?;
+    }
+
+    log::info!("已保存样式 {} 到表 {}", style_name, table_name);
+
+    let saved = read_styles(&mut styles, Some(table_name))?;
+    saved.into_iter()
+        .find(|s| s.style_name == style_name)
+        .ok_or_else(|| AppError::FileReadError(format!("找不到保存的样式: {}", style_name)))
+}
+
+fn read_styles(styles: &mut gdal::vector::Layer, table_name: Option<&str>) -> Result<Vec<StoredLayerStyle>> {
+    if let Some(table_name) = table_name {
+        styles.set_attribute_filter(&format!("f_table_name = {}", quote_literal(table_name)))
+            .map_err(|e| AppError::InvalidFormat(format!("设置过滤条件失败: {}", e)))?;
+    }
+
+    let mut result = Vec::new();
+    for feature in styles.features() {
+        let text = |name: &str| feature.field_as_string_by_name(name).ok().flatten().filter(|v| !v.is_empty());
+
+        // 其他软件写入的样式没有 styleJSON 字段
+        let style = text("styleJSON").and_then(|json| match serde_json::from_str::<LayerStyle>(&json) {
+            Ok(style) => Some(style),
+            Err(e) => {
+                log::warn!("样式JSON解析失败: {}", e);
+                None
+            }
+        });
+
+        result.push(StoredLayerStyle {
+            id: feature.fid().unwrap_or_default(),
+            table_name: text("f_table_name").unwrap_or_default(),
+            geometry_column: text("f_geometry_column"),
+            style_name: text("styleName").unwrap_or_default(),
+            use_as_default: feature.field_as_integer_by_name("useAsDefault").ok().flatten().unwrap_or(0) != 0,
+            description: text("description"),
+            style,
+            update_time: text("update_time"),
+        });
+    }
+
+    styles.clear_attribute_filter();
+    Ok(result)
+}
+
+/// 列出保存的图层样式，可按表过滤
+pub fn list_layer_styles(path: &str, table_name: Option<&str>) -> Result<Vec<StoredLayerStyle>> {
+    let (dataset, _) = open_store(path, false)?;
+    let mut styles = match dataset.layer_by_name(LAYER_STYLES_TABLE) {
+        Ok(layer) => layer,
+        Err(_) => return Ok(Vec::new()),
+    };
+    read_styles(&mut styles, table_name)
+}
+
+/// 获取表的默认样式（没有默认样式时取第一个）
+pub fn get_default_style(path: &str, table_name: &str) -> Result<Option<StoredLayerStyle>> {
+    let styles = list_layer_styles(path, Some(table_name))?;
+    let default = styles.iter().position(|s| s.use_as_default).unwrap_or(0);
+    Ok(styles.into_iter().nth(default))
+}
+
+/// 删除保存的样式
+pub fn delete_layer_style(path: &str, style_id: u64) -> Result<()> {
+    let (dataset, _) = open_store(path, true)?;
+    let styles = dataset.layer_by_name(LAYER_STYLES_TABLE)
+        .map_err(|_| AppError::FileReadError("数据库中没有样式表".to_string()))?;
+
+    let err = unsafe { gdal_sys::OGR_L_DeleteFeature(styles.c_layer(), style_id as i64) };
+    if err != gdal_sys::OGRErr::OGRERR_NONE {
+        return Err(AppError::FileWriteError(format!("删除样式失败: {}", gdal_service::last_gdal_error())));
+    }
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use crate::errors::{AppError, Result};
+use crate::gis::geometry;
+use crate::models::{DissolveRequest, ExportResult, FieldAggregation, TableFieldSchema};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::{aggregation_service, gdal_service, selection_service};
+use gdal::vector::{FieldValue, Geometry, LayerAccess};
+use std::collections::{HashMap, HashSet};
+
+/// 融合分组：融合字段值、组内几何及各汇总字段的值
+struct DissolveGroup {
+    keys: Vec<Option<FieldValue>>,
+    geometries: Vec<Geometry>,
+    values: Vec<Vec<Option<FieldValue>>>,
+}
+
+/// 分组关键字：各融合字段值依次连接（空值单独成组）
+fn group_key(keys: &[Option<FieldValue>]) -> String {
+    keys.iter()
+        .map(|value| format!("{:?}", value))
+        .collect::<Vec<_>>()
+        .join("\u{1f}")
+}
+
+/// 融合：融合字段值相同的要素合并为一个要素，其他字段按汇总方式统计
+/// 面级联合并、线和点组成多部件几何；multipart 为 false 时合并结果的每个部件单独输出
+pub fn dissolve(request: &DissolveRequest, job: &JobContext) -> Result<ExportResult> {
+    let layer_index = request.input.layer_index.unwrap_or(0);
+    log::info!("融合: {} 按 {:?}", request.input.path, request.fields);
+
+    let dataset = gdal_service::open_vector_dataset(&request.input.path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let fields = gdal_service::read_attribute_fields(layer.defn());
+    let find_field = |name: &str| {
+        fields.iter()
+            .position(|f| f.name == name)
+            .or_else(|| fields.iter().position(|f| f.name.eq_ignore_ascii_case(name)))
+            .ok_or_else(|| AppError::InvalidFormat(format!("字段不存在: {}", name)))
+    };
+
+    // 输出字段：融合字段在前，汇总字段在后
+    let mut used = HashSet::new();
+    let mut schemas: Vec<TableFieldSchema> = Vec::new();
+    let mut key_indexes = Vec::new();
+    for name in &request.fields {
+        let index = find_field(name)?;
+        let mut schema = export_service::field_schema(&fields[index]);
+        schema.name = export_service::unique_field_name(&fields[index].name, &mut used);
+        key_indexes.push(index);
+        schemas.push(schema);
+    }
+    let mut statistics: Vec<(usize, FieldAggregation)> = Vec::new();
+    for rule in &request.statistics {
+        let index = find_field(&rule.field)?;
+        schemas.push(aggregation_service::aggregation_schema(
+            &fields[index],
+            rule.aggregation,
+            rule.output_name.as_deref(),
+            &mut used,
+        )?);
+        statistics.push((index, rule.aggregation));
+    }
+
+    let input_fids = selection_service::input_fids(&request.input)?;
+    let total = input_fids.as_ref().map_or_else(|| layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut processed = 0u64;
+
+    // 第一阶段：读取要素并分组，保持首次出现的顺序
+    job.set_stage(0, 2);
+    job.report(0, Some(total));
+    let mut groups: Vec<DissolveGroup> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for feature in layer.features() {
+        job.check_cancelled()?;
+        if let Some(fids) = &input_fids {
+            if !feature.fid().is_some_and(|fid| fids.contains(&fid)) {
+                continue;
+            }
+        }
+        processed += 1;
+        if processed >= next_report {
+            job.report(processed, Some(total));
+            next_report += report_interval;
+        }
+
+        let values: Vec<Option<FieldValue>> = feature.fields().map(|(_, value)| value).collect();
+        let keys: Vec<Option<FieldValue>> = key_indexes.iter().map(|&i| values[i].clone()).collect();
+        let key = group_key(&keys);
+        let position = *positions.entry(key).or_insert_with(|| {
+            groups.push(DissolveGroup {
+                keys,
+                geometries: Vec::new(),
+                values: vec![Vec::new(); statistics.len()],
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[position];
+        for (column, &(index, _)) in statistics.iter().enumerate() {
+            group.values[column].push(values[index].clone());
+        }
+        if let Some(geom) = feature.geometry() {
+            match geometry::to_linear(geom.clone()) {
+                Ok(geom) => group.geometries.push(geom),
+                Err(e) => log::warn!("要素 {:?} 几何转换失败，已跳过: {}", feature.fid(), e),
+            }
+        }
+    }
+    job.report(processed, Some(total));
+    log::info!("读取 {} 个要素，分为 {} 组", processed, groups.len());
+
+    // 第二阶段：逐组合并几何并写出
+    job.set_stage(1, 2);
+    let geometry_type = geometry::result_geometry_type(
+        unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) },
+        request.multipart,
+    );
+    let mut output = OutputLayer::for_analysis(&request.output, layer.spatial_ref().as_ref(), geometry_type, &schemas)?;
+    let group_count = groups.len() as u64;
+    let report_interval = (group_count / 100).max(1);
+    let mut next_report = report_interval;
+    let mut failed = 0u64;
+    job.report(0, Some(group_count));
+
+    let mut run = || -> Result<()> {
+        for (written, group) in (1u64..).zip(&groups) {
+            job.check_cancelled()?;
+            if written >= next_report {
+                job.report(written, Some(group_count));
+                next_report += report_interval;
+            }
+
+            let mut values = group.keys.clone();
+            values.extend(
+                statistics.iter()
+                    .zip(&group.values)
+                    .map(|(&(_, aggregation), column)| aggregation_service::aggregate(column, aggregation)),
+            );
+            let merged = match geometry::dissolve_geometries(&group.geometries) {
+                Ok(merged) => merged,
+                Err(e) => {
+                    log::warn!("融合组 {} 几何合并失败，已跳过: {}", group_key(&group.keys), e);
+                    failed += 1;
+                    continue;
+                }
+            };
+            match merged {
+                Some(merged) if !request.multipart => {
+                    let mut parts = Vec::new();
+                    geometry::collect_parts(&merged, geometry::topological_dimension(&merged), &mut parts);
+                    for part in parts {
+                        output.write(Some(part), &values)?;
+                    }
+                }
+                merged => output.write(merged, &values)?,
+            }
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        output.discard();
+        return Err(e);
+    }
+    job.report(group_count, Some(group_count));
+    if failed > 0 {
+        log::warn!("{} 个融合组几何合并失败（可先修复几何）", failed);
+    }
+    let mut result = output.finish()?;
+    result.features_failed += failed;
+    Ok(result)
+}
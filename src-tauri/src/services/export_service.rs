@@ -0,0 +1,1003 @@
+use crate::errors::{AppError, Result};
+use crate::gis::geometry;
+use crate::models::{
+    AnalysisOutput, AttributeField, BatchConvertReport, BatchConvertRequest, BatchItemResult, BatchProgress, DimensionMode,
+    ExportDriver, ExportOptions, ExportResult, Extent, FieldMapping, FieldType, TableFieldSchema,
+};
+use crate::services::job_service::JobContext;
+use crate::services::{gdal_service, gdb_service, selection_service, worker_pool};
+use gdal::cpl::CslStringList;
+use gdal::spatial_ref::{AxisMappingStrategy, CoordTransform, SpatialRef};
+use gdal::vector::{FieldValue, Geometry, Layer, LayerAccess, LayerOptions, OGRwkbGeometryType};
+use gdal::{Dataset, Driver, DriverManager, DriverType, Metadata};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// 支持事务的数据源每写入多少要素提交一次
+const TRANSACTION_BATCH: u64 = 20_000;
+
+/// 未知要素总数时每写入多少要素报告一次进度
+const PROGRESS_INTERVAL: u64 = 1_000;
+
+/// 批量转换文件夹时识别的矢量文件扩展名（GDB 按文件夹识别）
+const BATCH_EXTENSIONS: [&str; 12] = [
+    "shp", "geojson", "kml", "kmz", "gpkg", "gml", "gpx", "tab", "mif", "sqlite", "dxf", "fgb",
+];
+
+/// 驱动是否可以创建矢量数据
+fn is_writable_vector_driver(driver: &Driver) -> bool {
+    driver.metadata_item("DCAP_VECTOR", "").is_some()
+        && driver.metadata_item("DCAP_CREATE", "").is_some()
+}
+
+/// 根据驱动名查找驱动（不区分大小写）
+fn driver_by_name(name: &str) -> Option<Driver> {
+    DriverManager::get_driver_by_name(name).ok().or_else(|| {
+        DriverManager::all().find(|driver| driver.short_name().eq_ignore_ascii_case(name))
+    })
+}
+
+/// 解析导出格式
+/// 兼容旧的格式名（KML、KMZ、GEOJSON、SHAPEFILE、GPKG），其余按GDAL驱动名查找；
+/// 格式为空或 AUTO 时根据输出文件扩展名选择驱动
+pub fn resolve_driver(format: &str, output_path: &str) -> Result<Driver> {
+    let format = format.trim();
+    let upper = format.to_uppercase();
+    let auto_detect = upper.is_empty() || upper == "AUTO";
+    let driver = match upper.as_str() {
+        _ if auto_detect => DriverManager::get_output_driver_for_dataset_name(output_path, DriverType::Vector),
+        "KMZ" => driver_by_name("LIBKML"),
+        "GEOJSON" => driver_by_name("GeoJSON"),
+        "SHAPEFILE" | "SHP" => driver_by_name("ESRI Shapefile"),
+        "GEOPACKAGE" => driver_by_name("GPKG"),
+        _ => driver_by_name(format),
+    }
+    .ok_or_else(|| match upper.as_str() {
+        _ if auto_detect => AppError::InvalidFormat(format!("无法根据文件名识别导出格式: {}", output_path)),
+        "KMZ" => AppError::InvalidFormat("当前GDAL未包含LIBKML驱动，无法导出KMZ".to_string()),
+        _ => AppError::InvalidFormat(format!("不支持的导出格式: {}", format)),
+    })?;
+
+    if !is_writable_vector_driver(&driver) {
+        return Err(AppError::InvalidFormat(format!("驱动 {} 不支持写入矢量数据", driver.short_name())));
+    }
+    Ok(driver)
+}
+
+/// 获取所有可写入的矢量驱动（用于导出格式列表）
+pub fn get_export_drivers() -> Vec<ExportDriver> {
+    let mut drivers: Vec<ExportDriver> = DriverManager::all()
+        .filter(is_writable_vector_driver)
+        .map(|driver| {
+            let extensions = driver
+                .metadata_item("DMD_EXTENSIONS", "")
+                .or_else(|| driver.metadata_item("DMD_EXTENSION", ""))
+                .map(|value| value.split_whitespace().map(|ext| ext.to_string()).collect())
+                .unwrap_or_default();
+            ExportDriver {
+                name: driver.short_name(),
+                long_name: driver.long_name(),
+                extensions,
+            }
+        })
+        .collect();
+    drivers.sort_by_key(|driver| driver.name.to_lowercase());
+    drivers
+}
+
+/// 根据字段映射生成目标字段定义，返回 (源字段索引, 目标字段定义)
+fn target_fields(source_fields: &[AttributeField], mapping: &[FieldMapping]) -> Result<Vec<(usize, TableFieldSchema)>> {
+    let schema_of = field_schema;
+
+    if mapping.is_empty() {
+        return Ok(source_fields.iter().map(schema_of).enumerate().collect());
+    }
+
+    mapping
+        .iter()
+        .map(|item| {
+            let index = source_fields
+                .iter()
+                .position(|field| field.name == item.source)
+                .or_else(|| source_fields.iter().position(|field| field.name.eq_ignore_ascii_case(&item.source)))
+                .ok_or_else(|| AppError::InvalidFormat(format!("源字段不存在: {}", item.source)))?;
+
+            let mut schema = schema_of(&source_fields[index]);
+            if let Some(target) = item.target.as_ref().filter(|name| !name.trim().is_empty()) {
+                schema.name = target.trim().to_string();
+            }
+            if let Some(field_type) = item.field_type {
+                if field_type != schema.field_type {
+                    // 类型变化时原宽度、精度和默认值不再适用
+                    schema.width = None;
+                    schema.precision = None;
+                    schema.default_value = None;
+                }
+                schema.field_type = field_type;
+            }
+            if item.width.is_some() {
+                schema.width = item.width;
+            }
+            if item.precision.is_some() {
+                schema.precision = item.precision;
+            }
+            Ok((index, schema))
+        })
+        .collect()
+}
+
+/// 检查输出路径与各输入数据源不同（写入前会删除已存在的输出，相同时输入数据会在读取前被删除）
+pub(crate) fn check_output_path<S: AsRef<str>>(output_path: &str, inputs: &[S]) -> Result<()> {
+    let output_key = worker_pool::lock_key(output_path);
+    match inputs.iter().find(|input| worker_pool::lock_key(input.as_ref()) == output_key) {
+        Some(input) => Err(AppError::InvalidFormat(format!("输出路径不能与输入数据相同: {}", input.as_ref()))),
+        None => Ok(()),
+    }
+}
+
+/// 删除已存在的输出（由驱动删除可同时清理 Shapefile 的附属文件）
+fn remove_existing_output(driver: &Driver, output_path: &str) {
+    let path = Path::new(output_path);
+    if !path.exists() {
+        return;
+    }
+    log::info!("删除已存在的输出文件: {}", output_path);
+    if driver.delete(path).is_err() {
+        let _ = if path.is_dir() {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+    }
+}
+
+/// 删除容器数据源中 first_index 及之后新建的图层（批量转换单项失败时清理未完成的图层）
+fn remove_created_layers(dataset: &mut Dataset, first_index: usize) {
+    // 写入中途失败时事务可能未提交，先回滚（没有进行中的事务时忽略错误）
+    unsafe { gdal_sys::GDALDatasetRollbackTransaction(dataset.c_dataset()) };
+    for index in (first_index..dataset.layer_count()).rev() {
+        let err = unsafe { gdal_sys::GDALDatasetDeleteLayer(dataset.c_dataset(), index as i32) };
+        if err != gdal_sys::OGRErr::OGRERR_NONE {
+            log::warn!("删除未完成的图层 {} 失败: {}", index, gdal_service::last_gdal_error());
+        }
+    }
+}
+
+/// 将WGS84空间范围转换为源坐标系下的多边形（加密边界，避免投影后变形）
+pub(crate) fn extent_geometry(extent: &Extent, source_srs: Option<SpatialRef>) -> Result<Geometry> {
+    let mut geom = Geometry::bbox(extent.min_x, extent.min_y, extent.max_x, extent.max_y)
+        .map_err(|e| AppError::InvalidFormat(format!("空间范围无效: {}", e)))?;
+    let max_length = (extent.max_x - extent.min_x).max(extent.max_y - extent.min_y) / 64.0;
+    if max_length > 0.0 {
+        unsafe { gdal_sys::OGR_G_Segmentize(geom.c_geometry(), max_length) };
+    }
+    if let Some(transform) = gdal_service::create_from_wgs84_transform(source_srs)? {
+        geom.transform_inplace(&transform)
+            .map_err(|e| AppError::InvalidFormat(format!("空间范围坐标转换失败: {}", e)))?;
+    }
+    Ok(geom)
+}
+
+/// 创建到目标坐标系的转换，返回 (输出坐标系, 坐标转换)
+fn target_spatial_ref(
+    source_srs: Option<SpatialRef>,
+    target_crs: Option<&str>,
+) -> Result<(Option<SpatialRef>, Option<CoordTransform>)> {
+    let Some(definition) = target_crs else {
+        return Ok((source_srs, None));
+    };
+    let mut target = SpatialRef::from_definition(definition)
+        .map_err(|e| AppError::InvalidFormat(format!("目标坐标系无效 {}: {}", definition, e)))?;
+    target.set_axis_mapping_strategy(AxisMappingStrategy::TraditionalGisOrder);
+
+    let transform = match source_srs {
+        Some(mut source) => {
+            source.set_axis_mapping_strategy(AxisMappingStrategy::TraditionalGisOrder);
+            Some(CoordTransform::new(&source, &target)
+                .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?)
+        }
+        None => {
+            log::warn!("源图层未定义坐标系，仅设置输出坐标系，不转换坐标");
+            None
+        }
+    };
+    Ok((Some(target), transform))
+}
+
+fn option_key(option: &str) -> String {
+    option.split('=').next().unwrap_or_default().trim().to_uppercase()
+}
+
+/// 创建目标数据源（已存在时先删除）
+fn create_target_dataset(driver: &Driver, output_path: &str, options: &[String]) -> Result<Dataset> {
+    remove_existing_output(driver, output_path);
+    let mut dataset_options = CslStringList::new();
+    for option in options {
+        dataset_options.add_string(option)
+            .map_err(|e| AppError::InvalidFormat(format!("数据源创建选项无效 {}: {}", option, e)))?;
+    }
+    driver
+        .create_with_band_type_with_options::<u8, _>(output_path, 0, 0, 0, &dataset_options)
+        .map_err(|e| AppError::FileWriteError(format!("创建输出文件失败: {}", e)))
+}
+
+/// 导出矢量数据到任意可写入的GDAL格式（进程内逐要素复制，不依赖ogr2ogr）
+/// layer_index: 可选的图层索引，用于导出多图层文件（如KML、GDB）的特定子图层
+/// options: 创建选项、字段映射、目标坐标系、属性/空间过滤、选中要素、提升为多部件
+/// job: 按约1%的间隔报告进度；取消或失败时删除不完整的输出文件
+pub fn export_vector(
+    input_path: &str,
+    output_path: &str,
+    format: &str,
+    layer_index: Option<usize>,
+    options: &ExportOptions,
+    job: &JobContext,
+) -> Result<ExportResult> {
+    check_output_path(output_path, &[input_path])?;
+    let driver = resolve_driver(format, output_path)?;
+    let driver_name = driver.short_name();
+    log::info!("开始导出: {} -> {} (驱动: {})", input_path, output_path, driver_name);
+
+    let dataset = gdal_service::open_vector_dataset(input_path)?;
+    let idx = layer_index.unwrap_or(0);
+    let mut layer = dataset.layer(idx)
+        .map_err(|e| AppError::FileReadError(format!("无法访问图层索引{}: {}", idx, e)))?;
+
+    let layer_name = options.layer_name.clone()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| layer.name());
+    check_selection_source(options, input_path, idx)?;
+
+    let mut target = create_target_dataset(&driver, output_path, &options.dataset_options)?;
+    let result = copy_layer(&mut layer, &mut target, &driver_name, &layer_name, output_path, options, job);
+    drop(target);
+    let (features_written, features_failed) = match result {
+        Ok(counts) => counts,
+        Err(e) => {
+            log::warn!("导出未完成，删除输出文件: {}", output_path);
+            remove_existing_output(&driver, output_path);
+            return Err(e);
+        }
+    };
+
+    if features_failed > 0 {
+        log::warn!("{} 个要素导出失败", features_failed);
+    }
+    log::info!("成功导出 {} 个要素到: {}", features_written, output_path);
+
+    Ok(ExportResult {
+        output_path: output_path.to_string(),
+        driver: driver_name,
+        layer_name,
+        features_written,
+        features_failed,
+    })
+}
+
+/// 导出选择集时检查其属于源图层（图层ID过期或不匹配时报错）
+fn check_selection_source(options: &ExportOptions, path: &str, layer_index: usize) -> Result<()> {
+    if let Some(layer_id) = &options.selection {
+        selection_service::selection_fids(layer_id, path, layer_index)?;
+    }
+    Ok(())
+}
+
+/// 将源图层复制为目标数据源中的新图层，返回 (写入要素数, 失败要素数)
+/// 输出图层按选项处理字段映射、过滤、裁剪、重投影和几何类型
+fn copy_layer(
+    layer: &mut Layer,
+    target: &mut Dataset,
+    driver_name: &str,
+    layer_name: &str,
+    output_path: &str,
+    options: &ExportOptions,
+    job: &JobContext,
+) -> Result<(u64, u64)> {
+    let source_fields = gdal_service::read_attribute_fields(layer.defn());
+    let fields = target_fields(&source_fields, &options.field_mapping)?;
+    let mut geometry_type = geometry::apply_dimension_mode_to_type(
+        unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) },
+        options.dimension,
+    );
+    if options.promote_to_multi {
+        geometry_type = geometry::multi_geometry_type(geometry_type);
+    }
+
+    // 属性过滤、空间范围过滤
+    let where_clause = options.where_clause.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    if let Some(clause) = where_clause {
+        layer.set_attribute_filter(clause)
+            .map_err(|e| AppError::InvalidFormat(format!("属性过滤条件无效 {}: {}", clause, e)))?;
+    }
+    let extent_filter = match &options.extent {
+        Some(extent) => {
+            let geom = extent_geometry(extent, layer.spatial_ref())?;
+            layer.set_spatial_filter(&geom);
+            Some(geom)
+        }
+        None => None,
+    };
+    let clip_geometry = extent_filter.as_ref().filter(|_| options.clip_to_extent);
+    let mut fid_filter: Option<HashSet<u64>> = options.fids.as_ref().map(|fids| fids.iter().copied().collect());
+    if let Some(layer_id) = &options.selection {
+        let selected = selection_service::selected_fids(layer_id)?;
+        fid_filter = Some(match fid_filter {
+            Some(fids) => selected.into_iter().filter(|fid| fids.contains(fid)).collect(),
+            None => selected.into_iter().collect(),
+        });
+    }
+
+    let target_crs = options.target_crs.as_deref().map(str::trim).filter(|crs| !crs.is_empty());
+    let (spatial_ref, transform) = target_spatial_ref(layer.spatial_ref(), target_crs)?;
+    let total_features = match &fid_filter {
+        Some(fids) => fids.len() as u64,
+        None => layer.feature_count(),
+    };
+    let transforms_geometry = clip_geometry.is_some()
+        || transform.is_some()
+        || options.dimension != DimensionMode::Preserve
+        || options.promote_to_multi;
+
+    let target_handle = target.c_dataset();
+
+    // Shapefile 默认以 UTF-8 编码写入属性（避免中文乱码）
+    let mut layer_options: Vec<&str> = options.layer_options.iter().map(|s| s.as_str()).collect();
+    if driver_name == "ESRI Shapefile" && !layer_options.iter().any(|o| option_key(o) == "ENCODING") {
+        layer_options.push("ENCODING=UTF-8");
+    }
+
+    // 写入同一数据源的多个图层时，检查格式是否支持多图层（如 GPKG、GDB）
+    if target.layer_count() > 0 && !unsafe { gdal_service::dataset_has_capability(target_handle, "CreateLayer") } {
+        return Err(AppError::FileWriteError(format!("目标数据源不支持创建更多图层: {}", output_path)));
+    }
+    let target_layer = target
+        .create_layer(LayerOptions {
+            name: layer_name,
+            srs: spatial_ref.as_ref(),
+            ty: geometry_type,
+            options: Some(&layer_options),
+        })
+        .map_err(|e| AppError::FileWriteError(format!("创建输出图层失败: {}", e)))?;
+
+    // 创建字段，记录源字段到目标字段的索引映射（驱动可能改写字段名，按索引对应）
+    let mut field_map = vec![-1i32; source_fields.len()];
+    for (source_index, schema) in &fields {
+        let field_count = || unsafe {
+            gdal_sys::OGR_FD_GetFieldCount(gdal_sys::OGR_L_GetLayerDefn(target_layer.c_layer()))
+        };
+        let before = field_count();
+        gdal_service::create_field(&target_layer, schema)?;
+        let after = field_count();
+        field_map[*source_index] = if after > before {
+            after - 1
+        } else {
+            let c_name = std::ffi::CString::new(schema.name.as_str())
+                .map_err(|e| AppError::InvalidFormat(format!("字段名无效: {}", e)))?;
+            unsafe { gdal_sys::OGR_L_FindFieldIndex(target_layer.c_layer(), c_name.as_ptr(), 0) }
+        };
+    }
+
+    // 支持事务的数据源（GPKG、SQLite、PostGIS等）分批提交以提高写入速度
+    let supports_transactions = unsafe { gdal_service::dataset_has_capability(target_handle, "Transactions") };
+    let start_transaction = || unsafe {
+        supports_transactions
+            && gdal_sys::GDALDatasetStartTransaction(target_handle, 0) == gdal_sys::OGRErr::OGRERR_NONE
+    };
+    let commit_transaction = || unsafe {
+        if gdal_sys::GDALDatasetCommitTransaction(target_handle) != gdal_sys::OGRErr::OGRERR_NONE {
+            Err(AppError::FileWriteError(format!("提交写入失败: {}", gdal_service::last_gdal_error())))
+        } else {
+            Ok(())
+        }
+    };
+    let mut in_transaction = start_transaction();
+
+    let report_interval = if total_features > 0 { (total_features / 100).max(1) } else { PROGRESS_INTERVAL };
+    job.report(0, Some(total_features));
+
+    let mut features_written = 0u64;
+    let mut features_failed = 0u64;
+    let mut next_commit = TRANSACTION_BATCH;
+    let mut next_report = report_interval;
+    // 只导出指定要素且没有属性过滤时按 FID 直接读取，避免遍历整个图层；
+    // 按 FID 读取不受图层过滤条件约束，空间范围需逐个判断
+    let direct_fids: Option<Vec<u64>> = fid_filter.as_ref()
+        .filter(|_| where_clause.is_none())
+        .map(|fids| {
+            let mut fids: Vec<u64> = fids.iter().copied().collect();
+            fids.sort_unstable();
+            fids
+        });
+    let sources: Box<dyn Iterator<Item = gdal::vector::Feature> + '_> = match direct_fids {
+        Some(fids) => {
+            let layer: &Layer = layer;
+            Box::new(fids.into_iter()
+                .filter_map(move |fid| layer.feature(fid))
+                .filter(|feature| match &extent_filter {
+                    Some(filter) => feature.geometry().is_some_and(|geom| geom.intersects(filter)),
+                    None => true,
+                }))
+        }
+        None => Box::new(layer.features()),
+    };
+    for source in sources {
+        job.check_cancelled()?;
+        if let Some(fids) = &fid_filter {
+            if !source.fid().is_some_and(|fid| fids.contains(&fid)) {
+                continue;
+            }
+        }
+
+        let mut feature = gdal::vector::Feature::new(target_layer.defn())
+            .map_err(|e| AppError::FileWriteError(format!("创建要素失败: {}", e)))?;
+
+        // 按字段映射复制属性和几何，类型不同时由OGR转换
+        let err = unsafe {
+            gdal_sys::OGR_F_SetFromWithMap(feature.c_feature(), source.c_feature(), 1, field_map.as_ptr())
+        };
+        if err != gdal_sys::OGRErr::OGRERR_NONE {
+            log::warn!("要素 {:?} 属性复制失败: {}", source.fid(), gdal_service::last_gdal_error());
+            features_failed += 1;
+            continue;
+        }
+
+        // 几何处理顺序：裁剪（源坐标系）-> 维度 -> 重投影 -> 提升为多部件
+        if let Some(geom) = source.geometry().filter(|_| transforms_geometry) {
+            let mut geom = match &clip_geometry {
+                Some(clip) => match geom.intersection(clip) {
+                    Some(clipped) if !clipped.is_empty() => clipped,
+                    _ => continue, // 与范围仅边界相交
+                },
+                None => geom.clone(),
+            };
+            geometry::apply_dimension_mode(&mut geom, options.dimension);
+            if let Some(transform) = &transform {
+                if let Err(e) = geom.transform_inplace(transform) {
+                    log::warn!("要素 {:?} 坐标转换失败: {}", source.fid(), e);
+                    features_failed += 1;
+                    continue;
+                }
+            }
+            if options.promote_to_multi {
+                let multi_type = geometry::multi_geometry_type(geom.geometry_type());
+                geometry::set_feature_geometry_as(&feature, geom, multi_type)?;
+            } else {
+                feature.set_geometry(geom)
+                    .map_err(|e| AppError::FileWriteError(format!("设置几何失败: {}", e)))?;
+            }
+        }
+
+        if let Err(e) = feature.create(&target_layer) {
+            log::warn!("要素 {:?} 写入失败: {}", source.fid(), e);
+            features_failed += 1;
+            continue;
+        }
+        features_written += 1;
+
+        if in_transaction && features_written >= next_commit {
+            commit_transaction()?;
+            in_transaction = start_transaction();
+            next_commit += TRANSACTION_BATCH;
+        }
+        if features_written >= next_report {
+            job.report(features_written, Some(total_features));
+            next_report += report_interval;
+        }
+    }
+
+    if in_transaction {
+        commit_transaction()?;
+    }
+    job.report(features_written, Some(total_features));
+
+    Ok((features_written, features_failed))
+}
+
+/// 按源字段定义生成输出字段定义
+pub(crate) fn field_schema(field: &AttributeField) -> TableFieldSchema {
+    TableFieldSchema {
+        name: field.name.clone(),
+        field_type: field.field_type,
+        width: field.width,
+        precision: field.precision,
+        alias: field.alias.clone(),
+        nullable: field.nullable,
+        unique: false,
+        default_value: field.default_value.clone(),
+        domain: None, // 属性域只存在于源数据库中
+    }
+}
+
+/// 分析工具新增的输出字段（如连接数、距离、面积）
+pub(crate) fn new_field_schema(name: &str, field_type: FieldType) -> TableFieldSchema {
+    TableFieldSchema {
+        name: name.to_string(),
+        field_type,
+        width: None,
+        precision: None,
+        alias: None,
+        nullable: true,
+        unique: false,
+        default_value: None,
+        domain: None,
+    }
+}
+
+/// 生成不与已有名称重复的字段名（不区分大小写，重复时追加 _1、_2…）
+pub(crate) fn unique_field_name(name: &str, used: &mut HashSet<String>) -> String {
+    let mut candidate = name.to_string();
+    let mut suffix = 1;
+    while !used.insert(candidate.to_lowercase()) {
+        candidate = format!("{}_{}", name, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+/// 按字段索引设置要素字段值
+pub(crate) fn set_field_by_index(feature: &gdal::vector::Feature, index: i32, value: Option<&FieldValue>) {
+    let handle = unsafe { feature.c_feature() };
+    let set_string = |s: &str| {
+        if let Ok(c_value) = std::ffi::CString::new(s) {
+            unsafe { gdal_sys::OGR_F_SetFieldString(handle, index, c_value.as_ptr()) };
+        }
+    };
+    match value {
+        Some(FieldValue::IntegerValue(i)) => unsafe { gdal_sys::OGR_F_SetFieldInteger(handle, index, *i) },
+        Some(FieldValue::Integer64Value(i)) => unsafe { gdal_sys::OGR_F_SetFieldInteger64(handle, index, *i) },
+        Some(FieldValue::RealValue(r)) => unsafe { gdal_sys::OGR_F_SetFieldDouble(handle, index, *r) },
+        Some(FieldValue::StringValue(s)) => set_string(s),
+        Some(FieldValue::DateValue(d)) => set_string(&d.format("%Y-%m-%d").to_string()),
+        Some(FieldValue::DateTimeValue(dt)) => set_string(&dt.format("%Y-%m-%dT%H:%M:%S%:z").to_string()),
+        Some(FieldValue::StringListValue(list)) => set_string(&list.join(",")),
+        // 其余列表类型不写入
+        Some(_) | None => unsafe { gdal_sys::OGR_F_SetFieldNull(handle, index) },
+    }
+}
+
+/// 分析工具的输出图层：创建输出数据源和单个图层，分批事务提交
+/// 写入失败的要素计数后跳过；调用 discard 删除不完整的输出
+pub(crate) struct OutputLayer {
+    driver: Driver,
+    dataset: Dataset,
+    driver_name: String,
+    output_path: String,
+    layer_name: String,
+    geometry_type: gdal::vector::OGRwkbGeometryType::Type,
+    field_indexes: Vec<i32>,
+    supports_transactions: bool,
+    in_transaction: bool,
+    next_commit: u64,
+    features_written: u64,
+    features_failed: u64,
+}
+
+impl OutputLayer {
+    /// 创建输出数据源（已存在时覆盖）和图层
+    pub fn create(
+        output_path: &str,
+        format: &str,
+        layer_name: &str,
+        srs: Option<&SpatialRef>,
+        geometry_type: gdal::vector::OGRwkbGeometryType::Type,
+        fields: &[TableFieldSchema],
+    ) -> Result<OutputLayer> {
+        let driver = resolve_driver(format, output_path)?;
+        let driver_name = driver.short_name();
+        let mut dataset = create_target_dataset(&driver, output_path, &[])?;
+
+        let mut layer_options = Vec::new();
+        if driver_name == "ESRI Shapefile" {
+            layer_options.push("ENCODING=UTF-8");
+        }
+        let layer = dataset
+            .create_layer(LayerOptions {
+                name: layer_name,
+                srs,
+                ty: geometry_type,
+                options: Some(&layer_options),
+            })
+            .map_err(|e| AppError::FileWriteError(format!("创建输出图层失败: {}", e)))?;
+
+        // 驱动可能改写字段名（如 Shapefile 截断为10个字符），按创建顺序记录字段索引
+        let mut field_indexes = Vec::with_capacity(fields.len());
+        for schema in fields {
+            gdal_service::create_field(&layer, schema)?;
+            let count = unsafe { gdal_sys::OGR_FD_GetFieldCount(gdal_sys::OGR_L_GetLayerDefn(layer.c_layer())) };
+            field_indexes.push(count - 1);
+        }
+
+        let handle = dataset.c_dataset();
+        let supports_transactions = unsafe { gdal_service::dataset_has_capability(handle, "Transactions") };
+        let mut output = OutputLayer {
+            driver,
+            dataset,
+            driver_name,
+            output_path: output_path.to_string(),
+            layer_name: layer_name.to_string(),
+            geometry_type,
+            field_indexes,
+            supports_transactions,
+            in_transaction: false,
+            next_commit: TRANSACTION_BATCH,
+            features_written: 0,
+            features_failed: 0,
+        };
+        output.in_transaction = output.start_transaction();
+        Ok(output)
+    }
+
+    /// 按分析工具的输出设置创建输出，未指定图层名时使用输出文件名
+    pub fn for_analysis(
+        output: &AnalysisOutput,
+        srs: Option<&SpatialRef>,
+        geometry_type: gdal::vector::OGRwkbGeometryType::Type,
+        fields: &[TableFieldSchema],
+    ) -> Result<OutputLayer> {
+        let layer_name = output.layer_name.clone()
+            .filter(|name| !name.trim().is_empty())
+            .or_else(|| Path::new(&output.path).file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "output".to_string());
+        Self::create(&output.path, output.format.as_deref().unwrap_or_default(), &layer_name, srs, geometry_type, fields)
+    }
+
+    fn start_transaction(&self) -> bool {
+        self.supports_transactions
+            && unsafe { gdal_sys::GDALDatasetStartTransaction(self.dataset.c_dataset(), 0) } == gdal_sys::OGRErr::OGRERR_NONE
+    }
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        self.in_transaction = false;
+        if unsafe { gdal_sys::GDALDatasetCommitTransaction(self.dataset.c_dataset()) } != gdal_sys::OGRErr::OGRERR_NONE {
+            return Err(AppError::FileWriteError(format!("提交写入失败: {}", gdal_service::last_gdal_error())));
+        }
+        Ok(())
+    }
+
+    /// 已写入的要素数
+    pub fn features_written(&self) -> u64 {
+        self.features_written
+    }
+
+    /// 写入一个要素，values 与创建时的字段一一对应
+    /// 图层声明了几何类型时几何先转换为该类型（如叠加结果的单部件提升为多部件）
+    pub fn write(&mut self, geometry: Option<Geometry>, values: &[Option<FieldValue>]) -> Result<()> {
+        let layer = self.dataset.layer(0)
+            .map_err(|e| AppError::FileWriteError(format!("无法访问输出图层: {}", e)))?;
+        let mut feature = gdal::vector::Feature::new(layer.defn())
+            .map_err(|e| AppError::FileWriteError(format!("创建要素失败: {}", e)))?;
+        for (&index, value) in self.field_indexes.iter().zip(values) {
+            set_field_by_index(&feature, index, value.as_ref());
+        }
+        match geometry {
+            Some(geometry) if unsafe { gdal_sys::OGR_GT_Flatten(self.geometry_type) } != OGRwkbGeometryType::wkbUnknown => {
+                geometry::set_feature_geometry_as(&feature, geometry, self.geometry_type)?;
+            }
+            Some(geometry) => {
+                feature.set_geometry(geometry)
+                    .map_err(|e| AppError::FileWriteError(format!("设置几何失败: {}", e)))?;
+            }
+            None => {}
+        }
+        if let Err(e) = feature.create(&layer) {
+            log::warn!("要素写入失败: {}", e);
+            self.features_failed += 1;
+            return Ok(());
+        }
+        drop(feature);
+
+        self.features_written += 1;
+        if self.in_transaction && self.features_written >= self.next_commit {
+            self.commit_transaction()?;
+            self.in_transaction = self.start_transaction();
+            self.next_commit += TRANSACTION_BATCH;
+        }
+        Ok(())
+    }
+
+    /// 提交并关闭输出
+    pub fn finish(mut self) -> Result<ExportResult> {
+        if self.in_transaction {
+            if let Err(e) = self.commit_transaction() {
+                self.discard();
+                return Err(e);
+            }
+        }
+        if self.features_failed > 0 {
+            log::warn!("{} 个要素写入失败", self.features_failed);
+        }
+        log::info!("输出 {} 个要素到: {}", self.features_written, self.output_path);
+        Ok(ExportResult {
+            output_path: self.output_path,
+            driver: self.driver_name,
+            layer_name: self.layer_name,
+            features_written: self.features_written,
+            features_failed: self.features_failed,
+        })
+    }
+
+    /// 关闭并删除不完整的输出
+    pub fn discard(self) {
+        log::warn!("分析未完成，删除输出文件: {}", self.output_path);
+        let OutputLayer { driver, dataset, output_path, .. } = self;
+        drop(dataset);
+        remove_existing_output(&driver, &output_path);
+    }
+}
+
+/// 批量转换中待转换的图层
+struct BatchItem {
+    source: String,
+    layer_index: usize,
+    layer_name: String,
+    output_name: String,
+}
+
+/// 收集文件夹中的矢量数据源（GDB 文件夹作为一个数据源，不再深入）
+fn collect_sources(dir: &Path, recursive: bool, exclude: &Path, sources: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path == exclude {
+            continue;
+        }
+        if path.is_dir() {
+            if gdb_service::is_gdb_path(&path.to_string_lossy()) {
+                sources.push(path);
+            } else if recursive {
+                collect_sources(&path, recursive, exclude, sources)?;
+            }
+        } else {
+            let extension = path.extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if BATCH_EXTENSIONS.contains(&extension.as_str()) {
+                sources.push(path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 列出数据源中需要转换的图层
+/// prefix: 遍历文件夹时以文件名作为输出名称（多图层数据源为 文件名_图层名）
+fn plan_source(source: &str, prefix: Option<&str>, filter: Option<&[String]>) -> Result<Vec<BatchItem>> {
+    let dataset = gdal_service::open_vector_dataset(source)?;
+    let layer_count = dataset.layer_count();
+    let mut items = Vec::new();
+
+    for (index, layer) in dataset.layers().enumerate() {
+        let layer_name = layer.name();
+        if let Some(names) = filter {
+            if !names.iter().any(|name| name.eq_ignore_ascii_case(&layer_name)) {
+                continue;
+            }
+        }
+        let output_name = match prefix {
+            Some(prefix) if layer_count == 1 => prefix.to_string(),
+            Some(prefix) => format!("{}_{}", prefix, layer_name),
+            None => layer_name.clone(),
+        };
+        items.push(BatchItem {
+            source: source.to_string(),
+            layer_index: index,
+            layer_name,
+            output_name,
+        });
+    }
+    Ok(items)
+}
+
+/// 输出名称中去掉文件名不允许的字符
+pub(crate) fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let name = name.trim().trim_matches('.').to_string();
+    if name.is_empty() { "layer".to_string() } else { name }
+}
+
+/// 生成不重复的输出名称（不区分大小写）
+pub(crate) fn unique_name(name: &str, used: &mut HashSet<String>) -> String {
+    let base = sanitize_name(name);
+    let mut candidate = base.clone();
+    let mut suffix = 2;
+    while !used.insert(candidate.to_lowercase()) {
+        candidate = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+/// 输出文件扩展名
+pub(crate) fn output_extension(format: &str, driver: &Driver) -> Option<String> {
+    if format.trim().eq_ignore_ascii_case("KMZ") {
+        return Some("kmz".to_string());
+    }
+    if driver.short_name() == "GeoJSON" {
+        return Some("geojson".to_string());
+    }
+    driver
+        .metadata_item("DMD_EXTENSION", "")
+        .or_else(|| driver.metadata_item("DMD_EXTENSIONS", ""))
+        .and_then(|value| value.split_whitespace().next().map(|ext| ext.to_string()))
+}
+
+/// 转换单个图层；target 为 None 时创建独立的输出文件
+fn convert_item(
+    item: &BatchItem,
+    target: Option<&mut Dataset>,
+    driver: &Driver,
+    output_layer: &str,
+    output_path: &str,
+    options: &ExportOptions,
+    job: &JobContext,
+) -> Result<(u64, u64)> {
+    let dataset = gdal_service::open_vector_dataset(&item.source)?;
+    let mut layer = dataset.layer(item.layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法访问图层索引{}: {}", item.layer_index, e)))?;
+    let driver_name = driver.short_name();
+    check_selection_source(options, &item.source, item.layer_index)?;
+
+    match target {
+        Some(target) => copy_layer(&mut layer, target, &driver_name, output_layer, output_path, options, job),
+        None => {
+            let mut target = create_target_dataset(driver, output_path, &options.dataset_options)?;
+            copy_layer(&mut layer, &mut target, &driver_name, output_layer, output_path, options, job)
+        }
+    }
+}
+
+/// 批量转换文件夹或多图层数据源中的所有图层
+/// 每个图层写入输出目录下的独立文件，或写入同一个数据源（container）；
+/// 单个图层失败不影响其他图层（删除其未完成的输出文件或图层），结果逐项报告；
+/// 取消时删除正在写入的文件（写入同一数据源时删除整个数据源），已完成的文件保留
+pub fn batch_convert<F>(
+    request: &BatchConvertRequest,
+    job: &JobContext,
+    mut progress: F,
+) -> Result<BatchConvertReport>
+where
+    F: FnMut(BatchProgress),
+{
+    let source_path = Path::new(&request.source);
+    if !source_path.exists() {
+        return Err(AppError::FileNotFound(request.source.clone()));
+    }
+    let output_path = Path::new(&request.output);
+    let driver = resolve_driver(&request.format, if request.container { &request.output } else { "" })?;
+    log::info!("开始批量转换: {} -> {} (驱动: {})", request.source, request.output, driver.short_name());
+
+    // 列出所有待转换的图层，无法打开的数据源直接记为失败
+    let filter = request.layers.as_deref();
+    let mut items = Vec::new();
+    let mut results: Vec<BatchItemResult> = Vec::new();
+    if source_path.is_dir() && !gdb_service::is_gdb_path(&request.source) {
+        let mut sources = Vec::new();
+        collect_sources(source_path, request.recursive, output_path, &mut sources)?;
+        for path in sources {
+            let source = path.to_string_lossy().into_owned();
+            let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            match plan_source(&source, Some(&stem), filter) {
+                Ok(source_items) => items.extend(source_items),
+                Err(e) => {
+                    log::warn!("无法打开数据源 {}: {}", source, e);
+                    results.push(BatchItemResult {
+                        source,
+                        layer_index: None,
+                        layer_name: None,
+                        output_path: String::new(),
+                        output_layer: None,
+                        success: false,
+                        features_written: 0,
+                        features_failed: 0,
+                        error: Some(e.to_string()),
+                    });
+                }
+            }
+        }
+    } else {
+        items = plan_source(&request.source, None, filter)?;
+    }
+
+    let total = items.len() + results.len();
+    if total == 0 {
+        return Err(AppError::InvalidFormat(format!("未找到可转换的图层: {}", request.source)));
+    }
+    for (index, item) in results.iter().enumerate() {
+        progress(BatchProgress { completed: index + 1, total, item: item.clone() });
+    }
+
+    // 输出目录或容器数据源
+    let extension = if request.container {
+        None
+    } else {
+        std::fs::create_dir_all(output_path)?;
+        Some(output_extension(&request.format, &driver).ok_or_else(|| {
+            AppError::InvalidFormat(format!("驱动 {} 没有文件扩展名，请写入同一个数据源", driver.short_name()))
+        })?)
+    };
+    let mut container = if request.container {
+        Some(create_target_dataset(&driver, &request.output, &request.options.dataset_options)?)
+    } else {
+        None
+    };
+
+    let options = ExportOptions { layer_name: None, ..request.options.clone() };
+    let mut used_names = HashSet::new();
+    for item in &items {
+        let output_layer = unique_name(&item.output_name, &mut used_names);
+        let item_output = match &extension {
+            Some(extension) => output_path.join(format!("{}.{}", output_layer, extension)).to_string_lossy().into_owned(),
+            None => request.output.clone(),
+        };
+
+        log::info!("转换图层 {} ({}) -> {}", item.layer_name, item.source, item_output);
+        job.set_stage(results.len(), total);
+        let layers_before = container.as_ref().map(|dataset| dataset.layer_count());
+        let result = job.check_cancelled()
+            .and_then(|_| convert_item(item, container.as_mut(), &driver, &output_layer, &item_output, &options, job));
+        if let Err(e @ AppError::Cancelled(_)) = result {
+            log::info!("批量转换已取消，删除未完成的输出: {}", item_output);
+            drop(container);
+            remove_existing_output(&driver, &item_output);
+            return Err(e);
+        }
+        let item_result = match result {
+            Ok((features_written, features_failed)) => BatchItemResult {
+                source: item.source.clone(),
+                layer_index: Some(item.layer_index),
+                layer_name: Some(item.layer_name.clone()),
+                output_path: item_output,
+                output_layer: Some(output_layer),
+                success: true,
+                features_written,
+                features_failed,
+                error: None,
+            },
+            Err(e) => {
+                log::warn!("图层 {} 转换失败: {}", item.layer_name, e);
+                match (container.as_mut(), layers_before) {
+                    (Some(dataset), Some(first_index)) => remove_created_layers(dataset, first_index),
+                    _ => remove_existing_output(&driver, &item_output),
+                }
+                BatchItemResult {
+                    source: item.source.clone(),
+                    layer_index: Some(item.layer_index),
+                    layer_name: Some(item.layer_name.clone()),
+                    output_path: item_output,
+                    output_layer: None,
+                    success: false,
+                    features_written: 0,
+                    features_failed: 0,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        results.push(item_result.clone());
+        progress(BatchProgress { completed: results.len(), total, item: item_result });
+    }
+    drop(container);
+
+    let succeeded = results.iter().filter(|item| item.success).count();
+    let features_written = results.iter().map(|item| item.features_written).sum();
+    log::info!("批量转换完成: 成功 {} 个，失败 {} 个", succeeded, total - succeeded);
+
+    Ok(BatchConvertReport {
+        output: request.output.clone(),
+        total,
+        succeeded,
+        failed: total - succeeded,
+        features_written,
+        items: results,
+    })
+}
@@ -1,8 +1,13 @@
 use crate::errors::{AppError, Result};
-use crate::models::{Extent, Feature, Geometry, VectorInfo, AttributeField, MultiLayerVectorInfo, LayerInfo};
+use crate::gis::geometry;
+use crate::services::attribute_join_service::AttributeJoiner;
+use crate::services::job_service::JobContext;
+use crate::services::{gdb_service, postgis_service, spatial_index_service, tile_service};
+use crate::models::{Extent, Feature, Geometry, VectorInfo, AttributeField, MultiLayerVectorInfo, LayerInfo, CoordinateDimension, DimensionMode, FeatureEdits, EditResult, FieldType, TableFieldSchema, FieldStatistics};
 use gdal::Dataset;
-use gdal::spatial_ref::SpatialRef;
-use gdal::vector::LayerAccess;
+use gdal::spatial_ref::{CoordTransform, SpatialRef};
+use gdal::vector::{LayerAccess, OGRFieldType, OGRwkbGeometryType};
+use gdal_sys::OGRFieldSubType;
 use gdal::DriverManager;
 use std::collections::HashMap;
 use std::path::Path;
@@ -93,6 +98,503 @@ fn open_vector_with_encoding(path: &str) -> Result<Dataset> {
     Err(AppError::FileReadError(format!("无法打开文件: 尝试了所有编码都失败")))
 }
 
+/// 使用WKT定义WGS84，避免依赖EPSG数据库
+const WGS84_WKT: &str = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563]],PRIMEM["Greenwich",0],UNIT["degree",0.0174532925199433]]"#;
+
+/// 提取复合坐标系中垂直坐标系部分的WKT（VERT_CS节点）
+fn vertical_cs_wkt(srs: &SpatialRef) -> Option<String> {
+    if !srs.is_compound() {
+        return None;
+    }
+    
+    let wkt = srs.to_wkt().ok()?;
+    let start = wkt.find("VERT_CS[")?;
+    let mut depth = 0;
+    for (i, c) in wkt[start..].char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(wkt[start..start + i + 1].to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 获取垂直坐标系名称
+fn vertical_crs_name(srs: &SpatialRef) -> Option<String> {
+    if !srs.is_compound() && !srs.is_vertical() {
+        return None;
+    }
+    srs.get_attr_value("VERT_CS", 0).ok().flatten()
+}
+
+/// 创建到 WGS84 (经纬度) 的坐标转换，WGS84/CGCS2000 或无坐标系时返回 None
+/// 源坐标系为复合坐标系时，目标坐标系保留其垂直坐标系，Z值按原高程基准保留
+pub(crate) fn create_wgs84_transform(source_srs: Option<SpatialRef>) -> Result<Option<CoordTransform>> {
+    let mut source = match source_srs {
+        Some(srs) => srs,
+        None => {
+            log::warn!("未检测到坐标系，假定为 WGS84");
+            return Ok(None);
+        }
+    };
+    
+    let epsg_code = source.auth_code().unwrap_or_default();
+    log::info!("源坐标系 EPSG: {}", epsg_code);
+    
+    // 4490 是 CGCS2000，近似 WGS84
+    if epsg_code == 4326 || epsg_code == 4490 {
+        return Ok(None);
+    }
+    
+    let target_wkt = match vertical_cs_wkt(&source) {
+        Some(vert_cs) => {
+            log::info!("检测到复合坐标系，保留垂直坐标系");
+            format!(r#"COMPD_CS["WGS 84 + Vertical",{},{}]"#, WGS84_WKT, vert_cs)
+        }
+        None => WGS84_WKT.to_string(),
+    };
+    
+    let mut target_srs = SpatialRef::from_wkt(&target_wkt)
+        .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e)))?;
+    
+    // 设置轴映射为传统 GIS 顺序 (经度, 纬度)
+    target_srs.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
+    source.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
+    
+    let transform = CoordTransform::new(&source, &target_srs)
+        .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?;
+    Ok(Some(transform))
+}
+
+/// 将图层范围转换到 WGS84 (经纬度)，按四个角点计算转换后的外包矩形
+fn wgs84_extent(envelope: &gdal::vector::Envelope, spatial_ref: Option<SpatialRef>) -> Result<Extent> {
+    let Some(transform) = create_wgs84_transform(spatial_ref)? else {
+        return Ok(Extent {
+            min_x: envelope.MinX,
+            min_y: envelope.MinY,
+            max_x: envelope.MaxX,
+            max_y: envelope.MaxY,
+        });
+    };
+    
+    let mut xs = vec![envelope.MinX, envelope.MaxX, envelope.MinX, envelope.MaxX];
+    let mut ys = vec![envelope.MinY, envelope.MinY, envelope.MaxY, envelope.MaxY];
+    let mut zs = vec![0.0; 4];
+    transform.transform_coords(&mut xs, &mut ys, &mut zs)
+        .map_err(|e| AppError::InvalidFormat(format!("范围坐标转换失败: {}", e)))?;
+    
+    // 此时 xs=经度, ys=纬度
+    let min_x = xs.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max_x = xs.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let min_y = ys.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max_y = ys.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    log::info!("范围已转换到 WGS84 (经度,纬度): [{}, {}, {}, {}]", min_x, min_y, max_x, max_y);
+    
+    Ok(Extent { min_x, min_y, max_x, max_y })
+}
+
+/// 创建从 WGS84 (经纬度) 到目标坐标系的转换，用于写回前端编辑的几何
+pub(crate) fn create_from_wgs84_transform(target_srs: Option<SpatialRef>) -> Result<Option<CoordTransform>> {
+    let mut target = match target_srs {
+        Some(srs) => srs,
+        None => return Ok(None),
+    };
+    
+    let epsg_code = target.auth_code().unwrap_or_default();
+    if epsg_code == 4326 || epsg_code == 4490 {
+        return Ok(None);
+    }
+    
+    let mut source = SpatialRef::from_wkt(WGS84_WKT)
+        .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e)))?;
+    source.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
+    target.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
+    
+    let transform = CoordTransform::new(&source, &target)
+        .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?;
+    Ok(Some(transform))
+}
+
+/// 创建从地图坐标系（如 "EPSG:4490"）到图层坐标系的转换，图层未定义坐标系时按 WGS84 处理；
+/// 坐标系相同或同为 WGS84/CGCS2000 时返回 None
+pub(crate) fn create_map_to_layer_transform(map_crs: &str, layer_srs: Option<SpatialRef>) -> Result<Option<CoordTransform>> {
+    let source = SpatialRef::from_definition(map_crs)
+        .map_err(|e| AppError::InvalidFormat(format!("地图坐标系无效 {}: {}", map_crs, e)))?;
+    create_layer_transform(Some(source), layer_srs)
+}
+
+/// 创建两个图层坐标系之间的转换，未定义坐标系时按 WGS84 处理；
+/// 坐标系相同或同为 WGS84/CGCS2000 时返回 None
+pub(crate) fn create_layer_transform(
+    source_srs: Option<SpatialRef>,
+    target_srs: Option<SpatialRef>,
+) -> Result<Option<CoordTransform>> {
+    let or_wgs84 = |srs: Option<SpatialRef>| match srs {
+        Some(srs) => Ok(srs),
+        None => SpatialRef::from_epsg(4326)
+            .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e))),
+    };
+    let mut source = or_wgs84(source_srs)?;
+    let mut target = or_wgs84(target_srs)?;
+
+    // 4490 是 CGCS2000，近似 WGS84
+    let is_wgs84 = |srs: &SpatialRef| matches!(srs.auth_code(), Ok(4326) | Ok(4490));
+    if source == target || (is_wgs84(&source) && is_wgs84(&target)) {
+        return Ok(None);
+    }
+
+    source.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
+    target.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
+    let transform = CoordTransform::new(&source, &target)
+        .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?;
+    Ok(Some(transform))
+}
+
+/// 获取GDAL最近一次错误信息
+pub(crate) fn last_gdal_error() -> String {
+    unsafe {
+        let msg = gdal_sys::CPLGetLastErrorMsg();
+        if msg.is_null() {
+            String::new()
+        } else {
+            std::ffi::CStr::from_ptr(msg).to_string_lossy().into_owned()
+        }
+    }
+}
+
+/// 检查数据源能力（如 "Transactions"、"CreateLayer"）
+///
+/// # Safety
+/// dataset 必须是有效的GDAL数据源句柄
+pub(crate) unsafe fn dataset_has_capability(dataset: gdal_sys::GDALDatasetH, capability: &str) -> bool {
+    let capability = std::ffi::CString::new(capability).unwrap_or_default();
+    gdal_sys::GDALDatasetTestCapability(dataset, capability.as_ptr()) != 0
+}
+
+/// 将图层复制到目标数据源（保留字段、几何和坐标系），返回新图层的实际名称（驱动可能规范化名称）
+pub(crate) fn copy_layer_to_dataset(
+    layer: &gdal::vector::Layer,
+    target: &Dataset,
+    name: &str,
+    options: &[(&str, &str)],
+) -> Result<String> {
+    let mut layer_options = gdal::cpl::CslStringList::new();
+    for (key, value) in options {
+        layer_options.set_name_value(key, value)
+            .map_err(|e| AppError::Unknown(format!("设置图层选项失败: {}", e)))?;
+    }
+    
+    let c_name = std::ffi::CString::new(name)
+        .map_err(|e| AppError::InvalidFormat(format!("图层名称无效: {}", e)))?;
+    let c_layer = unsafe {
+        gdal_sys::OGR_DS_CopyLayer(target.c_dataset(), layer.c_layer(), c_name.as_ptr(), layer_options.as_ptr())
+    };
+    if c_layer.is_null() {
+        return Err(AppError::FileWriteError(format!("复制图层失败: {}", last_gdal_error())));
+    }
+    let created = unsafe { std::ffi::CStr::from_ptr(gdal_sys::OGR_L_GetName(c_layer)) };
+    Ok(created.to_string_lossy().into_owned())
+}
+
+/// OGR 字段类型及子类型转换为 FieldType
+pub(crate) fn field_type_from_ogr(field_type: OGRFieldType::Type, sub_type: OGRFieldSubType::Type) -> FieldType {
+    match (field_type, sub_type) {
+        (OGRFieldType::OFTInteger, OGRFieldSubType::OFSTBoolean) => FieldType::Boolean,
+        (OGRFieldType::OFTInteger, OGRFieldSubType::OFSTInt16) => FieldType::Int16,
+        (OGRFieldType::OFTInteger, _) => FieldType::Integer,
+        (OGRFieldType::OFTInteger64, _) => FieldType::Integer64,
+        (OGRFieldType::OFTReal, OGRFieldSubType::OFSTFloat32) => FieldType::Float32,
+        (OGRFieldType::OFTReal, _) => FieldType::Real,
+        (OGRFieldType::OFTString, OGRFieldSubType::OFSTJSON) => FieldType::Json,
+        (OGRFieldType::OFTString, OGRFieldSubType::OFSTUUID) => FieldType::Uuid,
+        (OGRFieldType::OFTString, _) | (OGRFieldType::OFTWideString, _) => FieldType::String,
+        (OGRFieldType::OFTDate, _) => FieldType::Date,
+        (OGRFieldType::OFTTime, _) => FieldType::Time,
+        (OGRFieldType::OFTDateTime, _) => FieldType::DateTime,
+        (OGRFieldType::OFTBinary, _) => FieldType::Binary,
+        (OGRFieldType::OFTIntegerList, _) => FieldType::IntegerList,
+        (OGRFieldType::OFTInteger64List, _) => FieldType::Integer64List,
+        (OGRFieldType::OFTRealList, _) => FieldType::RealList,
+        (OGRFieldType::OFTStringList, _) | (OGRFieldType::OFTWideStringList, _) => FieldType::StringList,
+        _ => FieldType::Unknown,
+    }
+}
+
+/// FieldType 转换为 OGR 字段类型及子类型（未知类型按字符串处理）
+pub(crate) fn ogr_field_type(field_type: FieldType) -> (OGRFieldType::Type, OGRFieldSubType::Type) {
+    match field_type {
+        FieldType::String | FieldType::Unknown => (OGRFieldType::OFTString, OGRFieldSubType::OFSTNone),
+        FieldType::Json => (OGRFieldType::OFTString, OGRFieldSubType::OFSTJSON),
+        FieldType::Uuid => (OGRFieldType::OFTString, OGRFieldSubType::OFSTUUID),
+        FieldType::Integer => (OGRFieldType::OFTInteger, OGRFieldSubType::OFSTNone),
+        FieldType::Int16 => (OGRFieldType::OFTInteger, OGRFieldSubType::OFSTInt16),
+        FieldType::Boolean => (OGRFieldType::OFTInteger, OGRFieldSubType::OFSTBoolean),
+        FieldType::Integer64 => (OGRFieldType::OFTInteger64, OGRFieldSubType::OFSTNone),
+        FieldType::Real => (OGRFieldType::OFTReal, OGRFieldSubType::OFSTNone),
+        FieldType::Float32 => (OGRFieldType::OFTReal, OGRFieldSubType::OFSTFloat32),
+        FieldType::Date => (OGRFieldType::OFTDate, OGRFieldSubType::OFSTNone),
+        FieldType::Time => (OGRFieldType::OFTTime, OGRFieldSubType::OFSTNone),
+        FieldType::DateTime => (OGRFieldType::OFTDateTime, OGRFieldSubType::OFSTNone),
+        FieldType::Binary => (OGRFieldType::OFTBinary, OGRFieldSubType::OFSTNone),
+        FieldType::IntegerList => (OGRFieldType::OFTIntegerList, OGRFieldSubType::OFSTNone),
+        FieldType::Integer64List => (OGRFieldType::OFTInteger64List, OGRFieldSubType::OFSTNone),
+        FieldType::RealList => (OGRFieldType::OFTRealList, OGRFieldSubType::OFSTNone),
+        FieldType::StringList => (OGRFieldType::OFTStringList, OGRFieldSubType::OFSTNone),
+    }
+}
+
+fn c_string_opt(ptr: *const std::os::raw::c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    let value = unsafe { std::ffi::CStr::from_ptr(ptr) }.to_string_lossy().into_owned();
+    if value.is_empty() { None } else { Some(value) }
+}
+
+/// 读取图层的字段定义（类型、宽度、精度、别名、可空、唯一、默认值、属性域）
+pub(crate) fn read_attribute_fields(defn: &gdal::vector::Defn) -> Vec<AttributeField> {
+    let count = unsafe { gdal_sys::OGR_FD_GetFieldCount(defn.c_defn()) };
+    
+    (0..count)
+        .filter_map(|i| unsafe {
+            let field = gdal_sys::OGR_FD_GetFieldDefn(defn.c_defn(), i);
+            if field.is_null() {
+                return None;
+            }
+            let positive = |v: i32| if v > 0 { Some(v) } else { None };
+            Some(AttributeField {
+                name: c_string_opt(gdal_sys::OGR_Fld_GetNameRef(field)).unwrap_or_default(),
+                field_type: field_type_from_ogr(gdal_sys::OGR_Fld_GetType(field), gdal_sys::OGR_Fld_GetSubType(field)),
+                width: positive(gdal_sys::OGR_Fld_GetWidth(field)),
+                precision: positive(gdal_sys::OGR_Fld_GetPrecision(field)),
+                alias: c_string_opt(gdal_sys::OGR_Fld_GetAlternativeNameRef(field)),
+                nullable: gdal_sys::OGR_Fld_IsNullable(field) != 0,
+                unique: gdal_sys::OGR_Fld_IsUnique(field) != 0,
+                default_value: c_string_opt(gdal_sys::OGR_Fld_GetDefault(field)),
+                domain: c_string_opt(gdal_sys::OGR_Fld_GetDomainName(field)),
+                editable: false,
+                visible: true,
+            })
+        })
+        .collect()
+}
+
+/// 在图层中新建字段
+pub(crate) fn create_field<L: LayerAccess>(layer: &L, field: &TableFieldSchema) -> Result<()> {
+    let (field_type, sub_type) = ogr_field_type(field.field_type);
+    let to_c = |value: &str| std::ffi::CString::new(value)
+        .map_err(|e| AppError::InvalidFormat(format!("字段 {} 定义无效: {}", field.name, e)));
+    
+    let c_name = to_c(&field.name)?;
+    let c_alias = field.alias.as_deref().map(to_c).transpose()?;
+    let c_default = field.default_value.as_deref().map(to_c).transpose()?;
+    let c_domain = field.domain.as_deref().map(to_c).transpose()?;
+    
+    let err = unsafe {
+        let field_defn = gdal_sys::OGR_Fld_Create(c_name.as_ptr(), field_type);
+        gdal_sys::OGR_Fld_SetSubType(field_defn, sub_type);
+        if let Some(width) = field.width {
+            gdal_sys::OGR_Fld_SetWidth(field_defn, width);
+        }
+        if let Some(precision) = field.precision {
+            gdal_sys::OGR_Fld_SetPrecision(field_defn, precision);
+        }
+        if let Some(ref alias) = c_alias {
+            gdal_sys::OGR_Fld_SetAlternativeName(field_defn, alias.as_ptr());
+        }
+        if let Some(ref default) = c_default {
+            gdal_sys::OGR_Fld_SetDefault(field_defn, default.as_ptr());
+        }
+        if let Some(ref domain) = c_domain {
+            gdal_sys::OGR_Fld_SetDomainName(field_defn, domain.as_ptr());
+        }
+        gdal_sys::OGR_Fld_SetNullable(field_defn, field.nullable as i32);
+        gdal_sys::OGR_Fld_SetUnique(field_defn, field.unique as i32);
+        
+        let err = gdal_sys::OGR_L_CreateField(layer.c_layer(), field_defn, 1);
+        gdal_sys::OGR_Fld_Destroy(field_defn);
+        err
+    };
+    
+    if err != gdal_sys::OGRErr::OGRERR_NONE {
+        return Err(AppError::FileWriteError(format!("添加字段 {} 失败: {}", field.name, last_gdal_error())));
+    }
+    Ok(())
+}
+
+/// 按字段定义校验要写入的值（非空约束、字符串长度）
+pub(crate) fn validate_field_value(field: &AttributeField, value: &serde_json::Value) -> Result<()> {
+    match value {
+        serde_json::Value::Null if !field.nullable => {
+            Err(AppError::InvalidFormat(format!("字段 {} 不允许为空", field.name)))
+        }
+        serde_json::Value::String(s) if field.field_type == FieldType::String => {
+            match field.width {
+                Some(width) if s.chars().count() > width as usize => Err(AppError::InvalidFormat(format!(
+                    "字段 {} 的值超过最大长度 {}: {}", field.name, width, s
+                ))),
+                _ => Ok(()),
+            }
+        }
+        serde_json::Value::String(s) if field.field_type.is_numeric() && s.trim().parse::<f64>().is_err() => {
+            Err(AppError::InvalidFormat(format!("字段 {} 需要数值: {}", field.name, s)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 检测图层的坐标维度
+/// 优先使用图层声明的几何类型，未声明时（如KML、GeoJSON）查看第一个要素
+pub(crate) fn detect_coordinate_dimension<L: LayerAccess>(layer: &mut L) -> CoordinateDimension {
+    let declared_type = layer.defn().geom_fields().next().map(|f| f.field_type());
+    if let Some(ty) = declared_type {
+        if unsafe { gdal_sys::OGR_GT_Flatten(ty) } != OGRwkbGeometryType::wkbUnknown {
+            return geometry::dimension_of_type(ty);
+        }
+    }
+    
+    let dimension = layer.features()
+        .next()
+        .and_then(|feature| feature.geometry().map(geometry::coordinate_dimension))
+        .unwrap_or(CoordinateDimension::Xy);
+    layer.reset_feature_reading();
+    dimension
+}
+
+/// 打开矢量数据源
+/// - Shapefile、KML、KMZ：使用智能编码
+/// - `postgis:` 引用：解析为已保存的 PostgreSQL 连接
+/// - 其他：直接由 GDAL 打开
+pub(crate) fn open_vector_dataset(path: &str) -> Result<Dataset> {
+    if postgis_service::is_postgis_path(path) {
+        let connection_string = postgis_service::resolve_path(path)?;
+        return Dataset::open_ex(
+            &connection_string,
+            gdal::DatasetOptions {
+                open_flags: gdal::GdalOpenFlags::GDAL_OF_VECTOR | gdal::GdalOpenFlags::GDAL_OF_READONLY,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| AppError::DatabaseError(format!("无法连接PostGIS: {}", e)));
+    }
+    
+    let path_lower = path.to_lowercase();
+    if path_lower.ends_with(".shp") || path_lower.ends_with(".kml") || path_lower.ends_with(".kmz") {
+        open_vector_with_encoding(path)
+    } else {
+        Dataset::open(path)
+            .map_err(|e| AppError::FileReadError(format!("无法打开文件: {}", e)))
+    }
+}
+
+/// 以读写方式打开矢量数据源（用于直接修改图层，如新增字段）
+/// Shapefile 使用检测到的编码，保证写回的文本与读取时一致
+pub(crate) fn open_vector_dataset_for_update(path: &str) -> Result<Dataset> {
+    let open_flags = gdal::GdalOpenFlags::GDAL_OF_VECTOR | gdal::GdalOpenFlags::GDAL_OF_UPDATE;
+    if postgis_service::is_postgis_path(path) {
+        let connection_string = postgis_service::resolve_path(path)?;
+        return Dataset::open_ex(&connection_string, gdal::DatasetOptions { open_flags, ..Default::default() })
+            .map_err(|e| AppError::DatabaseError(format!("无法连接PostGIS: {}", e)));
+    }
+
+    let encoding_option = path.to_lowercase().ends_with(".shp")
+        .then(|| detect_file_encoding(path).into_iter().find(|encoding| !encoding.is_empty()))
+        .flatten()
+        .map(|encoding| format!("ENCODING={}", encoding));
+    let open_options: Vec<&str> = encoding_option.iter().map(String::as_str).collect();
+    Dataset::open_ex(
+        path,
+        gdal::DatasetOptions {
+            open_flags,
+            open_options: (!open_options.is_empty()).then_some(open_options.as_slice()),
+            ..Default::default()
+        },
+    )
+    .map_err(|e| AppError::FileWriteError(format!("无法以读写方式打开文件: {}", e)))
+}
+
+/// 数据源修改标记：最后修改时间（纳秒）和大小，任一变化即视为数据源已修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SourceStamp {
+    pub modified_nanos: u128,
+    pub size: u64,
+}
+
+impl std::fmt::Display for SourceStamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.modified_nanos, self.size)
+    }
+}
+
+/// 数据源修改标记，目录型数据源（如 GDB）取目录内文件的最新修改时间和总大小，
+/// Shapefile 同时计入 .dbf/.shx（只改属性时 .shp 不变）；数据库连接等无法获取时返回 None
+pub(crate) fn source_modified(path: &str) -> Option<SourceStamp> {
+    let modified = |meta: &std::fs::Metadata| {
+        meta.modified().ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+    };
+    let meta = std::fs::metadata(path).ok()?;
+    let entries: Vec<std::fs::Metadata> = if meta.is_dir() {
+        std::fs::read_dir(path).ok()?
+            .filter_map(|entry| entry.ok()?.metadata().ok())
+            .collect()
+    } else if path.to_lowercase().ends_with(".shp") {
+        ["dbf", "shx", "DBF", "SHX"].iter()
+            .filter_map(|extension| std::fs::metadata(Path::new(path).with_extension(extension)).ok())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let modified_nanos = entries.iter()
+        .filter_map(modified)
+        .chain(modified(&meta))
+        .max()?;
+    let size = entries.iter()
+        .chain(std::iter::once(&meta))
+        .filter(|entry| entry.is_file())
+        .map(|entry| entry.len())
+        .sum();
+    Some(SourceStamp { modified_nanos, size })
+}
+
+/// 按字段类型将JSON值写入要素字段
+pub(crate) fn set_field_from_json(
+    feature: &mut gdal::vector::Feature,
+    field_name: &str,
+    field_type: OGRFieldType::Type,
+    value: &serde_json::Value,
+) -> Result<()> {
+    let map_err = |e: gdal::errors::GdalError| AppError::InvalidFormat(format!("设置字段 {} 失败: {}", field_name, e));
+    
+    match value {
+        serde_json::Value::Null => feature.set_field_null(field_name).map_err(map_err),
+        serde_json::Value::Bool(b) => feature.set_field_integer(field_name, *b as i32).map_err(map_err),
+        serde_json::Value::Number(n) => match field_type {
+            OGRFieldType::OFTInteger => {
+                let v = n.as_i64().ok_or_else(|| AppError::InvalidFormat(format!("字段 {} 需要整数: {}", field_name, n)))?;
+                let v = i32::try_from(v).map_err(|_| AppError::InvalidFormat(format!("字段 {} 整数溢出: {}", field_name, n)))?;
+                feature.set_field_integer(field_name, v).map_err(map_err)
+            }
+            OGRFieldType::OFTInteger64 => {
+                let v = n.as_i64().ok_or_else(|| AppError::InvalidFormat(format!("字段 {} 需要整数: {}", field_name, n)))?;
+                feature.set_field_integer64(field_name, v).map_err(map_err)
+            }
+            OGRFieldType::OFTReal => feature.set_field_double(field_name, n.as_f64().unwrap_or_default()).map_err(map_err),
+            _ => feature.set_field_string(field_name, &n.to_string()).map_err(map_err),
+        },
+        // 字符串交给OGR按字段类型解析（日期、数字等）
+        serde_json::Value::String(s) => feature.set_field_string(field_name, s).map_err(map_err),
+        other => feature.set_field_string(field_name, &other.to_string()).map_err(map_err),
+    }
+}
+
 /// 解析KML的description字段
 /// KML文件通常将所有属性数据打包在description字段中
 /// 格式如: "OBJECTID":1 "HNNM":"岷江" "RIVER":"杂谷脑河"
@@ -132,18 +634,9 @@ fn parse_kml_description(description: &str) -> HashMap<String, serde_json::Value
 }
 
 /// 使用GDAL读取矢量文件信息
-pub async fn read_vector_info(path: &str) -> Result<VectorInfo> {
-    let path_lower = path.to_lowercase();
-    
-    // 使用智能编码打开 Shapefile、KML、KMZ
-    let dataset = if path_lower.ends_with(".shp") 
-        || path_lower.ends_with(".kml") 
-        || path_lower.ends_with(".kmz") {
-        open_vector_with_encoding(path)?
-    } else {
-        Dataset::open(path)
-            .map_err(|e| AppError::FileReadError(format!("无法打开文件: {}", e)))?
-    };
+pub fn read_vector_info(path: &str) -> Result<VectorInfo> {
+    // 打开数据源（Shapefile、KML、KMZ 使用智能编码）
+    let dataset = open_vector_dataset(path)?;
     
     let mut layer = dataset.layer(0)
         .map_err(|e| AppError::FileReadError(format!("无法读取图层: {}", e)))?;
@@ -156,86 +649,17 @@ pub async fn read_vector_info(path: &str) -> Result<VectorInfo> {
         .as_ref()
         .map(|sr| sr.to_wkt().unwrap_or_default())
         .unwrap_or_default();
+    let vertical_crs = spatial_ref.as_ref().and_then(vertical_crs_name);
     
     // 获取原始范围
     let envelope = layer.get_extent()
         .map_err(|e| AppError::FileReadError(format!("无法获取范围: {}", e)))?;
     
-    // 检测是否需要转换范围到 WGS84 (经纬度)
-    let needs_transform = if let Some(ref srs) = spatial_ref {
-        let epsg_code = srs.auth_code().unwrap_or_default();
-        log::info!("源坐标系 EPSG: {}", epsg_code);
-        // 如果不是 WGS84，需要转换
-        epsg_code != 4326 && epsg_code != 4490  // 4490 是 CGCS2000，近似 WGS84
-    } else {
-        log::warn!("未检测到坐标系，假定为 WGS84");
-        false  // 假定已经是 WGS84
-    };
-    
     // 转换范围到 WGS84 (经纬度) - MapLibre 需要经纬度坐标
-    let extent = if needs_transform {
-        // 使用WKT定义WGS84，避免依赖EPSG数据库
-        const WGS84_WKT: &str = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563]],PRIMEM["Greenwich",0],UNIT["degree",0.0174532925199433]]"#;
-        
-        let mut target_srs = SpatialRef::from_wkt(WGS84_WKT)
-            .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e)))?;
-        
-        // 设置轴映射为传统 GIS 顺序 (经度, 纬度)
-        target_srs.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-        
-        let mut source = match spatial_ref {
-            Some(s) => s,
-            None => SpatialRef::from_wkt(WGS84_WKT)
-                .map_err(|e| AppError::InvalidFormat(format!("创建默认WGS84坐标系失败: {}", e)))?
-        };
-        source.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-        
-        let transform = gdal::spatial_ref::CoordTransform::new(&source, &target_srs)
-            .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?;
-        
-        // 转换四个角点
-        let mut xs = vec![envelope.MinX, envelope.MaxX, envelope.MinX, envelope.MaxX];
-        let mut ys = vec![envelope.MinY, envelope.MinY, envelope.MaxY, envelope.MaxY];
-        let mut zs = vec![0.0; 4];
-        
-        transform.transform_coords(&mut xs, &mut ys, &mut zs)
-            .map_err(|e| AppError::InvalidFormat(format!("范围坐标转换失败: {}", e)))?;
-        
-        // 计算转换后的边界框（此时 xs=经度, ys=纬度）
-        let min_x = xs.iter().cloned().fold(f64::INFINITY, f64::min);
-        let max_x = xs.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-        let min_y = ys.iter().cloned().fold(f64::INFINITY, f64::min);
-        let max_y = ys.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-        
-        log::info!("范围已转换到 WGS84 (经度,纬度): [{}, {}, {}, {}]", min_x, min_y, max_x, max_y);
-        
-        Extent {
-            min_x,
-            min_y,
-            max_x,
-            max_y,
-        }
-    } else {
-        Extent {
-            min_x: envelope.MinX,
-            min_y: envelope.MinY,
-            max_x: envelope.MaxX,
-            max_y: envelope.MaxY,
-        }
-    };
+    let extent = wgs84_extent(&envelope, spatial_ref)?;
     
     // 获取字段信息
-    let layer_defn = layer.defn();
-    let mut fields = Vec::new();
-    for field in layer_defn.fields() {
-        fields.push(AttributeField {
-            name: field.name(),
-            field_type: format!("{:?}", field.field_type()),
-            alias: None,
-            editable: false,
-            visible: true,
-        });
-    }
+    let fields = read_attribute_fields(layer.defn());
     
     // 获取几何类型 - 尝试从第一个要素获取
     let mut geometry_type = "Unknown".to_string();
@@ -245,29 +669,96 @@ pub async fn read_vector_info(path: &str) -> Result<VectorInfo> {
         }
     }
     
+    let coordinate_dimension = detect_coordinate_dimension(&mut layer);
+    
     Ok(VectorInfo {
         path: path.to_string(),
         feature_count,
         geometry_type,
+        coordinate_dimension,
         fields,
         extent,
         projection: if projection.is_empty() { None } else { Some(projection) },
+        vertical_crs,
     })
 }
 
-/// 获取要素总数
-pub async fn get_feature_count(path: &str) -> Result<usize> {
-    let path_lower = path.to_lowercase();
-    
-    // 使用智能编码打开 Shapefile、KML、KMZ
-    let dataset = if path_lower.ends_with(".shp") 
-        || path_lower.ends_with(".kml") 
-        || path_lower.ends_with(".kmz") {
-        open_vector_with_encoding(path)?
-    } else {
-        Dataset::open(path)
-            .map_err(|e| AppError::FileReadError(format!("无法打开文件: {}", e)))?
+/// 读取图层字段定义
+pub fn read_layer_fields(path: &str, layer_index: Option<usize>) -> Result<Vec<AttributeField>> {
+    let dataset = open_vector_dataset(path)?;
+    let idx = layer_index.unwrap_or(0);
+    let layer = dataset.layer(idx)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", idx, e)))?;
+    Ok(read_attribute_fields(layer.defn()))
+}
+
+/// 统计字段值，fids 不为空时只统计指定要素（如选择集）
+/// 非数值字段只统计非空值和空值个数
+pub fn field_statistics(path: &str, layer_index: usize, field: &str, fids: Option<&[u64]>) -> Result<FieldStatistics> {
+    let dataset = open_vector_dataset(path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let field_type = layer.defn().fields()
+        .find(|f| f.name() == field)
+        .map(|f| f.field_type())
+        .ok_or_else(|| AppError::InvalidFormat(format!("字段不存在: {}", field)))?;
+    let numeric = matches!(field_type, OGRFieldType::OFTInteger | OGRFieldType::OFTInteger64 | OGRFieldType::OFTReal);
+
+    let mut stats = FieldStatistics {
+        field: field.to_string(),
+        count: 0,
+        null_count: 0,
+        min: None,
+        max: None,
+        sum: None,
+        mean: None,
+    };
+    let mut accumulate = |feature: &gdal::vector::Feature| -> Result<()> {
+        let read_error = |e: gdal::errors::GdalError| AppError::FileReadError(format!("读取字段失败 {}: {}", field, e));
+        let present = if numeric {
+            let value = feature.field_as_double_by_name(field).map_err(read_error)?;
+            if let Some(value) = value {
+                stats.min = Some(stats.min.map_or(value, |m| m.min(value)));
+                stats.max = Some(stats.max.map_or(value, |m| m.max(value)));
+                stats.sum = Some(stats.sum.unwrap_or(0.0) + value);
+            }
+            value.is_some()
+        } else {
+            feature.field(field).map_err(read_error)?.is_some()
+        };
+        if present {
+            stats.count += 1;
+        } else {
+            stats.null_count += 1;
+        }
+        Ok(())
     };
+
+    match fids {
+        Some(fids) => {
+            for &fid in fids {
+                if let Some(feature) = layer.feature(fid) {
+                    accumulate(&feature)?;
+                }
+            }
+        }
+        None => {
+            for feature in layer.features() {
+                accumulate(&feature)?;
+            }
+        }
+    }
+
+    if stats.count > 0 {
+        stats.mean = stats.sum.map(|sum| sum / stats.count as f64);
+    }
+    Ok(stats)
+}
+
+/// 获取要素总数
+pub fn get_feature_count(path: &str) -> Result<usize> {
+    // 打开数据源（Shapefile、KML、KMZ 使用智能编码）
+    let dataset = open_vector_dataset(path)?;
     
     let layer = dataset.layer(0)
         .map_err(|e| AppError::FileReadError(format!("无法读取图层: {}", e)))?;
@@ -276,18 +767,11 @@ pub async fn get_feature_count(path: &str) -> Result<usize> {
 }
 
 /// 使用GDAL读取要素（仅属性，不含几何）
-pub async fn read_vector_features(path: &str, offset: Option<usize>, limit: Option<usize>) -> Result<Vec<Feature>> {
-    let path_lower = path.to_lowercase();
+pub fn read_vector_features(path: &str, offset: Option<usize>, limit: Option<usize>) -> Result<Vec<Feature>> {
+    let is_kml = is_kml_path(path);
     
-    // 使用智能编码打开 Shapefile、KML、KMZ
-    let dataset = if path_lower.ends_with(".shp") 
-        || path_lower.ends_with(".kml") 
-        || path_lower.ends_with(".kmz") {
-        open_vector_with_encoding(path)?
-    } else {
-        Dataset::open(path)
-            .map_err(|e| AppError::FileReadError(format!("无法打开文件: {}", e)))?
-    };
+    // 打开数据源（Shapefile、KML、KMZ 使用智能编码）
+    let dataset = open_vector_dataset(path)?;
     
     let mut layer = dataset.layer(0)
         .map_err(|e| AppError::FileReadError(format!("无法读取图层: {}", e)))?;
@@ -305,63 +789,9 @@ pub async fn read_vector_features(path: &str, offset: Option<usize>, limit: Opti
             break;
         }
         
-        // 获取几何
-        let geometry = if let Some(geom) = feature.geometry() {
-            let geojson = geom.json()
-                .map_err(|e| AppError::InvalidFormat(format!("几何转换失败: {}", e)))?;
-            
-            let geojson_value: serde_json::Value = serde_json::from_str(&geojson)
-                .map_err(|e| AppError::InvalidFormat(format!("JSON解析失败: {}", e)))?;
-            
-            Geometry {
-                geom_type: geojson_value["type"].as_str().unwrap_or("Unknown").to_string(),
-                coordinates: geojson_value["coordinates"].clone(),
-            }
-        } else {
-            Geometry {
-                geom_type: "Null".to_string(),
-                coordinates: serde_json::Value::Null,
-            }
-        };
-        
-        // 获取属性
-        let mut properties = HashMap::new();
-        let mut has_description = false;
-        let mut description_content = String::new();
-        
-        for (field_name, field_value) in feature.fields() {
-            let json_value = match field_value {
-                Some(gdal::vector::FieldValue::StringValue(s)) => {
-                    // 检测是否为KML的description字段
-                    if field_name.to_lowercase() == "description" && (path_lower.ends_with(".kml") || path_lower.ends_with(".kmz")) {
-                        has_description = true;
-                        description_content = s.clone();
-                    }
-                    serde_json::Value::String(s.clone())
-                },
-                Some(gdal::vector::FieldValue::IntegerValue(i)) => serde_json::Value::Number(i.into()),
-                Some(gdal::vector::FieldValue::Integer64Value(i)) => serde_json::Value::Number(i.into()),
-                Some(gdal::vector::FieldValue::RealValue(r)) => {
-                    serde_json::Number::from_f64(r)
-                        .map(serde_json::Value::Number)
-                        .unwrap_or(serde_json::Value::Null)
-                },
-                Some(gdal::vector::FieldValue::DateValue(d)) => serde_json::Value::String(format!("{:?}", d)),
-                Some(gdal::vector::FieldValue::DateTimeValue(dt)) => serde_json::Value::String(format!("{:?}", dt)),
-                _ => serde_json::Value::Null,
-            };
-            properties.insert(field_name.clone(), json_value);
-        }
-        
-        // 如果是KML且有description字段，解析并合并属性
-        if has_description && !description_content.is_empty() {
-            let parsed_props = parse_kml_description(&description_content);
-            log::info!("解析KML description字段，提取了 {} 个属性", parsed_props.len());
-            // 将解析出的属性合并到properties中
-            for (key, value) in parsed_props {
-                properties.insert(key, value);
-            }
-        }
+        // 几何保持图层坐标系
+        let geometry = to_feature_geometry(feature_geometry_json(&feature, None, DimensionMode::Preserve)?);
+        let properties = feature_properties(&feature, is_kml, None).into_iter().collect();
         
         features.push(Feature {
             id: feature.fid().unwrap_or(idx as u64).to_string(),
@@ -374,18 +804,19 @@ pub async fn read_vector_features(path: &str, offset: Option<usize>, limit: Opti
 }
 
 /// 使用GDAL读取要素（包含几何信息，用于属性表）
-pub async fn read_vector_features_with_geometry(path: &str, offset: Option<usize>, limit: Option<usize>) -> Result<Vec<Feature>> {
-    let path_lower = path.to_lowercase();
-    
-    // 使用智能编码打开 Shapefile、KML、KMZ
-    let dataset = if path_lower.ends_with(".shp") 
-        || path_lower.ends_with(".kml") 
-        || path_lower.ends_with(".kmz") {
-        open_vector_with_encoding(path)?
-    } else {
-        Dataset::open(path)
-            .map_err(|e| AppError::FileReadError(format!("无法打开文件: {}", e)))?
-    };
+/// decode_domains: 将编码值域字段的编码替换为描述（GDB、GeoPackage）
+/// joiners: 虚拟属性连接，附加表格字段
+pub fn read_vector_features_with_geometry(
+    path: &str,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    decode_domains: bool,
+    joiners: &[AttributeJoiner],
+) -> Result<Vec<Feature>> {
+    let is_kml = is_kml_path(path);
+    
+    // 打开数据源（Shapefile、KML、KMZ 使用智能编码）
+    let dataset = open_vector_dataset(path)?;
     
     let mut layer = dataset.layer(0)
         .map_err(|e| AppError::FileReadError(format!("无法读取图层: {}", e)))?;
@@ -394,34 +825,10 @@ pub async fn read_vector_features_with_geometry(path: &str, offset: Option<usize
     let limit = limit.unwrap_or(usize::MAX);
     
     // 检测坐标系并创建转换器 - 转换到 WGS84
-    let source_srs = layer.spatial_ref();
-    let needs_transform = if let Some(ref srs) = source_srs {
-        let epsg_code = srs.auth_code().unwrap_or_default();
-        // 如果不是 WGS84 或 CGCS2000，需要转换
-        epsg_code != 4326 && epsg_code != 4490
-    } else {
-        false  // 假定已经是 WGS84
-    };
+    let transform = create_wgs84_transform(layer.spatial_ref())?;
     
-    let transform = if needs_transform {
-        // 使用WKT定义WGS84，避免依赖EPSG数据库
-        const WGS84_WKT: &str = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563]],PRIMEM["Greenwich",0],UNIT["degree",0.0174532925199433]]"#;
-        
-        let mut target_srs = SpatialRef::from_wkt(WGS84_WKT)
-            .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e)))?;
-        
-        // 设置轴映射为传统 GIS 顺序 (经度, 纬度)
-        target_srs.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-        
-        let mut source = match source_srs {
-            Some(s) => s,
-            None => SpatialRef::from_wkt(WGS84_WKT)
-                .map_err(|e| AppError::InvalidFormat(format!("创建默认WGS84坐标系失败: {}", e)))?
-        };
-        source.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-        
-        Some(gdal::spatial_ref::CoordTransform::new(&source, &target_srs)
-            .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?)
+    let decoder = if decode_domains {
+        gdb_service::DomainDecoder::for_layer(&dataset, &layer)
     } else {
         None
     };
@@ -436,71 +843,14 @@ pub async fn read_vector_features_with_geometry(path: &str, offset: Option<usize
             break;
         }
         
-        // 获取几何并转换
-        let geometry = if let Some(geom) = feature.geometry() {
-            // 克隆几何对象以便修改
-            let mut geom_owned = geom.clone();
-            
-            // 如果需要转换，执行坐标转换
-            if let Some(ref trans) = transform {
-                geom_owned.transform_inplace(trans)
-                    .map_err(|e| AppError::InvalidFormat(format!("坐标转换失败: {}", e)))?;
-            }
-            
-            let geojson = geom_owned.json()
-                .map_err(|e| AppError::InvalidFormat(format!("几何转换失败: {}", e)))?;
-            
-            let geojson_value: serde_json::Value = serde_json::from_str(&geojson)
-                .map_err(|e| AppError::InvalidFormat(format!("JSON解析失败: {}", e)))?;
-            
-            Geometry {
-                geom_type: geojson_value["type"].as_str().unwrap_or("Unknown").to_string(),
-                coordinates: geojson_value["coordinates"].clone(),
-            }
-        } else {
-            Geometry {
-                geom_type: "Null".to_string(),
-                coordinates: serde_json::Value::Null,
-            }
-        };
-        
-        // 获取属性
-        let mut properties = HashMap::new();
-        let mut has_description = false;
-        let mut description_content = String::new();
+        let geometry = to_feature_geometry(feature_geometry_json(&feature, transform.as_ref(), DimensionMode::Preserve)?);
+        let mut properties: HashMap<String, serde_json::Value> =
+            feature_properties(&feature, is_kml, decoder.as_ref()).into_iter().collect();
         
-        for (field_name, field_value) in feature.fields() {
-            let json_value = match field_value {
-                Some(gdal::vector::FieldValue::StringValue(s)) => {
-                    // 检测是否为KML的description字段
-                    if field_name.to_lowercase() == "description" && (path_lower.ends_with(".kml") || path_lower.ends_with(".kmz")) {
-                        has_description = true;
-                        description_content = s.clone();
-                    }
-                    serde_json::Value::String(s.clone())
-                },
-                Some(gdal::vector::FieldValue::IntegerValue(i)) => serde_json::Value::Number(i.into()),
-                Some(gdal::vector::FieldValue::Integer64Value(i)) => serde_json::Value::Number(i.into()),
-                Some(gdal::vector::FieldValue::RealValue(r)) => {
-                    serde_json::Number::from_f64(r)
-                        .map(serde_json::Value::Number)
-                        .unwrap_or(serde_json::Value::Null)
-                },
-                Some(gdal::vector::FieldValue::DateValue(d)) => serde_json::Value::String(format!("{:?}", d)),
-                Some(gdal::vector::FieldValue::DateTimeValue(dt)) => serde_json::Value::String(format!("{:?}", dt)),
-                _ => serde_json::Value::Null,
-            };
-            properties.insert(field_name.clone(), json_value);
-        }
-        
-        // 如果是KML且有description字段，解析并合并属性
-        if has_description && !description_content.is_empty() {
-            let parsed_props = parse_kml_description(&description_content);
-            log::info!("解析KML description字段，提取了 {} 个属性", parsed_props.len());
-            // 将解析出的属性合并到properties中
-            for (key, value) in parsed_props {
-                properties.insert(key, value);
-            }
+        for joiner in joiners {
+            joiner.apply(&feature, |name, value| {
+                properties.insert(name, value);
+            });
         }
         
         features.push(Feature {
@@ -514,134 +864,23 @@ pub async fn read_vector_features_with_geometry(path: &str, offset: Option<usize
 }
 
 /// 读取为GeoJSON格式，自动转换到 WGS84 (EPSG:4326)
-pub async fn read_vector_as_geojson(path: &str) -> Result<serde_json::Value> {
-    let path_lower = path.to_lowercase();
+/// dimension: Z值/M值的处理方式（保留、去掉M值或强制二维）
+pub fn read_vector_as_geojson(path: &str, dimension: DimensionMode) -> Result<serde_json::Value> {
+    let is_kml = is_kml_path(path);
     
-    // 使用智能编码打开 Shapefile、KML、KMZ
-    let dataset = if path_lower.ends_with(".shp") 
-        || path_lower.ends_with(".kml") 
-        || path_lower.ends_with(".kmz") {
-        open_vector_with_encoding(path)?
-    } else {
-        Dataset::open(path)
-            .map_err(|e| AppError::FileReadError(format!("无法打开文件: {}", e)))?
-    };
+    // 打开数据源（Shapefile、KML、KMZ 使用智能编码）
+    let dataset = open_vector_dataset(path)?;
     
     let mut layer = dataset.layer(0)
         .map_err(|e| AppError::FileReadError(format!("无法读取图层: {}", e)))?;
     
-    // 检测源坐标系
-    let source_srs = layer.spatial_ref();
-    let needs_transform = if let Some(ref srs) = source_srs {
-        // 获取 EPSG 代码
-        let epsg_code = srs.auth_code().unwrap_or_default();
-        log::info!("源坐标系 EPSG: {}", epsg_code);
-        
-        // 如果不是 WGS84 或 CGCS2000，需要转换
-        epsg_code != 4326 && epsg_code != 4490
-    } else {
-        log::warn!("未检测到坐标系，假定为 WGS84");
-        false // 假定已经是 WGS84
-    };
-    
-    log::info!("是否需要坐标转换到 WGS84: {}", needs_transform);
-    
-    // 创建坐标转换器 - 转换到 WGS84
-    let transform = if needs_transform {
-        // 使用WKT定义WGS84，避免依赖EPSG数据库
-        const WGS84_WKT: &str = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563]],PRIMEM["Greenwich",0],UNIT["degree",0.0174532925199433]]"#;
-        
-        let mut target_srs = SpatialRef::from_wkt(WGS84_WKT)
-            .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e)))?;
-        
-        // 设置轴映射为传统 GIS 顺序 (经度, 纬度)
-        target_srs.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-        
-        let mut source = match source_srs {
-            Some(s) => s,
-            None => SpatialRef::from_wkt(WGS84_WKT)
-                .map_err(|e| AppError::InvalidFormat(format!("创建默认WGS84坐标系失败: {}", e)))?
-        };
-        source.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-        
-        Some(gdal::spatial_ref::CoordTransform::new(&source, &target_srs)
-            .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?)
-    } else {
-        None
-    };
-    
-    // 读取并转换要素
-    let mut geojson_features = Vec::new();
+    // 检测源坐标系，创建坐标转换器 - 转换到 WGS84
+    let transform = create_wgs84_transform(layer.spatial_ref())?;
+    log::info!("是否需要坐标转换到 WGS84: {}", transform.is_some());
     
-    for feature in layer.features() {
-        // 获取几何并转换
-        let geometry = if let Some(geom) = feature.geometry() {
-            // 克隆几何对象以便修改
-            let mut geom_owned = geom.clone();
-            
-            // 如果需要转换，执行坐标转换
-            if let Some(ref trans) = transform {
-                geom_owned.transform_inplace(trans)
-                    .map_err(|e| AppError::InvalidFormat(format!("坐标转换失败: {}", e)))?;
-            }
-            
-            let geojson = geom_owned.json()
-                .map_err(|e| AppError::InvalidFormat(format!("几何转换失败: {}", e)))?;
-            
-            let geojson_value: serde_json::Value = serde_json::from_str(&geojson)
-                .map_err(|e| AppError::InvalidFormat(format!("JSON解析失败: {}", e)))?;
-            
-            geojson_value
-        } else {
-            serde_json::json!(null)
-        };
-        
-        // 获取属性
-        let mut properties = serde_json::Map::new();
-        let mut has_description = false;
-        let mut description_content = String::new();
-        
-        for (field_name, field_value) in feature.fields() {
-            let json_value = match field_value {
-                Some(gdal::vector::FieldValue::StringValue(s)) => {
-                    // 检测是否为KML的description字段
-                    if field_name.to_lowercase() == "description" && (path_lower.ends_with(".kml") || path_lower.ends_with(".kmz")) {
-                        has_description = true;
-                        description_content = s.clone();
-                    }
-                    serde_json::Value::String(s.clone())
-                },
-                Some(gdal::vector::FieldValue::IntegerValue(i)) => serde_json::Value::Number(i.into()),
-                Some(gdal::vector::FieldValue::Integer64Value(i)) => serde_json::Value::Number(i.into()),
-                Some(gdal::vector::FieldValue::RealValue(r)) => {
-                    serde_json::Number::from_f64(r)
-                        .map(serde_json::Value::Number)
-                        .unwrap_or(serde_json::Value::Null)
-                },
-                Some(gdal::vector::FieldValue::DateValue(d)) => serde_json::Value::String(format!("{:?}", d)),
-                Some(gdal::vector::FieldValue::DateTimeValue(dt)) => serde_json::Value::String(format!("{:?}", dt)),
-                _ => serde_json::Value::Null,
-            };
-            properties.insert(field_name.clone(), json_value);
-        }
-        
-        // 如果是KML且有description字段，解析并合并属性
-        if has_description && !description_content.is_empty() {
-            let parsed_props = parse_kml_description(&description_content);
-            log::info!("解析KML description字段，提取了 {} 个属性", parsed_props.len());
-            // 将解析出的属性合并到properties中
-            for (key, value) in parsed_props {
-                properties.insert(key, value);
-            }
-        }
-        
-        geojson_features.push(serde_json::json!({
-            "type": "Feature",
-            "id": feature.fid().unwrap_or(0),
-            "properties": properties,
-            "geometry": geometry
-        }));
-    }
+    let geojson_features = layer.features()
+        .map(|feature| feature_to_geojson(&feature, transform.as_ref(), dimension, is_kml, None))
+        .collect::<Result<Vec<_>>>()?;
     
     Ok(serde_json::json!({
         "type": "FeatureCollection",
@@ -650,30 +889,46 @@ pub async fn read_vector_as_geojson(path: &str) -> Result<serde_json::Value> {
 }
 
 /// 坐标转换
-pub async fn transform_coordinates(
+/// 每个坐标为 [x, y]、[x, y, z] 或 [x, y, z, m]，Z值参与转换（含垂直坐标系转换），M值原样保留
+pub fn transform_coordinates(
     from_srs: &str,
     to_srs: &str,
-    coordinates: Vec<(f64, f64)>
-) -> Result<Vec<(f64, f64)>> {
-    let source = SpatialRef::from_definition(from_srs)
+    coordinates: Vec<Vec<f64>>
+) -> Result<Vec<Vec<f64>>> {
+    if let Some(c) = coordinates.iter().find(|c| c.len() < 2 || c.len() > 4) {
+        return Err(AppError::InvalidFormat(format!("坐标维度无效: {:?}", c)));
+    }
+    
+    let mut source = SpatialRef::from_definition(from_srs)
         .map_err(|e| AppError::InvalidFormat(format!("源坐标系无效: {}", e)))?;
     
-    let target = SpatialRef::from_definition(to_srs)
+    let mut target = SpatialRef::from_definition(to_srs)
         .map_err(|e| AppError::InvalidFormat(format!("目标坐标系无效: {}", e)))?;
     
-    let transform = gdal::spatial_ref::CoordTransform::new(&source, &target)
+    // 坐标按 (x, y) 即 (经度, 纬度) 顺序传入
+    source.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
+    target.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
+    
+    let transform = CoordTransform::new(&source, &target)
         .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?;
     
-    let mut x_coords: Vec<f64> = coordinates.iter().map(|(x, _)| *x).collect();
-    let mut y_coords: Vec<f64> = coordinates.iter().map(|(_, y)| *y).collect();
-    let mut z_coords: Vec<f64> = vec![0.0; coordinates.len()];
+    let mut x_coords: Vec<f64> = coordinates.iter().map(|c| c[0]).collect();
+    let mut y_coords: Vec<f64> = coordinates.iter().map(|c| c[1]).collect();
+    let mut z_coords: Vec<f64> = coordinates.iter().map(|c| c.get(2).copied().unwrap_or(0.0)).collect();
     
     transform.transform_coords(&mut x_coords, &mut y_coords, &mut z_coords)
         .map_err(|e| AppError::InvalidFormat(format!("坐标转换失败: {}", e)))?;
     
-    let result = x_coords.iter()
-        .zip(y_coords.iter())
-        .map(|(x, y)| (*x, *y))
+    let result = coordinates.iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let mut out = vec![x_coords[i], y_coords[i]];
+            if c.len() > 2 {
+                out.push(z_coords[i]);
+            }
+            out.extend_from_slice(&c[3.min(c.len())..]);
+            out
+        })
         .collect();
     
     Ok(result)
@@ -698,163 +953,10 @@ pub fn get_gdal_version() -> String {
     gdal::version::version_info("RELEASE_NAME")
 }
 
-/// 查找ogr2ogr可执行文件
-fn find_ogr2ogr() -> Option<std::path::PathBuf> {
-    use std::env;
-    
-    log::info!("开始查找ogr2ogr可执行文件...");
-    
-    // 1. 尝试应用程序目录（开发环境）
-    if let Ok(exe_path) = env::current_exe() {
-        if let Some(app_dir) = exe_path.parent() {
-            let app_ogr2ogr = app_dir.join("ogr2ogr.exe");
-            log::info!("检查应用目录: {:?}", app_ogr2ogr);
-            if app_ogr2ogr.exists() {
-                log::info!("✓ 找到ogr2ogr (应用目录): {:?}", app_ogr2ogr);
-                return Some(app_ogr2ogr);
-            }
-            
-            // 2. 尝试应用程序目录下的gdal-tools子目录（打包环境）
-            let tools_ogr2ogr = app_dir.join("gdal-tools").join("ogr2ogr.exe");
-            log::info!("检查gdal-tools子目录: {:?}", tools_ogr2ogr);
-            if tools_ogr2ogr.exists() {
-                log::info!("✓ 找到ogr2ogr (gdal-tools): {:?}", tools_ogr2ogr);
-                return Some(tools_ogr2ogr);
-            }
-        }
-    }
-    
-    // 3. 尝试PATH环境变量
-    if let Ok(path_var) = env::var("PATH") {
-        log::info!("在PATH中查找ogr2ogr...");
-        for path_dir in env::split_paths(&path_var) {
-            let candidate = path_dir.join("ogr2ogr.exe");
-            if candidate.exists() {
-                log::info!("✓ 找到ogr2ogr (PATH): {:?}", candidate);
-                return Some(candidate);
-            }
-        }
-    }
-    
-    log::error!("✗ 未找到ogr2ogr可执行文件");
-    log::error!("  请确保ogr2ogr.exe在以下位置之一:");
-    log::error!("  1. 应用程序目录或其gdal-tools子目录");
-    log::error!("  2. PATH环境变量中");
-    None
-}
-
-/// 导出矢量数据到指定格式
-/// 支持的格式: KML, KMZ, GeoJSON, Shapefile等
-/// 使用ogr2ogr方式转换以确保字段值正确导出
-/// layer_index: 可选的图层索引，用于导出多图层文件（如KML）的特定子图层
-pub async fn export_vector(
-    input_path: &str,
-    output_path: &str,
-    format: &str,
-    layer_index: Option<usize>,
-) -> Result<()> {
-    use std::process::Command;
-    
-    if let Some(idx) = layer_index {
-        log::info!("开始导出图层{}: {} -> {} (格式: {})", idx, input_path, output_path, format);
-    } else {
-        log::info!("开始导出: {} -> {} (格式: {})", input_path, output_path, format);
-    }
-    
-    // 查找ogr2ogr
-    let ogr2ogr_path = find_ogr2ogr()
-        .ok_or_else(|| AppError::FileWriteError(
-            "未找到ogr2ogr工具。请确保GDAL已正确安装".to_string()
-        ))?;
-    
-    log::info!("使用ogr2ogr: {:?}", ogr2ogr_path);
-    
-    // 确定GDAL格式名称
-    let gdal_format = match format.to_uppercase().as_str() {
-        "KML" => "KML",
-        "KMZ" => "LIBKML",
-        "GEOJSON" => "GeoJSON",
-        "SHAPEFILE" | "SHP" => "ESRI Shapefile",
-        "GPKG" => "GPKG",
-        _ => return Err(AppError::InvalidFormat(format!("不支持的导出格式: {}", format))),
-    };
-    
-    log::info!("目标格式: {}", gdal_format);
-    
-    // 删除已存在的输出文件
-    if std::path::Path::new(output_path).exists() {
-        log::info!("删除已存在的输出文件: {}", output_path);
-        let _ = std::fs::remove_file(output_path);
-    }
-    
-    // 如果指定了图层索引，需要获取图层名称
-    let layer_name = if let Some(idx) = layer_index {
-        let dataset = Dataset::open(input_path)
-            .map_err(|e| AppError::FileReadError(format!("无法打开输入文件: {}", e)))?;
-        
-        let layer = dataset.layer(idx)
-            .map_err(|e| AppError::FileReadError(format!("无法访问图层索引{}: {}", idx, e)))?;
-        
-        let name = layer.name();
-        log::info!("图层索引{}对应的图层名称: {}", idx, name);
-        Some(name)
-    } else {
-        None
-    };
-    
-    // 使用ogr2ogr命令行工具进行转换（保留所有字段）
-    let mut cmd = Command::new(ogr2ogr_path);
-    cmd.arg("-f")
-        .arg(gdal_format)
-        .arg(output_path)
-        .arg(input_path);
-    
-    // 如果有图层名称，则只导出该图层
-    if let Some(name) = layer_name {
-        cmd.arg(name);
-    }
-    
-    // Windows下隐藏控制台窗口，避免弹出黑框
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-    
-    let output = cmd.output();
-    
-    match output {
-        Ok(result) => {
-            if result.status.success() {
-                log::info!("成功导出到: {}", output_path);
-                Ok(())
-            } else {
-                let error_msg = String::from_utf8_lossy(&result.stderr);
-                log::error!("ogr2ogr导出失败: {}", error_msg);
-                Err(AppError::FileWriteError(format!("导出失败: {}", error_msg)))
-            }
-        },
-        Err(e) => {
-            log::error!("无法执行ogr2ogr命令: {}", e);
-            Err(AppError::FileWriteError(format!("无法执行ogr2ogr: {}", e)))
-        }
-    }
-}
-
 /// 读取多图层矢量文件信息（用于KML、GDB等格式）
-pub async fn read_multi_layer_vector_info(path: &str) -> Result<MultiLayerVectorInfo> {
-    let path_lower = path.to_lowercase();
-    
-    // 使用智能编码打开文件
-    let dataset = if path_lower.ends_with(".shp") 
-        || path_lower.ends_with(".kml") 
-        || path_lower.ends_with(".kmz") {
-        open_vector_with_encoding(path)?
-    } else {
-        Dataset::open(path)
-            .map_err(|e| AppError::FileReadError(format!("无法打开文件: {}", e)))?
-    };
+pub fn read_multi_layer_vector_info(path: &str) -> Result<MultiLayerVectorInfo> {
+    // 打开数据源（Shapefile、KML、KMZ 使用智能编码）
+    let dataset = open_vector_dataset(path)?;
     
     let layer_count = dataset.layer_count();
     log::info!("检测到 {} 个图层", layer_count);
@@ -874,8 +976,12 @@ pub async fn read_multi_layer_vector_info(path: &str) -> Result<MultiLayerVector
         None
     };
     
-    // 检测是否是GDB格式
-    let is_gdb = path_lower.ends_with(".gdb") || path_lower.contains(".gdb\\") || path_lower.contains(".gdb/");
+    // GDB要素集（从数据库目录读取）
+    let feature_datasets = if gdb_service::is_gdb_path(path) {
+        gdb_service::feature_dataset_map(&dataset)
+    } else {
+        HashMap::new()
+    };
     
     // 遍历所有图层
     for i in 0..layer_count {
@@ -883,27 +989,14 @@ pub async fn read_multi_layer_vector_info(path: &str) -> Result<MultiLayerVector
             let layer_name = layer.name();
             let feature_count = layer.feature_count() as usize;
             
-            // 对于GDB，检测图层所属的要素集
-            let feature_dataset = if is_gdb {
-                // GDB中的图层名称格式可能是 "FeatureDatasetName.FeatureClassName"
-                if layer_name.contains('.') {
-                    let parts: Vec<&str> = layer_name.split('.').collect();
-                    if parts.len() > 1 {
-                        Some(parts[0].to_string())
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+            // 对于GDB，查找图层所属的要素集
+            let feature_dataset = feature_datasets.get(&layer_name).cloned();
             
             log::info!("处理图层 {}: {} (要素数: {})", i, layer_name, feature_count);
             
             // 获取空间参考
             let spatial_ref = layer.spatial_ref();
+            let vertical_crs = spatial_ref.as_ref().and_then(vertical_crs_name);
             
             // 获取范围
             let envelope = match layer.get_extent() {
@@ -914,66 +1007,11 @@ pub async fn read_multi_layer_vector_info(path: &str) -> Result<MultiLayerVector
                 }
             };
             
-            // 检测是否需要转换范围到 WGS84
-            let needs_transform = if let Some(ref srs) = spatial_ref {
-                let epsg_code = srs.auth_code().unwrap_or_default();
-                epsg_code != 4326 && epsg_code != 4490
-            } else {
-                false
-            };
-            
             // 转换范围到 WGS84
-            let extent = if needs_transform {
-                const WGS84_WKT: &str = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563]],PRIMEM["Greenwich",0],UNIT["degree",0.0174532925199433]]"#;
-                
-                let mut target_srs = SpatialRef::from_wkt(WGS84_WKT)
-                    .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e)))?;
-                target_srs.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-                
-                let mut source = match spatial_ref {
-                    Some(s) => s,
-                    None => SpatialRef::from_wkt(WGS84_WKT)
-                        .map_err(|e| AppError::InvalidFormat(format!("创建默认WGS84坐标系失败: {}", e)))?
-                };
-                source.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-                
-                let transform = gdal::spatial_ref::CoordTransform::new(&source, &target_srs)
-                    .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?;
-                
-                let mut xs = vec![envelope.MinX, envelope.MaxX, envelope.MinX, envelope.MaxX];
-                let mut ys = vec![envelope.MinY, envelope.MinY, envelope.MaxY, envelope.MaxY];
-                let mut zs = vec![0.0; 4];
-                
-                transform.transform_coords(&mut xs, &mut ys, &mut zs)
-                    .map_err(|e| AppError::InvalidFormat(format!("范围坐标转换失败: {}", e)))?;
-                
-                let min_x = xs.iter().cloned().fold(f64::INFINITY, f64::min);
-                let max_x = xs.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-                let min_y = ys.iter().cloned().fold(f64::INFINITY, f64::min);
-                let max_y = ys.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-                
-                Extent { min_x, min_y, max_x, max_y }
-            } else {
-                Extent {
-                    min_x: envelope.MinX,
-                    min_y: envelope.MinY,
-                    max_x: envelope.MaxX,
-                    max_y: envelope.MaxY,
-                }
-            };
+            let extent = wgs84_extent(&envelope, spatial_ref)?;
             
             // 获取字段信息
-            let layer_defn = layer.defn();
-            let mut fields = Vec::new();
-            for field in layer_defn.fields() {
-                fields.push(AttributeField {
-                    name: field.name(),
-                    field_type: format!("{:?}", field.field_type()),
-                    alias: None,
-                    editable: false,
-                    visible: true,
-                });
-            }
+            let fields = read_attribute_fields(layer.defn());
             
             // 获取几何类型
             let mut geometry_type = "Unknown".to_string();
@@ -983,13 +1021,17 @@ pub async fn read_multi_layer_vector_info(path: &str) -> Result<MultiLayerVector
                 }
             }
             
+            let coordinate_dimension = detect_coordinate_dimension(&mut layer);
+            
             layers.push(LayerInfo {
                 name: layer_name,
                 index: i,
                 feature_count,
                 geometry_type,
+                coordinate_dimension,
                 fields,
                 extent,
+                vertical_crs,
                 feature_dataset,
             });
         }
@@ -1004,122 +1046,336 @@ pub async fn read_multi_layer_vector_info(path: &str) -> Result<MultiLayerVector
 }
 
 /// 读取指定图层的GeoJSON数据
-pub async fn read_layer_as_geojson(path: &str, layer_index: usize) -> Result<serde_json::Value> {
-    let path_lower = path.to_lowercase();
+pub fn read_layer_as_geojson(
+    path: &str,
+    layer_index: usize,
+    dimension: DimensionMode,
+    decode_domains: bool,
+    joiners: &[AttributeJoiner],
+    job: &JobContext,
+) -> Result<serde_json::Value> {
+    let mut geojson_features = Vec::new();
+    stream_layer_as_geojson(path, layer_index, dimension, decode_domains, joiners, usize::MAX, job, |batch, _, _| {
+        geojson_features.extend(batch);
+        Ok(())
+    })?;
     
-    // 使用智能编码打开文件
-    let dataset = if path_lower.ends_with(".shp") 
-        || path_lower.ends_with(".kml") 
-        || path_lower.ends_with(".kmz") {
-        open_vector_with_encoding(path)?
-    } else {
-        Dataset::open(path)
-            .map_err(|e| AppError::FileReadError(format!("无法打开文件: {}", e)))?
-    };
+    Ok(serde_json::json!({
+        "type": "FeatureCollection",
+        "features": geojson_features
+    }))
+}
+
+/// 分批读取指定图层的GeoJSON要素
+/// 每读满 batch_size 个要素调用一次 on_batch(要素, 已读取数, 要素总数)，内存中只保留当前批次；
+/// 返回读取的要素总数
+#[allow(clippy::too_many_arguments)]
+pub fn stream_layer_as_geojson<F>(
+    path: &str,
+    layer_index: usize,
+    dimension: DimensionMode,
+    decode_domains: bool,
+    joiners: &[AttributeJoiner],
+    batch_size: usize,
+    job: &JobContext,
+    mut on_batch: F,
+) -> Result<u64>
+where
+    F: FnMut(Vec<serde_json::Value>, u64, u64) -> Result<()>,
+{
+    let is_kml = is_kml_path(path);
+    
+    // 打开数据源（Shapefile、KML、KMZ 使用智能编码）
+    let dataset = open_vector_dataset(path)?;
     
     let mut layer = dataset.layer(layer_index)
         .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
     
-    // 检测源坐标系
-    let source_srs = layer.spatial_ref();
-    let needs_transform = if let Some(ref srs) = source_srs {
-        let epsg_code = srs.auth_code().unwrap_or_default();
-        epsg_code != 4326 && epsg_code != 4490
-    } else {
-        false
-    };
+    // 检测源坐标系，创建坐标转换器
+    let transform = create_wgs84_transform(layer.spatial_ref())?;
     
-    // 创建坐标转换器
-    let transform = if needs_transform {
-        const WGS84_WKT: &str = r#"GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563]],PRIMEM["Greenwich",0],UNIT["degree",0.0174532925199433]]"#;
-        
-        let mut target_srs = SpatialRef::from_wkt(WGS84_WKT)
-            .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e)))?;
-        target_srs.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-        
-        let mut source = match source_srs {
-            Some(s) => s,
-            None => SpatialRef::from_wkt(WGS84_WKT)
-                .map_err(|e| AppError::InvalidFormat(format!("创建默认WGS84坐标系失败: {}", e)))?
-        };
-        source.set_axis_mapping_strategy(gdal::spatial_ref::AxisMappingStrategy::TraditionalGisOrder);
-        
-        Some(gdal::spatial_ref::CoordTransform::new(&source, &target_srs)
-            .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))?)
+    // 编码值显示为描述（GDB 属性域、子类型）
+    let decoder = if decode_domains {
+        gdb_service::DomainDecoder::for_layer(&dataset, &layer)
     } else {
         None
     };
     
     // 读取并转换要素
-    let mut geojson_features = Vec::new();
+    let mut batch = Vec::new();
+    let mut read: u64 = 0;
+    let total = layer.feature_count();
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    job.report(0, Some(total));
     
     for feature in layer.features() {
-        // 获取几何并转换
-        let geometry = if let Some(geom) = feature.geometry() {
-            let mut geom_owned = geom.clone();
-            
-            if let Some(ref trans) = transform {
-                geom_owned.transform_inplace(trans)
-                    .map_err(|e| AppError::InvalidFormat(format!("坐标转换失败: {}", e)))?;
-            }
-            
-            let geojson = geom_owned.json()
-                .map_err(|e| AppError::InvalidFormat(format!("几何转换失败: {}", e)))?;
-            
-            let geojson_value: serde_json::Value = serde_json::from_str(&geojson)
-                .map_err(|e| AppError::InvalidFormat(format!("JSON解析失败: {}", e)))?;
-            
-            geojson_value
-        } else {
-            serde_json::json!(null)
-        };
-        
-        // 获取属性
-        let mut properties = serde_json::Map::new();
-        let mut has_description = false;
-        let mut description_content = String::new();
-        
-        for (field_name, field_value) in feature.fields() {
-            let json_value = match field_value {
-                Some(gdal::vector::FieldValue::StringValue(s)) => {
-                    if field_name.to_lowercase() == "description" && (path_lower.ends_with(".kml") || path_lower.ends_with(".kmz")) {
-                        has_description = true;
-                        description_content = s.clone();
-                    }
-                    serde_json::Value::String(s.clone())
-                },
-                Some(gdal::vector::FieldValue::IntegerValue(i)) => serde_json::Value::Number(i.into()),
-                Some(gdal::vector::FieldValue::Integer64Value(i)) => serde_json::Value::Number(i.into()),
-                Some(gdal::vector::FieldValue::RealValue(r)) => {
-                    serde_json::Number::from_f64(r)
-                        .map(serde_json::Value::Number)
-                        .unwrap_or(serde_json::Value::Null)
-                },
-                Some(gdal::vector::FieldValue::DateValue(d)) => serde_json::Value::String(format!("{:?}", d)),
-                Some(gdal::vector::FieldValue::DateTimeValue(dt)) => serde_json::Value::String(format!("{:?}", dt)),
-                _ => serde_json::Value::Null,
-            };
-            properties.insert(field_name.clone(), json_value);
-        }
+        job.check_cancelled()?;
         
-        // 解析KML的description字段
-        if has_description && !description_content.is_empty() {
-            let parsed_props = parse_kml_description(&description_content);
-            for (key, value) in parsed_props {
-                properties.insert(key, value);
+        let mut value = feature_to_geojson(&feature, transform.as_ref(), dimension, is_kml, decoder.as_ref())?;
+        if let Some(properties) = value["properties"].as_object_mut() {
+            for joiner in joiners {
+                joiner.apply(&feature, |name, value| {
+                    properties.insert(name, value);
+                });
             }
         }
+        batch.push(value);
+        read += 1;
         
-        geojson_features.push(serde_json::json!({
-            "type": "Feature",
-            "id": feature.fid().unwrap_or(0),
-            "properties": properties,
-            "geometry": geometry
-        }));
+        if batch.len() >= batch_size {
+            on_batch(std::mem::take(&mut batch), read, total)?;
+        }
+        if read >= next_report {
+            job.report(read, Some(total));
+            next_report += report_interval;
+        }
+    }
+    
+    if !batch.is_empty() {
+        on_batch(batch, read, total)?;
+    }
+    Ok(read)
+}
+
+/// 要素几何（按维度处理并转换到WGS84）的GeoJSON，没有几何时为 null
+fn feature_geometry_json(
+    feature: &gdal::vector::Feature,
+    transform: Option<&CoordTransform>,
+    dimension: DimensionMode,
+) -> Result<serde_json::Value> {
+    let Some(geom) = feature.geometry() else {
+        return Ok(serde_json::Value::Null);
+    };
+    let mut geom_owned = geom.clone();
+    geometry::apply_dimension_mode(&mut geom_owned, dimension);
+    
+    // Z值、M值随几何一起转换
+    if let Some(trans) = transform {
+        geom_owned.transform_inplace(trans)
+            .map_err(|e| AppError::InvalidFormat(format!("坐标转换失败: {}", e)))?;
+    }
+    
+    geometry::to_geojson_value(&geom_owned)
+}
+
+/// GeoJSON几何转换为要素列表使用的几何结构，null 对应 "Null"
+fn to_feature_geometry(value: serde_json::Value) -> Geometry {
+    match value {
+        serde_json::Value::Null => Geometry {
+            geom_type: "Null".to_string(),
+            coordinates: serde_json::Value::Null,
+        },
+        value => Geometry {
+            geom_type: value["type"].as_str().unwrap_or("Unknown").to_string(),
+            coordinates: value["coordinates"].clone(),
+        },
+    }
+}
+
+/// 要素属性转换为JSON：KML 的 description 字段解析后合并到属性中，指定值域解码器时将编码替换为描述
+pub(crate) fn feature_properties(
+    feature: &gdal::vector::Feature,
+    is_kml: bool,
+    decoder: Option<&gdb_service::DomainDecoder>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut properties = serde_json::Map::new();
+    let mut description_content = String::new();
+    
+    for (field_name, field_value) in feature.fields() {
+        let json_value = match field_value {
+            Some(gdal::vector::FieldValue::StringValue(s)) => {
+                if is_kml && field_name.to_lowercase() == "description" {
+                    description_content = s.clone();
+                }
+                serde_json::Value::String(s)
+            },
+            Some(gdal::vector::FieldValue::IntegerValue(i)) => serde_json::Value::Number(i.into()),
+            Some(gdal::vector::FieldValue::Integer64Value(i)) => serde_json::Value::Number(i.into()),
+            Some(gdal::vector::FieldValue::RealValue(r)) => {
+                serde_json::Number::from_f64(r)
+                    .map(serde_json::Value::Number)
+                    .unwrap_or(serde_json::Value::Null)
+            },
+            Some(gdal::vector::FieldValue::DateValue(d)) => serde_json::Value::String(format!("{:?}", d)),
+            Some(gdal::vector::FieldValue::DateTimeValue(dt)) => serde_json::Value::String(format!("{:?}", dt)),
+            _ => serde_json::Value::Null,
+        };
+        properties.insert(field_name, json_value);
     }
     
+    // 解析KML的description字段
+    if !description_content.is_empty() {
+        for (key, value) in parse_kml_description(&description_content) {
+            properties.insert(key, value);
+        }
+    }
+    
+    if let Some(decoder) = decoder {
+        decoder.decode(properties.iter_mut());
+    }
+    
+    properties
+}
+
+/// 路径是否为 KML/KMZ（需要解析 description 字段）
+pub(crate) fn is_kml_path(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+    path_lower.ends_with(".kml") || path_lower.ends_with(".kmz")
+}
+
+/// 将要素转换为WGS84坐标的GeoJSON Feature
+pub(crate) fn feature_to_geojson(
+    feature: &gdal::vector::Feature,
+    transform: Option<&CoordTransform>,
+    dimension: DimensionMode,
+    is_kml: bool,
+    decoder: Option<&gdb_service::DomainDecoder>,
+) -> Result<serde_json::Value> {
+    let geometry = feature_geometry_json(feature, transform, dimension)?;
+    let properties = feature_properties(feature, is_kml, decoder);
+    
     Ok(serde_json::json!({
-        "type": "FeatureCollection",
-        "features": geojson_features
+        "type": "Feature",
+        "id": feature.fid().unwrap_or(0),
+        "properties": properties,
+        "geometry": geometry
     }))
 }
+
+/// 在图层中原位写入修改：支持事务的数据源在事务中提交，失败时回滚；
+/// 不支持事务的数据源（如 Shapefile）直接写入，失败前已写入的修改会保留
+pub(crate) fn write_in_transaction<T, F>(dataset: &mut Dataset, layer_index: usize, write: F) -> Result<T>
+where
+    F: FnOnce(&gdal::vector::Layer) -> Result<T>,
+{
+    let supports_transactions = unsafe { dataset_has_capability(dataset.c_dataset(), "Transactions") };
+
+    if !supports_transactions {
+        log::info!("数据源不支持事务，直接写入修改");
+        let layer = dataset.layer(layer_index)
+            .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+        return write(&layer);
+    }
+
+    let txn = dataset.start_transaction()
+        .map_err(|e| AppError::FileWriteError(format!("开启事务失败: {}", e)))?;
+    let result = {
+        let layer = txn.layer(layer_index)
+            .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+        write(&layer)
+    };
+    match result {
+        Ok(result) => {
+            txn.commit()
+                .map_err(|e| AppError::FileWriteError(format!("提交修改失败: {}", e)))?;
+            Ok(result)
+        }
+        Err(e) => {
+            log::warn!("写入失败，回滚事务: {}", e);
+            let _ = txn.rollback();
+            Err(e)
+        }
+    }
+}
+
+/// 原位修改数据源后丢弃其空间索引和瓦片缓存（修改时间可能与构建时相同或无法获取）
+pub(crate) fn invalidate_source_caches(path: &str) {
+    spatial_index_service::invalidate(path, None);
+    tile_service::clear_path_cache(path);
+}
+
+/// 将编辑写入图层（新增、修改、删除要素）
+/// 要素几何为 WGS84 GeoJSON，写入前转换到图层坐标系；支持事务的数据源在事务中提交
+pub(crate) fn apply_feature_edits(
+    dataset: &mut Dataset,
+    layer_index: usize,
+    edits: &FeatureEdits,
+) -> Result<EditResult> {
+    write_in_transaction(dataset, layer_index, |layer| write_edits_to_layer(layer, edits))
+}
+
+fn write_edits_to_layer(layer: &gdal::vector::Layer, edits: &FeatureEdits) -> Result<EditResult> {
+    let transform = create_from_wgs84_transform(layer.spatial_ref())?;
+    let fields: HashMap<String, AttributeField> = read_attribute_fields(layer.defn())
+        .into_iter()
+        .map(|f| (f.name.clone(), f))
+        .collect();
+    
+    let mut result = EditResult::default();
+    
+    // 删除
+    for fid in &edits.deletes {
+        let rv = unsafe { gdal_sys::OGR_L_DeleteFeature(layer.c_layer(), *fid as i64) };
+        if rv != gdal_sys::OGRErr::OGRERR_NONE {
+            return Err(AppError::FileWriteError(format!("删除要素 {} 失败: {}", fid, last_gdal_error())));
+        }
+        result.deleted += 1;
+    }
+    
+    // 修改
+    for item in &edits.updates {
+        let fid = feature_id_from_json(item)
+            .ok_or_else(|| AppError::InvalidFormat("修改的要素缺少 id".to_string()))?;
+        let mut feature = layer.feature(fid)
+            .ok_or_else(|| AppError::FileReadError(format!("要素 {} 不存在", fid)))?;
+        fill_feature_from_json(&mut feature, item, &fields, transform.as_ref())?;
+        layer.set_feature(feature)
+            .map_err(|e| AppError::FileWriteError(format!("修改要素 {} 失败: {}", fid, e)))?;
+        result.updated += 1;
+    }
+    
+    // 新增
+    for item in &edits.inserts {
+        let mut feature = gdal::vector::Feature::new(layer.defn())
+            .map_err(|e| AppError::FileWriteError(format!("创建要素失败: {}", e)))?;
+        fill_feature_from_json(&mut feature, item, &fields, transform.as_ref())?;
+        feature.create(layer)
+            .map_err(|e| AppError::FileWriteError(format!("写入要素失败: {}", e)))?;
+        result.inserted += 1;
+    }
+    
+    log::info!("编辑已写入: 新增 {}，修改 {}，删除 {}", result.inserted, result.updated, result.deleted);
+    Ok(result)
+}
+
+/// 读取GeoJSON要素的 id（数字或数字字符串）
+fn feature_id_from_json(item: &serde_json::Value) -> Option<u64> {
+    match &item["id"] {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// 用GeoJSON要素的几何和属性填充OGR要素
+fn fill_feature_from_json(
+    feature: &mut gdal::vector::Feature,
+    item: &serde_json::Value,
+    fields: &HashMap<String, AttributeField>,
+    transform: Option<&CoordTransform>,
+) -> Result<()> {
+    if let Some(properties) = item["properties"].as_object() {
+        for (name, value) in properties {
+            // 忽略图层中不存在的属性（如KML解析出的附加属性）
+            if let Some(field) = fields.get(name) {
+                validate_field_value(field, value)?;
+                set_field_from_json(feature, name, ogr_field_type(field.field_type).0, value)?;
+            }
+        }
+    }
+    
+    let geometry = &item["geometry"];
+    if !geometry.is_null() {
+        let mut geom = gdal::vector::Geometry::from_geojson(&geometry.to_string())
+            .map_err(|e| AppError::InvalidFormat(format!("几何解析失败: {}", e)))?;
+        if let Some(trans) = transform {
+            geom.transform_inplace(trans)
+                .map_err(|e| AppError::InvalidFormat(format!("坐标转换失败: {}", e)))?;
+        }
+        feature.set_geometry(geom)
+            .map_err(|e| AppError::FileWriteError(format!("设置几何失败: {}", e)))?;
+    }
+    
+    Ok(())
+}
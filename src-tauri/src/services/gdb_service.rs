@@ -0,0 +1,523 @@
+use crate::errors::Result;
+use crate::models::{
+    CodedValue, FeatureDatasetInfo, FieldDomain, FieldDomainKind, GdbLayerMetadata, GdbMetadata,
+    RelationshipClass, Subtype,
+};
+use crate::services::gdal_service;
+use gdal::vector::{LayerAccess, OGRFieldType};
+use gdal::{Dataset, Metadata};
+use regex::Regex;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+/// 是否为文件地理数据库路径
+pub fn is_gdb_path(path: &str) -> bool {
+    let path_lower = path.to_lowercase();
+    path_lower.ends_with(".gdb") || path_lower.contains(".gdb\\") || path_lower.contains(".gdb/")
+}
+
+fn c_string(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    let value = unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// 读取并释放 GDAL 字符串列表
+fn take_string_list(list: *mut *mut c_char) -> Vec<String> {
+    if list.is_null() {
+        return Vec::new();
+    }
+    let mut values = Vec::new();
+    unsafe {
+        let mut i = 0;
+        while !(*list.add(i)).is_null() {
+            values.push(CStr::from_ptr(*list.add(i)).to_string_lossy().into_owned());
+            i += 1;
+        }
+        gdal_sys::CSLDestroy(list);
+    }
+    values
+}
+
+// ---------------------------------------------------------------------------
+// 要素集
+// ---------------------------------------------------------------------------
+
+/// 通过数据源根组读取要素集（OpenFileGDB 将要素集作为子组暴露）
+fn feature_datasets_from_root_group(dataset: &Dataset) -> Option<Vec<FeatureDatasetInfo>> {
+    unsafe {
+        let root = gdal_sys::GDALDatasetGetRootGroup(dataset.c_dataset());
+        if root.is_null() {
+            return None;
+        }
+
+        let mut datasets = Vec::new();
+        for name in take_string_list(gdal_sys::GDALGroupGetGroupNames(root, std::ptr::null_mut())) {
+            let c_name = match CString::new(name.as_str()) {
+                Ok(c_name) => c_name,
+                Err(_) => continue,
+            };
+            let group = gdal_sys::GDALGroupOpenGroup(root, c_name.as_ptr(), std::ptr::null_mut());
+            if group.is_null() {
+                continue;
+            }
+            let layers = take_string_list(gdal_sys::GDALGroupGetVectorLayerNames(group, std::ptr::null_mut()));
+            gdal_sys::GDALGroupRelease(group);
+            datasets.push(FeatureDatasetInfo { name, layers });
+        }
+
+        gdal_sys::GDALGroupRelease(root);
+        Some(datasets)
+    }
+}
+
+/// 图层的 GDB 定义 XML（DEFeatureClassInfo / DETableInfo）
+fn layer_definition_xml<L: Metadata>(layer: &L) -> Option<String> {
+    layer.metadata_domain("xml:definition")
+        .and_then(|items| items.into_iter().next())
+}
+
+/// 从定义 XML 的 CatalogPath（如 \交通\道路）推断所属要素集
+fn feature_dataset_from_catalog_path(xml: &str) -> Option<String> {
+    let catalog_path = xml_text(xml, "CatalogPath")?;
+    let parts: Vec<&str> = catalog_path.split('\\').filter(|p| !p.is_empty()).collect();
+    if parts.len() > 1 {
+        Some(parts[parts.len() - 2].to_string())
+    } else {
+        None
+    }
+}
+
+/// 读取要素集及其包含的图层
+/// 优先使用 GDAL 的根组接口，旧版本 GDAL 回退到解析图层定义中的 CatalogPath
+pub fn read_feature_datasets(dataset: &Dataset) -> Vec<FeatureDatasetInfo> {
+    if let Some(datasets) = feature_datasets_from_root_group(dataset) {
+        return datasets;
+    }
+
+    let mut datasets: Vec<FeatureDatasetInfo> = Vec::new();
+    for layer in dataset.layers() {
+        let feature_dataset = layer_definition_xml(&layer).and_then(|xml| feature_dataset_from_catalog_path(&xml));
+        if let Some(name) = feature_dataset {
+            match datasets.iter_mut().find(|d| d.name == name) {
+                Some(existing) => existing.layers.push(layer.name()),
+                None => datasets.push(FeatureDatasetInfo { name, layers: vec![layer.name()] }),
+            }
+        }
+    }
+    datasets
+}
+
+/// 图层名 -> 所属要素集
+pub fn feature_dataset_map(dataset: &Dataset) -> HashMap<String, String> {
+    read_feature_datasets(dataset)
+        .into_iter()
+        .flat_map(|fd| {
+            let name = fd.name;
+            fd.layers.into_iter().map(move |layer| (layer, name.clone()))
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// 属性域
+// ---------------------------------------------------------------------------
+
+unsafe fn raw_field_as_f64(field: *const gdal_sys::OGRField, field_type: OGRFieldType::Type) -> Option<f64> {
+    if field.is_null() || gdal_sys::OGR_RawField_IsUnset(field) != 0 || gdal_sys::OGR_RawField_IsNull(field) != 0 {
+        return None;
+    }
+    match field_type {
+        OGRFieldType::OFTInteger => Some((*field).Integer as f64),
+        OGRFieldType::OFTInteger64 => Some((*field).Integer64 as f64),
+        OGRFieldType::OFTReal => Some((*field).Real),
+        _ => None,
+    }
+}
+
+fn read_field_domain(dataset: &Dataset, name: &str) -> Option<FieldDomain> {
+    let c_name = CString::new(name).ok()?;
+    unsafe {
+        // 属性域归数据源所有，无需释放
+        let domain = gdal_sys::GDALDatasetGetFieldDomain(dataset.c_dataset(), c_name.as_ptr());
+        if domain.is_null() {
+            return None;
+        }
+
+        let field_type = gdal_sys::OGR_FldDomain_GetFieldType(domain);
+        let mut result = FieldDomain {
+            name: name.to_string(),
+            description: c_string(gdal_sys::OGR_FldDomain_GetDescription(domain)),
+            kind: FieldDomainKind::Coded,
+            field_type: c_string(gdal_sys::OGR_GetFieldTypeName(field_type)).unwrap_or_default(),
+            coded_values: Vec::new(),
+            min: None,
+            max: None,
+            min_inclusive: false,
+            max_inclusive: false,
+            glob: None,
+        };
+
+        match gdal_sys::OGR_FldDomain_GetDomainType(domain) {
+            gdal_sys::OGRFieldDomainType::OFDT_CODED => {
+                let values = gdal_sys::OGR_CodedFldDomain_GetEnumeration(domain);
+                if !values.is_null() {
+                    let mut i = 0;
+                    while !(*values.add(i)).pszCode.is_null() {
+                        let value = &*values.add(i);
+                        result.coded_values.push(CodedValue {
+                            code: c_string(value.pszCode).unwrap_or_default(),
+                            description: c_string(value.pszValue),
+                        });
+                        i += 1;
+                    }
+                }
+            }
+            gdal_sys::OGRFieldDomainType::OFDT_RANGE => {
+                result.kind = FieldDomainKind::Range;
+                let mut min_inclusive = false;
+                let mut max_inclusive = false;
+                let min = gdal_sys::OGR_RangeFldDomain_GetMin(domain, &mut min_inclusive);
+                let max = gdal_sys::OGR_RangeFldDomain_GetMax(domain, &mut max_inclusive);
+                result.min = raw_field_as_f64(min, field_type);
+                result.max = raw_field_as_f64(max, field_type);
+                result.min_inclusive = min_inclusive;
+                result.max_inclusive = max_inclusive;
+            }
+            gdal_sys::OGRFieldDomainType::OFDT_GLOB => {
+                result.kind = FieldDomainKind::Glob;
+                result.glob = c_string(gdal_sys::OGR_GlobFldDomain_GetGlob(domain));
+            }
+            _ => return None,
+        }
+
+        Some(result)
+    }
+}
+
+/// 读取数据源中的全部属性域
+pub fn read_field_domains(dataset: &Dataset) -> Vec<FieldDomain> {
+    let names = take_string_list(unsafe {
+        gdal_sys::GDALDatasetGetFieldDomainNames(dataset.c_dataset(), std::ptr::null_mut())
+    });
+    names.iter().filter_map(|name| read_field_domain(dataset, name)).collect()
+}
+
+// ---------------------------------------------------------------------------
+// 关系类
+// ---------------------------------------------------------------------------
+
+/// 读取数据源中的关系类
+pub fn read_relationships(dataset: &Dataset) -> Vec<RelationshipClass> {
+    let names = take_string_list(unsafe {
+        gdal_sys::GDALDatasetGetRelationshipNames(dataset.c_dataset(), std::ptr::null_mut())
+    });
+
+    let mut relationships = Vec::new();
+    for name in names {
+        let c_name = match CString::new(name.as_str()) {
+            Ok(c_name) => c_name,
+            Err(_) => continue,
+        };
+        unsafe {
+            // 关系类归数据源所有，无需释放
+            let rel = gdal_sys::GDALDatasetGetRelationship(dataset.c_dataset(), c_name.as_ptr());
+            if rel.is_null() {
+                continue;
+            }
+
+            let cardinality = match gdal_sys::GDALRelationshipGetCardinality(rel) {
+                gdal_sys::GDALRelationshipCardinality::GRC_ONE_TO_ONE => "one_to_one",
+                gdal_sys::GDALRelationshipCardinality::GRC_ONE_TO_MANY => "one_to_many",
+                gdal_sys::GDALRelationshipCardinality::GRC_MANY_TO_ONE => "many_to_one",
+                _ => "many_to_many",
+            };
+            let relationship_type = match gdal_sys::GDALRelationshipGetType(rel) {
+                gdal_sys::GDALRelationshipType::GRT_COMPOSITE => "composite",
+                gdal_sys::GDALRelationshipType::GRT_AGGREGATION => "aggregation",
+                _ => "association",
+            };
+
+            relationships.push(RelationshipClass {
+                name,
+                cardinality: cardinality.to_string(),
+                relationship_type: relationship_type.to_string(),
+                origin_table: c_string(gdal_sys::GDALRelationshipGetLeftTableName(rel)).unwrap_or_default(),
+                destination_table: c_string(gdal_sys::GDALRelationshipGetRightTableName(rel)).unwrap_or_default(),
+                mapping_table: c_string(gdal_sys::GDALRelationshipGetMappingTableName(rel)),
+                origin_fields: take_string_list(gdal_sys::GDALRelationshipGetLeftTableFields(rel)),
+                destination_fields: take_string_list(gdal_sys::GDALRelationshipGetRightTableFields(rel)),
+                forward_label: c_string(gdal_sys::GDALRelationshipGetForwardPathLabel(rel)),
+                backward_label: c_string(gdal_sys::GDALRelationshipGetBackwardPathLabel(rel)),
+            });
+        }
+    }
+    relationships
+}
+
+// ---------------------------------------------------------------------------
+// 子类型（解析图层定义 XML）
+// ---------------------------------------------------------------------------
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 读取第一个指定标签的文本
+fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    let re = Regex::new(&format!(r"(?s)<{}(?:\s[^>]*)?>(.*?)</{}>", tag, tag)).ok()?;
+    re.captures(xml)
+        .map(|cap| unescape_xml(cap[1].trim()))
+        .filter(|text| !text.is_empty())
+}
+
+/// 读取所有指定标签的内容
+fn xml_blocks<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    match Regex::new(&format!(r"(?s)<{}(?:\s[^>]*)?>(.*?)</{}>", tag, tag)) {
+        Ok(re) => re.captures_iter(xml)
+            .filter_map(|cap| cap.get(1).map(|m| m.as_str()))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// 去掉嵌套的字段、子类型定义，只保留要素类自身的标签
+fn strip_nested_definitions(xml: &str) -> String {
+    let mut result = xml.to_string();
+    for tag in ["GPFieldInfoExs", "Fields", "Subtypes", "Indexes"] {
+        if let Ok(re) = Regex::new(&format!(r"(?s)<{}(?:\s[^>]*)?>.*?</{}>", tag, tag)) {
+            result = re.replace_all(&result, "").into_owned();
+        }
+    }
+    result
+}
+
+fn parse_subtypes(xml: &str) -> Vec<Subtype> {
+    let subtypes_xml = match xml_blocks(xml, "Subtypes").into_iter().next() {
+        Some(block) => block,
+        None => return Vec::new(),
+    };
+
+    let mut subtypes = Vec::new();
+    for block in xml_blocks(subtypes_xml, "Subtype") {
+        let code = match xml_text(block, "SubtypeCode").and_then(|c| c.parse::<i64>().ok()) {
+            Some(code) => code,
+            None => continue,
+        };
+
+        let mut field_domains = HashMap::new();
+        let mut default_values = HashMap::new();
+        for info in xml_blocks(block, "SubtypeFieldInfo") {
+            let field_name = match xml_text(info, "FieldName") {
+                Some(name) => name,
+                None => continue,
+            };
+            if let Some(domain) = xml_text(info, "DomainName") {
+                field_domains.insert(field_name.clone(), domain);
+            }
+            if let Some(default) = xml_text(info, "DefaultValue") {
+                default_values.insert(field_name, default);
+            }
+        }
+
+        subtypes.push(Subtype {
+            code,
+            name: xml_text(block, "SubtypeName").unwrap_or_else(|| code.to_string()),
+            field_domains,
+            default_values,
+        });
+    }
+    subtypes
+}
+
+/// 读取单个图层的元数据（别名、字段属性域、子类型）
+fn read_layer_metadata(
+    layer: &gdal::vector::Layer,
+    index: usize,
+    feature_datasets: &HashMap<String, String>,
+) -> GdbLayerMetadata {
+    let name = layer.name();
+    let xml = layer_definition_xml(layer);
+    let own_xml = xml.as_deref().map(strip_nested_definitions);
+
+    let alias = own_xml.as_deref()
+        .and_then(|x| xml_text(x, "AliasName"))
+        .or_else(|| layer.metadata_item("ALIAS_NAME", ""))
+        .filter(|alias| *alias != name);
+
+    let fields = gdal_service::read_attribute_fields(layer.defn());
+
+    GdbLayerMetadata {
+        feature_dataset: feature_datasets.get(&name).cloned(),
+        index,
+        alias,
+        fields,
+        subtype_field: own_xml.as_deref().and_then(|x| xml_text(x, "SubtypeFieldName")),
+        default_subtype: own_xml.as_deref()
+            .and_then(|x| xml_text(x, "DefaultSubtypeCode"))
+            .and_then(|c| c.parse().ok()),
+        subtypes: xml.as_deref().map(parse_subtypes).unwrap_or_default(),
+        name,
+    }
+}
+
+/// 读取文件地理数据库元数据：要素集、属性域、子类型、别名和关系类
+pub fn read_gdb_metadata(path: &str) -> Result<GdbMetadata> {
+    let dataset = gdal_service::open_vector_dataset(path)?;
+
+    let feature_datasets = read_feature_datasets(&dataset);
+    let dataset_map: HashMap<String, String> = feature_datasets.iter()
+        .flat_map(|fd| fd.layers.iter().map(move |layer| (layer.clone(), fd.name.clone())))
+        .collect();
+
+    let layers = (0..dataset.layer_count())
+        .filter_map(|i| dataset.layer(i).ok().map(|layer| read_layer_metadata(&layer, i, &dataset_map)))
+        .collect::<Vec<_>>();
+
+    let domains = read_field_domains(&dataset);
+    let relationships = read_relationships(&dataset);
+
+    log::info!(
+        "GDB元数据: {} 个要素集, {} 个属性域, {} 个关系类, {} 个图层",
+        feature_datasets.len(), domains.len(), relationships.len(), layers.len()
+    );
+
+    Ok(GdbMetadata {
+        path: path.to_string(),
+        feature_datasets,
+        domains,
+        relationships,
+        layers,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// 编码值显示
+// ---------------------------------------------------------------------------
+
+type CodeLookup = HashMap<String, String>;
+
+/// 将编码值转换为描述（按子类型选择属性域）
+pub struct DomainDecoder {
+    subtype_field: Option<String>,
+    subtype_names: HashMap<String, String>,
+    field_lookups: HashMap<String, CodeLookup>,
+    subtype_lookups: HashMap<String, HashMap<String, CodeLookup>>,
+}
+
+/// 属性值转为编码字符串（整数编码不带小数部分）
+fn code_of(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Some(i.to_string())
+            } else {
+                n.as_f64().map(|f| if f.fract() == 0.0 { (f as i64).to_string() } else { f.to_string() })
+            }
+        }
+        _ => None,
+    }
+}
+
+impl DomainDecoder {
+    /// 为图层创建解码器；图层没有编码值域和子类型时返回 None
+    pub fn for_layer(dataset: &Dataset, layer: &gdal::vector::Layer) -> Option<DomainDecoder> {
+        let mut coded_domains: HashMap<String, Option<CodeLookup>> = HashMap::new();
+        let mut lookup = |domain: &str| -> Option<CodeLookup> {
+            coded_domains
+                .entry(domain.to_string())
+                .or_insert_with(|| {
+                    read_field_domain(dataset, domain)
+                        .filter(|d| d.kind == FieldDomainKind::Coded)
+                        .map(|d| {
+                            d.coded_values.into_iter()
+                                .filter_map(|v| v.description.map(|desc| (v.code, desc)))
+                                .collect()
+                        })
+                })
+                .clone()
+        };
+
+        let mut field_lookups = HashMap::new();
+        for field in gdal_service::read_attribute_fields(layer.defn()) {
+            if let Some(codes) = field.domain.as_deref().and_then(&mut lookup) {
+                field_lookups.insert(field.name, codes);
+            }
+        }
+
+        let xml = layer_definition_xml(layer);
+        let subtype_field = xml.as_deref()
+            .map(strip_nested_definitions)
+            .and_then(|x| xml_text(&x, "SubtypeFieldName"));
+
+        let mut subtype_names = HashMap::new();
+        let mut subtype_lookups = HashMap::new();
+        if subtype_field.is_some() {
+            for subtype in xml.as_deref().map(parse_subtypes).unwrap_or_default() {
+                let code = subtype.code.to_string();
+                let lookups: HashMap<String, CodeLookup> = subtype.field_domains.iter()
+                    .filter_map(|(field, domain)| lookup(domain).map(|codes| (field.clone(), codes)))
+                    .collect();
+                subtype_lookups.insert(code.clone(), lookups);
+                subtype_names.insert(code, subtype.name);
+            }
+        }
+
+        if field_lookups.is_empty() && subtype_names.is_empty() {
+            return None;
+        }
+
+        Some(DomainDecoder {
+            subtype_field,
+            subtype_names,
+            field_lookups,
+            subtype_lookups,
+        })
+    }
+
+    /// 将要素属性中的编码值替换为描述
+    pub fn decode<'a, I>(&self, properties: I)
+    where
+        I: IntoIterator<Item = (&'a String, &'a mut serde_json::Value)>,
+    {
+        let mut entries: Vec<(&String, &mut serde_json::Value)> = properties.into_iter().collect();
+
+        // 子类型决定其他字段使用的属性域，需先读取原始编码
+        let subtype_code = self.subtype_field.as_ref()
+            .and_then(|field| entries.iter().find(|(name, _)| *name == field))
+            .and_then(|(_, value)| code_of(value));
+        let subtype_lookups = subtype_code.as_ref().and_then(|code| self.subtype_lookups.get(code));
+
+        for (field, value) in entries.iter_mut() {
+            let code = match code_of(value) {
+                Some(code) => code,
+                None => continue,
+            };
+
+            let description = if Some(*field) == self.subtype_field.as_ref() {
+                self.subtype_names.get(&code)
+            } else {
+                subtype_lookups
+                    .and_then(|lookups| lookups.get(*field))
+                    .or_else(|| self.field_lookups.get(*field))
+                    .and_then(|codes| codes.get(&code))
+            };
+
+            if let Some(description) = description {
+                **value = serde_json::Value::String(description.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use crate::errors::{AppError, Result};
+use crate::gis::geometry;
+use crate::gis::grid::Grid;
+use crate::gis::sampling::{self, SeededRng};
+use crate::models::{
+    AnalysisInput, Extent, FieldType, GridRequest, GridResult, SampleMethod, SamplePointsRequest, SamplePointsResult,
+};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::{gdal_service, measure_service, selection_service};
+use gdal::spatial_ref::SpatialRef;
+use gdal::vector::{Envelope, FieldValue, Geometry, LayerAccess, OGRwkbGeometryType};
+use std::collections::HashMap;
+
+/// 单次生成的最大格网数
+const MAX_GRID_CELLS: u64 = 2_000_000;
+/// 单个面按间距生成点阵时最多检查的点位数
+const MAX_REGULAR_CANDIDATES: f64 = 10_000_000.0;
+
+/// 各格网 (行, 列) 内的点数
+type CellCounts = HashMap<(usize, usize), u64>;
+
+/// 格网坐标系：请求指定 > 图层坐标系 > WGS84
+fn grid_srs(request: &GridRequest) -> Result<Option<SpatialRef>> {
+    if let Some(definition) = request.crs.as_deref().filter(|crs| !crs.is_empty()) {
+        return measure_service::parse_srs(definition).map(Some);
+    }
+    if let Some(input) = &request.layer {
+        let dataset = gdal_service::open_vector_dataset(&input.path)?;
+        let layer_index = input.layer_index.unwrap_or(0);
+        let layer = dataset.layer(layer_index)
+            .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+        if let Some(srs) = layer.spatial_ref() {
+            return Ok(Some(srs));
+        }
+    }
+    SpatialRef::from_epsg(4326)
+        .map(Some)
+        .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e)))
+}
+
+/// 图层（或其选择集）在格网坐标系下的范围，边界加密后转换，避免投影后范围偏小
+fn layer_extent(input: &AnalysisInput, srs: Option<&SpatialRef>) -> Result<Extent> {
+    let layer_index = input.layer_index.unwrap_or(0);
+    let dataset = gdal_service::open_vector_dataset(&input.path)?;
+    let layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+
+    let envelope = match selection_service::input_fids(input)? {
+        Some(fids) => fids.iter()
+            .filter_map(|&fid| layer.feature(fid)?.geometry().map(Geometry::envelope))
+            .reduce(|a, b| Envelope {
+                MinX: a.MinX.min(b.MinX),
+                MaxX: a.MaxX.max(b.MaxX),
+                MinY: a.MinY.min(b.MinY),
+                MaxY: a.MaxY.max(b.MaxY),
+            })
+            .ok_or_else(|| AppError::InvalidFormat("选择集中没有带几何的要素".to_string()))?,
+        None => layer.get_extent()
+            .map_err(|e| AppError::FileReadError(format!("无法获取范围: {}", e)))?,
+    };
+
+    let mut geom = Geometry::bbox(envelope.MinX, envelope.MinY, envelope.MaxX, envelope.MaxY)
+        .map_err(|e| AppError::InvalidFormat(format!("空间范围无效: {}", e)))?;
+    if let Some(transform) = gdal_service::create_layer_transform(layer.spatial_ref(), srs.cloned())? {
+        let max_length = (envelope.MaxX - envelope.MinX).max(envelope.MaxY - envelope.MinY) / 64.0;
+        if max_length > 0.0 {
+            unsafe { gdal_sys::OGR_G_Segmentize(geom.c_geometry(), max_length) };
+        }
+        geom.transform_inplace(&transform)
+            .map_err(|e| AppError::InvalidFormat(format!("空间范围坐标转换失败: {}", e)))?;
+    }
+    let envelope = geom.envelope();
+    Ok(Extent { min_x: envelope.MinX, min_y: envelope.MinY, max_x: envelope.MaxX, max_y: envelope.MaxY })
+}
+
+/// 统计每个格网内的点数（多点按各点分别统计，非点要素忽略），返回 (各格网点数, 落在格网内的点数)
+fn count_points(
+    input: &AnalysisInput,
+    grid: &Grid,
+    srs: Option<&SpatialRef>,
+    job: &JobContext,
+) -> Result<(CellCounts, u64)> {
+    let layer_index = input.layer_index.unwrap_or(0);
+    let dataset = gdal_service::open_vector_dataset(&input.path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取点图层 {}: {}", layer_index, e)))?;
+    let transform = gdal_service::create_layer_transform(layer.spatial_ref(), srs.cloned())?;
+    let input_fids = selection_service::input_fids(input)?;
+
+    let total = input_fids.as_ref().map_or_else(|| layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut processed = 0u64;
+    let mut counts = CellCounts::new();
+    let mut counted = 0u64;
+    job.report(0, Some(total));
+
+    for feature in layer.features() {
+        job.check_cancelled()?;
+        if let Some(fids) = &input_fids {
+            if !feature.fid().is_some_and(|fid| fids.contains(&fid)) {
+                continue;
+            }
+        }
+        processed += 1;
+        if processed >= next_report {
+            job.report(processed, Some(total));
+            next_report += report_interval;
+        }
+
+        let Some(geom) = feature.geometry() else { continue };
+        let mut geom = geom.clone();
+        if let Some(transform) = &transform {
+            if geom.transform_inplace(transform).is_err() {
+                log::warn!("要素 {:?} 坐标转换失败，不参与统计", feature.fid());
+                continue;
+            }
+        }
+        let mut points = Vec::new();
+        geometry::collect_parts(&geom, 0, &mut points);
+        for point in points {
+            let (x, y, _) = point.get_point(0);
+            if let Some(cell) = grid.locate(x, y) {
+                *counts.entry(cell).or_default() += 1;
+                counted += 1;
+            }
+        }
+    }
+    job.report(processed, Some(total));
+    Ok((counts, counted))
+}
+
+/// 生成矩形或六边形格网（面图层），输出 cell_id、row、col；
+/// 指定点图层时统计每个格网内的点数（point_count），可只输出有点的格网（六边形分箱）
+pub fn create_grid(request: &GridRequest, job: &JobContext) -> Result<GridResult> {
+    log::info!("生成格网 ({:?}, 大小 {}): {}", request.shape, request.cell_size, request.output.path);
+    let srs = grid_srs(request)?;
+    let extent = match (&request.extent, &request.layer) {
+        (Some(extent), _) => extent.clone(),
+        (None, Some(input)) => layer_extent(input, srs.as_ref())?,
+        (None, None) => return Err(AppError::InvalidFormat("需要指定格网范围或图层".to_string())),
+    };
+    let cell_height = request.cell_height.unwrap_or(request.cell_size);
+    let grid = Grid::new(request.shape, &extent, request.cell_size, cell_height, MAX_GRID_CELLS)?;
+
+    let stages = if request.count_points.is_some() { 2 } else { 1 };
+    let (counts, points_counted) = match &request.count_points {
+        Some(input) => {
+            job.set_stage(0, stages);
+            let (counts, counted) = count_points(input, &grid, srs.as_ref(), job)?;
+            (Some(counts), counted)
+        }
+        None => (None, 0),
+    };
+
+    let mut schemas = vec![
+        export_service::new_field_schema("cell_id", FieldType::Integer64),
+        export_service::new_field_schema("row", FieldType::Integer),
+        export_service::new_field_schema("col", FieldType::Integer),
+    ];
+    if counts.is_some() {
+        schemas.push(export_service::new_field_schema("point_count", FieldType::Integer64));
+    }
+    let mut output = OutputLayer::for_analysis(&request.output, srs.as_ref(), OGRwkbGeometryType::wkbPolygon, &schemas)?;
+
+    job.set_stage(stages - 1, stages);
+    let total = grid.cell_count();
+    let mut processed = 0u64;
+    job.report(0, Some(total));
+
+    let mut run = || -> Result<()> {
+        let mut cell_id = 0i64;
+        for row in 0..grid.rows {
+            job.check_cancelled()?;
+            for col in 0..grid.cols {
+                if !grid.intersects_extent(row, col) {
+                    continue;
+                }
+                let count = counts.as_ref().map(|counts| counts.get(&(row, col)).copied().unwrap_or(0));
+                if request.skip_empty && count == Some(0) {
+                    continue;
+                }
+                cell_id += 1;
+                let mut values = vec![
+                    Some(FieldValue::Integer64Value(cell_id)),
+                    Some(FieldValue::IntegerValue(row as i32)),
+                    Some(FieldValue::IntegerValue(col as i32)),
+                ];
+                if let Some(count) = count {
+                    values.push(Some(FieldValue::Integer64Value(count as i64)));
+                }
+                output.write(Some(grid.cell_geometry(row, col)?), &values)?;
+            }
+            processed += grid.cols as u64;
+            job.report(processed, Some(total));
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        output.discard();
+        return Err(e);
+    }
+    let output = output.finish()?;
+    log::info!("格网生成完成: {} 行 x {} 列, 输出 {} 个格网", grid.rows, grid.cols, output.features_written);
+    Ok(GridResult { output, rows: grid.rows, cols: grid.cols, points_counted })
+}
+
+/// 未指定种子时由当前时间生成（不超过 2^53，前端可无损保存）
+fn random_seed() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    SeededRng::new(nanos).next_u64() >> 11
+}
+
+/// 在面要素内生成采样点（点图层，坐标系与输入相同），输出 point_id 和所在面的 source_fid
+/// random 按面积均匀随机取点，相同种子和输入生成相同的点；regular 按间距生成对齐的规则点阵
+pub fn sample_points(request: &SamplePointsRequest, job: &JobContext) -> Result<SamplePointsResult> {
+    let layer_index = request.input.layer_index.unwrap_or(0);
+    log::info!("生成采样点 ({:?}): {} (图层 {})", request.method, request.input.path, layer_index);
+    let seed = match request.method {
+        SampleMethod::Random => {
+            if request.count.unwrap_or(0) == 0 {
+                return Err(AppError::InvalidFormat("随机采样需要指定每个面的点数".to_string()));
+            }
+            Some(request.seed.unwrap_or_else(random_seed))
+        }
+        SampleMethod::Regular => {
+            if !request.spacing.is_some_and(|spacing| spacing > 0.0) {
+                return Err(AppError::InvalidFormat("规则采样需要指定大于 0 的点间距".to_string()));
+            }
+            None
+        }
+    };
+
+    let dataset = gdal_service::open_vector_dataset(&request.input.path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let input_fids = selection_service::input_fids(&request.input)?;
+    let schemas = [
+        export_service::new_field_schema("point_id", FieldType::Integer64),
+        export_service::new_field_schema("source_fid", FieldType::Integer64),
+    ];
+    let mut output = OutputLayer::for_analysis(&request.output, layer.spatial_ref().as_ref(), OGRwkbGeometryType::wkbPoint, &schemas)?;
+
+    let total = input_fids.as_ref().map_or_else(|| layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut processed = 0u64;
+    let mut skipped = 0u64;
+    let mut rng = SeededRng::new(seed.unwrap_or_default());
+    job.report(0, Some(total));
+
+    let mut run = || -> Result<()> {
+        let mut point_id = 0i64;
+        for feature in layer.features() {
+            job.check_cancelled()?;
+            let fid = feature.fid();
+            if let Some(fids) = &input_fids {
+                if !fid.is_some_and(|fid| fids.contains(&fid)) {
+                    continue;
+                }
+            }
+            processed += 1;
+            if processed >= next_report {
+                job.report(processed, Some(total));
+                next_report += report_interval;
+            }
+
+            let Some(geom) = feature.geometry() else { continue };
+            let polygons = geometry::to_linear(geom.clone())
+                .and_then(|geom| measure_service::to_geo(&geom))
+                .map(|geom| sampling::polygons(&geom))
+                .unwrap_or_default();
+            if polygons.is_empty() {
+                skipped += 1;
+                continue;
+            }
+            let points = match request.method {
+                SampleMethod::Random => sampling::random_points(&polygons, request.count.unwrap_or(0), &mut rng),
+                SampleMethod::Regular => {
+                    let spacing = request.spacing.unwrap_or_default();
+                    if sampling::regular_candidates(&polygons, spacing) > MAX_REGULAR_CANDIDATES {
+                        return Err(AppError::InvalidFormat(format!("要素 {:?} 的点位过多，请增大点间距", fid)));
+                    }
+                    sampling::regular_points(&polygons, spacing)
+                }
+            };
+
+            for (x, y) in points {
+                let mut point = Geometry::empty(OGRwkbGeometryType::wkbPoint)
+                    .map_err(|e| AppError::InvalidFormat(format!("创建几何失败: {}", e)))?;
+                point.add_point_2d((x, y));
+                point_id += 1;
+                let values = [
+                    Some(FieldValue::Integer64Value(point_id)),
+                    fid.map(|fid| FieldValue::Integer64Value(fid as i64)),
+                ];
+                output.write(Some(point), &values)?;
+            }
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        output.discard();
+        return Err(e);
+    }
+    job.report(processed, Some(total));
+    if skipped > 0 {
+        log::warn!("{} 个要素不是面或几何无效，未生成采样点", skipped);
+    }
+    let output = output.finish()?;
+    log::info!("采样点生成完成: {} 个点", output.features_written);
+    Ok(SamplePointsResult { output, seed })
+}
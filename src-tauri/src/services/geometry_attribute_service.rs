@@ -0,0 +1,309 @@
+use crate::errors::{AppError, Result};
+use crate::gis::{geometry, measure};
+use crate::models::{
+    AttributeField, FieldType, GeometryAttribute, GeometryAttributesRequest, GeometryAttributesResult, MeasureMethod,
+};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::{gdal_service, measure_service, selection_service};
+use gdal::spatial_ref::{CoordTransform, SpatialRef};
+use gdal::vector::{FieldValue, Geometry, LayerAccess};
+use std::collections::HashSet;
+
+/// 属性的默认字段名
+fn default_name(attribute: GeometryAttribute) -> &'static str {
+    match attribute {
+        GeometryAttribute::Area => "area",
+        GeometryAttribute::Perimeter => "perimeter",
+        GeometryAttribute::Length => "length",
+        GeometryAttribute::CentroidX => "centroid_x",
+        GeometryAttribute::CentroidY => "centroid_y",
+        GeometryAttribute::VertexCount => "vertex_count",
+        GeometryAttribute::MinX => "min_x",
+        GeometryAttribute::MinY => "min_y",
+        GeometryAttribute::MaxX => "max_x",
+        GeometryAttribute::MaxY => "max_y",
+    }
+}
+
+/// 要写入的字段：existing 为图层中同名字段的序号（覆盖时）
+struct Column {
+    attribute: GeometryAttribute,
+    name: String,
+    field_type: FieldType,
+    existing: Option<usize>,
+}
+
+/// 按请求确定写入的字段，同名字段未允许覆盖、类型不是数值或实数值对应的字段不是实数字段时报错
+fn resolve_columns(request: &GeometryAttributesRequest, layer_fields: &[AttributeField]) -> Result<Vec<Column>> {
+    if request.fields.is_empty() {
+        return Err(AppError::InvalidFormat("未指定要计算的几何属性".to_string()));
+    }
+    let mut used = HashSet::new();
+    request.fields.iter()
+        .map(|field| {
+            let name = field.name.as_deref()
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| default_name(field.attribute))
+                .to_string();
+            if !used.insert(name.to_lowercase()) {
+                return Err(AppError::InvalidFormat(format!("字段名重复: {}", name)));
+            }
+            let field_type = match field.attribute {
+                GeometryAttribute::VertexCount => FieldType::Integer,
+                _ => FieldType::Real,
+            };
+            let existing = layer_fields.iter().position(|existing| existing.name.eq_ignore_ascii_case(&name));
+            if let Some(index) = existing {
+                if !request.overwrite {
+                    return Err(AppError::InvalidFormat(format!("字段 {} 已存在", name)));
+                }
+                let existing_type = layer_fields[index].field_type;
+                if !existing_type.is_numeric() {
+                    return Err(AppError::InvalidFormat(format!("字段 {} 不是数值字段，无法写入几何属性", name)));
+                }
+                // 面积、长度等实数写入整数字段会被截断
+                if field_type == FieldType::Real && existing_type != FieldType::Real {
+                    return Err(AppError::InvalidFormat(format!(
+                        "字段 {} 不是实数字段，无法写入 {}", name, default_name(field.attribute)
+                    )));
+                }
+            }
+            Ok(Column { attribute: field.attribute, name, field_type, existing })
+        })
+        .collect()
+}
+
+/// 量算所在的坐标空间：椭球面量算转为经纬度，平面量算转为投影坐标系
+struct Calculator {
+    method: MeasureMethod,
+    transform: Option<CoordTransform>,
+    meters_per_unit: f64,
+}
+
+impl Calculator {
+    fn new(request: &GeometryAttributesRequest, layer_srs: Option<SpatialRef>) -> Result<Calculator> {
+        match request.method {
+            MeasureMethod::Geodesic => Ok(Calculator {
+                method: request.method,
+                transform: gdal_service::create_wgs84_transform(layer_srs)?,
+                meters_per_unit: 1.0,
+            }),
+            MeasureMethod::Planar => {
+                let (planar_srs, transform) = match request.planar_crs.as_deref() {
+                    Some(definition) => {
+                        let planar_srs = measure_service::projected_srs(definition)?;
+                        let transform = gdal_service::create_layer_transform(layer_srs, Some(planar_srs.clone()))?;
+                        (planar_srs, transform)
+                    }
+                    None => match layer_srs.filter(|srs| srs.is_projected()) {
+                        Some(srs) => (srs, None),
+                        None => return Err(AppError::InvalidFormat(
+                            "图层不是投影坐标系，平面量算需要指定投影坐标系".to_string(),
+                        )),
+                    },
+                };
+                Ok(Calculator {
+                    method: request.method,
+                    transform,
+                    meters_per_unit: planar_srs.linear_units(),
+                })
+            }
+        }
+    }
+
+    /// 计算一个要素的字段值，没有几何时全部为空
+    fn values(&self, geom: Option<&Geometry>, columns: &[Column], request: &GeometryAttributesRequest) -> Result<Vec<Option<FieldValue>>> {
+        let Some(geom) = geom else {
+            return Ok(vec![None; columns.len()]);
+        };
+        let mut geom = geometry::to_linear(geom.clone())?;
+        if let Some(transform) = &self.transform {
+            geom.transform_inplace(transform)
+                .map_err(|e| AppError::InvalidFormat(format!("坐标转换失败: {}", e)))?;
+        }
+        let geom = measure_service::to_geo(&geom)?;
+        let measured = match self.method {
+            MeasureMethod::Geodesic => measure::geodesic(&geom),
+            MeasureMethod::Planar => measure::planar(&geom, self.meters_per_unit),
+        };
+        let measured = measure::convert_values(&measured, request.length_unit, request.area_unit);
+        let centroid = measure::centroid(&geom);
+        let bounds = measure::bounds(&geom);
+
+        let real = |value: Option<f64>| value.map(FieldValue::RealValue);
+        Ok(columns.iter()
+            .map(|column| match column.attribute {
+                GeometryAttribute::Area => real(Some(measured.area)),
+                GeometryAttribute::Perimeter => real(Some(measured.perimeter)),
+                GeometryAttribute::Length => real(Some(measured.length)),
+                GeometryAttribute::CentroidX => real(centroid.map(|(x, _)| x)),
+                GeometryAttribute::CentroidY => real(centroid.map(|(_, y)| y)),
+                GeometryAttribute::VertexCount => {
+                    let count = measure::vertex_count(&geom);
+                    Some(FieldValue::IntegerValue(i32::try_from(count).unwrap_or(i32::MAX)))
+                }
+                GeometryAttribute::MinX => real(bounds.map(|b| b[0])),
+                GeometryAttribute::MinY => real(bounds.map(|b| b[1])),
+                GeometryAttribute::MaxX => real(bounds.map(|b| b[2])),
+                GeometryAttribute::MaxY => real(bounds.map(|b| b[3])),
+            })
+            .collect())
+    }
+}
+
+/// 计算几何属性（面积、周长、长度、中心点、顶点数、外包矩形）并写入字段
+/// 面积和长度按请求的单位换算；中心点和外包矩形为量算坐标空间的坐标（椭球面量算为经纬度）
+/// 指定输出时写入新图层（保留原有字段）；否则先算出全部要素的值，再在输入图层中新增字段并写入，
+/// 支持事务的数据源在事务中提交，不支持事务时写入失败会报告已写入的要素数
+pub fn add_geometry_attributes(request: &GeometryAttributesRequest, job: &JobContext) -> Result<GeometryAttributesResult> {
+    let layer_index = request.input.layer_index.unwrap_or(0);
+    log::info!("计算几何属性 ({:?}): {} (图层 {})", request.method, request.input.path, layer_index);
+
+    let mut dataset = match request.output {
+        Some(_) => gdal_service::open_vector_dataset(&request.input.path)?,
+        None => gdal_service::open_vector_dataset_for_update(&request.input.path)?,
+    };
+    let input_fids = selection_service::input_fids(&request.input)?;
+
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let layer_fields = gdal_service::read_attribute_fields(layer.defn());
+    let columns = resolve_columns(request, &layer_fields)?;
+    let calculator = Calculator::new(request, layer.spatial_ref())?;
+
+    let mut output = match &request.output {
+        Some(output) => {
+            let mut schemas: Vec<_> = layer_fields.iter().map(export_service::field_schema).collect();
+            for column in columns.iter().filter(|column| column.existing.is_none()) {
+                schemas.push(export_service::new_field_schema(&column.name, column.field_type));
+            }
+            let geometry_type = unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) };
+            Some(OutputLayer::for_analysis(output, layer.spatial_ref().as_ref(), geometry_type, &schemas)?)
+        }
+        None => None,
+    };
+
+    let total = input_fids.as_ref().map_or_else(|| layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut processed = 0u64;
+    let mut rows = Vec::new();
+    job.report(0, Some(total));
+
+    let mut run = || -> Result<()> {
+        for feature in layer.features() {
+            job.check_cancelled()?;
+            let Some(fid) = feature.fid() else { continue };
+            if let Some(fids) = &input_fids {
+                if !fids.contains(&fid) {
+                    continue;
+                }
+            }
+            processed += 1;
+            if processed >= next_report {
+                job.report(processed, Some(total));
+                next_report += report_interval;
+            }
+
+            let values = calculator.values(feature.geometry(), &columns, request)
+                .map_err(|e| AppError::InvalidFormat(format!("要素 {} 计算失败: {}", fid, e)))?;
+            match &mut output {
+                Some(writer) => {
+                    let mut record: Vec<Option<FieldValue>> = feature.fields().map(|(_, value)| value).collect();
+                    for (column, value) in columns.iter().zip(values) {
+                        match column.existing {
+                            Some(index) => record[index] = value,
+                            None => record.push(value),
+                        }
+                    }
+                    writer.write(feature.geometry().cloned(), &record)?;
+                }
+                None => rows.push((fid, values)),
+            }
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        if let Some(output) = output {
+            output.discard();
+        }
+        return Err(e);
+    }
+    job.report(processed, Some(total));
+
+    let result = match output {
+        Some(output) => GeometryAttributesResult {
+            features_updated: processed,
+            fields: columns.into_iter().map(|column| column.name).collect(),
+            output: Some(output.finish()?),
+        },
+        None => GeometryAttributesResult {
+            features_updated: processed,
+            fields: write_in_place(&mut dataset, &request.input.path, layer_index, &columns, &rows)?,
+            output: None,
+        },
+    };
+    log::info!("几何属性计算完成: {} 个要素, 字段 {:?}", result.features_updated, result.fields);
+    Ok(result)
+}
+
+/// 在输入图层中新增字段并写入属性值，返回实际字段名（部分格式会截断或改写字段名）
+/// 不支持事务的数据源（如 Shapefile）写入失败时，新增字段和已写入的值会保留，错误信息中报告已写入的要素数；
+/// 写入后丢弃该数据源的空间索引和瓦片缓存
+fn write_in_place(
+    dataset: &mut gdal::Dataset,
+    path: &str,
+    layer_index: usize,
+    columns: &[Column],
+    rows: &[(u64, Vec<Option<FieldValue>>)],
+) -> Result<Vec<String>> {
+    let fields = gdal_service::write_in_transaction(dataset, layer_index, |layer| write_layer_fields(layer, columns, rows));
+    // 写入失败时不支持事务的数据源可能已部分修改，同样丢弃缓存
+    gdal_service::invalidate_source_caches(path);
+    fields
+}
+
+fn write_layer_fields(
+    layer: &gdal::vector::Layer,
+    columns: &[Column],
+    rows: &[(u64, Vec<Option<FieldValue>>)],
+) -> Result<Vec<String>> {
+    // 新字段追加在末尾，序号为新增后的字段数减一
+    let mut indexes = Vec::with_capacity(columns.len());
+    for column in columns {
+        let index = match column.existing {
+            Some(index) => index,
+            None => {
+                gdal_service::create_field(layer, &export_service::new_field_schema(&column.name, column.field_type))?;
+                layer.defn().fields().count() - 1
+            }
+        };
+        indexes.push(index);
+    }
+    let names: Vec<String> = gdal_service::read_attribute_fields(layer.defn())
+        .into_iter()
+        .map(|field| field.name)
+        .collect();
+
+    let write = |fid: u64, values: &[Option<FieldValue>]| -> Result<()> {
+        let feature = layer.feature(fid)
+            .ok_or_else(|| AppError::FileReadError(format!("要素 {} 不存在", fid)))?;
+        for (&index, value) in indexes.iter().zip(values) {
+            export_service::set_field_by_index(&feature, index as i32, value.as_ref());
+        }
+        layer.set_feature(feature)
+            .map_err(|e| AppError::FileWriteError(format!("修改要素 {} 失败: {}", fid, e)))
+    };
+    for (written, (fid, values)) in rows.iter().enumerate() {
+        write(*fid, values).map_err(|e| {
+            AppError::FileWriteError(format!(
+                "{}（已写入 {}/{} 个要素，新增字段已保留，不支持事务的数据源中已写入的修改不会撤销）",
+                e, written, rows.len()
+            ))
+        })?;
+    }
+    Ok(indexes.iter().map(|&index| names[index].clone()).collect())
+}
@@ -0,0 +1,87 @@
+use crate::errors::{AppError, Result};
+use crate::models::{DimensionMode, IdentifyLayer, IdentifyLayerResult, IdentifyRequest};
+use crate::services::{gdal_service, gdb_service, spatial_index_service};
+use gdal::vector::{Geometry, LayerAccess, OGRwkbGeometryType};
+
+/// 默认像素容差
+const DEFAULT_TOLERANCE: f64 = 3.0;
+
+/// 每个图层默认最多返回的要素数
+const DEFAULT_MAX_FEATURES: usize = 50;
+
+/// 地图坐标系下的点几何
+fn point(x: f64, y: f64) -> Result<Geometry> {
+    let mut point = Geometry::empty(OGRwkbGeometryType::wkbPoint)
+        .map_err(|e| AppError::InvalidFormat(format!("创建点几何失败: {}", e)))?;
+    point.set_point_2d(0, (x, y));
+    Ok(point)
+}
+
+/// 识别单个图层在指定位置的要素
+/// 像素容差按地图分辨率换算为地面距离，识别点缓冲为圆后转换到图层坐标系，
+/// 经空间索引筛选候选要素后精确判断相交，命中要素按与识别点的距离排序
+pub fn identify_layer(layer_ref: &IdentifyLayer, request: &IdentifyRequest) -> Result<IdentifyLayerResult> {
+    let layer_index = layer_ref.layer_index.unwrap_or(0);
+    let path = layer_ref.path.as_str();
+    let index = spatial_index_service::layer_index(path, layer_index)?;
+
+    let dataset = gdal_service::open_vector_dataset(path)?;
+    let layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let layer_srs = layer.spatial_ref();
+
+    // 识别范围（地图坐标系 → 图层坐标系）
+    let tolerance = request.tolerance.unwrap_or(DEFAULT_TOLERANCE).max(0.0) * request.resolution;
+    let mut center = point(request.x, request.y)?;
+    let mut search = if tolerance > 0.0 {
+        center.buffer(tolerance, 8)
+            .map_err(|e| AppError::InvalidFormat(format!("创建识别范围失败: {}", e)))?
+    } else {
+        center.clone()
+    };
+    if let Some(transform) = gdal_service::create_map_to_layer_transform(&request.crs, layer_srs.clone())? {
+        search.transform_inplace(&transform)
+            .map_err(|e| AppError::InvalidFormat(format!("识别范围坐标转换失败: {}", e)))?;
+        center.transform_inplace(&transform)
+            .map_err(|e| AppError::InvalidFormat(format!("识别点坐标转换失败: {}", e)))?;
+    }
+
+    // 命中要素及其与识别点的距离（图层坐标系单位，仅用于排序）
+    let mut hits: Vec<(f64, u64, gdal::vector::Feature)> = Vec::new();
+    for fid in index.candidates(&search.envelope()) {
+        let Some(feature) = layer.feature(fid) else { continue };
+        let distance = match feature.geometry() {
+            Some(geom) if geom.intersects(&search) => unsafe {
+                gdal_sys::OGR_G_Distance(center.c_geometry(), geom.c_geometry())
+            },
+            _ => continue,
+        };
+        hits.push((distance.max(0.0), fid, feature));
+    }
+    hits.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let max_features = request.max_features.unwrap_or(DEFAULT_MAX_FEATURES);
+    let truncated = hits.len() > max_features;
+    hits.truncate(max_features);
+
+    let is_kml = gdal_service::is_kml_path(path);
+    let transform = gdal_service::create_wgs84_transform(layer_srs)?;
+    let decoder = if request.decode_domains.unwrap_or(true) {
+        gdb_service::DomainDecoder::for_layer(&dataset, &layer)
+    } else {
+        None
+    };
+    let features = hits.iter()
+        .map(|(_, _, feature)| {
+            gdal_service::feature_to_geojson(feature, transform.as_ref(), DimensionMode::Preserve, is_kml, decoder.as_ref())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(IdentifyLayerResult {
+        layer_id: layer_ref.layer_id.clone(),
+        layer_name: layer.name(),
+        features,
+        truncated,
+        error: None,
+    })
+}
@@ -0,0 +1,199 @@
+use crate::errors::{AppError, Result};
+use crate::models::{JobInfo, JobProgress, JobStatus};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// 进度回调（由命令层转为 Tauri 事件）
+pub type ProgressReporter = Box<dyn Fn(JobProgress) + Send + Sync>;
+
+lazy_static! {
+    // 正在运行的任务
+    static ref JOBS: Mutex<HashMap<String, JobContext>> = Mutex::new(HashMap::new());
+}
+
+/// 多阶段任务（如批量转换）的当前阶段
+#[derive(Default)]
+struct Stage {
+    index: usize,
+    count: usize,
+    processed_before: u64, // 之前阶段已处理的数量
+    processed: u64,
+}
+
+struct JobState {
+    id: String,
+    kind: String,
+    started: Instant,
+    cancelled: AtomicBool,
+    reporter: Option<ProgressReporter>,
+    stage: Mutex<Stage>,
+}
+
+/// 长时间运行的任务（导出、批量转换、读取大图层）
+/// 任务循环中调用 check_cancelled 响应取消，调用 report 推送进度
+#[derive(Clone)]
+pub struct JobContext {
+    state: Arc<JobState>,
+}
+
+impl JobContext {
+    fn new(id: String, kind: &str, reporter: Option<ProgressReporter>) -> Self {
+        JobContext {
+            state: Arc::new(JobState {
+                id,
+                kind: kind.to_string(),
+                started: Instant::now(),
+                cancelled: AtomicBool::new(false),
+                reporter,
+                stage: Mutex::new(Stage::default()),
+            }),
+        }
+    }
+
+    /// 不登记、不推送进度、不可取消的任务（内部调用使用）
+    pub fn untracked(kind: &str) -> Self {
+        JobContext::new(String::new(), kind, None)
+    }
+
+    pub fn id(&self) -> &str {
+        &self.state.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// 任务已取消时返回 AppError::Cancelled
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(AppError::Cancelled(format!("任务已取消: {}", self.state.id)))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 进入第 index 个阶段（共 count 个），总进度按阶段平均分配
+    pub fn set_stage(&self, index: usize, count: usize) {
+        let mut stage = self.state.stage.lock().unwrap();
+        stage.processed_before += stage.processed;
+        stage.processed = 0;
+        stage.index = index;
+        stage.count = count;
+    }
+
+    /// 报告当前阶段进度：processed 为已处理要素数，total 为要素总数（未知时为 None）
+    pub fn report(&self, processed: u64, total: Option<u64>) {
+        let Some(reporter) = &self.state.reporter else {
+            return;
+        };
+
+        let (percent, processed, staged) = {
+            let mut stage = self.state.stage.lock().unwrap();
+            stage.processed = processed;
+            let fraction = match total {
+                Some(total) if total > 0 => Some((processed as f64 / total as f64).min(1.0)),
+                Some(_) => Some(1.0),
+                None => None,
+            };
+            let percent = match (fraction, stage.count) {
+                (Some(fraction), 0) => Some(fraction * 100.0),
+                (Some(fraction), count) => Some((stage.index as f64 + fraction) / count as f64 * 100.0),
+                (None, 0) => None,
+                (None, count) => Some(stage.index as f64 / count as f64 * 100.0),
+            };
+            (percent, stage.processed_before + processed, stage.count > 0)
+        };
+
+        let elapsed = self.state.started.elapsed().as_secs_f64();
+        let eta_seconds = percent
+            .filter(|percent| *percent > 0.0 && *percent < 100.0)
+            .map(|percent| elapsed * (100.0 - percent) / percent);
+
+        reporter(JobProgress {
+            job_id: self.state.id.clone(),
+            kind: self.state.kind.clone(),
+            status: JobStatus::Running,
+            percent,
+            processed,
+            total: total.filter(|_| !staged), // 多阶段任务的总数仅对当前阶段有效
+            elapsed_seconds: elapsed,
+            eta_seconds,
+            error: None,
+        });
+    }
+
+    /// 任务结束：移出任务列表并推送最终状态
+    pub fn finish<T>(&self, result: &Result<T>) {
+        {
+            let mut jobs = JOBS.lock().unwrap();
+            if jobs.get(&self.state.id).is_some_and(|job| Arc::ptr_eq(&job.state, &self.state)) {
+                jobs.remove(&self.state.id);
+            }
+        }
+
+        let Some(reporter) = &self.state.reporter else {
+            return;
+        };
+        let (status, error) = match result {
+            Ok(_) => (JobStatus::Completed, None),
+            Err(AppError::Cancelled(_)) => (JobStatus::Cancelled, None),
+            Err(e) => (JobStatus::Failed, Some(e.to_string())),
+        };
+        let stage = self.state.stage.lock().unwrap();
+        reporter(JobProgress {
+            job_id: self.state.id.clone(),
+            kind: self.state.kind.clone(),
+            percent: (status == JobStatus::Completed).then_some(100.0),
+            status,
+            processed: stage.processed_before + stage.processed,
+            total: None,
+            elapsed_seconds: self.state.started.elapsed().as_secs_f64(),
+            eta_seconds: None,
+            error,
+        });
+    }
+}
+
+/// 登记新任务，job_id 为空时自动生成；与正在运行的任务 ID 重复时报错
+pub fn start(kind: &str, job_id: Option<String>, reporter: ProgressReporter) -> Result<JobContext> {
+    let id = job_id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let mut jobs = JOBS.lock().unwrap();
+    if jobs.contains_key(&id) {
+        return Err(AppError::InvalidFormat(format!("任务ID已在使用: {}", id)));
+    }
+    let job = JobContext::new(id.clone(), kind, Some(reporter));
+    log::info!("开始任务 {} ({})", id, kind);
+    jobs.insert(id, job.clone());
+    Ok(job)
+}
+
+/// 取消任务，任务不存在（已结束）时返回 false
+pub fn cancel(job_id: &str) -> bool {
+    match JOBS.lock().unwrap().get(job_id) {
+        Some(job) => {
+            log::info!("取消任务 {} ({})", job_id, job.state.kind);
+            job.state.cancelled.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 列出正在运行的任务
+pub fn list_jobs() -> Vec<JobInfo> {
+    JOBS.lock()
+        .unwrap()
+        .values()
+        .map(|job| JobInfo {
+            job_id: job.state.id.clone(),
+            kind: job.state.kind.clone(),
+            elapsed_seconds: job.state.started.elapsed().as_secs_f64(),
+            cancelled: job.is_cancelled(),
+        })
+        .collect()
+}
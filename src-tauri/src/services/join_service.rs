@@ -0,0 +1,303 @@
+use crate::errors::{AppError, Result};
+use crate::models::{
+    AttributeField, FieldType, FieldAggregation, JoinCardinality, AggregationRule, JoinPredicate, SpatialJoinRequest,
+    SpatialJoinResult, SpatialRelation, TableFieldSchema,
+};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::spatial_index_service::LayerIndex;
+use crate::services::{aggregation_service, gdal_service, selection_service, spatial_index_service};
+use gdal::vector::{Envelope, FieldValue, Geometry, LayerAccess};
+use std::collections::HashSet;
+
+/// 连接图层中与待连接要素匹配的要素
+struct JoinMatch {
+    fid: u64,
+    distance: Option<f64>,
+}
+
+/// 输出中的连接字段
+enum JoinColumn {
+    /// 连接要素的字段（一对多，或一对一取第一个匹配要素）
+    Field(usize),
+    /// 一对一时按规则汇总的字段
+    Aggregate(usize, FieldAggregation),
+}
+
+pub(crate) fn expand(envelope: &Envelope, distance: f64) -> Envelope {
+    Envelope {
+        MinX: envelope.MinX - distance,
+        MaxX: envelope.MaxX + distance,
+        MinY: envelope.MinY - distance,
+        MaxY: envelope.MaxY + distance,
+    }
+}
+
+pub(crate) fn geometry_distance(a: &Geometry, b: &Geometry) -> f64 {
+    unsafe { gdal_sys::OGR_G_Distance(a.c_geometry(), b.c_geometry()) }
+}
+
+/// 查找最近的连接要素（同距离时取 FID 较小者）
+/// 未指定搜索距离时从较小的半径开始逐步扩大，找到的要素距离不超过搜索半径时即为最近要素
+fn nearest<L: LayerAccess>(
+    layer: &L,
+    index: &LayerIndex,
+    geom: &Geometry,
+    distance: Option<f64>,
+    allowed: Option<&HashSet<u64>>,
+) -> Option<JoinMatch> {
+    let extent = index.extent()?;
+    let envelope = geom.envelope();
+    let span_x = extent.max_x.max(envelope.MaxX) - extent.min_x.min(envelope.MinX);
+    let span_y = extent.max_y.max(envelope.MaxY) - extent.min_y.min(envelope.MinY);
+    let max_radius = span_x.hypot(span_y);
+    let mut radius = distance.unwrap_or((max_radius / 1024.0).max(f64::EPSILON));
+
+    loop {
+        let mut best: Option<JoinMatch> = None;
+        for fid in index.candidates(&expand(&envelope, radius)) {
+            if allowed.is_some_and(|allowed| !allowed.contains(&fid)) {
+                continue;
+            }
+            let Some(feature) = layer.feature(fid) else { continue };
+            let Some(join_geom) = feature.geometry() else { continue };
+            let d = geometry_distance(geom, join_geom);
+            if d >= 0.0 && best.as_ref().is_none_or(|b| d < b.distance.unwrap_or(f64::INFINITY)) {
+                best = Some(JoinMatch { fid, distance: Some(d) });
+            }
+        }
+
+        let best_distance = best.as_ref().and_then(|b| b.distance).unwrap_or(f64::INFINITY);
+        if distance.is_some() {
+            return best.filter(|_| best_distance <= radius);
+        }
+        if best_distance <= radius || radius >= max_radius {
+            return best;
+        }
+        radius *= 2.0;
+    }
+}
+
+/// 查找与待连接几何（连接图层坐标系）匹配的连接要素
+fn find_matches<L: LayerAccess>(
+    layer: &L,
+    index: &LayerIndex,
+    geom: &Geometry,
+    predicate: JoinPredicate,
+    distance: Option<f64>,
+    allowed: Option<&HashSet<u64>>,
+) -> Vec<JoinMatch> {
+    let relation = match predicate {
+        JoinPredicate::Intersects => SpatialRelation::Intersects,
+        JoinPredicate::Within => SpatialRelation::Within,
+        JoinPredicate::Contains => SpatialRelation::Contains,
+        JoinPredicate::Nearest => return nearest(layer, index, geom, distance, allowed).into_iter().collect(),
+    };
+    index.candidates(&geom.envelope())
+        .into_iter()
+        .filter(|fid| allowed.is_none_or(|allowed| allowed.contains(fid)))
+        .filter(|&fid| {
+            layer.feature(fid)
+                .and_then(|feature| {
+                    feature.geometry().map(|join_geom| selection_service::relation_matches(geom, join_geom, relation))
+                })
+                .unwrap_or(false)
+        })
+        .map(|fid| JoinMatch { fid, distance: None })
+        .collect()
+}
+
+/// 确定输出的连接字段及其定义
+fn join_columns(
+    join_fields: &[AttributeField],
+    rules: &[AggregationRule],
+    cardinality: JoinCardinality,
+    used: &mut HashSet<String>,
+) -> Result<(Vec<JoinColumn>, Vec<TableFieldSchema>)> {
+    let mut columns = Vec::new();
+    let mut schemas = Vec::new();
+    if rules.is_empty() {
+        for (index, field) in join_fields.iter().enumerate() {
+            let mut schema = export_service::field_schema(field);
+            schema.name = export_service::unique_field_name(&field.name, used);
+            columns.push(JoinColumn::Field(index));
+            schemas.push(schema);
+        }
+        return Ok((columns, schemas));
+    }
+
+    for rule in rules {
+        let index = join_fields.iter()
+            .position(|f| f.name == rule.field)
+            .or_else(|| join_fields.iter().position(|f| f.name.eq_ignore_ascii_case(&rule.field)))
+            .ok_or_else(|| AppError::InvalidFormat(format!("连接图层字段不存在: {}", rule.field)))?;
+        let field = &join_fields[index];
+
+        // 一对多时每个匹配单独输出，忽略汇总方式
+        let aggregation = match cardinality {
+            JoinCardinality::OneToOne => rule.aggregation,
+            JoinCardinality::OneToMany => FieldAggregation::First,
+        };
+        let schema = aggregation_service::aggregation_schema(field, aggregation, rule.output_name.as_deref(), used)?;
+        columns.push(match cardinality {
+            JoinCardinality::OneToOne => JoinColumn::Aggregate(index, aggregation),
+            JoinCardinality::OneToMany => JoinColumn::Field(index),
+        });
+        schemas.push(schema);
+    }
+    Ok((columns, schemas))
+}
+
+/// 空间连接：按空间关系将连接图层的属性附加到待连接图层，写入新图层
+/// 一对一时输出 join_count（匹配数），最近要素连接时输出 join_dist（连接图层坐标系单位）；
+/// 一对多时每个匹配输出一个要素并记录 join_fid
+pub fn spatial_join(request: &SpatialJoinRequest, job: &JobContext) -> Result<SpatialJoinResult> {
+    let target_index = request.target.layer_index.unwrap_or(0);
+    let join_index = request.join.layer_index.unwrap_or(0);
+    log::info!(
+        "空间连接: {} <- {} ({:?}, {:?})",
+        request.target.path, request.join.path, request.predicate, request.cardinality
+    );
+
+    let target_dataset = gdal_service::open_vector_dataset(&request.target.path)?;
+    let mut target_layer = target_dataset.layer(target_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取待连接图层 {}: {}", target_index, e)))?;
+    let join_dataset = gdal_service::open_vector_dataset(&request.join.path)?;
+    let join_layer = join_dataset.layer(join_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取连接图层 {}: {}", join_index, e)))?;
+
+    job.set_stage(0, 2);
+    let index = spatial_index_service::layer_index(&request.join.path, join_index)?;
+    let transform = gdal_service::create_layer_transform(target_layer.spatial_ref(), join_layer.spatial_ref())?;
+    let target_fids = selection_service::input_fids(&request.target)?;
+    let join_fids = selection_service::input_fids(&request.join)?;
+
+    // 输出字段：待连接图层字段 + 连接信息 + 连接字段
+    let target_fields = gdal_service::read_attribute_fields(target_layer.defn());
+    let join_fields = gdal_service::read_attribute_fields(join_layer.defn());
+    let mut used = HashSet::new();
+    let mut schemas: Vec<TableFieldSchema> = target_fields.iter()
+        .map(|field| {
+            let mut schema = export_service::field_schema(field);
+            schema.name = export_service::unique_field_name(&field.name, &mut used);
+            schema
+        })
+        .collect();
+    let one_to_one = request.cardinality == JoinCardinality::OneToOne;
+    let with_distance = request.predicate == JoinPredicate::Nearest;
+    let info_field = if one_to_one { "join_count" } else { "join_fid" };
+    schemas.push(export_service::new_field_schema(&export_service::unique_field_name(info_field, &mut used), FieldType::Integer64));
+    if with_distance {
+        schemas.push(export_service::new_field_schema(&export_service::unique_field_name("join_dist", &mut used), FieldType::Real));
+    }
+    let (columns, join_schemas) = join_columns(&join_fields, &request.fields, request.cardinality, &mut used)?;
+    schemas.extend(join_schemas);
+
+    let geometry_type = unsafe { gdal_sys::OGR_L_GetGeomType(target_layer.c_layer()) };
+    let mut output = OutputLayer::for_analysis(&request.output, target_layer.spatial_ref().as_ref(), geometry_type, &schemas)?;
+
+    job.set_stage(1, 2);
+    let total = target_fids.as_ref().map_or_else(|| target_layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut processed = 0u64;
+    let mut matched = 0u64;
+    let mut unmatched = 0u64;
+    job.report(0, Some(total));
+
+    let mut run = || -> Result<()> {
+        for feature in target_layer.features() {
+            job.check_cancelled()?;
+            if let Some(fids) = &target_fids {
+                if !feature.fid().is_some_and(|fid| fids.contains(&fid)) {
+                    continue;
+                }
+            }
+            processed += 1;
+            if processed >= next_report {
+                job.report(processed, Some(total));
+                next_report += report_interval;
+            }
+
+            let geometry = feature.geometry().cloned();
+            let matches = match &geometry {
+                Some(geom) => {
+                    let mut query = geom.clone();
+                    let transformed = match &transform {
+                        Some(transform) => query.transform_inplace(transform).is_ok(),
+                        None => true,
+                    };
+                    if transformed {
+                        find_matches(&join_layer, &index, &query, request.predicate, request.distance, join_fids.as_ref())
+                    } else {
+                        log::warn!("要素 {:?} 坐标转换失败，视为无匹配", feature.fid());
+                        Vec::new()
+                    }
+                }
+                None => Vec::new(),
+            };
+            if matches.is_empty() {
+                unmatched += 1;
+                if !request.keep_all {
+                    continue;
+                }
+            } else {
+                matched += 1;
+            }
+
+            let target_values: Vec<Option<FieldValue>> = feature.fields().map(|(_, value)| value).collect();
+            let join_values = |fid: u64| -> Vec<Option<FieldValue>> {
+                join_layer.feature(fid)
+                    .map(|f| f.fields().map(|(_, value)| value).collect())
+                    .unwrap_or_else(|| vec![None; join_fields.len()])
+            };
+
+            if one_to_one {
+                let match_values: Vec<Vec<Option<FieldValue>>> = matches.iter().map(|m| join_values(m.fid)).collect();
+                let mut values = target_values;
+                values.push(Some(FieldValue::Integer64Value(matches.len() as i64)));
+                if with_distance {
+                    values.push(matches.first().and_then(|m| m.distance).map(FieldValue::RealValue));
+                }
+                for column in &columns {
+                    values.push(match *column {
+                        JoinColumn::Field(index) => match_values.first().and_then(|v| v[index].clone()),
+                        JoinColumn::Aggregate(index, aggregation) => {
+                            let field_values: Vec<Option<FieldValue>> = match_values.iter().map(|v| v[index].clone()).collect();
+                            aggregation_service::aggregate(&field_values, aggregation)
+                        }
+                    });
+                }
+                output.write(geometry, &values)?;
+            } else if matches.is_empty() {
+                let mut values = target_values;
+                values.resize(schemas.len(), None);
+                output.write(geometry, &values)?;
+            } else {
+                for m in &matches {
+                    let joined = join_values(m.fid);
+                    let mut values = target_values.clone();
+                    values.push(Some(FieldValue::Integer64Value(m.fid as i64)));
+                    if with_distance {
+                        values.push(m.distance.map(FieldValue::RealValue));
+                    }
+                    for column in &columns {
+                        let (JoinColumn::Field(index) | JoinColumn::Aggregate(index, _)) = *column;
+                        values.push(joined[index].clone());
+                    }
+                    output.write(geometry.clone(), &values)?;
+                }
+            }
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        output.discard();
+        return Err(e);
+    }
+    job.report(processed, Some(total));
+    let output = output.finish()?;
+    log::info!("空间连接完成: {} 个要素有匹配, {} 个无匹配", matched, unmatched);
+    Ok(SpatialJoinResult { output, matched, unmatched })
+}
@@ -0,0 +1,62 @@
+use crate::errors::{AppError, Result};
+use crate::gis::measure;
+use crate::models::{MeasureRequest, MeasureResult};
+use crate::services::gdal_service;
+use gdal::spatial_ref::SpatialRef;
+use gdal::vector::Geometry;
+
+/// 解析坐标系定义（如 "EPSG:4527"、WKT）
+pub(crate) fn parse_srs(definition: &str) -> Result<SpatialRef> {
+    SpatialRef::from_definition(definition)
+        .map_err(|e| AppError::InvalidFormat(format!("坐标系无效 {}: {}", definition, e)))
+}
+
+/// 转为 geo 几何用于量算
+pub(crate) fn to_geo(geom: &Geometry) -> Result<geo_types::Geometry<f64>> {
+    geom.to_geo().map_err(|e| AppError::InvalidFormat(format!("几何转换失败: {}", e)))
+}
+
+/// 平面量算使用的坐标系，必须为投影坐标系
+pub(crate) fn projected_srs(definition: &str) -> Result<SpatialRef> {
+    let srs = parse_srs(definition)?;
+    if !srs.is_projected() {
+        return Err(AppError::InvalidFormat(format!("平面量算需要投影坐标系: {}", definition)));
+    }
+    Ok(srs)
+}
+
+/// 量算几何：转为经纬度后在椭球面上计算长度、面积和周长，指定投影坐标系时同时计算平面值
+/// 平面值按投影坐标系的长度单位换算为米，再统一转为请求的单位
+pub fn measure(request: &MeasureRequest) -> Result<MeasureResult> {
+    let geom = Geometry::from_geojson(&request.geometry.to_string())
+        .map_err(|e| AppError::InvalidFormat(format!("几何解析失败: {}", e)))?;
+    let source_srs = request.crs.as_deref().map(parse_srs).transpose()?;
+
+    let mut geographic = geom.clone();
+    if let Some(transform) = gdal_service::create_wgs84_transform(source_srs.clone())? {
+        geographic.transform_inplace(&transform)
+            .map_err(|e| AppError::InvalidFormat(format!("坐标转换失败: {}", e)))?;
+    }
+    let geodesic = measure::geodesic(&to_geo(&geographic)?);
+
+    let planar = match request.planar_crs.as_deref() {
+        Some(definition) => {
+            let planar_srs = projected_srs(definition)?;
+            let meters_per_unit = planar_srs.linear_units();
+            let mut projected = geom;
+            if let Some(transform) = gdal_service::create_layer_transform(source_srs, Some(planar_srs))? {
+                projected.transform_inplace(&transform)
+                    .map_err(|e| AppError::InvalidFormat(format!("坐标转换失败: {}", e)))?;
+            }
+            Some(measure::planar(&to_geo(&projected)?, meters_per_unit))
+        }
+        None => None,
+    };
+
+    Ok(MeasureResult {
+        geodesic: measure::convert_values(&geodesic, request.length_unit, request.area_unit),
+        planar: planar.map(|values| measure::convert_values(&values, request.length_unit, request.area_unit)),
+        length_unit: request.length_unit,
+        area_unit: request.area_unit,
+    })
+}
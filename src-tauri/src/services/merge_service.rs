@@ -0,0 +1,231 @@
+use crate::errors::{AppError, Result};
+use crate::models::{AttributeField, FieldType, MergeInput, MergeRequest, MergeResult, TableFieldSchema};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::{gdal_service, selection_service};
+use gdal::vector::{FieldValue, LayerAccess, OGRwkbGeometryType};
+use gdal::Dataset;
+use std::collections::{HashMap, HashSet};
+
+/// 整数类字段的宽度等级（布尔视为最窄的整数）
+fn integer_rank(field_type: FieldType) -> Option<u8> {
+    match field_type {
+        FieldType::Boolean => Some(0),
+        FieldType::Int16 => Some(1),
+        FieldType::Integer => Some(2),
+        FieldType::Integer64 => Some(3),
+        _ => None,
+    }
+}
+
+/// 两个输入字段类型不一致时的输出类型：
+/// 整数取较宽的类型（布尔与整数合并为 Integer），整数与实数为 Real，日期与日期时间为 DateTime，其他为 String
+fn widen_type(a: FieldType, b: FieldType) -> FieldType {
+    if a == b {
+        return a;
+    }
+    let is_real = |t| matches!(t, FieldType::Real | FieldType::Float32);
+    match (integer_rank(a), integer_rank(b)) {
+        (Some(rank_a), Some(rank_b)) => match rank_a.max(rank_b) {
+            3 => FieldType::Integer64,
+            _ => FieldType::Integer,
+        },
+        _ if (is_real(a) || integer_rank(a).is_some()) && (is_real(b) || integer_rank(b).is_some()) => FieldType::Real,
+        _ => match (a, b) {
+            (FieldType::Date | FieldType::DateTime, FieldType::Date | FieldType::DateTime) => FieldType::DateTime,
+            _ => FieldType::String,
+        },
+    }
+}
+
+/// 合并输出的字段：按输出字段名（不区分大小写）合并各输入的字段
+struct MergedFields {
+    schemas: Vec<TableFieldSchema>,
+    positions: HashMap<String, usize>,
+    widened: Vec<String>,
+}
+
+impl MergedFields {
+    /// 加入一个输入字段，返回其在输出中的字段位置
+    fn add(&mut self, field: &AttributeField, name: &str) -> usize {
+        let key = name.to_lowercase();
+        if let Some(&position) = self.positions.get(&key) {
+            let schema = &mut self.schemas[position];
+            let field_type = widen_type(schema.field_type, field.field_type);
+            if field.field_type != schema.field_type {
+                if !self.widened.contains(&schema.name) {
+                    log::info!("字段 {} 类型不一致 ({:?} / {:?})，输出为 {:?}", schema.name, schema.field_type, field.field_type, field_type);
+                    self.widened.push(schema.name.clone());
+                }
+                // 类型改变后原宽度和精度不再适用
+                if field_type != schema.field_type {
+                    schema.width = None;
+                    schema.precision = None;
+                }
+                schema.field_type = field_type;
+            } else {
+                schema.width = schema.width.zip(field.width).map(|(a, b)| a.max(b));
+                schema.precision = schema.precision.max(field.precision);
+            }
+            return position;
+        }
+
+        let mut schema = export_service::field_schema(field);
+        schema.name = name.to_string();
+        schema.nullable = true; // 其他输入中可能没有该字段
+        self.positions.insert(key, self.schemas.len());
+        self.schemas.push(schema);
+        self.schemas.len() - 1
+    }
+}
+
+/// 输入字段在输出中的名称：按字段映射改名（不区分大小写），映射为空时不输出
+fn mapped_name<'a>(input: &'a MergeInput, field: &'a AttributeField) -> Option<&'a str> {
+    let mapped = input.field_map.get(&field.name)
+        .or_else(|| input.field_map.iter().find(|(name, _)| name.eq_ignore_ascii_case(&field.name)).map(|(_, v)| v));
+    match mapped {
+        Some(name) if name.trim().is_empty() => None,
+        Some(name) => Some(name.trim()),
+        None => Some(&field.name),
+    }
+}
+
+/// 输出几何类型：各输入相同时沿用；同为点、线或面时为对应的多部件类型（任一有Z/M则保留）；否则不限类型
+fn merged_geometry_type(types: &[OGRwkbGeometryType::Type]) -> OGRwkbGeometryType::Type {
+    let types: Vec<_> = types.iter().copied().filter(|&t| t != OGRwkbGeometryType::wkbNone).collect();
+    let Some(&first) = types.first() else {
+        return OGRwkbGeometryType::wkbNone;
+    };
+    if types.iter().all(|&t| t == first) {
+        return first;
+    }
+    let family = |geom_type| match unsafe { gdal_sys::OGR_GT_Flatten(geom_type) } {
+        OGRwkbGeometryType::wkbPoint | OGRwkbGeometryType::wkbMultiPoint => Some(OGRwkbGeometryType::wkbMultiPoint),
+        OGRwkbGeometryType::wkbLineString | OGRwkbGeometryType::wkbMultiLineString => Some(OGRwkbGeometryType::wkbMultiLineString),
+        OGRwkbGeometryType::wkbPolygon | OGRwkbGeometryType::wkbMultiPolygon => Some(OGRwkbGeometryType::wkbMultiPolygon),
+        _ => None,
+    };
+    match family(first) {
+        Some(multi) if types.iter().all(|&t| family(t) == Some(multi)) => {
+            let has_z = types.iter().any(|&t| unsafe { gdal_sys::OGR_GT_HasZ(t) } != 0);
+            let has_m = types.iter().any(|&t| unsafe { gdal_sys::OGR_GT_HasM(t) } != 0);
+            unsafe { gdal_sys::OGR_GT_SetModifier(multi, has_z as i32, has_m as i32) }
+        }
+        _ => OGRwkbGeometryType::wkbUnknown,
+    }
+}
+
+/// 合并：多个图层的要素写入一个输出，字段按名称合并（可映射改名），类型不一致时放宽
+/// 输出使用第一个输入的坐标系，其他输入的几何转换到该坐标系
+pub fn merge(request: &MergeRequest, job: &JobContext) -> Result<MergeResult> {
+    if request.inputs.len() < 2 {
+        return Err(AppError::InvalidFormat("合并至少需要两个输入图层".to_string()));
+    }
+    log::info!("合并 {} 个图层 -> {}", request.inputs.len(), request.output.path);
+
+    let datasets = request.inputs.iter()
+        .map(|merge_input| gdal_service::open_vector_dataset(&merge_input.input.path))
+        .collect::<Result<Vec<Dataset>>>()?;
+    let layer_of = |index: usize| {
+        let layer_index = request.inputs[index].input.layer_index.unwrap_or(0);
+        datasets[index].layer(layer_index)
+            .map_err(|e| AppError::FileReadError(format!("无法读取图层 {} ({}): {}", layer_index, request.inputs[index].input.path, e)))
+    };
+
+    // 合并字段定义，记录每个输入字段在输出中的位置
+    let mut merged = MergedFields {
+        schemas: Vec::new(),
+        positions: HashMap::new(),
+        widened: Vec::new(),
+    };
+    let mut mappings: Vec<Vec<Option<usize>>> = Vec::new();
+    let mut geometry_types = Vec::new();
+    let mut layer_names = Vec::new();
+    for (index, merge_input) in request.inputs.iter().enumerate() {
+        let layer = layer_of(index)?;
+        let fields = gdal_service::read_attribute_fields(layer.defn());
+        mappings.push(
+            fields.iter()
+                .map(|field| mapped_name(merge_input, field).map(|name| merged.add(field, name)))
+                .collect(),
+        );
+        geometry_types.push(unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) });
+        layer_names.push(layer.name());
+    }
+    let MergedFields { mut schemas, widened, .. } = merged;
+    let source_column = match request.source_field.as_deref().map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => {
+            let mut used: HashSet<String> = schemas.iter().map(|s| s.name.to_lowercase()).collect();
+            let name = export_service::unique_field_name(name, &mut used);
+            schemas.push(export_service::new_field_schema(&name, FieldType::String));
+            Some(schemas.len() - 1)
+        }
+        None => None,
+    };
+
+    let target_srs = layer_of(0)?.spatial_ref();
+    let geometry_type = merged_geometry_type(&geometry_types);
+    let mut output = OutputLayer::for_analysis(&request.output, target_srs.as_ref(), geometry_type, &schemas)?;
+    let mut failed = 0u64;
+
+    let mut run = || -> Result<()> {
+        for (index, merge_input) in request.inputs.iter().enumerate() {
+            job.set_stage(index, request.inputs.len());
+            let mut layer = layer_of(index)?;
+            let transform = gdal_service::create_layer_transform(layer.spatial_ref(), target_srs.clone())?;
+            let input_fids = selection_service::input_fids(&merge_input.input)?;
+            let total = input_fids.as_ref().map_or_else(|| layer.feature_count(), |fids| fids.len() as u64);
+            let report_interval = (total / 100).max(1);
+            let mut next_report = report_interval;
+            let mut processed = 0u64;
+            job.report(0, Some(total));
+
+            for feature in layer.features() {
+                job.check_cancelled()?;
+                if let Some(fids) = &input_fids {
+                    if !feature.fid().is_some_and(|fid| fids.contains(&fid)) {
+                        continue;
+                    }
+                }
+                processed += 1;
+                if processed >= next_report {
+                    job.report(processed, Some(total));
+                    next_report += report_interval;
+                }
+
+                let mut values: Vec<Option<FieldValue>> = vec![None; schemas.len()];
+                for ((_, value), position) in feature.fields().zip(&mappings[index]) {
+                    if let Some(position) = position {
+                        values[*position] = value;
+                    }
+                }
+                if let Some(column) = source_column {
+                    values[column] = Some(FieldValue::StringValue(layer_names[index].clone()));
+                }
+                let mut geom = feature.geometry().cloned();
+                if let (Some(g), Some(transform)) = (&mut geom, &transform) {
+                    if let Err(e) = g.transform_inplace(transform) {
+                        log::warn!("要素 {:?} 坐标转换失败，已跳过: {}", feature.fid(), e);
+                        failed += 1;
+                        continue;
+                    }
+                }
+                output.write(geom, &values)?;
+            }
+            job.report(processed, Some(total));
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        output.discard();
+        return Err(e);
+    }
+    let mut result = output.finish()?;
+    result.features_failed += failed;
+    Ok(MergeResult {
+        output: result,
+        fields: schemas,
+        widened,
+    })
+}
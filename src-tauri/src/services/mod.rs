@@ -1,3 +1,25 @@
 pub mod layer_service;
 pub mod project_service;
 pub mod gdal_service;
+pub mod postgis_service;
+pub mod datastore_service;
+pub mod gdb_service;
+pub mod export_service;
+pub mod job_service;
+pub mod worker_pool;
+pub mod tile_service;
+pub mod spatial_index_service;
+pub mod identify_service;
+pub mod selection_service;
+pub mod aggregation_service;
+pub mod join_service;
+pub mod attribute_join_service;
+pub mod overlay_service;
+pub mod dissolve_service;
+pub mod merge_service;
+pub mod validation_service;
+pub mod topology_service;
+pub mod measure_service;
+pub mod geometry_attribute_service;
+pub mod near_service;
+pub mod generate_service;
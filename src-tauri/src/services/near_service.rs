@@ -0,0 +1,329 @@
+use crate::errors::{AppError, Result};
+use crate::gis::measure;
+use crate::models::{
+    AttributeField, FieldType, LengthUnit, NearMode, NearRequest, NearResult, TableFieldSchema,
+};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::join_service::{expand, geometry_distance};
+use crate::services::spatial_index_service::LayerIndex;
+use crate::services::{gdal_service, measure_service, selection_service, spatial_index_service};
+use gdal::vector::{FieldValue, Geometry, Layer, LayerAccess, OGRwkbGeometryType};
+use std::collections::{HashMap, HashSet};
+
+/// 纬度 1 度的最小长度（米，取整偏小），用于由经纬度搜索半径估算距离下限
+const METERS_PER_DEGREE: f64 = 110_000.0;
+
+/// 已读取的目标要素
+struct Target {
+    geometry: Geometry,
+    geo: Option<geo_types::Geometry<f64>>, // 椭球面距离使用的经纬度几何
+    id: Option<FieldValue>,
+}
+
+/// 目标图层：按空间索引查找候选要素，读取过的要素缓存在内存中
+struct NearLayer<'a> {
+    layer: Layer<'a>,
+    index: &'a LayerIndex,
+    allowed: Option<HashSet<u64>>,
+    id_field: Option<usize>,
+    geodesic: bool,
+    meters_per_unit: f64,
+    cache: HashMap<u64, Option<Target>>,
+}
+
+impl NearLayer<'_> {
+    fn target(&mut self, fid: u64) -> Option<&Target> {
+        let NearLayer { layer, id_field, geodesic, cache, .. } = self;
+        cache.entry(fid)
+            .or_insert_with(|| {
+                let feature = layer.feature(fid)?;
+                let geometry = feature.geometry()?.clone();
+                let geo = if *geodesic { Some(measure_service::to_geo(&geometry).ok()?) } else { None };
+                let id = id_field.and_then(|index| feature.fields().nth(index).and_then(|(_, value)| value));
+                Some(Target { geometry, geo, id })
+            })
+            .as_ref()
+    }
+
+    /// 查询几何与目标要素的距离（米），无法计算时返回 None
+    fn distance(&mut self, query: &Geometry, query_geo: Option<&geo_types::Geometry<f64>>, fid: u64) -> Option<f64> {
+        let geodesic = self.geodesic;
+        let meters_per_unit = self.meters_per_unit;
+        let target = self.target(fid)?;
+        if geodesic {
+            measure::geodesic_distance(query_geo?, target.geo.as_ref()?)
+        } else {
+            let d = geometry_distance(query, &target.geometry);
+            (d >= 0.0).then_some(d * meters_per_unit)
+        }
+    }
+
+    /// 搜索半径（图层坐标系单位）之外的要素与查询几何距离的下限（米）
+    /// 经纬度按查询范围内的最高纬度估算经度方向的长度
+    fn outside_distance(&self, query: &gdal::vector::Envelope, radius: f64) -> f64 {
+        if !self.geodesic {
+            return radius * self.meters_per_unit;
+        }
+        let max_latitude = (query.MinY - radius).abs().max((query.MaxY + radius).abs()).min(90.0);
+        radius * METERS_PER_DEGREE * max_latitude.to_radians().cos()
+    }
+
+    /// 查找最近的 k 个目标要素（距离升序，同距离时 FID 较小者在前），k 为空时返回搜索距离内的全部要素
+    /// 从较小的半径开始逐步扩大，半径之外的要素不可能更近时停止
+    fn nearest(&mut self, query: &Geometry, k: Option<usize>, max_distance: Option<f64>) -> Result<Vec<(u64, f64)>> {
+        let Some(extent) = self.index.extent() else { return Ok(Vec::new()) };
+        let query_geo = if self.geodesic { Some(measure_service::to_geo(query)?) } else { None };
+        let envelope = query.envelope();
+        let span_x = extent.max_x.max(envelope.MaxX) - extent.min_x.min(envelope.MinX);
+        let span_y = extent.max_y.max(envelope.MaxY) - extent.min_y.min(envelope.MinY);
+        let max_radius = span_x.hypot(span_y);
+        let mut radius = match (k, max_distance) {
+            (None, None) => max_radius,
+            _ => (max_radius / 1024.0).max(f64::EPSILON),
+        };
+
+        let mut distances: HashMap<u64, Option<f64>> = HashMap::new();
+        loop {
+            for fid in self.index.candidates(&expand(&envelope, radius)) {
+                if distances.contains_key(&fid) || self.allowed.as_ref().is_some_and(|allowed| !allowed.contains(&fid)) {
+                    continue;
+                }
+                let d = self.distance(query, query_geo.as_ref(), fid);
+                distances.insert(fid, d);
+            }
+
+            let mut found: Vec<(u64, f64)> = distances.iter()
+                .filter_map(|(&fid, &d)| d.map(|d| (fid, d)))
+                .filter(|&(_, d)| max_distance.is_none_or(|max| d <= max))
+                .collect();
+            found.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+
+            let bound = self.outside_distance(&envelope, radius);
+            let complete = match k {
+                Some(k) => found.len() >= k && found[k - 1].1 <= bound,
+                None => false,
+            };
+            if complete || max_distance.is_some_and(|max| max <= bound) || radius >= max_radius {
+                if let Some(k) = k {
+                    found.truncate(k);
+                }
+                return Ok(found);
+            }
+            radius *= 2.0;
+        }
+    }
+}
+
+/// 按名称查找标识字段的序号
+fn id_field_index(fields: &[AttributeField], name: Option<&str>, layer: &str) -> Result<Option<usize>> {
+    let Some(name) = name.filter(|name| !name.is_empty()) else { return Ok(None) };
+    fields.iter()
+        .position(|f| f.name == name)
+        .or_else(|| fields.iter().position(|f| f.name.eq_ignore_ascii_case(name)))
+        .map(Some)
+        .ok_or_else(|| AppError::InvalidFormat(format!("{}字段不存在: {}", layer, name)))
+}
+
+/// 标识字段的输出定义
+fn id_schema(field: &AttributeField, name: &str, used: &mut HashSet<String>) -> TableFieldSchema {
+    let mut schema = export_service::field_schema(field);
+    schema.name = export_service::unique_field_name(name, used);
+    schema.nullable = true;
+    schema.default_value = None;
+    schema
+}
+
+fn to_meters(value: f64, unit: LengthUnit) -> f64 {
+    value / measure::convert_length(1.0, unit)
+}
+
+/// 邻近分析：为输入图层的每个要素查找目标图层中最近的 k 个要素，写入新图层
+/// nearest 输出输入要素及 near_rank、near_fid、near_dist（每个邻近要素一行，没有邻近要素时邻近字段为空）；
+/// matrix 输出无几何的起点-终点距离表（origin_fid、dest_fid、rank、distance）
+/// 目标图层为地理坐标系时按椭球面计算距离，否则按投影平面计算；目标图层坐标系未知时按平面计算，
+/// 坐标单位视为米，输入图层不做坐标转换（两个图层的坐标视为同一坐标系）；距离按 length_unit 输出
+pub fn near(request: &NearRequest, job: &JobContext) -> Result<NearResult> {
+    let input_index = request.input.layer_index.unwrap_or(0);
+    let near_index = request.near.layer_index.unwrap_or(0);
+    log::info!("邻近分析 ({:?}): {} -> {}", request.mode, request.input.path, request.near.path);
+    if request.k == Some(0) {
+        return Err(AppError::InvalidFormat("邻近要素数必须大于 0".to_string()));
+    }
+
+    let input_dataset = gdal_service::open_vector_dataset(&request.input.path)?;
+    let mut input_layer = input_dataset.layer(input_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取输入图层 {}: {}", input_index, e)))?;
+    let near_dataset = gdal_service::open_vector_dataset(&request.near.path)?;
+    let near_layer = near_dataset.layer(near_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取目标图层 {}: {}", near_index, e)))?;
+
+    job.set_stage(0, 2);
+    let index = spatial_index_service::layer_index(&request.near.path, near_index)?;
+    let near_srs = near_layer.spatial_ref();
+    let geodesic = near_srs.as_ref().is_some_and(|srs| srs.is_geographic());
+    let meters_per_unit = near_srs.as_ref().map_or(1.0, |srs| srs.linear_units());
+    // 目标图层坐标系未知时无法确定转换目标，输入坐标视为与目标图层相同，不做转换
+    let transform = match near_srs {
+        Some(near_srs) => gdal_service::create_layer_transform(input_layer.spatial_ref(), Some(near_srs))?,
+        None => None,
+    };
+    let input_fids = selection_service::input_fids(&request.input)?;
+
+    let input_fields = gdal_service::read_attribute_fields(input_layer.defn());
+    let near_fields = gdal_service::read_attribute_fields(near_layer.defn());
+    let input_id = id_field_index(&input_fields, request.input_id_field.as_deref(), "输入图层")?;
+    let near_id = id_field_index(&near_fields, request.near_id_field.as_deref(), "目标图层")?;
+    let mut targets = NearLayer {
+        layer: near_layer,
+        index: &index,
+        allowed: selection_service::input_fids(&request.near)?,
+        id_field: near_id,
+        geodesic,
+        meters_per_unit,
+        cache: HashMap::new(),
+    };
+    let k = match request.mode {
+        NearMode::Nearest => Some(request.k.unwrap_or(1)),
+        NearMode::Matrix => request.k,
+    };
+    let max_distance = request.max_distance.map(|distance| to_meters(distance, request.length_unit));
+
+    // 输出字段：nearest 为输入字段 + 邻近信息，matrix 为起点、终点和距离
+    let mut used = HashSet::new();
+    let mut schemas: Vec<TableFieldSchema> = Vec::new();
+    match request.mode {
+        NearMode::Nearest => {
+            for field in &input_fields {
+                let mut schema = export_service::field_schema(field);
+                schema.name = export_service::unique_field_name(&field.name, &mut used);
+                schemas.push(schema);
+            }
+            schemas.push(export_service::new_field_schema(&export_service::unique_field_name("near_rank", &mut used), FieldType::Integer));
+            schemas.push(export_service::new_field_schema(&export_service::unique_field_name("near_fid", &mut used), FieldType::Integer64));
+            if let Some(index) = near_id {
+                schemas.push(id_schema(&near_fields[index], "near_id", &mut used));
+            }
+            schemas.push(export_service::new_field_schema(&export_service::unique_field_name("near_dist", &mut used), FieldType::Real));
+        }
+        NearMode::Matrix => {
+            schemas.push(export_service::new_field_schema(&export_service::unique_field_name("origin_fid", &mut used), FieldType::Integer64));
+            if let Some(index) = input_id {
+                schemas.push(id_schema(&input_fields[index], "origin_id", &mut used));
+            }
+            schemas.push(export_service::new_field_schema(&export_service::unique_field_name("dest_fid", &mut used), FieldType::Integer64));
+            if let Some(index) = near_id {
+                schemas.push(id_schema(&near_fields[index], "dest_id", &mut used));
+            }
+            schemas.push(export_service::new_field_schema(&export_service::unique_field_name("rank", &mut used), FieldType::Integer));
+            schemas.push(export_service::new_field_schema(&export_service::unique_field_name("distance", &mut used), FieldType::Real));
+        }
+    }
+    let (srs, geometry_type) = match request.mode {
+        NearMode::Nearest => (input_layer.spatial_ref(), unsafe { gdal_sys::OGR_L_GetGeomType(input_layer.c_layer()) }),
+        NearMode::Matrix => (None, OGRwkbGeometryType::wkbNone),
+    };
+    let mut output = OutputLayer::for_analysis(&request.output, srs.as_ref(), geometry_type, &schemas)?;
+
+    job.set_stage(1, 2);
+    let total = input_fids.as_ref().map_or_else(|| input_layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut processed = 0u64;
+    let mut matched = 0u64;
+    let mut unmatched = 0u64;
+    job.report(0, Some(total));
+
+    let mut run = || -> Result<()> {
+        for feature in input_layer.features() {
+            job.check_cancelled()?;
+            let fid = feature.fid();
+            if let Some(fids) = &input_fids {
+                if !fid.is_some_and(|fid| fids.contains(&fid)) {
+                    continue;
+                }
+            }
+            processed += 1;
+            if processed >= next_report {
+                job.report(processed, Some(total));
+                next_report += report_interval;
+            }
+
+            let geometry = feature.geometry().cloned();
+            let neighbors = match &geometry {
+                Some(geom) => {
+                    let mut query = geom.clone();
+                    let transformed = match &transform {
+                        Some(transform) => query.transform_inplace(transform).is_ok(),
+                        None => true,
+                    };
+                    if transformed {
+                        targets.nearest(&query, k, max_distance).unwrap_or_else(|e| {
+                            log::warn!("要素 {:?} 距离计算失败，视为无邻近要素: {}", fid, e);
+                            Vec::new()
+                        })
+                    } else {
+                        log::warn!("要素 {:?} 坐标转换失败，视为无邻近要素", fid);
+                        Vec::new()
+                    }
+                }
+                None => Vec::new(),
+            };
+            if neighbors.is_empty() {
+                unmatched += 1;
+            } else {
+                matched += 1;
+            }
+
+            let distance = |meters: f64| Some(FieldValue::RealValue(measure::convert_length(meters, request.length_unit)));
+            let target_id = |targets: &mut NearLayer, fid: u64| targets.target(fid).and_then(|target| target.id.clone());
+            match request.mode {
+                NearMode::Nearest => {
+                    let input_values: Vec<Option<FieldValue>> = feature.fields().map(|(_, value)| value).collect();
+                    if neighbors.is_empty() {
+                        let mut values = input_values;
+                        values.resize(schemas.len(), None);
+                        output.write(geometry, &values)?;
+                        continue;
+                    }
+                    for (rank, &(near_fid, meters)) in neighbors.iter().enumerate() {
+                        let mut values = input_values.clone();
+                        values.push(Some(FieldValue::IntegerValue(rank as i32 + 1)));
+                        values.push(Some(FieldValue::Integer64Value(near_fid as i64)));
+                        if near_id.is_some() {
+                            values.push(target_id(&mut targets, near_fid));
+                        }
+                        values.push(distance(meters));
+                        output.write(geometry.clone(), &values)?;
+                    }
+                }
+                NearMode::Matrix => {
+                    let origin_id = input_id.and_then(|index| feature.fields().nth(index).and_then(|(_, value)| value));
+                    for (rank, &(near_fid, meters)) in neighbors.iter().enumerate() {
+                        let mut values = vec![fid.map(|fid| FieldValue::Integer64Value(fid as i64))];
+                        if input_id.is_some() {
+                            values.push(origin_id.clone());
+                        }
+                        values.push(Some(FieldValue::Integer64Value(near_fid as i64)));
+                        if near_id.is_some() {
+                            values.push(target_id(&mut targets, near_fid));
+                        }
+                        values.push(Some(FieldValue::IntegerValue(rank as i32 + 1)));
+                        values.push(distance(meters));
+                        output.write(None, &values)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        output.discard();
+        return Err(e);
+    }
+    job.report(processed, Some(total));
+    let output = output.finish()?;
+    log::info!("邻近分析完成: {} 个要素有邻近要素, {} 个没有", matched, unmatched);
+    Ok(NearResult { output, matched, unmatched, geodesic })
+}
@@ -0,0 +1,338 @@
+use crate::errors::{AppError, Result};
+use crate::gis::geometry;
+use crate::models::{AnalysisInput, ExportResult, OverlayRequest, SplitOutput, SplitRequest, SplitResult, TableFieldSchema};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::spatial_index_service::LayerIndex;
+use crate::services::{gdal_service, selection_service, spatial_index_service};
+use gdal::spatial_ref::SpatialRef;
+use gdal::vector::{FieldValue, Geometry, Layer, LayerAccess, OGRwkbGeometryType};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// 叠加方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayMode {
+    Clip,  // 保留掩膜内的部分
+    Erase, // 去掉掩膜内的部分
+}
+
+/// 读取掩膜图层中的面几何（转换到输入图层坐标系，统一为多面）
+/// name_field 不为空时同时返回各掩膜面的命名字段值，空值以 FID 命名
+fn load_masks(
+    mask: &AnalysisInput,
+    target_srs: Option<SpatialRef>,
+    name_field: Option<&str>,
+) -> Result<(Vec<Geometry>, Vec<String>)> {
+    let layer_index = mask.layer_index.unwrap_or(0);
+    let dataset = gdal_service::open_vector_dataset(&mask.path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取掩膜图层 {}: {}", layer_index, e)))?;
+    let name_field = match name_field {
+        Some(name) => {
+            let fields = gdal_service::read_attribute_fields(layer.defn());
+            let field = fields.iter()
+                .find(|f| f.name == name)
+                .or_else(|| fields.iter().find(|f| f.name.eq_ignore_ascii_case(name)))
+                .ok_or_else(|| AppError::InvalidFormat(format!("掩膜图层字段不存在: {}", name)))?;
+            Some(field.name.clone())
+        }
+        None => None,
+    };
+    let transform = gdal_service::create_layer_transform(layer.spatial_ref(), target_srs)?;
+    let fids = selection_service::input_fids(mask)?;
+
+    let mut masks = Vec::new();
+    let mut names = Vec::new();
+    for feature in layer.features() {
+        let fid = feature.fid();
+        if let Some(fids) = &fids {
+            if !fid.is_some_and(|fid| fids.contains(&fid)) {
+                continue;
+            }
+        }
+        let Some(geom) = feature.geometry() else { continue };
+        let mut geom = geometry::to_linear(geom.clone())?;
+        if let Some(transform) = &transform {
+            if let Err(e) = geom.transform_inplace(transform) {
+                log::warn!("掩膜要素 {:?} 坐标转换失败，已跳过: {}", fid, e);
+                continue;
+            }
+        }
+        let Some(polygon) = geometry::overlay_parts(&geom, 2)? else { continue };
+        if let Some(field) = &name_field {
+            let name = feature.field_as_string_by_name(field).ok().flatten()
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| format!("mask_{}", fid.unwrap_or(0)));
+            names.push(name);
+        }
+        masks.push(polygon);
+    }
+    if masks.is_empty() {
+        return Err(AppError::InvalidFormat(format!("掩膜图层中没有面要素: {}", mask.path)));
+    }
+    log::info!("已读取 {} 个掩膜面: {}", masks.len(), mask.path);
+    Ok((masks, names))
+}
+
+/// 输出图层的几何类型：点保持不变，线和面为多部件类型（叠加运算不保留M值）
+fn output_geometry_type(input_type: OGRwkbGeometryType::Type) -> OGRwkbGeometryType::Type {
+    if unsafe { gdal_sys::OGR_GT_Flatten(input_type) } == OGRwkbGeometryType::wkbPoint {
+        return input_type;
+    }
+    geometry::result_geometry_type(input_type, true)
+}
+
+/// 单个要素与相交的掩膜面叠加，None 表示不输出该要素
+/// 单点只判断是否落在掩膜内并保留原始几何；结果只保留与输入同维度的部分
+fn overlay_geometry(geom: &Geometry, masks: &[&Geometry], mode: OverlayMode) -> Result<Option<Geometry>> {
+    if masks.is_empty() {
+        return Ok(match mode {
+            OverlayMode::Clip => None,
+            OverlayMode::Erase => Some(geom.clone()),
+        });
+    }
+    if unsafe { gdal_sys::OGR_GT_Flatten(geom.geometry_type()) } == OGRwkbGeometryType::wkbPoint {
+        return Ok(match mode {
+            OverlayMode::Clip => Some(geom.clone()),
+            OverlayMode::Erase => None,
+        });
+    }
+
+    let merged;
+    let mask = match masks {
+        [single] => *single,
+        _ => {
+            let polygons: Vec<Geometry> = masks.iter().map(|m| (*m).clone()).collect();
+            merged = geometry::union_polygons(&polygons)?
+                .ok_or_else(|| AppError::InvalidFormat("合并掩膜面失败".to_string()))?;
+            &merged
+        }
+    };
+    let result = match mode {
+        OverlayMode::Clip => geom.intersection(mask),
+        OverlayMode::Erase => geom.difference(mask),
+    }
+    .ok_or_else(|| AppError::InvalidFormat(format!("叠加运算失败: {}", gdal_service::last_gdal_error())))?;
+    geometry::overlay_parts(&result, geometry::topological_dimension(geom))
+}
+
+/// 输入图层的字段定义（输出保留全部属性）
+fn input_schemas(layer: &Layer) -> Vec<TableFieldSchema> {
+    gdal_service::read_attribute_fields(layer.defn())
+        .iter()
+        .map(export_service::field_schema)
+        .collect()
+}
+
+/// 裁剪或擦除：输入图层与掩膜面叠加，结果写入新图层并保留属性
+/// 多部件几何整体参与运算，结果统一为多部件类型
+pub fn overlay(request: &OverlayRequest, mode: OverlayMode, job: &JobContext) -> Result<ExportResult> {
+    let layer_index = request.input.layer_index.unwrap_or(0);
+    log::info!("叠加分析 ({:?}): {} / {}", mode, request.input.path, request.mask.path);
+
+    let dataset = gdal_service::open_vector_dataset(&request.input.path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let (masks, _) = load_masks(&request.mask, layer.spatial_ref(), None)?;
+    let mask_index = LayerIndex::from_geometries(masks.iter().enumerate().map(|(i, geom)| (i as u64, geom)));
+    let input_fids = selection_service::input_fids(&request.input)?;
+
+    let schemas = input_schemas(&layer);
+    let geometry_type = output_geometry_type(unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) });
+    let mut output = OutputLayer::for_analysis(&request.output, layer.spatial_ref().as_ref(), geometry_type, &schemas)?;
+
+    let total = input_fids.as_ref().map_or_else(|| layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut processed = 0u64;
+    let mut failed = 0u64;
+    job.report(0, Some(total));
+
+    let mut run = || -> Result<()> {
+        for feature in layer.features() {
+            job.check_cancelled()?;
+            if let Some(fids) = &input_fids {
+                if !feature.fid().is_some_and(|fid| fids.contains(&fid)) {
+                    continue;
+                }
+            }
+            processed += 1;
+            if processed >= next_report {
+                job.report(processed, Some(total));
+                next_report += report_interval;
+            }
+
+            let values: Vec<Option<FieldValue>> = feature.fields().map(|(_, value)| value).collect();
+            let Some(geom) = feature.geometry() else {
+                // 没有几何的要素不在任何掩膜内
+                if mode == OverlayMode::Erase {
+                    output.write(None, &values)?;
+                }
+                continue;
+            };
+            let hits: Vec<&Geometry> = mask_index.candidates(&geom.envelope())
+                .into_iter()
+                .map(|i| &masks[i as usize])
+                .filter(|mask| mask.intersects(geom))
+                .collect();
+            match overlay_geometry(geom, &hits, mode) {
+                Ok(Some(result)) => output.write(Some(result), &values)?,
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!("要素 {:?} 叠加失败，已跳过: {}", feature.fid(), e);
+                    failed += 1;
+                }
+            }
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        output.discard();
+        return Err(e);
+    }
+    job.report(processed, Some(total));
+    if failed > 0 {
+        log::warn!("{} 个要素叠加失败（可先修复几何）", failed);
+    }
+    let mut result = output.finish()?;
+    result.features_failed += failed;
+    Ok(result)
+}
+
+/// 将一组掩膜面范围内的要素裁剪后写入单独的文件，范围内没有要素时不创建文件
+#[allow(clippy::too_many_arguments)]
+fn split_group(
+    layer: &Layer,
+    index: &LayerIndex,
+    mask: &Geometry,
+    input_fids: Option<&HashSet<u64>>,
+    output_path: &str,
+    request: &SplitRequest,
+    layer_name: &str,
+    job: &JobContext,
+) -> Result<Option<ExportResult>> {
+    let schemas = input_schemas(layer);
+    let geometry_type = output_geometry_type(unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) });
+    let srs = layer.spatial_ref();
+    let candidates: Vec<u64> = spatial_index_service::intersecting(layer, index, mask, true)
+        .into_iter()
+        .filter(|fid| input_fids.is_none_or(|fids| fids.contains(fid)))
+        .collect();
+
+    let total = candidates.len() as u64;
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut output: Option<OutputLayer> = None;
+    job.report(0, Some(total));
+
+    let mut run = || -> Result<()> {
+        for (processed, fid) in (1u64..).zip(candidates.iter().copied()) {
+            job.check_cancelled()?;
+            if processed >= next_report {
+                job.report(processed, Some(total));
+                next_report += report_interval;
+            }
+            let Some(feature) = layer.feature(fid) else { continue };
+            let Some(geom) = feature.geometry() else { continue };
+            let result = match overlay_geometry(geom, &[mask], OverlayMode::Clip) {
+                Ok(Some(result)) => result,
+                Ok(None) => continue,
+                Err(e) => {
+                    log::warn!("要素 {} 裁剪失败，已跳过: {}", fid, e);
+                    continue;
+                }
+            };
+            let values: Vec<Option<FieldValue>> = feature.fields().map(|(_, value)| value).collect();
+            if output.is_none() {
+                output = Some(OutputLayer::create(
+                    output_path,
+                    &request.format,
+                    layer_name,
+                    srs.as_ref(),
+                    geometry_type,
+                    &schemas,
+                )?);
+            }
+            if let Some(writer) = &mut output {
+                writer.write(Some(result), &values)?;
+            }
+        }
+        Ok(())
+    };
+
+    let result = run();
+    match (result, output) {
+        (Ok(()), Some(output)) => output.finish().map(Some),
+        (Ok(()), None) => Ok(None),
+        (Err(e), Some(output)) => {
+            output.discard();
+            Err(e)
+        }
+        (Err(e), None) => Err(e),
+    }
+}
+
+/// 按掩膜面分割图层：每个掩膜面（命名字段值相同的合并）范围内的要素裁剪后写入输出目录下以字段值命名的文件
+/// 取消时删除正在写入的文件，已完成的文件保留
+pub fn split(request: &SplitRequest, job: &JobContext) -> Result<SplitResult> {
+    let layer_index = request.input.layer_index.unwrap_or(0);
+    log::info!("分割图层: {} 按 {} ({}) -> {}", request.input.path, request.mask.path, request.name_field, request.output_dir);
+
+    let dataset = gdal_service::open_vector_dataset(&request.input.path)?;
+    let layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let (masks, names) = load_masks(&request.mask, layer.spatial_ref(), Some(&request.name_field))?;
+    let input_fids = selection_service::input_fids(&request.input)?;
+    let index = spatial_index_service::layer_index(&request.input.path, layer_index)?;
+
+    // 按字段值分组，保持掩膜图层中的顺序
+    let mut groups: Vec<(String, Vec<Geometry>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (name, mask) in names.into_iter().zip(masks) {
+        match positions.get(&name) {
+            Some(&position) => groups[position].1.push(mask),
+            None => {
+                positions.insert(name.clone(), groups.len());
+                groups.push((name, vec![mask]));
+            }
+        }
+    }
+
+    let driver = export_service::resolve_driver(&request.format, "")?;
+    let extension = export_service::output_extension(&request.format, &driver).ok_or_else(|| {
+        AppError::InvalidFormat(format!("驱动 {} 没有文件扩展名，无法分割为多个文件", driver.short_name()))
+    })?;
+    std::fs::create_dir_all(&request.output_dir)?;
+
+    let mut outputs = Vec::new();
+    let mut empty = Vec::new();
+    let mut used_names = HashSet::new();
+    for (group_index, (name, polygons)) in groups.iter().enumerate() {
+        job.set_stage(group_index, groups.len());
+        job.check_cancelled()?;
+        let Some(mask) = geometry::union_polygons(polygons)? else { continue };
+        let output_name = export_service::unique_name(name, &mut used_names);
+        let output_path = Path::new(&request.output_dir)
+            .join(format!("{}.{}", output_name, extension))
+            .to_string_lossy()
+            .into_owned();
+
+        match split_group(&layer, &index, &mask, input_fids.as_ref(), &output_path, request, &output_name, job)? {
+            Some(output) => outputs.push(SplitOutput { name: name.clone(), output }),
+            None => {
+                log::info!("掩膜 {} 范围内没有要素，不生成文件", name);
+                empty.push(name.clone());
+            }
+        }
+    }
+
+    log::info!("分割完成: 生成 {} 个文件, {} 个掩膜范围内没有要素", outputs.len(), empty.len());
+    Ok(SplitResult {
+        output_dir: request.output_dir.clone(),
+        outputs,
+        empty,
+    })
+}
@@ -0,0 +1,395 @@
+use crate::errors::{AppError, Result};
+use crate::models::{CoordinateDimension, EditResult, FeatureEdits, PostgisConnection, PostgisTableInfo};
+use crate::services::gdal_service;
+use gdal::vector::LayerAccess;
+use gdal::Dataset;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// PostGIS 图层引用前缀，格式: postgis:连接ID/模式.表名
+const POSTGIS_PATH_PREFIX: &str = "postgis:";
+
+/// 连接配置文件名（位于应用配置目录），不含密码
+const CONNECTIONS_FILE: &str = "postgis_connections.json";
+
+/// 系统钥匙串中保存密码使用的服务名，账户名为连接ID
+const KEYRING_SERVICE: &str = "com.xiaofu.minigis.postgis";
+
+/// libpq 支持的 sslmode
+const SSL_MODES: [&str; 6] = ["disable", "allow", "prefer", "require", "verify-ca", "verify-full"];
+
+lazy_static::lazy_static! {
+    static ref CONNECTIONS: Mutex<HashMap<String, PostgisConnection>> = Mutex::new(HashMap::new());
+    static ref CONFIG_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+fn keyring_entry(connection_id: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, connection_id)
+        .map_err(|e| AppError::Unknown(format!("无法访问系统钥匙串: {}", e)))
+}
+
+/// 读取钥匙串中保存的密码，没有保存时返回 None
+fn load_password(connection_id: &str) -> Result<Option<String>> {
+    match keyring_entry(connection_id)?.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(AppError::Unknown(format!("读取系统钥匙串失败: {}", e))),
+    }
+}
+
+/// 勾选保存密码时将密码写入钥匙串，否则删除钥匙串中的密码
+fn store_password(conn: &PostgisConnection) -> Result<()> {
+    let entry = keyring_entry(&conn.id)?;
+    match conn.password.as_deref().filter(|_| conn.save_password) {
+        Some(password) => entry.set_password(password)
+            .map_err(|e| AppError::Unknown(format!("密码写入系统钥匙串失败: {}", e))),
+        None => delete_password(&conn.id),
+    }
+}
+
+fn delete_password(connection_id: &str) -> Result<()> {
+    match keyring_entry(connection_id)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(AppError::Unknown(format!("删除系统钥匙串中的密码失败: {}", e))),
+    }
+}
+
+/// 初始化连接存储，从应用配置目录加载已保存的连接，密码从系统钥匙串读取
+/// 旧版本写在配置文件中的明文密码迁移到钥匙串后从配置文件移除
+pub fn init(config_dir: PathBuf) {
+    let file = config_dir.join(CONNECTIONS_FILE);
+    *CONFIG_DIR.lock().unwrap() = Some(config_dir);
+    if !file.exists() {
+        return;
+    }
+
+    let saved = match std::fs::read_to_string(&file)
+        .map_err(AppError::from)
+        .and_then(|content| serde_json::from_str::<Vec<PostgisConnection>>(&content).map_err(AppError::from))
+    {
+        Ok(saved) => saved,
+        Err(e) => {
+            log::error!("读取PostGIS连接配置失败: {}", e);
+            return;
+        }
+    };
+    log::info!("已加载 {} 个PostGIS连接", saved.len());
+
+    let mut migrated = false;
+    let mut connections = CONNECTIONS.lock().unwrap();
+    for mut conn in saved {
+        if conn.password.is_some() {
+            migrated = true;
+            if let Err(e) = store_password(&conn) {
+                log::error!("迁移连接 {} 的密码失败，密码仅在本次会话有效: {}", conn.name, e);
+            }
+        } else if conn.save_password {
+            match load_password(&conn.id) {
+                Ok(password) => conn.password = password,
+                Err(e) => log::warn!("读取连接 {} 的密码失败: {}", conn.name, e),
+            }
+        }
+        connections.insert(conn.id.clone(), conn);
+    }
+    if migrated {
+        if let Err(e) = persist(&connections) {
+            log::error!("移除配置文件中的明文密码失败: {}", e);
+        }
+    }
+}
+
+/// 将连接写入配置文件（不含密码，密码由 store_password 保存到系统钥匙串）
+fn persist(connections: &HashMap<String, PostgisConnection>) -> Result<()> {
+    let config_dir = match CONFIG_DIR.lock().unwrap().clone() {
+        Some(dir) => dir,
+        None => {
+            log::warn!("未设置配置目录，PostGIS连接仅在本次会话有效");
+            return Ok(());
+        }
+    };
+
+    std::fs::create_dir_all(&config_dir)?;
+    let mut saved: Vec<PostgisConnection> = connections
+        .values()
+        .cloned()
+        .map(|mut conn| {
+            conn.password = None;
+            conn
+        })
+        .collect();
+    saved.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let content = serde_json::to_string_pretty(&saved)?;
+    std::fs::write(config_dir.join(CONNECTIONS_FILE), content)?;
+    Ok(())
+}
+
+/// 列出所有连接（不返回密码）
+pub async fn list_connections() -> Result<Vec<PostgisConnection>> {
+    let connections = CONNECTIONS.lock().unwrap();
+    let mut list: Vec<PostgisConnection> = connections
+        .values()
+        .cloned()
+        .map(|mut conn| {
+            conn.password = None;
+            conn
+        })
+        .collect();
+    list.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(list)
+}
+
+/// 新建或更新连接；更新时未提供密码则沿用原密码
+pub async fn save_connection(mut connection: PostgisConnection) -> Result<PostgisConnection> {
+    let mut connections = CONNECTIONS.lock().unwrap();
+
+    if connection.id.is_empty() {
+        connection.id = Uuid::new_v4().to_string();
+    } else if connection.password.is_none() {
+        if let Some(existing) = connections.get(&connection.id) {
+            connection.password = existing.password.clone();
+        }
+    }
+
+    store_password(&connection)?;
+    connections.insert(connection.id.clone(), connection.clone());
+    persist(&connections)?;
+
+    connection.password = None;
+    Ok(connection)
+}
+
+/// 删除连接
+pub async fn delete_connection(connection_id: &str) -> Result<()> {
+    let mut connections = CONNECTIONS.lock().unwrap();
+    connections.remove(connection_id);
+    persist(&connections)?;
+    delete_password(connection_id)
+}
+
+fn get_connection(connection_id: &str) -> Result<PostgisConnection> {
+    CONNECTIONS.lock().unwrap()
+        .get(connection_id)
+        .cloned()
+        .ok_or_else(|| AppError::DatabaseError(format!("PostGIS连接不存在: {}", connection_id)))
+}
+
+/// libpq 连接参数值加引号
+fn quote_param(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// SQL 字符串字面量
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// 构造 GDAL PG 驱动连接字符串
+/// tables: 仅暴露指定的表，格式 "模式.表名" 或 "模式.表名(几何列)"；
+/// GDAL 在传给 libpq 之前按空格截取该参数且不支持引号，含空白、引号、反斜杠或逗号的表名无法引用
+fn connection_string(conn: &PostgisConnection, tables: Option<&str>) -> Result<String> {
+    let mut parts = vec![
+        format!("host={}", quote_param(&conn.host)),
+        format!("port={}", conn.port),
+        format!("dbname={}", quote_param(&conn.database)),
+        format!("user={}", quote_param(&conn.username)),
+    ];
+    if let Some(password) = conn.password.as_deref().filter(|p| !p.is_empty()) {
+        parts.push(format!("password={}", quote_param(password)));
+    }
+    if let Some(ssl_mode) = conn.ssl_mode.as_deref().filter(|m| !m.is_empty()) {
+        if !SSL_MODES.contains(&ssl_mode) {
+            return Err(AppError::InvalidFormat(format!("不支持的SSL模式: {}", ssl_mode)));
+        }
+        parts.push(format!("sslmode={}", quote_param(ssl_mode)));
+    }
+    if let Some(tables) = tables {
+        if tables.is_empty() || tables.chars().any(|c| c.is_whitespace() || matches!(c, '\'' | '"' | '\\' | ',')) {
+            return Err(AppError::InvalidFormat(format!("表名包含不支持的字符（空白、引号、反斜杠或逗号）: {}", tables)));
+        }
+        parts.push(format!("tables={}", tables));
+    }
+    Ok(format!("PG:{}", parts.join(" ")))
+}
+
+fn open_connection(conn: &PostgisConnection, tables: Option<&str>, update: bool) -> Result<Dataset> {
+    let mut open_flags = gdal::GdalOpenFlags::GDAL_OF_VECTOR;
+    if update {
+        open_flags |= gdal::GdalOpenFlags::GDAL_OF_UPDATE;
+    }
+
+    Dataset::open_ex(
+        connection_string(conn, tables)?,
+        gdal::DatasetOptions {
+            open_flags,
+            ..Default::default()
+        },
+    )
+    .map_err(|e| AppError::DatabaseError(format!("无法连接PostGIS {}@{}:{}/{}: {}", conn.username, conn.host, conn.port, conn.database, e)))
+}
+
+/// 生成图层引用路径
+pub fn layer_path(connection_id: &str, schema: &str, table: &str) -> String {
+    format!("{}{}/{}.{}", POSTGIS_PATH_PREFIX, connection_id, schema, table)
+}
+
+/// 是否为 PostGIS 图层引用
+pub fn is_postgis_path(path: &str) -> bool {
+    path.starts_with(POSTGIS_PATH_PREFIX)
+}
+
+/// 将图层引用解析为连接字符串（密码不会出现在项目文件中）
+pub fn resolve_path(path: &str) -> Result<String> {
+    let reference = path.strip_prefix(POSTGIS_PATH_PREFIX)
+        .ok_or_else(|| AppError::InvalidFormat(format!("不是PostGIS图层引用: {}", path)))?;
+    let (connection_id, table) = reference.split_once('/')
+        .filter(|(id, table)| !id.is_empty() && !table.is_empty())
+        .ok_or_else(|| AppError::InvalidFormat(format!("PostGIS图层引用格式错误: {}", path)))?;
+
+    let conn = get_connection(connection_id)?;
+    connection_string(&conn, Some(table))
+}
+
+/// 测试连接，返回 PostgreSQL/PostGIS 版本
+pub fn test_connection(connection: &PostgisConnection) -> Result<String> {
+    let dataset = open_connection(connection, None, false)?;
+    let version = query_strings(&dataset, "SELECT version() || ' / PostGIS ' || postgis_lib_version() AS version", "version")?;
+    version.into_iter().next()
+        .ok_or_else(|| AppError::DatabaseError("无法获取数据库版本".to_string()))
+}
+
+/// 执行查询并读取一列字符串
+fn query_strings(dataset: &Dataset, sql: &str, column: &str) -> Result<Vec<String>> {
+    let mut result_set = dataset.execute_sql(sql, None, gdal::vector::sql::Dialect::DEFAULT)
+        .map_err(|e| AppError::DatabaseError(format!("查询失败: {}", e)))?
+        .ok_or_else(|| AppError::DatabaseError("查询无结果".to_string()))?;
+
+    let mut values = Vec::new();
+    for feature in result_set.features() {
+        if let Ok(Some(value)) = feature.field_as_string_by_name(column) {
+            values.push(value);
+        }
+    }
+    Ok(values)
+}
+
+/// 列出模式（排除系统模式）
+pub fn list_schemas(connection_id: &str) -> Result<Vec<String>> {
+    let conn = get_connection(connection_id)?;
+    let dataset = open_connection(&conn, None, false)?;
+    query_strings(
+        &dataset,
+        "SELECT nspname AS schema_name FROM pg_namespace \
+         WHERE nspname NOT LIKE 'pg\\_%' AND nspname <> 'information_schema' \
+         ORDER BY nspname",
+        "schema_name",
+    )
+}
+
+/// 列出空间表和视图（含几何列、几何类型、SRID）
+pub fn list_tables(connection_id: &str, schema: Option<&str>) -> Result<Vec<PostgisTableInfo>> {
+    let conn = get_connection(connection_id)?;
+    let dataset = open_connection(&conn, None, false)?;
+
+    let schema_filter = schema
+        .map(|s| format!("WHERE gc.f_table_schema = {}", quote_literal(s)))
+        .unwrap_or_default();
+    let sql = format!(
+        "SELECT gc.f_table_schema AS schema_name, gc.f_table_name AS table_name, \
+                gc.f_geometry_column AS geometry_column, gc.type AS geometry_type, \
+                gc.srid AS srid, gc.coord_dimension AS coord_dimension, c.relkind::text AS relkind \
+         FROM geometry_columns gc \
+         JOIN pg_namespace n ON n.nspname = gc.f_table_schema \
+         JOIN pg_class c ON c.relnamespace = n.oid AND c.relname = gc.f_table_name \
+         {} ORDER BY gc.f_table_schema, gc.f_table_name",
+        schema_filter
+    );
+
+    let mut result_set = dataset.execute_sql(&sql, None, gdal::vector::sql::Dialect::DEFAULT)
+        .map_err(|e| AppError::DatabaseError(format!("查询空间表失败: {}", e)))?
+        .ok_or_else(|| AppError::DatabaseError("查询空间表无结果".to_string()))?;
+
+    let mut tables = Vec::new();
+    for feature in result_set.features() {
+        let text = |name: &str| feature.field_as_string_by_name(name).ok().flatten().unwrap_or_default();
+        let schema = text("schema_name");
+        let table = text("table_name");
+        let geometry_column = text("geometry_column");
+        let geometry_type = text("geometry_type");
+        let coord_dimension = feature.field_as_integer_by_name("coord_dimension").ok().flatten().unwrap_or(2);
+
+        // geometry_columns 中带M值的类型以 M 结尾（如 POINTM）
+        let coordinate_dimension = match coord_dimension {
+            4 => CoordinateDimension::Xyzm,
+            3 if geometry_type.ends_with('M') => CoordinateDimension::Xym,
+            3 => CoordinateDimension::Xyz,
+            _ => CoordinateDimension::Xy,
+        };
+
+        // 多几何列的表需要在引用中指明几何列
+        let table_ref = format!("{}({})", table, geometry_column);
+
+        tables.push(PostgisTableInfo {
+            path: layer_path(connection_id, &schema, &table_ref),
+            srid: feature.field_as_integer_by_name("srid").ok().flatten().unwrap_or(0),
+            is_view: matches!(text("relkind").as_str(), "v" | "m"),
+            schema,
+            table,
+            geometry_column,
+            geometry_type,
+            coordinate_dimension,
+        });
+    }
+
+    log::info!("连接 {} 中找到 {} 个空间表/视图", conn.name, tables.len());
+    Ok(tables)
+}
+
+/// 将编辑写回 PostGIS 表（事务提交）
+pub fn apply_edits(
+    connection_id: &str,
+    schema: &str,
+    table: &str,
+    edits: &FeatureEdits,
+) -> Result<EditResult> {
+    let conn = get_connection(connection_id)?;
+    let mut dataset = open_connection(&conn, Some(&format!("{}.{}", schema, table)), true)?;
+    gdal_service::apply_feature_edits(&mut dataset, 0, edits)
+}
+
+/// 将矢量图层导出到 PostGIS 新表
+pub fn export_layer(
+    input_path: &str,
+    layer_index: Option<usize>,
+    connection_id: &str,
+    schema: &str,
+    table: &str,
+    overwrite: bool,
+) -> Result<String> {
+    let conn = get_connection(connection_id)?;
+
+    let source = gdal_service::open_vector_dataset(input_path)?;
+    let idx = layer_index.unwrap_or(0);
+    let layer = source.layer(idx)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", idx, e)))?;
+
+    let target = open_connection(&conn, None, true)?;
+
+    let mut options = vec![("GEOMETRY_NAME", "geom"), ("LAUNDER", "YES"), ("SPATIAL_INDEX", "GIST")];
+    if overwrite {
+        options.push(("OVERWRITE", "YES"));
+    }
+
+    let layer_name = format!("{}.{}", schema, table);
+    log::info!("导出图层到PostGIS: {} -> {}", input_path, layer_name);
+    let created = gdal_service::copy_layer_to_dataset(&layer, &target, &layer_name, &options)
+        .map_err(|e| AppError::DatabaseError(format!("导出到PostGIS失败: {}", e)))?;
+
+    // LAUNDER 会规范化表名（转小写、替换特殊字符），按实际创建的表生成引用；
+    // 默认模式（public）中的表图层名不带模式前缀
+    let created_table = created.strip_prefix(&format!("{}.", schema)).unwrap_or(&created);
+    log::info!("成功导出到PostGIS: {}.{}", schema, created_table);
+    Ok(layer_path(connection_id, schema, created_table))
+}
@@ -18,6 +18,7 @@ pub async fn create_project(name: String) -> Result<Project> {
         },
         created_at: Utc::now().to_rfc3339(),
         updated_at: Utc::now().to_rfc3339(),
+        selections: Vec::new(),
     };
     
     Ok(project)
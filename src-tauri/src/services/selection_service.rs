@@ -0,0 +1,263 @@
+use crate::errors::{AppError, Result};
+use crate::models::{AnalysisInput, Extent, SelectionMode, SelectionQuery, SelectionSet, SelectionSummary, SpatialRelation};
+use crate::services::{gdal_service, spatial_index_service};
+use crate::services::spatial_index_service::LayerIndex;
+use gdal::vector::{Geometry, LayerAccess};
+use lazy_static::lazy_static;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Mutex;
+
+/// 图层选择集
+struct LayerSelection {
+    path: String,
+    layer_index: usize,
+    fids: BTreeSet<u64>,
+}
+
+lazy_static! {
+    // 各图层的选择集，键为项目图层ID
+    static ref SELECTIONS: Mutex<HashMap<String, LayerSelection>> = Mutex::new(HashMap::new());
+}
+
+/// 判断待选几何与参照几何的空间关系
+pub fn relation_matches(geom: &Geometry, reference: &Geometry, relation: SpatialRelation) -> bool {
+    match relation {
+        SpatialRelation::Intersects => geom.intersects(reference),
+        SpatialRelation::Within => geom.within(reference),
+        SpatialRelation::Contains => geom.contains(reference),
+        SpatialRelation::Touches => geom.touches(reference),
+        SpatialRelation::Crosses => geom.crosses(reference),
+        SpatialRelation::Overlaps => geom.overlaps(reference),
+    }
+}
+
+/// 查找与参照几何（图层坐标系）满足空间关系的要素，已在 matched 中的要素不再判断
+fn match_geometry<L: LayerAccess>(
+    layer: &L,
+    index: &LayerIndex,
+    reference: &Geometry,
+    relation: SpatialRelation,
+    matched: &mut BTreeSet<u64>,
+) {
+    for fid in index.candidates(&reference.envelope()) {
+        if matched.contains(&fid) {
+            continue;
+        }
+        let hit = layer.feature(fid)
+            .and_then(|feature| feature.geometry().map(|geom| relation_matches(geom, reference, relation)))
+            .unwrap_or(false);
+        if hit {
+            matched.insert(fid);
+        }
+    }
+}
+
+/// 将选择几何从其坐标系（默认 WGS84）转换到图层坐标系
+fn to_layer_crs(mut geom: Geometry, crs: Option<&str>, layer_srs: Option<gdal::spatial_ref::SpatialRef>) -> Result<Geometry> {
+    let crs = crs.map(str::trim).filter(|c| !c.is_empty()).unwrap_or("EPSG:4326");
+    if let Some(transform) = gdal_service::create_map_to_layer_transform(crs, layer_srs)? {
+        geom.transform_inplace(&transform)
+            .map_err(|e| AppError::InvalidFormat(format!("选择范围坐标转换失败: {}", e)))?;
+    }
+    Ok(geom)
+}
+
+fn extent_polygon(extent: &Extent) -> Result<Geometry> {
+    let geom = Geometry::bbox(extent.min_x, extent.min_y, extent.max_x, extent.max_y)
+        .map_err(|e| AppError::InvalidFormat(format!("选择范围无效: {}", e)))?;
+    // 加密边界，避免投影后变形
+    let max_length = (extent.max_x - extent.min_x).max(extent.max_y - extent.min_y) / 64.0;
+    if max_length > 0.0 {
+        unsafe { gdal_sys::OGR_G_Segmentize(geom.c_geometry(), max_length) };
+    }
+    Ok(geom)
+}
+
+/// 查询满足选择条件的要素 FID
+pub fn query_fids(path: &str, layer_index: usize, query: &SelectionQuery) -> Result<BTreeSet<u64>> {
+    let dataset = gdal_service::open_vector_dataset(path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let mut matched = BTreeSet::new();
+
+    match query {
+        SelectionQuery::Rectangle { extent, crs, relation } => {
+            let reference = to_layer_crs(extent_polygon(extent)?, crs.as_deref(), layer.spatial_ref())?;
+            let index = spatial_index_service::layer_index(path, layer_index)?;
+            match_geometry(&layer, &index, &reference, relation.unwrap_or_default(), &mut matched);
+        }
+        SelectionQuery::Polygon { geometry, crs, relation } => {
+            let geom = Geometry::from_geojson(&geometry.to_string())
+                .map_err(|e| AppError::InvalidFormat(format!("选择几何无效: {}", e)))?;
+            let reference = to_layer_crs(geom, crs.as_deref(), layer.spatial_ref())?;
+            let index = spatial_index_service::layer_index(path, layer_index)?;
+            match_geometry(&layer, &index, &reference, relation.unwrap_or_default(), &mut matched);
+        }
+        SelectionQuery::Expression { where_clause } => {
+            let clause = where_clause.trim();
+            layer.set_attribute_filter(clause)
+                .map_err(|e| AppError::InvalidFormat(format!("属性条件无效 {}: {}", clause, e)))?;
+            matched.extend(layer.features().filter_map(|feature| feature.fid()));
+        }
+        SelectionQuery::Spatial { path: reference_path, layer_index: reference_index, selection, relation } => {
+            let relation = relation.unwrap_or_default();
+            let index = spatial_index_service::layer_index(path, layer_index)?;
+            let reference_index = reference_index.unwrap_or(0);
+            let reference_dataset = gdal_service::open_vector_dataset(reference_path)?;
+            let mut reference_layer = reference_dataset.layer(reference_index)
+                .map_err(|e| AppError::FileReadError(format!("无法读取参照图层 {}: {}", reference_index, e)))?;
+            let transform = gdal_service::create_layer_transform(reference_layer.spatial_ref(), layer.spatial_ref())?;
+
+            let mut match_reference = |geom: &Geometry| {
+                let mut reference = geom.clone();
+                if let Some(transform) = &transform {
+                    if reference.transform_inplace(transform).is_err() {
+                        return;
+                    }
+                }
+                match_geometry(&layer, &index, &reference, relation, &mut matched);
+            };
+            match selection {
+                Some(reference_id) => {
+                    for fid in selection_fids(reference_id, reference_path, reference_index)? {
+                        if let Some(geom) = reference_layer.feature(fid).as_ref().and_then(|f| f.geometry()) {
+                            match_reference(geom);
+                        }
+                    }
+                }
+                None => {
+                    for feature in reference_layer.features() {
+                        if let Some(geom) = feature.geometry() {
+                            match_reference(geom);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(matched)
+}
+
+/// 按选择方式更新图层选择集（数据源变化时当前选择集视为空）
+pub fn apply(layer_id: &str, path: &str, layer_index: usize, matched: BTreeSet<u64>, mode: SelectionMode) -> SelectionSummary {
+    let mut selections = SELECTIONS.lock().unwrap();
+    let current = selections.remove(layer_id)
+        .filter(|s| s.path == path && s.layer_index == layer_index)
+        .map(|s| s.fids)
+        .unwrap_or_default();
+
+    let fids: BTreeSet<u64> = match mode {
+        SelectionMode::New => matched,
+        SelectionMode::Add => current.union(&matched).copied().collect(),
+        SelectionMode::Remove => current.difference(&matched).copied().collect(),
+        SelectionMode::Intersect => current.intersection(&matched).copied().collect(),
+    };
+    log::info!("图层 {} 选择集: {} 个要素 ({:?})", layer_id, fids.len(), mode);
+
+    let summary = SelectionSummary {
+        layer_id: layer_id.to_string(),
+        path: path.to_string(),
+        layer_index,
+        count: fids.len(),
+    };
+    if !fids.is_empty() {
+        selections.insert(layer_id.to_string(), LayerSelection { path: path.to_string(), layer_index, fids });
+    }
+    summary
+}
+
+/// 按条件选择要素并更新图层选择集
+pub fn select(
+    layer_id: &str,
+    path: &str,
+    layer_index: usize,
+    query: &SelectionQuery,
+    mode: SelectionMode,
+) -> Result<SelectionSummary> {
+    let matched = query_fids(path, layer_index, query)?;
+    Ok(apply(layer_id, path, layer_index, matched, mode))
+}
+
+/// 图层选择集中的要素 FID（升序）
+pub fn selected_fids(layer_id: &str) -> Result<Vec<u64>> {
+    SELECTIONS.lock().unwrap()
+        .get(layer_id)
+        .map(|s| s.fids.iter().copied().collect())
+        .ok_or_else(|| AppError::InvalidFormat(format!("图层没有选择集: {}", layer_id)))
+}
+
+/// 图层选择集中的要素 FID（升序），选择集不属于指定的数据源图层时报错（图层ID过期或不匹配）
+pub fn selection_fids(layer_id: &str, path: &str, layer_index: usize) -> Result<Vec<u64>> {
+    let selections = SELECTIONS.lock().unwrap();
+    let selection = selections.get(layer_id)
+        .ok_or_else(|| AppError::InvalidFormat(format!("图层没有选择集: {}", layer_id)))?;
+    if selection.path != path || selection.layer_index != layer_index {
+        return Err(AppError::InvalidFormat(format!(
+            "图层 {} 的选择集属于 {} (图层 {})，与 {} (图层 {}) 不一致",
+            layer_id, selection.path, selection.layer_index, path, layer_index
+        )));
+    }
+    Ok(selection.fids.iter().copied().collect())
+}
+
+/// 分析工具输入图层的要素过滤：指定选择集时返回其中的 FID，否则为 None（处理全部要素）
+pub fn input_fids(input: &AnalysisInput) -> Result<Option<HashSet<u64>>> {
+    input.selection.as_deref()
+        .map(|layer_id| {
+            selection_fids(layer_id, &input.path, input.layer_index.unwrap_or(0))
+                .map(|fids| fids.into_iter().collect())
+        })
+        .transpose()
+}
+
+/// 所有非空选择集的概要
+pub fn list() -> Vec<SelectionSummary> {
+    SELECTIONS.lock().unwrap()
+        .iter()
+        .map(|(layer_id, s)| SelectionSummary {
+            layer_id: layer_id.clone(),
+            path: s.path.clone(),
+            layer_index: s.layer_index,
+            count: s.fids.len(),
+        })
+        .collect()
+}
+
+/// 清除选择集，不指定图层时清除全部
+pub fn clear(layer_id: Option<&str>) {
+    let mut selections = SELECTIONS.lock().unwrap();
+    match layer_id {
+        Some(layer_id) => {
+            selections.remove(layer_id);
+        }
+        None => selections.clear(),
+    }
+}
+
+/// 导出全部选择集（保存项目时写入项目文件）
+pub fn snapshot() -> Vec<SelectionSet> {
+    let mut sets: Vec<SelectionSet> = SELECTIONS.lock().unwrap()
+        .iter()
+        .map(|(layer_id, s)| SelectionSet {
+            layer_id: layer_id.clone(),
+            path: s.path.clone(),
+            layer_index: s.layer_index,
+            fids: s.fids.iter().copied().collect(),
+        })
+        .collect();
+    sets.sort_by(|a, b| a.layer_id.cmp(&b.layer_id));
+    sets
+}
+
+/// 恢复项目中保存的选择集（替换当前全部选择集）
+pub fn restore(sets: &[SelectionSet]) {
+    let mut selections = SELECTIONS.lock().unwrap();
+    selections.clear();
+    for set in sets.iter().filter(|s| !s.fids.is_empty()) {
+        selections.insert(set.layer_id.clone(), LayerSelection {
+            path: set.path.clone(),
+            layer_index: set.layer_index,
+            fids: set.fids.iter().copied().collect(),
+        });
+    }
+}
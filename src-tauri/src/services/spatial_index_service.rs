@@ -0,0 +1,200 @@
+use crate::errors::{AppError, Result};
+use crate::models::{Extent, SpatialIndexInfo};
+use crate::services::{export_service, gdal_service};
+use crate::services::gdal_service::SourceStamp;
+use gdal::vector::{Envelope, Geometry, LayerAccess};
+use lazy_static::lazy_static;
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// 索引项：要素外包矩形（图层坐标系）+ FID
+type IndexEntry = GeomWithData<Rectangle<[f64; 2]>, u64>;
+
+/// 同一图层的索引槽，构建期间持有其锁，并发请求等待同一次构建而不是重复构建
+type IndexSlot = Arc<Mutex<Option<Arc<LayerIndex>>>>;
+
+lazy_static! {
+    // 已构建的图层索引，键为 (数据源路径, 图层索引)
+    static ref INDEXES: Mutex<HashMap<(String, usize), IndexSlot>> = Mutex::new(HashMap::new());
+}
+
+/// 图层要素外包矩形的内存 R 树
+pub struct LayerIndex {
+    tree: RTree<IndexEntry>,
+    stamp: Option<SourceStamp>, // 构建时数据源的修改时间和大小，变化后重建
+    build_ms: u64,
+}
+
+impl LayerIndex {
+    /// 已索引的要素数（不含空几何）
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.size() == 0
+    }
+
+    /// 所有要素的外包范围（图层坐标系）
+    pub fn extent(&self) -> Option<Extent> {
+        if self.is_empty() {
+            return None;
+        }
+        let envelope = self.tree.root().envelope();
+        let [min_x, min_y] = envelope.lower();
+        let [max_x, max_y] = envelope.upper();
+        Some(Extent { min_x, min_y, max_x, max_y })
+    }
+
+    /// 由内存中的几何构建临时索引（如叠加分析的掩膜面），键为调用方指定的编号
+    pub fn from_geometries<'a, I>(geometries: I) -> LayerIndex
+    where
+        I: IntoIterator<Item = (u64, &'a Geometry)>,
+    {
+        let started = Instant::now();
+        let entries: Vec<IndexEntry> = geometries.into_iter()
+            .filter(|(_, geom)| !geom.is_empty())
+            .map(|(key, geom)| {
+                let envelope = geom.envelope();
+                GeomWithData::new(Rectangle::from_corners([envelope.MinX, envelope.MinY], [envelope.MaxX, envelope.MaxY]), key)
+            })
+            .collect();
+        LayerIndex {
+            tree: RTree::bulk_load(entries),
+            stamp: None,
+            build_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+
+    /// 外包矩形与查询范围相交的要素 FID（升序）
+    pub fn candidates(&self, envelope: &Envelope) -> Vec<u64> {
+        let query = AABB::from_corners([envelope.MinX, envelope.MinY], [envelope.MaxX, envelope.MaxY]);
+        let mut fids: Vec<u64> = self.tree
+            .locate_in_envelope_intersecting(&query)
+            .map(|entry| entry.data)
+            .collect();
+        fids.sort_unstable();
+        fids
+    }
+}
+
+fn slot(path: &str, layer_index: usize) -> IndexSlot {
+    INDEXES.lock().unwrap()
+        .entry((path.to_string(), layer_index))
+        .or_default()
+        .clone()
+}
+
+/// 获取图层的空间索引，首次使用或数据源修改后重新构建
+pub fn layer_index(path: &str, layer_index: usize) -> Result<Arc<LayerIndex>> {
+    let slot = slot(path, layer_index);
+    let mut current = slot.lock().unwrap();
+    let stamp = gdal_service::source_modified(path);
+    if let Some(index) = current.as_ref() {
+        if index.stamp == stamp {
+            return Ok(index.clone());
+        }
+        log::info!("数据源已修改，重建空间索引: {} (图层 {})", path, layer_index);
+    }
+
+    let index = Arc::new(build(path, layer_index, stamp)?);
+    *current = Some(index.clone());
+    Ok(index)
+}
+
+/// 构建空间索引时只读取几何，跳过属性字段
+fn ignore_attribute_fields<L: LayerAccess>(layer: &L) {
+    let names: Vec<CString> = layer.defn().fields()
+        .filter_map(|field| CString::new(field.name()).ok())
+        .chain(CString::new("OGR_STYLE").ok())
+        .collect();
+    let mut pointers: Vec<*const std::ffi::c_char> = names.iter().map(|name| name.as_ptr()).collect();
+    pointers.push(std::ptr::null());
+    let err = unsafe { gdal_sys::OGR_L_SetIgnoredFields(layer.c_layer(), pointers.as_mut_ptr()) };
+    if err != gdal_sys::OGRErr::OGRERR_NONE {
+        log::debug!("数据源不支持忽略字段，构建索引时将读取完整要素");
+    }
+}
+
+fn build(path: &str, layer_index: usize, stamp: Option<SourceStamp>) -> Result<LayerIndex> {
+    let started = Instant::now();
+    let dataset = gdal_service::open_vector_dataset(path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    ignore_attribute_fields(&layer);
+
+    let mut entries = Vec::with_capacity(layer.feature_count() as usize);
+    for feature in layer.features() {
+        let (Some(fid), Some(geom)) = (feature.fid(), feature.geometry()) else { continue };
+        if geom.is_empty() {
+            continue;
+        }
+        let envelope = geom.envelope();
+        if [envelope.MinX, envelope.MinY, envelope.MaxX, envelope.MaxY].iter().any(|v| !v.is_finite()) {
+            continue;
+        }
+        entries.push(GeomWithData::new(
+            Rectangle::from_corners([envelope.MinX, envelope.MinY], [envelope.MaxX, envelope.MaxY]),
+            fid,
+        ));
+    }
+
+    let count = entries.len();
+    let tree = RTree::bulk_load(entries);
+    let build_ms = started.elapsed().as_millis() as u64;
+    log::info!("空间索引构建完成: {} (图层 {}, {} 个要素, {} ms)", path, layer_index, count, build_ms);
+    Ok(LayerIndex { tree, stamp, build_ms })
+}
+
+/// 构建（或复用）空间索引并返回索引信息
+pub fn build_index(path: &str, layer_index: usize) -> Result<SpatialIndexInfo> {
+    let index = self::layer_index(path, layer_index)?;
+    Ok(SpatialIndexInfo {
+        path: path.to_string(),
+        layer_index,
+        features: index.len(),
+        extent: index.extent(),
+        build_ms: index.build_ms,
+    })
+}
+
+/// 丢弃空间索引（数据编辑或图层移除后调用），不指定图层时丢弃数据源的全部图层索引
+pub fn invalidate(path: &str, layer_index: Option<usize>) {
+    INDEXES.lock().unwrap()
+        .retain(|(p, i), _| p != path || layer_index.is_some_and(|index| index != *i));
+}
+
+/// 丢弃路径以指定前缀开头的全部索引（如 PostGIS 表的各几何列）
+pub fn invalidate_prefix(prefix: &str) {
+    INDEXES.lock().unwrap().retain(|(p, _), _| !p.starts_with(prefix));
+}
+
+/// 查找与查询几何（图层坐标系）相交的要素 FID（升序）
+/// exact 为 false 时只比较外包矩形
+pub fn intersecting<L: LayerAccess>(layer: &L, index: &LayerIndex, query: &Geometry, exact: bool) -> Vec<u64> {
+    let candidates = index.candidates(&query.envelope());
+    if !exact {
+        return candidates;
+    }
+    candidates.into_iter()
+        .filter(|&fid| {
+            layer.feature(fid)
+                .and_then(|feature| feature.geometry().map(|geom| geom.intersects(query)))
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// 查询与空间范围（WGS84/CGCS2000 经纬度）相交的要素 FID
+pub fn query_extent(path: &str, layer_index: usize, extent: &Extent, exact: bool) -> Result<Vec<u64>> {
+    let index = self::layer_index(path, layer_index)?;
+    let dataset = gdal_service::open_vector_dataset(path)?;
+    let layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let query = export_service::extent_geometry(extent, layer.spatial_ref())?;
+    Ok(intersecting(&layer, &index, &query, exact))
+}
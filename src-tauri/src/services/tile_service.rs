@@ -0,0 +1,481 @@
+use crate::errors::{AppError, Result};
+use crate::gis::geometry;
+use crate::gis::mvt::{self, LayerBuilder, TileBounds, TileValue};
+use crate::models::{Extent, FieldType, TileCacheMode, TileGrid, TileSourceInfo, TileSourceOptions};
+use crate::services::{export_service, gdal_service, worker_pool};
+use gdal::spatial_ref::{AxisMappingStrategy, CoordTransform, SpatialRef};
+use gdal::vector::{FieldValue, Geometry, LayerAccess};
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 瓦片 URI 协议名（前端通过 mvt://localhost/… 或 http://mvt.localhost/… 访问）
+pub const TILE_SCHEME: &str = "mvt";
+
+/// 内存缓存上限（字节）
+const MEMORY_CACHE_LIMIT: usize = 256 * 1024 * 1024;
+
+/// 瓦片缓冲区（瓦片坐标单位），避免线宽和符号在瓦片边缘被截断
+const TILE_BUFFER: f64 = 64.0;
+
+/// Web墨卡托（EPSG:3857）半周长
+const MERCATOR_HALF: f64 = 20037508.342789244;
+
+/// 磁盘缓存中记录数据源修改时间的文件
+const SOURCE_STAMP_FILE: &str = "source.stamp";
+
+type TileKey = (String, u8, u32, u32);
+
+/// FNV-1a 64 位哈希，结果不随 Rust 版本变化，用于生成跨会话稳定的瓦片源ID
+fn fnv1a_64(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for &byte in *part {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        // 分隔各部分，避免拼接歧义
+        hash ^= 0xff;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// 内存瓦片缓存，超出上限时淘汰最早缓存的瓦片
+#[derive(Default)]
+struct TileCache {
+    tiles: HashMap<TileKey, Arc<Vec<u8>>>,
+    order: VecDeque<TileKey>,
+    bytes: usize,
+}
+
+impl TileCache {
+    fn insert(&mut self, key: TileKey, tile: Arc<Vec<u8>>) {
+        self.bytes += tile.len();
+        if let Some(old) = self.tiles.insert(key.clone(), tile) {
+            self.bytes -= old.len();
+        } else {
+            self.order.push_back(key);
+        }
+        while self.bytes > MEMORY_CACHE_LIMIT {
+            let Some(oldest) = self.order.pop_front() else { break };
+            if let Some(tile) = self.tiles.remove(&oldest) {
+                self.bytes -= tile.len();
+            }
+        }
+    }
+
+    fn remove_source(&mut self, source_id: &str) {
+        self.order.retain(|key| key.0 != source_id);
+        let tiles = &mut self.tiles;
+        let mut freed = 0;
+        tiles.retain(|key, tile| {
+            let keep = key.0 != source_id;
+            if !keep {
+                freed += tile.len();
+            }
+            keep
+        });
+        self.bytes -= freed;
+    }
+}
+
+/// 已注册的瓦片源
+pub struct TileSource {
+    pub info: TileSourceInfo,
+    disk_dir: Option<PathBuf>,
+    path_key: String, // 数据源的规范化路径，按路径清除缓存时比较
+    // 缓存代数：清除缓存时加一，生成前记录的代数已过期的瓦片不再写入缓存；
+    // 写入和清除缓存时都持有该锁，避免清除后写回旧瓦片
+    generation: Mutex<u64>,
+}
+
+impl TileSource {
+    /// 当前缓存代数，生成瓦片前记录，写入缓存时传给 store_tile
+    pub fn generation(&self) -> u64 {
+        *self.generation.lock().unwrap()
+    }
+}
+
+lazy_static! {
+    static ref SOURCES: Mutex<HashMap<String, Arc<TileSource>>> = Mutex::new(HashMap::new());
+    static ref MEMORY_CACHE: Mutex<TileCache> = Mutex::new(TileCache::default());
+    static ref CACHE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// 设置磁盘瓦片缓存目录（应用缓存目录下的 tiles）
+pub fn init(cache_dir: PathBuf) {
+    *CACHE_DIR.lock().unwrap() = Some(cache_dir.join("tiles"));
+}
+
+/// 瓦片 URL 模板（Windows/Android 上自定义协议通过 http://<协议>.localhost 访问）
+fn tile_url(source_id: &str) -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost/{}/{{z}}/{{x}}/{{y}}.pbf", TILE_SCHEME, source_id)
+    } else {
+        format!("{}://localhost/{}/{{z}}/{{x}}/{{y}}.pbf", TILE_SCHEME, source_id)
+    }
+}
+
+/// 数据源修改时间，用于判断磁盘缓存是否过期
+fn source_stamp(path: &str) -> String {
+    gdal_service::source_modified(path)
+        .map(|t| t.to_string())
+        .unwrap_or_default()
+}
+
+/// 准备磁盘缓存目录，数据源已修改时清空旧瓦片
+fn prepare_disk_cache(source_id: &str, path: &str) -> Option<PathBuf> {
+    let dir = CACHE_DIR.lock().unwrap().clone()?.join(source_id);
+    let stamp = source_stamp(path);
+    let stamp_file = dir.join(SOURCE_STAMP_FILE);
+    if std::fs::read_to_string(&stamp_file).ok().as_deref() != Some(stamp.as_str()) {
+        let _ = std::fs::remove_dir_all(&dir);
+        if let Err(e) = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&stamp_file, &stamp)) {
+            log::warn!("无法创建瓦片缓存目录 {:?}: {}", dir, e);
+            return None;
+        }
+    }
+    Some(dir)
+}
+
+/// 注册瓦片源，相同数据源和选项返回相同ID（磁盘缓存可跨会话复用）
+pub fn register_source(path: &str, layer_index: usize, options: TileSourceOptions) -> Result<TileSourceInfo> {
+    if options.min_zoom > options.max_zoom || options.max_zoom > 24 {
+        return Err(AppError::InvalidFormat(format!(
+            "缩放级别范围无效: {}-{}", options.min_zoom, options.max_zoom
+        )));
+    }
+
+    let dataset = gdal_service::open_vector_dataset(path)?;
+    let layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let layer_name = layer.name();
+    let fields: Vec<String> = layer.defn().fields()
+        .map(|f| f.name())
+        .filter(|name| options.fields.as_ref().is_none_or(|wanted| wanted.contains(name)))
+        .collect();
+    let geometry_type = geometry::geometry_type_name(unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) });
+
+    let canonical_path = std::fs::canonicalize(path)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| path.to_string());
+    let options_json = serde_json::to_string(&options)?;
+    let id = format!("{:016x}", fnv1a_64(&[
+        canonical_path.as_bytes(),
+        &(layer_index as u64).to_le_bytes(),
+        options_json.as_bytes(),
+    ]));
+
+    let disk_dir = match options.cache {
+        TileCacheMode::Disk => prepare_disk_cache(&id, path),
+        _ => None,
+    };
+
+    let info = TileSourceInfo {
+        id: id.clone(),
+        path: path.to_string(),
+        layer_index,
+        layer_name,
+        geometry_type,
+        fields,
+        tile_url: tile_url(&id),
+        options,
+    };
+    log::info!("注册矢量瓦片源 {}: {} (图层 {})", id, path, layer_index);
+
+    let source = Arc::new(TileSource {
+        info: info.clone(),
+        disk_dir,
+        path_key: worker_pool::lock_key(path),
+        generation: Mutex::new(0),
+    });
+    MEMORY_CACHE.lock().unwrap().remove_source(&id);
+    if let Some(old) = SOURCES.lock().unwrap().insert(id, source) {
+        // 被替换的瓦片源上尚未完成的瓦片不再写入缓存
+        *old.generation.lock().unwrap() += 1;
+    }
+    Ok(info)
+}
+
+/// 注销瓦片源并清除其内存缓存
+pub fn unregister_source(source_id: &str) {
+    if let Some(source) = SOURCES.lock().unwrap().remove(source_id) {
+        *source.generation.lock().unwrap() += 1;
+    }
+    MEMORY_CACHE.lock().unwrap().remove_source(source_id);
+}
+
+/// 清除瓦片缓存（数据编辑后调用）；source_id 为空时清除全部瓦片源的缓存
+pub fn clear_cache(source_id: Option<&str>) {
+    let sources: Vec<Arc<TileSource>> = {
+        let all = SOURCES.lock().unwrap();
+        match source_id {
+            Some(id) => all.get(id).cloned().into_iter().collect(),
+            None => all.values().cloned().collect(),
+        }
+    };
+    clear_sources(sources);
+}
+
+/// 清除指定数据源的全部瓦片源的缓存（后端原位修改数据源后调用），按规范化路径比较
+pub fn clear_path_cache(path: &str) {
+    let key = worker_pool::lock_key(path);
+    let sources: Vec<Arc<TileSource>> = SOURCES.lock().unwrap()
+        .values()
+        .filter(|source| source.path_key == key)
+        .cloned()
+        .collect();
+    clear_sources(sources);
+}
+
+/// 清除路径以指定前缀开头的瓦片源的缓存（如 PostGIS 表的各几何列）
+pub fn clear_prefix_cache(prefix: &str) {
+    let sources: Vec<Arc<TileSource>> = SOURCES.lock().unwrap()
+        .values()
+        .filter(|source| source.info.path.starts_with(prefix))
+        .cloned()
+        .collect();
+    clear_sources(sources);
+}
+
+fn clear_sources(sources: Vec<Arc<TileSource>>) {
+    for source in sources {
+        let mut generation = source.generation.lock().unwrap();
+        *generation += 1;
+        MEMORY_CACHE.lock().unwrap().remove_source(&source.info.id);
+        if let Some(dir) = &source.disk_dir {
+            let _ = std::fs::remove_dir_all(dir);
+            let _ = std::fs::create_dir_all(dir)
+                .and_then(|_| std::fs::write(dir.join(SOURCE_STAMP_FILE), source_stamp(&source.info.path)));
+        }
+    }
+}
+
+pub fn get_source(source_id: &str) -> Result<Arc<TileSource>> {
+    SOURCES.lock().unwrap()
+        .get(source_id)
+        .cloned()
+        .ok_or_else(|| AppError::FileNotFound(format!("瓦片源不存在: {}", source_id)))
+}
+
+pub fn list_sources() -> Vec<TileSourceInfo> {
+    SOURCES.lock().unwrap().values().map(|s| s.info.clone()).collect()
+}
+
+fn disk_tile_path(dir: &Path, z: u8, x: u32, y: u32) -> PathBuf {
+    dir.join(z.to_string()).join(x.to_string()).join(format!("{}.pbf", y))
+}
+
+/// 从缓存读取瓦片
+pub fn cached_tile(source: &TileSource, z: u8, x: u32, y: u32) -> Option<Arc<Vec<u8>>> {
+    let key = (source.info.id.clone(), z, x, y);
+    if let Some(tile) = MEMORY_CACHE.lock().unwrap().tiles.get(&key) {
+        return Some(tile.clone());
+    }
+    let dir = source.disk_dir.as_ref()?;
+    let tile = Arc::new(std::fs::read(disk_tile_path(dir, z, x, y)).ok()?);
+    MEMORY_CACHE.lock().unwrap().insert(key, tile.clone());
+    Some(tile)
+}
+
+/// 写入缓存；generation 为生成瓦片前记录的缓存代数，期间缓存已被清除（数据已修改）时丢弃
+pub fn store_tile(source: &TileSource, z: u8, x: u32, y: u32, tile: Arc<Vec<u8>>, generation: u64) {
+    if source.info.options.cache == TileCacheMode::None {
+        return;
+    }
+    let current = source.generation.lock().unwrap();
+    if *current != generation {
+        return;
+    }
+    if let Some(dir) = &source.disk_dir {
+        let file = disk_tile_path(dir, z, x, y);
+        let written = file.parent()
+            .map(|parent| std::fs::create_dir_all(parent).and_then(|_| std::fs::write(&file, tile.as_slice())));
+        if let Some(Err(e)) = written {
+            log::warn!("写入瓦片缓存失败 {:?}: {}", file, e);
+        }
+    }
+    MEMORY_CACHE.lock().unwrap().insert((source.info.id.clone(), z, x, y), tile);
+}
+
+/// 每一级的列数和行数
+fn grid_size(grid: TileGrid, z: u8) -> (u32, u32) {
+    match grid {
+        TileGrid::WebMercator => (1 << z, 1 << z),
+        TileGrid::Geographic => (2 << z, 1 << z),
+    }
+}
+
+/// 瓦片在切片坐标系（EPSG:3857 或经纬度）中的范围
+fn tile_bounds(grid: TileGrid, z: u8, x: u32, y: u32) -> TileBounds {
+    let (left, top, world) = match grid {
+        TileGrid::WebMercator => (-MERCATOR_HALF, MERCATOR_HALF, 2.0 * MERCATOR_HALF),
+        TileGrid::Geographic => (-180.0, 90.0, 180.0),
+    };
+    let span = world / (1u64 << z) as f64;
+    let min_x = left + x as f64 * span;
+    let max_y = top - y as f64 * span;
+    TileBounds { min_x, min_y: max_y - span, max_x: min_x + span, max_y }
+}
+
+/// 切片坐标转经纬度
+fn grid_to_lon_lat(grid: TileGrid, x: f64, y: f64) -> (f64, f64) {
+    match grid {
+        TileGrid::WebMercator => {
+            let x = x.clamp(-MERCATOR_HALF, MERCATOR_HALF);
+            let y = y.clamp(-MERCATOR_HALF, MERCATOR_HALF);
+            let lon = x / MERCATOR_HALF * 180.0;
+            let lat = (y / MERCATOR_HALF * std::f64::consts::PI).sinh().atan().to_degrees();
+            (lon, lat)
+        }
+        TileGrid::Geographic => (x.clamp(-180.0, 180.0), y.clamp(-90.0, 90.0)),
+    }
+}
+
+/// 创建源坐标系到切片坐标系的转换（无坐标系时按 WGS84 处理，经纬度数据用于经纬度瓦片时无需转换）
+fn create_grid_transform(source_srs: Option<SpatialRef>, grid: TileGrid) -> Result<Option<CoordTransform>> {
+    let mut source = match source_srs {
+        Some(srs) => srs,
+        None if grid == TileGrid::Geographic => return Ok(None),
+        None => SpatialRef::from_epsg(4326)
+            .map_err(|e| AppError::InvalidFormat(format!("创建WGS84坐标系失败: {}", e)))?,
+    };
+    let epsg_code = source.auth_code().unwrap_or_default();
+    // 4490 是 CGCS2000，近似 WGS84
+    if grid == TileGrid::Geographic && (epsg_code == 4326 || epsg_code == 4490) {
+        return Ok(None);
+    }
+
+    let mut target = SpatialRef::from_epsg(match grid {
+        TileGrid::WebMercator => 3857,
+        TileGrid::Geographic => 4326,
+    })
+    .map_err(|e| AppError::InvalidFormat(format!("创建瓦片坐标系失败: {}", e)))?;
+    source.set_axis_mapping_strategy(AxisMappingStrategy::TraditionalGisOrder);
+    target.set_axis_mapping_strategy(AxisMappingStrategy::TraditionalGisOrder);
+    CoordTransform::new(&source, &target)
+        .map(Some)
+        .map_err(|e| AppError::InvalidFormat(format!("创建坐标转换失败: {}", e)))
+}
+
+fn tile_value(value: FieldValue, is_boolean: bool) -> Option<TileValue> {
+    match value {
+        FieldValue::StringValue(s) => Some(TileValue::String(s)),
+        FieldValue::IntegerValue(i) if is_boolean => Some(TileValue::Bool(i != 0)),
+        FieldValue::IntegerValue(i) => Some(TileValue::Int(i as i64)),
+        FieldValue::Integer64Value(i) => Some(TileValue::Int(i)),
+        FieldValue::RealValue(r) => Some(TileValue::Double(r)),
+        FieldValue::DateValue(d) => Some(TileValue::String(d.to_string())),
+        FieldValue::DateTimeValue(dt) => Some(TileValue::String(dt.to_rfc3339())),
+        _ => None,
+    }
+}
+
+/// 生成一个瓦片（Mapbox Vector Tile）
+/// 要素按瓦片范围（含缓冲区）筛选和裁剪，按缩放级别简化，超出缩放级别范围时返回空瓦片
+pub fn render_tile(source: &TileSource, z: u8, x: u32, y: u32) -> Result<Vec<u8>> {
+    let info = &source.info;
+    let options = &info.options;
+    // 先检查缩放级别（最大 24），再计算行列数，避免移位溢出
+    if z < options.min_zoom || z > options.max_zoom {
+        return Ok(Vec::new());
+    }
+    let (columns, rows) = grid_size(options.grid, z);
+    if x >= columns || y >= rows {
+        return Ok(Vec::new());
+    }
+
+    let dataset = gdal_service::open_vector_dataset(&info.path)?;
+    let mut layer = dataset.layer(info.layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", info.layer_index, e)))?;
+    let source_srs = layer.spatial_ref();
+    let transform = create_grid_transform(source_srs.clone(), options.grid)?;
+
+    // 含缓冲区的裁剪范围
+    let bounds = tile_bounds(options.grid, z, x, y);
+    let buffer = (bounds.max_x - bounds.min_x) * TILE_BUFFER / mvt::EXTENT as f64;
+    let clip = TileBounds {
+        min_x: bounds.min_x - buffer,
+        min_y: bounds.min_y - buffer,
+        max_x: bounds.max_x + buffer,
+        max_y: bounds.max_y + buffer,
+    };
+    let clip_geometry = Geometry::bbox(clip.min_x, clip.min_y, clip.max_x, clip.max_y)
+        .map_err(|e| AppError::InvalidFormat(format!("瓦片范围无效: {}", e)))?;
+
+    // 按瓦片范围筛选要素（范围转换到数据源坐标系）
+    let (west, south) = grid_to_lon_lat(options.grid, clip.min_x, clip.min_y);
+    let (east, north) = grid_to_lon_lat(options.grid, clip.max_x, clip.max_y);
+    let filter = export_service::extent_geometry(
+        &Extent { min_x: west, min_y: south, max_x: east, max_y: north },
+        source_srs,
+    )?;
+    layer.set_spatial_filter(&filter);
+    if let Some(where_clause) = &options.where_clause {
+        layer.set_attribute_filter(where_clause)
+            .map_err(|e| AppError::InvalidFormat(format!("属性过滤条件无效: {}", e)))?;
+    }
+
+    // 简化容差：屏幕像素按256像素瓦片换算为切片坐标距离
+    let tolerance = (bounds.max_x - bounds.min_x) / 256.0 * options.simplify;
+    let wanted: Option<HashSet<&str>> = options.fields.as_ref()
+        .map(|fields| fields.iter().map(String::as_str).collect());
+    let boolean_fields: HashSet<String> = gdal_service::read_attribute_fields(layer.defn())
+        .into_iter()
+        .filter(|field| field.field_type == FieldType::Boolean)
+        .map(|field| field.name)
+        .collect();
+
+    let mut builder = LayerBuilder::new(&info.layer_name);
+    for feature in layer.features() {
+        let Some(geom) = feature.geometry() else { continue };
+        let mut geom = match geometry::to_linear(geom.clone()) {
+            Ok(geom) => geom,
+            Err(e) => {
+                log::debug!("跳过无法转换的几何 (FID {:?}): {}", feature.fid(), e);
+                continue;
+            }
+        };
+        geom.flatten_to_2d();
+        if let Some(transform) = &transform {
+            if geom.transform_inplace(transform).is_err() {
+                continue;
+            }
+        }
+
+        // 完全位于裁剪范围内的几何无需裁剪
+        let envelope = geom.envelope();
+        let inside = envelope.MinX >= clip.min_x && envelope.MaxX <= clip.max_x
+            && envelope.MinY >= clip.min_y && envelope.MaxY <= clip.max_y;
+        if !inside {
+            match geom.intersection(&clip_geometry) {
+                Some(clipped) if !clipped.is_empty() => geom = clipped,
+                _ => continue,
+            }
+        }
+
+        let flat_type = unsafe { gdal_sys::OGR_GT_Flatten(geom.geometry_type()) };
+        let is_point = flat_type == gdal::vector::OGRwkbGeometryType::wkbPoint
+            || flat_type == gdal::vector::OGRwkbGeometryType::wkbMultiPoint;
+        if tolerance > 0.0 && !is_point {
+            if let Ok(simplified) = geom.simplify_preserve_topology(tolerance) {
+                geom = simplified;
+            }
+        }
+
+        let properties: Vec<(String, TileValue)> = feature.fields()
+            .filter(|(name, _)| wanted.as_ref().is_none_or(|w| w.contains(name.as_str())))
+            .filter_map(|(name, value)| {
+                let is_boolean = boolean_fields.contains(&name);
+                value.and_then(|value| tile_value(value, is_boolean)).map(|v| (name, v))
+            })
+            .collect();
+        for tile_geometry in mvt::tile_geometries(&geom, bounds) {
+            builder.add_feature(feature.fid(), &tile_geometry, &properties);
+        }
+    }
+
+    Ok(mvt::encode_tile(&[builder]))
+}
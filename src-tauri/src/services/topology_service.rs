@@ -0,0 +1,407 @@
+use crate::errors::{AppError, Result};
+use crate::gis::{geometry, validation};
+use crate::models::{
+    AnalysisInput, FieldType, TopologyRequest, TopologyResult, TopologyRule, TopologyRuleKind, TopologyRuleSummary,
+};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::spatial_index_service::LayerIndex;
+use crate::services::join_service::expand;
+use crate::services::{gdal_service, selection_service};
+use gdal::spatial_ref::SpatialRef;
+use gdal::vector::{FieldValue, Geometry, LayerAccess, OGRwkbGeometryType};
+
+/// 每个图层只能有一种几何类型、无法写入混合几何错误图层的驱动
+const SINGLE_GEOMETRY_DRIVERS: [&str; 3] = ["ESRI Shapefile", "OpenFileGDB", "FileGDB"];
+
+/// 参与检查的图层要素（已线性化并转换到错误图层坐标系），索引键为要素在列表中的位置
+struct RuleLayer {
+    name: String,
+    srs: Option<SpatialRef>, // 要素几何所在的坐标系
+    fids: Vec<u64>,
+    geometries: Vec<Geometry>,
+    index: LayerIndex,
+}
+
+impl RuleLayer {
+    /// 图层要素的最高拓扑维度，没有要素时为 None
+    fn dimension(&self) -> Option<i32> {
+        self.geometries.iter().map(geometry::topological_dimension).max()
+    }
+
+    fn require_dimension(&self, rule: TopologyRuleKind, dimension: i32) -> Result<()> {
+        match self.dimension() {
+            Some(d) if d != dimension => Err(AppError::InvalidFormat(format!(
+                "规则 {} 只适用于{}图层: {}",
+                rule_name(rule),
+                if dimension == 2 { "面" } else { "线" },
+                self.name
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 拓扑错误
+struct TopologyError {
+    fid: Option<u64>,
+    other_fid: Option<u64>, // 重叠的另一个要素
+    geometry: Geometry,
+    detail: String,
+}
+
+fn rule_name(rule: TopologyRuleKind) -> &'static str {
+    match rule {
+        TopologyRuleKind::MustNotOverlap => "must_not_overlap",
+        TopologyRuleKind::MustNotHaveGaps => "must_not_have_gaps",
+        TopologyRuleKind::MustBeCoveredBy => "must_be_covered_by",
+        TopologyRuleKind::MustNotHaveDangles => "must_not_have_dangles",
+        TopologyRuleKind::MustNotSelfIntersect => "must_not_self_intersect",
+    }
+}
+
+/// 读取图层要素时的目标坐标系
+enum TargetSrs {
+    /// 保留图层自身坐标系
+    Source,
+    /// 转换到指定坐标系（None 按 WGS84 处理）
+    To(Option<SpatialRef>),
+}
+
+/// 读取图层要素（只读选择集中的要素），没有几何或坐标转换失败的要素跳过
+fn load_layer(input: &AnalysisInput, target: TargetSrs, job: &JobContext) -> Result<RuleLayer> {
+    let layer_index = input.layer_index.unwrap_or(0);
+    let dataset = gdal_service::open_vector_dataset(&input.path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let (srs, transform) = match target {
+        TargetSrs::Source => (layer.spatial_ref(), None),
+        TargetSrs::To(target_srs) => {
+            let transform = gdal_service::create_layer_transform(layer.spatial_ref(), target_srs.clone())?;
+            (target_srs, transform)
+        }
+    };
+    let input_fids = selection_service::input_fids(input)?;
+
+    let mut fids = Vec::new();
+    let mut geometries = Vec::new();
+    for feature in layer.features() {
+        job.check_cancelled()?;
+        let Some(fid) = feature.fid() else { continue };
+        if input_fids.as_ref().is_some_and(|fids| !fids.contains(&fid)) {
+            continue;
+        }
+        let Some(geom) = feature.geometry() else { continue };
+        let mut geom = geometry::to_linear(geom.clone())?;
+        if geom.is_empty() {
+            continue;
+        }
+        if let Some(transform) = &transform {
+            if let Err(e) = geom.transform_inplace(transform) {
+                log::warn!("要素 {} 坐标转换失败，已跳过: {}", fid, e);
+                continue;
+            }
+        }
+        fids.push(fid);
+        geometries.push(geom);
+    }
+    let index = LayerIndex::from_geometries(geometries.iter().enumerate().map(|(i, geom)| (i as u64, geom)));
+    Ok(RuleLayer {
+        name: layer.name(),
+        srs,
+        fids,
+        geometries,
+        index,
+    })
+}
+
+/// 按阈值上报进度
+struct Progress<'a> {
+    job: &'a JobContext,
+    total: u64,
+    interval: u64,
+    next: u64,
+}
+
+impl<'a> Progress<'a> {
+    fn new(job: &'a JobContext, total: usize) -> Progress<'a> {
+        let total = total as u64;
+        let interval = (total / 100).max(1);
+        job.report(0, Some(total));
+        Progress { job, total, interval, next: interval }
+    }
+
+    fn tick(&mut self, processed: usize) -> Result<()> {
+        self.job.check_cancelled()?;
+        if processed as u64 >= self.next {
+            self.job.report(processed as u64, Some(self.total));
+            self.next += self.interval;
+        }
+        Ok(())
+    }
+}
+
+/// 面之间不能重叠：每对重叠的要素输出一个重叠部分
+fn check_overlaps(layer: &RuleLayer, job: &JobContext) -> Result<Vec<TopologyError>> {
+    layer.require_dimension(TopologyRuleKind::MustNotOverlap, 2)?;
+    let mut errors = Vec::new();
+    let mut progress = Progress::new(job, layer.geometries.len());
+    for (i, geom) in layer.geometries.iter().enumerate() {
+        progress.tick(i + 1)?;
+        for j in layer.index.candidates(&geom.envelope()) {
+            let j = j as usize;
+            let other = &layer.geometries[j];
+            if j <= i || !geom.intersects(other) {
+                continue;
+            }
+            let Some(intersection) = geom.intersection(other) else {
+                log::warn!("要素 {} 与 {} 求交失败: {}", layer.fids[i], layer.fids[j], gdal_service::last_gdal_error());
+                continue;
+            };
+            // 只在边界接触时交集为线或点，不算重叠
+            if let Some(overlap) = geometry::overlay_parts(&intersection, 2)? {
+                if overlap.area() > 0.0 {
+                    errors.push(TopologyError {
+                        fid: Some(layer.fids[i]),
+                        other_fid: Some(layer.fids[j]),
+                        geometry: overlap,
+                        detail: format!("要素 {} 与 {} 重叠", layer.fids[i], layer.fids[j]),
+                    });
+                }
+            }
+        }
+    }
+    Ok(errors)
+}
+
+/// 面之间不能有空隙：全部面合并后的内环即为空隙
+fn check_gaps(layer: &RuleLayer, job: &JobContext) -> Result<Vec<TopologyError>> {
+    layer.require_dimension(TopologyRuleKind::MustNotHaveGaps, 2)?;
+    job.report(0, Some(1));
+    let Some(merged) = geometry::union_polygons(&layer.geometries)? else {
+        return Ok(Vec::new());
+    };
+    job.check_cancelled()?;
+
+    let mut parts = Vec::new();
+    geometry::collect_parts(&merged, 2, &mut parts);
+    let mut errors = Vec::new();
+    for part in &parts {
+        for r in 1..part.geometry_count() {
+            let mut gap = Geometry::empty(OGRwkbGeometryType::wkbPolygon)
+                .map_err(|e| AppError::InvalidFormat(format!("创建几何失败: {}", e)))?;
+            gap.add_geometry(part.get_geometry(r).clone())
+                .map_err(|e| AppError::InvalidFormat(format!("创建几何失败: {}", e)))?;
+            errors.push(TopologyError {
+                fid: None,
+                other_fid: None,
+                detail: format!("面之间的空隙（面积 {:.3}）", gap.area()),
+                geometry: gap,
+            });
+        }
+    }
+    job.report(1, Some(1));
+    Ok(errors)
+}
+
+/// 必须被覆盖：要素未被覆盖图层的面覆盖的部分（点为整个点）
+fn check_covered_by(layer: &RuleLayer, cover: &RuleLayer, job: &JobContext) -> Result<Vec<TopologyError>> {
+    cover.require_dimension(TopologyRuleKind::MustBeCoveredBy, 2)?;
+    let mut errors = Vec::new();
+    let mut progress = Progress::new(job, layer.geometries.len());
+    for (i, geom) in layer.geometries.iter().enumerate() {
+        progress.tick(i + 1)?;
+        let hits: Vec<Geometry> = cover.index.candidates(&geom.envelope())
+            .into_iter()
+            .map(|j| &cover.geometries[j as usize])
+            .filter(|polygon| polygon.intersects(geom))
+            .cloned()
+            .collect();
+        let dimension = geometry::topological_dimension(geom);
+        let uncovered = match geometry::union_polygons(&hits)? {
+            None => Some(geom.clone()),
+            Some(_) if dimension == 0 => None,
+            Some(covering) => {
+                let difference = geom.difference(&covering).ok_or_else(|| {
+                    AppError::InvalidFormat(format!("要素 {} 求差失败: {}", layer.fids[i], gdal_service::last_gdal_error()))
+                })?;
+                geometry::overlay_parts(&difference, dimension)?
+                    .filter(|rest| if dimension == 2 { rest.area() > 0.0 } else { rest.length() > 0.0 })
+            }
+        };
+        if let Some(uncovered) = uncovered {
+            errors.push(TopologyError {
+                fid: Some(layer.fids[i]),
+                other_fid: None,
+                geometry: uncovered,
+                detail: format!("要素 {} 未被 {} 完全覆盖", layer.fids[i], cover.name),
+            });
+        }
+    }
+    Ok(errors)
+}
+
+/// 线不能有悬挂点：线的端点（首尾相连的除外）在容差内必须接触其他线或同一要素的其他部件
+fn check_dangles(layer: &RuleLayer, tolerance: f64, job: &JobContext) -> Result<Vec<TopologyError>> {
+    layer.require_dimension(TopologyRuleKind::MustNotHaveDangles, 1)?;
+    let mut errors = Vec::new();
+    let mut progress = Progress::new(job, layer.geometries.len());
+    for (i, geom) in layer.geometries.iter().enumerate() {
+        progress.tick(i + 1)?;
+        let mut lines = Vec::new();
+        geometry::collect_parts(geom, 1, &mut lines);
+        for (part_index, line) in lines.iter().enumerate() {
+            let count = line.point_count();
+            if count < 2 {
+                continue;
+            }
+            let (first, last) = (line.get_point(0), line.get_point(count as i32 - 1));
+            if first.0 == last.0 && first.1 == last.1 {
+                continue;
+            }
+            for (x, y, _) in [first, last] {
+                let mut point = Geometry::empty(OGRwkbGeometryType::wkbPoint)
+                    .map_err(|e| AppError::InvalidFormat(format!("创建几何失败: {}", e)))?;
+                point.add_point_2d((x, y));
+                let touches = |other: &Geometry| unsafe {
+                    gdal_sys::OGR_G_Distance(point.c_geometry(), other.c_geometry()) <= tolerance
+                };
+                let connected = layer.index.candidates(&expand(&point.envelope(), tolerance))
+                    .into_iter()
+                    .any(|j| match j as usize {
+                        j if j == i => lines.iter().enumerate().any(|(k, other)| k != part_index && touches(other)),
+                        j => touches(&layer.geometries[j]),
+                    });
+                if !connected {
+                    errors.push(TopologyError {
+                        fid: Some(layer.fids[i]),
+                        other_fid: None,
+                        geometry: point,
+                        detail: format!("要素 {} 的端点未与其他线相连", layer.fids[i]),
+                    });
+                }
+            }
+        }
+    }
+    Ok(errors)
+}
+
+/// 不能自相交：输出每个线、面的第一个自相交位置
+fn check_self_intersections(layer: &RuleLayer, job: &JobContext) -> Result<Vec<TopologyError>> {
+    let mut errors = Vec::new();
+    let mut progress = Progress::new(job, layer.geometries.len());
+    for (i, geom) in layer.geometries.iter().enumerate() {
+        progress.tick(i + 1)?;
+        for (x, y) in validation::self_intersections(geom) {
+            let mut point = Geometry::empty(OGRwkbGeometryType::wkbPoint)
+                .map_err(|e| AppError::InvalidFormat(format!("创建几何失败: {}", e)))?;
+            point.add_point_2d((x, y));
+            errors.push(TopologyError {
+                fid: Some(layer.fids[i]),
+                other_fid: None,
+                geometry: point,
+                detail: format!("要素 {} 自相交", layer.fids[i]),
+            });
+        }
+    }
+    Ok(errors)
+}
+
+fn check_rule(rule: &TopologyRule, layer: &RuleLayer, job: &JobContext) -> Result<Vec<TopologyError>> {
+    let errors = match rule.rule {
+        TopologyRuleKind::MustNotOverlap => check_overlaps(layer, job)?,
+        TopologyRuleKind::MustNotHaveGaps => check_gaps(layer, job)?,
+        TopologyRuleKind::MustBeCoveredBy => {
+            let cover = rule.cover_layer.as_ref()
+                .ok_or_else(|| AppError::InvalidFormat("规则 must_be_covered_by 需要指定覆盖图层".to_string()))?;
+            let cover = load_layer(cover, TargetSrs::To(layer.srs.clone()), job)?;
+            check_covered_by(layer, &cover, job)?
+        }
+        TopologyRuleKind::MustNotHaveDangles => check_dangles(layer, rule.tolerance.unwrap_or(0.0).max(0.0), job)?,
+        TopologyRuleKind::MustNotSelfIntersect => check_self_intersections(layer, job)?,
+    };
+    Ok(errors)
+}
+
+/// 拓扑检查：逐条规则检查图层，错误几何写入错误图层（字段 rule_id、rule、layer、fid、fid2、detail）
+/// 错误图层使用第一个规则图层的坐标系，几何类型不限（面、线、点混合），需输出为支持混合几何的格式（如 GeoPackage），
+/// 输出为 Shapefile、FileGDB 时报错
+pub fn check_topology(request: &TopologyRequest, job: &JobContext) -> Result<TopologyResult> {
+    let Some(first) = request.rules.first() else {
+        return Err(AppError::InvalidFormat("没有拓扑规则".to_string()));
+    };
+    log::info!("拓扑检查: {} 条规则 -> {}", request.rules.len(), request.output.path);
+    let driver = export_service::resolve_driver(
+        request.output.format.as_deref().unwrap_or_default(),
+        &request.output.path,
+    )?;
+    let driver_name = driver.short_name();
+    if SINGLE_GEOMETRY_DRIVERS.contains(&driver_name.as_str()) {
+        return Err(AppError::InvalidFormat(format!(
+            "错误图层包含面、线、点混合几何，{} 格式不支持，请输出为 GeoPackage 等格式",
+            driver_name
+        )));
+    }
+
+    // 第一个规则图层按自身坐标系读取，作为错误图层坐标系，后续规则直接复用
+    let mut first_layer = Some(load_layer(&first.layer, TargetSrs::Source, job)?);
+    let target_srs = first_layer.as_ref().and_then(|layer| layer.srs.clone());
+    let schemas = vec![
+        export_service::new_field_schema("rule_id", FieldType::String),
+        export_service::new_field_schema("rule", FieldType::String),
+        export_service::new_field_schema("layer", FieldType::String),
+        export_service::new_field_schema("fid", FieldType::Integer64),
+        export_service::new_field_schema("fid2", FieldType::Integer64),
+        export_service::new_field_schema("detail", FieldType::String),
+    ];
+    let mut output = OutputLayer::for_analysis(
+        &request.output,
+        target_srs.as_ref(),
+        OGRwkbGeometryType::wkbUnknown,
+        &schemas,
+    )?;
+    let mut summaries = Vec::new();
+
+    let mut run = || -> Result<()> {
+        for (index, rule) in request.rules.iter().enumerate() {
+            job.set_stage(index, request.rules.len());
+            let id = rule.id.clone()
+                .filter(|id| !id.trim().is_empty())
+                .unwrap_or_else(|| format!("rule_{}", index + 1));
+            let layer = match first_layer.take() {
+                Some(layer) => layer,
+                None => load_layer(&rule.layer, TargetSrs::To(target_srs.clone()), job)?,
+            };
+            let errors = check_rule(rule, &layer, job)?;
+            log::info!("规则 {} ({}): {} 个错误", id, rule_name(rule.rule), errors.len());
+
+            summaries.push(TopologyRuleSummary {
+                id: id.clone(),
+                rule: rule.rule,
+                features_checked: layer.fids.len() as u64,
+                errors: errors.len() as u64,
+            });
+            for error in errors {
+                let values = [
+                    Some(FieldValue::StringValue(id.clone())),
+                    Some(FieldValue::StringValue(rule_name(rule.rule).to_string())),
+                    Some(FieldValue::StringValue(layer.name.clone())),
+                    error.fid.map(|fid| FieldValue::Integer64Value(fid as i64)),
+                    error.other_fid.map(|fid| FieldValue::Integer64Value(fid as i64)),
+                    Some(FieldValue::StringValue(error.detail)),
+                ];
+                output.write(Some(error.geometry), &values)?;
+            }
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        output.discard();
+        return Err(e);
+    }
+    Ok(TopologyResult {
+        output: output.finish()?,
+        rules: summaries,
+    })
+}
@@ -0,0 +1,250 @@
+use crate::errors::{AppError, Result};
+use crate::gis::{geometry, validation};
+use crate::models::{
+    GeometryIssue, GeometryIssueKind, RepairRequest, RepairResult, ValidationReport, ValidationRequest,
+};
+use crate::services::export_service::{self, OutputLayer};
+use crate::services::job_service::JobContext;
+use crate::services::{gdal_service, selection_service};
+use gdal::spatial_ref::CoordTransform;
+use gdal::vector::{FieldValue, Geometry, LayerAccess, OGRwkbGeometryType};
+
+const DEFAULT_MAX_ISSUES: usize = 10000;
+
+/// 图层坐标转为经纬度，转换失败时返回 None
+fn to_wgs84(transform: Option<&CoordTransform>, (x, y): (f64, f64)) -> Option<[f64; 2]> {
+    let Some(transform) = transform else {
+        return Some([x, y]);
+    };
+    let (mut xs, mut ys, mut zs) = ([x], [y], [0.0]);
+    transform.transform_coords(&mut xs, &mut ys, &mut zs).ok()?;
+    Some([xs[0], ys[0]])
+}
+
+/// 检查图层几何：逐要素报告问题类型、原因和位置（经纬度），没有几何的要素不检查
+pub fn validate_geometries(request: &ValidationRequest, job: &JobContext) -> Result<ValidationReport> {
+    let layer_index = request.input.layer_index.unwrap_or(0);
+    let max_issues = request.max_issues.unwrap_or(DEFAULT_MAX_ISSUES);
+    log::info!("检查几何: {} (图层 {})", request.input.path, layer_index);
+
+    let dataset = gdal_service::open_vector_dataset(&request.input.path)?;
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let transform = gdal_service::create_wgs84_transform(layer.spatial_ref())?;
+    let input_fids = selection_service::input_fids(&request.input)?;
+
+    let total = input_fids.as_ref().map_or_else(|| layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut report = ValidationReport {
+        features_checked: 0,
+        invalid_features: 0,
+        issues: Vec::new(),
+        truncated: false,
+    };
+    job.report(0, Some(total));
+
+    for feature in layer.features() {
+        job.check_cancelled()?;
+        let fid = feature.fid();
+        if let Some(fids) = &input_fids {
+            if !fid.is_some_and(|fid| fids.contains(&fid)) {
+                continue;
+            }
+        }
+        report.features_checked += 1;
+        if report.features_checked >= next_report {
+            job.report(report.features_checked, Some(total));
+            next_report += report_interval;
+        }
+
+        let Some(geom) = feature.geometry() else { continue };
+        let issues = match geometry::to_linear(geom.clone()) {
+            Ok(geom) => validation::check_geometry(&geom),
+            Err(e) => vec![validation::Issue {
+                kind: GeometryIssueKind::Invalid,
+                reason: e.to_string(),
+                location: None,
+            }],
+        };
+        if issues.is_empty() {
+            continue;
+        }
+        report.invalid_features += 1;
+        for issue in issues {
+            if report.issues.len() >= max_issues {
+                report.truncated = true;
+                break;
+            }
+            report.issues.push(GeometryIssue {
+                fid,
+                kind: issue.kind,
+                reason: issue.reason,
+                location: issue.location.and_then(|point| to_wgs84(transform.as_ref(), point)),
+            });
+        }
+    }
+    job.report(report.features_checked, Some(total));
+    log::info!("几何检查完成: {} 个要素, {} 个有问题", report.features_checked, report.invalid_features);
+    Ok(report)
+}
+
+/// 修复单个要素的几何，没有问题（或几何为空）时返回 None，修复后退化为空时返回错误
+fn repair_feature(geom: &Geometry, request: &RepairRequest) -> Result<Option<Geometry>> {
+    let linear = geometry::to_linear(geom.clone())?;
+    let issues = validation::check_geometry(&linear);
+    if issues.is_empty() || issues.iter().any(|issue| issue.kind == GeometryIssueKind::EmptyGeometry) {
+        return Ok(None);
+    }
+    validation::repair_geometry(&linear, request.method, request.remove_spikes)?
+        .map(Some)
+        .ok_or_else(|| AppError::InvalidFormat("修复后几何为空".to_string()))
+}
+
+/// 将修复后的几何（图层坐标系）原位写回图层，返回写入的要素数
+/// 图层为单部件类型时，只有一个部件的修复结果转回单部件（多个部件的结果在修复时已保持原样）
+fn write_repaired(dataset: &mut gdal::Dataset, layer_index: usize, repaired: Vec<(u64, Geometry)>) -> Result<u64> {
+    let total = repaired.len();
+    gdal_service::write_in_transaction(dataset, layer_index, |layer| {
+        let layer_type = unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) };
+        let mut written = 0u64;
+        let write = |fid: u64, geom: Geometry| -> Result<()> {
+            let mut feature = layer.feature(fid)
+                .ok_or_else(|| AppError::FileReadError(format!("要素 {} 不存在", fid)))?;
+            if layer_type == OGRwkbGeometryType::wkbUnknown {
+                feature.set_geometry(geom)
+                    .map_err(|e| AppError::InvalidFormat(format!("设置要素 {} 几何失败: {}", fid, e)))?;
+            } else {
+                geometry::set_feature_geometry_as(&feature, geom, layer_type)?;
+            }
+            layer.set_feature(feature)
+                .map_err(|e| AppError::FileWriteError(format!("修改要素 {} 失败: {}", fid, e)))
+        };
+        for (fid, geom) in repaired {
+            write(fid, geom).map_err(|e| {
+                AppError::FileWriteError(format!("{}（已写入 {}/{} 个要素，不支持事务的数据源中已写入的修改不会撤销）", e, written, total))
+            })?;
+            written += 1;
+        }
+        Ok(written)
+    })
+}
+
+/// 修复图层几何：有问题的要素清理顶点后按修复方式处理
+/// 指定输出时写入新图层（保留全部要素和属性）；否则先修复全部要素，再原位写回输入图层，
+/// 支持事务的数据源（GeoPackage、PostGIS 等）在事务中提交
+pub fn repair_geometries(request: &RepairRequest, job: &JobContext) -> Result<RepairResult> {
+    let layer_index = request.input.layer_index.unwrap_or(0);
+    log::info!("修复几何 ({:?}): {} (图层 {})", request.method, request.input.path, layer_index);
+
+    let mut dataset = match request.output {
+        Some(_) => gdal_service::open_vector_dataset(&request.input.path)?,
+        None => gdal_service::open_vector_dataset_for_update(&request.input.path)?,
+    };
+    let mut layer = dataset.layer(layer_index)
+        .map_err(|e| AppError::FileReadError(format!("无法读取图层 {}: {}", layer_index, e)))?;
+    let input_fids = selection_service::input_fids(&request.input)?;
+
+    let layer_type = unsafe { gdal_sys::OGR_L_GetGeomType(layer.c_layer()) };
+    let mut output = match &request.output {
+        Some(output) => {
+            // 修复结果可能拆分为多个部件，线和面输出为多部件类型
+            let geometry_type = if unsafe { gdal_sys::OGR_GT_Flatten(layer_type) } == OGRwkbGeometryType::wkbPoint {
+                layer_type
+            } else {
+                geometry::result_geometry_type(layer_type, true)
+            };
+            let schemas: Vec<_> = gdal_service::read_attribute_fields(layer.defn())
+                .iter()
+                .map(export_service::field_schema)
+                .collect();
+            Some(OutputLayer::for_analysis(output, layer.spatial_ref().as_ref(), geometry_type, &schemas)?)
+        }
+        None => None,
+    };
+
+    let total = input_fids.as_ref().map_or_else(|| layer.feature_count(), |fids| fids.len() as u64);
+    let report_interval = (total / 100).max(1);
+    let mut next_report = report_interval;
+    let mut result = RepairResult {
+        features_checked: 0,
+        repaired: 0,
+        unrepaired: Vec::new(),
+        output: None,
+    };
+    let mut repaired_geometries = Vec::new();
+    job.report(0, Some(total));
+
+    let mut run = || -> Result<()> {
+        for feature in layer.features() {
+            job.check_cancelled()?;
+            let fid = feature.fid();
+            if let Some(fids) = &input_fids {
+                if !fid.is_some_and(|fid| fids.contains(&fid)) {
+                    continue;
+                }
+            }
+            result.features_checked += 1;
+            if result.features_checked >= next_report {
+                job.report(result.features_checked, Some(total));
+                next_report += report_interval;
+            }
+
+            let original = feature.geometry().cloned();
+            let repaired = match original.as_ref().map(|geom| repair_feature(geom, request)) {
+                Some(Ok(repaired)) => repaired,
+                Some(Err(e)) => {
+                    log::warn!("要素 {:?} 修复失败，保持原样: {}", fid, e);
+                    result.unrepaired.extend(fid);
+                    None
+                }
+                None => None,
+            };
+            // 原位写回单部件图层时，拆分为多个部件的修复结果无法写入，保持原样
+            let repaired = match repaired {
+                Some(geom) if output.is_none() && !geometry::fits_layer_type(&geom, layer_type) => {
+                    log::warn!("要素 {:?} 修复后有多个部件，图层为单部件类型，保持原样", fid);
+                    result.unrepaired.extend(fid);
+                    None
+                }
+                repaired => repaired,
+            };
+            if repaired.is_some() {
+                result.repaired += 1;
+            }
+
+            match &mut output {
+                Some(writer) => {
+                    let values: Vec<Option<FieldValue>> = feature.fields().map(|(_, value)| value).collect();
+                    writer.write(repaired.or(original), &values)?;
+                }
+                None => {
+                    if let (Some(geom), Some(fid)) = (repaired, fid) {
+                        repaired_geometries.push((fid, geom));
+                    }
+                }
+            }
+        }
+        Ok(())
+    };
+
+    if let Err(e) = run() {
+        if let Some(output) = output {
+            output.discard();
+        }
+        return Err(e);
+    }
+    job.report(result.features_checked, Some(total));
+    log::info!("几何修复完成: 修复 {} 个要素, {} 个无法修复", result.repaired, result.unrepaired.len());
+    match output {
+        Some(output) => result.output = Some(output.finish()?),
+        None if repaired_geometries.is_empty() => {}
+        None => {
+            // 写入失败时不支持事务的数据源可能已部分修改，同样丢弃缓存
+            let written = write_repaired(&mut dataset, layer_index, repaired_geometries);
+            gdal_service::invalidate_source_caches(&request.input.path);
+            log::info!("已原位写入 {} 个修复后的要素", written?);
+        }
+    }
+    Ok(result)
+}
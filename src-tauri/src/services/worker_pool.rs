@@ -0,0 +1,251 @@
+use crate::errors::{AppError, Result};
+use crate::models::WorkerPoolStatus;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore};
+
+/// 通过环境变量指定并发数（未设置时按CPU核数计算）
+const WORKERS_ENV: &str = "MINIGIS_GDAL_WORKERS";
+
+/// 并发数上限
+const MAX_CONCURRENCY: usize = 32;
+
+/// GDAL任务在 tokio 阻塞线程上执行，不占用异步运行时线程；
+/// 同时执行的任务数由信号量限制，超出的任务排队等待
+struct Pool {
+    permits: Arc<Semaphore>,
+    concurrency: Mutex<usize>,
+    surplus: AtomicUsize, // 降低并发数时仍被占用、归还后需要收回的许可
+    active: AtomicUsize,
+    queued: AtomicUsize,
+}
+
+lazy_static! {
+    static ref POOL: Pool = {
+        let concurrency = default_concurrency();
+        log::info!("GDAL工作线程池并发数: {}", concurrency);
+        Pool {
+            permits: Arc::new(Semaphore::new(concurrency)),
+            concurrency: Mutex::new(concurrency),
+            surplus: AtomicUsize::new(0),
+            active: AtomicUsize::new(0),
+            queued: AtomicUsize::new(0),
+        }
+    };
+
+    // 数据集读写锁（按路径）：同一数据集可同时读取，写入时独占
+    static ref DATASET_LOCKS: Mutex<HashMap<String, Arc<RwLock<()>>>> = Mutex::new(HashMap::new());
+}
+
+/// 默认并发数：保留一个核心给界面和异步运行时
+fn default_concurrency() -> usize {
+    if let Some(value) = std::env::var(WORKERS_ENV).ok().and_then(|v| v.trim().parse::<usize>().ok()) {
+        return value.clamp(1, MAX_CONCURRENCY);
+    }
+    std::thread::available_parallelism()
+        .map(|n| n.get().saturating_sub(1))
+        .unwrap_or(2)
+        .clamp(2, 8)
+}
+
+/// 排队计数（等待许可期间有效，任务被放弃时同样减少）
+struct Queued;
+
+impl Queued {
+    fn new() -> Self {
+        POOL.queued.fetch_add(1, Ordering::Relaxed);
+        Queued
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        POOL.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 占用的工作线程，任务结束时归还许可
+struct Slot {
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Slot {
+    fn new(permit: OwnedSemaphorePermit) -> Self {
+        POOL.active.fetch_add(1, Ordering::Relaxed);
+        Slot { permit: Some(permit) }
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        POOL.active.fetch_sub(1, Ordering::Relaxed);
+        let reclaimed = POOL.surplus
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+            .is_ok();
+        if let Some(permit) = self.permit.take() {
+            if reclaimed {
+                permit.forget();
+            }
+        }
+    }
+}
+
+/// 在工作线程上执行GDAL任务
+async fn execute<F, T>(task: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let permit = {
+        let _queued = Queued::new();
+        POOL.permits.clone().acquire_owned().await
+            .map_err(|e| AppError::Unknown(format!("GDAL工作线程池已关闭: {}", e)))?
+    };
+
+    // 许可随任务移入工作线程，即使调用方不再等待结果，也在任务真正结束后才归还
+    tokio::task::spawn_blocking(move || {
+        let _slot = Slot::new(permit);
+        task()
+    })
+    .await
+    .map_err(|e| AppError::Unknown(format!("GDAL任务异常终止: {}", e)))?
+}
+
+/// 数据集锁的键：规范化路径；文件尚未创建时规范化其所在目录再拼接文件名，
+/// 保证输出文件创建前后、相对路径与绝对路径得到相同的键
+pub(crate) fn lock_key(path: &str) -> String {
+    let path = Path::new(path);
+    if let Ok(canonical) = std::fs::canonicalize(path) {
+        return canonical.to_string_lossy().to_string();
+    }
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let joined = match (std::fs::canonicalize(parent), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _ => std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+    };
+    joined.to_string_lossy().to_string()
+}
+
+/// 数据集路径对应的锁
+fn dataset_lock(path: &str) -> Arc<RwLock<()>> {
+    key_lock(lock_key(path))
+}
+
+fn key_lock(key: String) -> Arc<RwLock<()>> {
+    let mut locks = DATASET_LOCKS.lock().unwrap();
+    locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+    locks.entry(key).or_default().clone()
+}
+
+/// 执行不针对单个文件数据集的GDAL任务（如访问数据库连接）
+pub async fn run<F, T>(task: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    execute(task).await
+}
+
+/// 执行读取数据集的任务，同一数据集的读取任务可并行执行
+/// 每个任务在工作线程内自行打开数据集，数据集句柄不跨线程共享
+pub async fn read<F, T>(path: String, task: F) -> Result<T>
+where
+    F: FnOnce(&str) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let guard = dataset_lock(&path).read_owned().await;
+    execute(move || {
+        let _guard = guard;
+        task(&path)
+    })
+    .await
+}
+
+/// 执行写入数据集的任务，等待该数据集上的其他任务结束后独占执行
+pub async fn write<F, T>(path: String, task: F) -> Result<T>
+where
+    F: FnOnce(&str) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let guard = dataset_lock(&path).write_owned().await;
+    execute(move || {
+        let _guard = guard;
+        task(&path)
+    })
+    .await
+}
+
+/// 执行读取输入数据集并写入输出数据集的任务（如导出、分析工具）：输入加读锁，输出加写锁
+/// 按锁键排序依次获取，多个任务交叉读写同一组数据集时不会互相等待造成死锁
+pub async fn write_with_inputs<F, T>(output: String, inputs: Vec<String>, task: F) -> Result<T>
+where
+    F: FnOnce(&str) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let output_key = lock_key(&output);
+    let mut keys: Vec<String> = inputs.iter()
+        .map(|path| lock_key(path))
+        .filter(|key| *key != output_key)
+        .collect();
+    keys.push(output_key.clone());
+    keys.sort();
+    keys.dedup();
+
+    let mut read_guards = Vec::with_capacity(keys.len());
+    let mut write_guard = None;
+    for key in keys {
+        let is_output = key == output_key;
+        let lock = key_lock(key);
+        if is_output {
+            write_guard = Some(lock.write_owned().await);
+        } else {
+            read_guards.push(lock.read_owned().await);
+        }
+    }
+    execute(move || {
+        let _guards = (read_guards, write_guard);
+        task(&output)
+    })
+    .await
+}
+
+/// 当前线程池状态
+pub fn status() -> WorkerPoolStatus {
+    WorkerPoolStatus {
+        concurrency: *POOL.concurrency.lock().unwrap(),
+        active: POOL.active.load(Ordering::Relaxed),
+        queued: POOL.queued.load(Ordering::Relaxed),
+    }
+}
+
+/// 调整并发数：增加时立即生效；减少时先收回空闲许可，其余在正在执行的任务结束后收回
+pub fn set_concurrency(concurrency: usize) -> Result<WorkerPoolStatus> {
+    if concurrency == 0 || concurrency > MAX_CONCURRENCY {
+        return Err(AppError::InvalidFormat(format!("并发数必须在 1 到 {} 之间", MAX_CONCURRENCY)));
+    }
+
+    {
+        let mut current = POOL.concurrency.lock().unwrap();
+        if concurrency > *current {
+            // 先抵消尚未收回的许可，再补充新许可
+            let mut needed = concurrency - *current;
+            let cancelled = POOL.surplus
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| Some(n - n.min(needed)))
+                .unwrap_or(0)
+                .min(needed);
+            needed -= cancelled;
+            POOL.permits.add_permits(needed);
+        } else if concurrency < *current {
+            let excess = *current - concurrency;
+            let forgotten = POOL.permits.forget_permits(excess);
+            POOL.surplus.fetch_add(excess - forgotten, Ordering::AcqRel);
+        }
+        log::info!("GDAL工作线程池并发数: {} -> {}", *current, concurrency);
+        *current = concurrency;
+    }
+
+    Ok(status())
+}
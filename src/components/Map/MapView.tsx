@@ -24,6 +24,7 @@ import { registerAllProjections } from "../../utils/projectionRegistry";
 import type { Layer } from "../../stores/layerStore";
 import { symbolizerToOLStyle, createTextStyle } from "../../utils/symbolRenderer";
 import { latLngToOL, olToLatLng, geoJsonExtentToOL, createXYZUrl } from "../../utils/olHelpers";
+import { gdalService, type IdentifyLayerResult } from "../../services/gdalService";
 import HistoryImageControl from "./HistoryImageControl";
 import "./MapView.css";
 import * as turf from "@turf/turf";
@@ -755,8 +756,15 @@ const MapView: React.FC<MapViewProps> = ({ tabId }) => {
           foundFeature = true;
         });
       } else {
-        // 浏览模式：收集所有重叠要素
-        const inspectedFeatures: InspectedFeatureInfo[] = [];
+        // 浏览模式：有数据源路径的矢量图层由后端识别（包括未完全加载到前端的图层），
+        // 其余图层（及后端识别失败的图层）使用前端已加载的要素
+        const flattenLayers = (layers: Layer[]): Layer[] =>
+          layers.flatMap(l => (l.isGroup ? flattenLayers(l.children || []) : [l]));
+        const renderLayers = flattenLayers(layersRef.current);
+        const backendLayers = renderLayers.filter(l => l.visible && l.type === 'vector' && l.source.path);
+
+        // 前端识别结果（按图层分组）
+        const frontendFeatures = new globalThis.Map<string, InspectedFeatureInfo[]>();
         
         map.forEachFeatureAtPixel(pixel, (feature, layer) => {
           if (!layer || layer === highlightLayerRef.current || layer === measureLayerRef.current) return;
@@ -782,18 +790,60 @@ const MapView: React.FC<MapViewProps> = ({ tabId }) => {
           });
           
           // 添加到识别要素列表
-          inspectedFeatures.push({
+          const layerId = (targetLayer as Layer).id;
+          const list = frontendFeatures.get(layerId) || [];
+          list.push({
             feature: geojsonFeature,
             layer: targetLayer,
             layerName: (targetLayer as Layer).name,
-            layerId: (targetLayer as Layer).id,
+            layerId,
           });
+          frontendFeatures.set(layerId, list);
         });
-        
-        // 如果识别到要素，调用处理函数
-        if (inspectedFeatures.length > 0) {
-          handleFeatureInspect(inspectedFeatures);
-        }
+
+        const view = map.getView();
+        const backendRequest: Promise<IdentifyLayerResult[]> = backendLayers.length > 0
+          ? gdalService.identify({
+              x: e.coordinate[0],
+              y: e.coordinate[1],
+              crs: view.getProjection().getCode(),
+              resolution: view.getResolution() ?? 0,
+              layers: backendLayers.map(l => ({
+                layer_id: l.id,
+                path: l.source.path!,
+                layer_index: l.source.layerIndex,
+              })),
+            })
+          : Promise.resolve([]);
+
+        backendRequest
+          .catch((error) => {
+            console.error('后端要素识别失败，使用前端识别结果:', error);
+            return [] as IdentifyLayerResult[];
+          })
+          .then((results) => {
+            const backendResults = new globalThis.Map(
+              results.filter(r => !r.error).map(r => [r.layer_id, r] as const)
+            );
+            results.filter(r => r.error).forEach(r => console.warn(`图层识别失败 ${r.layer_id}:`, r.error));
+
+            // 按图层从上到下的顺序合并结果
+            const inspectedFeatures: InspectedFeatureInfo[] = renderLayers.flatMap((layer) => {
+              const result = backendResults.get(layer.id);
+              if (!result) return frontendFeatures.get(layer.id) || [];
+              return result.features.map((feature) => ({
+                feature,
+                layer,
+                layerName: layer.name,
+                layerId: layer.id,
+              }));
+            });
+
+            // 如果识别到要素，调用处理函数
+            if (inspectedFeatures.length > 0) {
+              handleFeatureInspect(inspectedFeatures);
+            }
+          });
       }
     });
 
@@ -1035,7 +1085,8 @@ const MapView: React.FC<MapViewProps> = ({ tabId }) => {
                   // 多图层文件（KML/GDB）
                   geojsonData = await invoke('gdal_get_layer_geojson', {
                     path: layer.source.path,
-                    layerIndex: layer.source.layerIndex
+                    layerIndex: layer.source.layerIndex,
+                    joins: layer.joins,
                   });
                 } else {
                   // 单图层文件
@@ -1255,7 +1306,8 @@ const MapView: React.FC<MapViewProps> = ({ tabId }) => {
                 if (layer.source.layerIndex !== undefined) {
                   geojsonData = await invoke('gdal_get_layer_geojson', {
                     path: layer.source.path,
-                    layerIndex: layer.source.layerIndex
+                    layerIndex: layer.source.layerIndex,
+                    joins: layer.joins,
                   });
                 } else {
                   geojsonData = await invoke('gdal_get_geojson', {
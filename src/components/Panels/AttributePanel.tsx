@@ -155,6 +155,8 @@ const SingleAttributeTable: React.FC<{ layerId: string }> = ({ layerId }) => {
         const geojson = await invoke<any>("gdal_get_layer_geojson", {
           path: currentLayerPath,
           layerIndex: currentLayer.source.layerIndex,
+          decodeDomains: true, // 编码值显示为属性域描述
+          joins: currentLayer.joins,
         });
         
         console.log('[属性表] GeoJSON读取成功:', geojson.features?.length, '个要素');
@@ -171,6 +173,7 @@ const SingleAttributeTable: React.FC<{ layerId: string }> = ({ layerId }) => {
           path: currentLayerPath,
           offset: 0,
           limit: 100000, // 加载所有数据
+          joins: currentLayer?.joins,
         });
       }
 
@@ -192,8 +195,14 @@ const SingleAttributeTable: React.FC<{ layerId: string }> = ({ layerId }) => {
         };
 
         // 从第一个要素的 properties 中获取字段
+        // 有字段别名时以别名作为列标题
+        const aliases: Record<string, string> = {};
+        (result.fields || []).forEach((f: any) => {
+          if (f.alias) aliases[f.name] = f.alias;
+        });
+
         const cols = Object.keys(firstFeature.properties || {}).map((key) => ({
-          title: key,
+          title: aliases[key] || key,
           dataIndex: key,
           key: key,
           ellipsis: {
@@ -1,8 +1,8 @@
-import React, { useState } from 'react';
-import { Form, Select, Button, App, Space, Typography, Divider } from 'antd';
-import { ExportOutlined, FileOutlined } from '@ant-design/icons';
+import React, { useEffect, useState } from 'react';
+import { Form, Select, Button, App, Space, Typography, Divider, Input, Checkbox } from 'antd';
+import { ExportOutlined, FileOutlined, StopOutlined } from '@ant-design/icons';
 import { useMapTabsStore } from '../../stores/mapTabsStore';
-import { invoke } from '@tauri-apps/api/core';
+import { gdalService, ExportDriver } from '../../services/gdalService';
 import { save } from '@tauri-apps/plugin-dialog';
 import './SymbologyPanel.css';
 
@@ -19,6 +19,15 @@ const ExportPanel: React.FC<ExportPanelProps> = ({ onClose }) => {
   const layers = currentTab?.layers || [];
   const [form] = Form.useForm();
   const [exporting, setExporting] = useState(false);
+  const [jobId, setJobId] = useState<string | null>(null);
+  const [drivers, setDrivers] = useState<ExportDriver[]>([]);
+
+  // 加载GDAL支持写入的其他矢量格式
+  useEffect(() => {
+    gdalService.getExportDrivers()
+      .then(setDrivers)
+      .catch(error => console.error('获取导出驱动失败:', error));
+  }, []);
 
   // 获取所有矢量图层（包括分组图层的子图层）
   const getAllVectorLayers = () => {
@@ -45,19 +54,26 @@ const ExportPanel: React.FC<ExportPanelProps> = ({ onClose }) => {
   const vectorLayers = getAllVectorLayers();
 
   // 导出格式选项
-  const formatOptions = [
+  const commonFormats = [
     { label: 'KML', value: 'KML', extension: 'kml' },
     { label: 'KMZ (压缩KML)', value: 'KMZ', extension: 'kmz' },
     { label: 'GeoJSON', value: 'GEOJSON', extension: 'geojson' },
     { label: 'Shapefile', value: 'SHAPEFILE', extension: 'shp' },
     { label: 'GeoPackage', value: 'GPKG', extension: 'gpkg' },
   ];
+  const commonDrivers = ['KML', 'LIBKML', 'GeoJSON', 'ESRI Shapefile', 'GPKG'];
+  const formatOptions = [
+    ...commonFormats,
+    ...drivers
+      .filter(d => !commonDrivers.includes(d.name) && d.extensions.length > 0)
+      .map(d => ({ label: `${d.long_name} (${d.name})`, value: d.name, extension: d.extensions[0] })),
+  ];
 
   // 执行导出
   const handleExport = async () => {
     try {
       const values = await form.validateFields();
-      const { layerId, format } = values;
+      const { layerId, format, targetCrs, whereClause, promoteToMulti } = values;
 
       // 获取选中的图层（包括子图层）
       const layer = vectorLayers.find(l => l.id === layerId);
@@ -90,21 +106,37 @@ const ExportPanel: React.FC<ExportPanelProps> = ({ onClose }) => {
       setExporting(true);
       message.loading({ content: '正在导出...', key: 'export', duration: 0 });
 
-      // 构建导出参数，如果是子图层则传递layerIndex
-      const exportParams: any = {
-        inputPath: layer.source.path,
-        outputPath: outputPath,
-        format: format,
-      };
-      
-      // 如果图层有layerIndex（KML/GDB等多图层文件的子图层），则传递索引
-      if (layer.source.layerIndex !== undefined) {
-        exportParams.layerIndex = layer.source.layerIndex;
-      }
+      // 如果图层有layerIndex（KML/GDB等多图层文件的子图层），则只导出该图层
+      const layerIndex = layer.source.layerIndex;
 
-      await invoke('gdal_export_vector', exportParams);
+      const id = crypto.randomUUID();
+      setJobId(id);
+      const unlisten = await gdalService.onJobProgress(progress => {
+        if (progress.job_id === id && progress.status === 'running' && progress.percent !== null) {
+          const eta = progress.eta_seconds !== null ? `，剩余约 ${Math.ceil(progress.eta_seconds)} 秒` : '';
+          message.loading({
+            content: `正在导出... ${Math.floor(progress.percent)}%（${progress.processed} 个要素${eta}）`,
+            key: 'export',
+            duration: 0,
+          });
+        }
+      });
 
-      message.success({ content: `导出成功: ${outputPath}`, key: 'export', duration: 3 });
+      try {
+        const result = await gdalService.exportVector(layer.source.path, outputPath, format, layerIndex, {
+          target_crs: targetCrs?.trim() || null,
+          where_clause: whereClause?.trim() || null,
+          promote_to_multi: !!promoteToMulti,
+        }, id);
+        const failed = result.features_failed > 0 ? `，${result.features_failed} 个要素失败` : '';
+        message.success({
+          content: `导出成功: ${outputPath}（${result.features_written} 个要素${failed}）`,
+          key: 'export',
+          duration: 3,
+        });
+      } finally {
+        unlisten();
+      }
       
     } catch (error) {
       console.error('导出失败详细信息:', error);
@@ -124,9 +156,22 @@ const ExportPanel: React.FC<ExportPanelProps> = ({ onClose }) => {
       }
       
       console.error('错误消息:', errorMsg);
-      message.error({ content: `导出失败: ${errorMsg}`, key: 'export', duration: 5 });
+      // 后端错误序列化为 { 变体名: 消息 }
+      if (error && typeof error === 'object' && 'Cancelled' in error) {
+        message.info({ content: '导出已取消', key: 'export', duration: 3 });
+      } else {
+        message.error({ content: `导出失败: ${errorMsg}`, key: 'export', duration: 5 });
+      }
     } finally {
       setExporting(false);
+      setJobId(null);
+    }
+  };
+
+  // 取消导出（后台停止写入并删除不完整的输出文件）
+  const handleCancel = async () => {
+    if (jobId) {
+      await gdalService.cancelJob(jobId).catch(error => console.error('取消导出失败:', error));
     }
   };
 
@@ -204,6 +249,28 @@ const ExportPanel: React.FC<ExportPanelProps> = ({ onClose }) => {
             </Select>
           </Form.Item>
 
+          <Form.Item
+            name="targetCrs"
+            label="目标坐标系"
+            tooltip="EPSG代码、WKT或PROJ.4，留空则保持原坐标系"
+            style={{ marginBottom: 16 }}
+          >
+            <Input placeholder="如 EPSG:4490" size="middle" allowClear />
+          </Form.Item>
+
+          <Form.Item
+            name="whereClause"
+            label="属性过滤"
+            tooltip="OGR SQL WHERE 条件，留空则导出全部要素"
+            style={{ marginBottom: 16 }}
+          >
+            <Input placeholder="如 面积 > 100 AND 类型 = '耕地'" size="middle" allowClear />
+          </Form.Item>
+
+          <Form.Item name="promoteToMulti" valuePropName="checked" style={{ marginBottom: 16 }}>
+            <Checkbox>单部件几何转为多部件（如 Polygon → MultiPolygon）</Checkbox>
+          </Form.Item>
+
           <Divider style={{ margin: '16px 0' }} />
 
           <div style={{ marginBottom: 16 }}>
@@ -216,6 +283,7 @@ const ExportPanel: React.FC<ExportPanelProps> = ({ onClose }) => {
               <li>GeoJSON：Web应用常用格式</li>
               <li>Shapefile：GIS标准格式</li>
               <li>GeoPackage：现代GIS交换格式</li>
+              <li>其他：GDAL支持写入的全部矢量格式</li>
             </ul>
           </div>
 
@@ -230,6 +298,11 @@ const ExportPanel: React.FC<ExportPanelProps> = ({ onClose }) => {
             >
               导出
             </Button>
+            {exporting && (
+              <Button icon={<StopOutlined />} onClick={handleCancel} block size="middle" danger>
+                取消导出
+              </Button>
+            )}
           </Space>
         </Form>
       </div>
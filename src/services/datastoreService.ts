@@ -0,0 +1,132 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { AttributeField, CoordinateDimension, FieldType } from './gdalService';
+
+export type DataStoreKind = 'geopackage' | 'spatialite';
+
+export interface TableFieldSchema {
+  name: string;
+  field_type: FieldType;
+  width?: number;
+  precision?: number;
+  alias?: string;
+  nullable?: boolean;
+  unique?: boolean;
+  /** OGR 默认值表达式，字符串需带单引号 */
+  default_value?: string;
+  domain?: string;
+}
+
+export interface TableSchema {
+  name: string;
+  /** 如 Point、MultiPolygonZ；为空时创建属性表 */
+  geometry_type?: string | null;
+  geometry_column?: string;
+  crs?: string;
+  fields: TableFieldSchema[];
+  description?: string;
+}
+
+export interface DataStoreTable {
+  name: string;
+  index: number;
+  is_spatial: boolean;
+  geometry_type?: string | null;
+  geometry_column?: string | null;
+  coordinate_dimension: CoordinateDimension;
+  projection?: string | null;
+  feature_count: number;
+  fields: AttributeField[];
+  identifier?: string;
+  description?: string;
+}
+
+export interface DataStoreInfo {
+  path: string;
+  kind: DataStoreKind;
+  tables: DataStoreTable[];
+  style_count: number;
+}
+
+export interface StoredLayerStyle {
+  id: number;
+  table_name: string;
+  geometry_column?: string | null;
+  style_name: string;
+  use_as_default: boolean;
+  description?: string | null;
+  /** MiniGIS 样式，其他软件（如 QGIS）写入的样式为空 */
+  style?: Record<string, any> | null;
+  update_time?: string | null;
+}
+
+/**
+ * GeoPackage / SpatiaLite 数据库服务
+ */
+class DataStoreService {
+  async create(path: string, kind: DataStoreKind, overwrite?: boolean): Promise<void> {
+    return invoke('datastore_create', { path, kind, overwrite });
+  }
+
+  async createTable(path: string, schema: TableSchema): Promise<DataStoreTable> {
+    return invoke<DataStoreTable>('datastore_create_table', { path, schema });
+  }
+
+  async listContents(path: string): Promise<DataStoreInfo> {
+    return invoke<DataStoreInfo>('datastore_list_contents', { path });
+  }
+
+  /**
+   * 复制矢量图层到数据库（数据库不存在时自动创建），返回表名
+   */
+  async importLayer(
+    sourcePath: string,
+    storePath: string,
+    options?: { layerIndex?: number; tableName?: string; overwrite?: boolean }
+  ): Promise<string> {
+    return invoke<string>('datastore_import_layer', {
+      sourcePath,
+      layerIndex: options?.layerIndex,
+      storePath,
+      tableName: options?.tableName,
+      overwrite: options?.overwrite
+    });
+  }
+
+  async exportTable(storePath: string, tableName: string, outputPath: string, format: string): Promise<void> {
+    return invoke('datastore_export_table', { storePath, tableName, outputPath, format });
+  }
+
+  async saveStyle(
+    path: string,
+    tableName: string,
+    styleName: string,
+    style: Record<string, any>,
+    options?: { useAsDefault?: boolean; description?: string }
+  ): Promise<StoredLayerStyle> {
+    return invoke<StoredLayerStyle>('datastore_save_style', {
+      path,
+      tableName,
+      styleName,
+      style,
+      useAsDefault: options?.useAsDefault,
+      description: options?.description
+    });
+  }
+
+  async listStyles(path: string, tableName?: string): Promise<StoredLayerStyle[]> {
+    return invoke<StoredLayerStyle[]>('datastore_list_styles', { path, tableName });
+  }
+
+  async getDefaultStyle(path: string, tableName: string): Promise<StoredLayerStyle | null> {
+    return invoke<StoredLayerStyle | null>('datastore_get_default_style', { path, tableName });
+  }
+
+  async deleteStyle(path: string, styleId: number): Promise<void> {
+    return invoke('datastore_delete_style', { path, styleId });
+  }
+}
+
+// 导出单例
+export const dataStoreService = new DataStoreService();
+
+export default DataStoreService;
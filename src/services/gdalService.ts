@@ -1,4 +1,43 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke, Channel } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import type { TableFieldSchema } from './datastoreService';
+
+/**
+ * 坐标维度
+ */
+export type CoordinateDimension = 'XY' | 'XYZ' | 'XYM' | 'XYZM';
+
+/**
+ * Z值/M值处理方式
+ */
+export type DimensionMode = 'preserve' | 'drop_m' | 'force_2d';
+
+/**
+ * 字段类型
+ */
+export type FieldType =
+  | 'string' | 'integer' | 'int16' | 'integer64' | 'real' | 'float32' | 'boolean'
+  | 'date' | 'time' | 'datetime' | 'binary' | 'json' | 'uuid'
+  | 'integer_list' | 'integer64_list' | 'real_list' | 'string_list' | 'unknown';
+
+/**
+ * 字段定义
+ */
+export interface AttributeField {
+  name: string;
+  field_type: FieldType;
+  width?: number | null;
+  precision?: number | null;
+  alias?: string | null;
+  nullable: boolean;
+  unique: boolean;
+  /** OGR 默认值表达式（字符串带单引号，如 'abc'） */
+  default_value?: string | null;
+  /** 属性域名称（GDB、GeoPackage） */
+  domain?: string | null;
+  editable: boolean;
+  visible: boolean;
+}
 
 /**
  * 矢量文件信息
@@ -7,12 +46,8 @@ export interface VectorInfo {
   path: string;
   feature_count: number;
   geometry_type: string;
-  fields: Array<{
-    name: string;
-    type: string;
-    width: number;
-    precision: number;
-  }>;
+  coordinate_dimension: CoordinateDimension;
+  fields: AttributeField[];
   extent: {
     min_x: number;
     min_y: number;
@@ -20,6 +55,717 @@ export interface VectorInfo {
     max_y: number;
   };
   projection?: string;
+  vertical_crs?: string;
+}
+
+/**
+ * 属性域（GDB 编码值域、范围域）
+ */
+export interface FieldDomain {
+  name: string;
+  description?: string | null;
+  kind: 'coded' | 'range' | 'glob';
+  field_type: string;
+  coded_values?: Array<{ code: string; description?: string | null }>;
+  min?: number;
+  max?: number;
+  min_inclusive: boolean;
+  max_inclusive: boolean;
+  glob?: string;
+}
+
+/**
+ * 文件地理数据库元数据
+ */
+export interface GdbMetadata {
+  path: string;
+  feature_datasets: Array<{ name: string; layers: string[] }>;
+  domains: FieldDomain[];
+  relationships: Array<{
+    name: string;
+    cardinality: 'one_to_one' | 'one_to_many' | 'many_to_one' | 'many_to_many';
+    relationship_type: 'composite' | 'association' | 'aggregation';
+    origin_table: string;
+    destination_table: string;
+    mapping_table?: string | null;
+    origin_fields: string[];
+    destination_fields: string[];
+    forward_label?: string | null;
+    backward_label?: string | null;
+  }>;
+  layers: Array<{
+    name: string;
+    index: number;
+    alias?: string | null;
+    feature_dataset?: string | null;
+    fields: AttributeField[];
+    subtype_field?: string | null;
+    default_subtype?: number | null;
+    subtypes: Array<{
+      code: number;
+      name: string;
+      field_domains: Record<string, string>;
+      default_values: Record<string, string>;
+    }>;
+  }>;
+}
+
+/**
+ * 导出字段映射
+ */
+export interface FieldMapping {
+  source: string;
+  /** 目标字段名，为空时沿用源字段名 */
+  target?: string | null;
+  /** 目标字段类型，为空时沿用源字段类型 */
+  field_type?: FieldType | null;
+  width?: number | null;
+  precision?: number | null;
+}
+
+/**
+ * 导出选项
+ */
+export interface ExportOptions {
+  layer_name?: string | null;
+  dimension?: DimensionMode;
+  /** 数据源创建选项，如 "SPATIALITE=YES" */
+  dataset_options?: string[];
+  /** 图层创建选项，如 "ENCODING=UTF-8" */
+  layer_options?: string[];
+  /** 为空时导出全部字段；可用于选择字段子集并重命名 */
+  field_mapping?: FieldMapping[];
+  /** 目标坐标系（EPSG代码、WKT或PROJ.4），为空时沿用源坐标系 */
+  target_crs?: string | null;
+  /** 属性过滤条件（OGR SQL WHERE 语法） */
+  where_clause?: string | null;
+  /** 空间范围（WGS84经纬度），只导出与范围相交的要素 */
+  extent?: { min_x: number; min_y: number; max_x: number; max_y: number } | null;
+  /** 按空间范围裁剪几何 */
+  clip_to_extent?: boolean;
+  /** 只导出指定要素 */
+  fids?: number[] | null;
+  /** 只导出该图层ID的选择集中的要素（与 fids 同时指定时取交集） */
+  selection?: string | null;
+  /** 单部件几何提升为多部件 */
+  promote_to_multi?: boolean;
+}
+
+export type JobStatus = 'running' | 'completed' | 'cancelled' | 'failed';
+
+/**
+ * 任务进度（job-progress 事件）
+ */
+export interface JobProgress {
+  job_id: string;
+  /** 任务类型：export、batch_convert、read_layer */
+  kind: string;
+  status: JobStatus;
+  /** 百分比（总数未知时为空） */
+  percent: number | null;
+  /** 已处理要素数 */
+  processed: number;
+  total: number | null;
+  elapsed_seconds: number;
+  /** 预计剩余秒数 */
+  eta_seconds: number | null;
+  error: string | null;
+}
+
+/**
+ * 正在运行的任务
+ */
+export interface JobInfo {
+  job_id: string;
+  kind: string;
+  elapsed_seconds: number;
+  cancelled: boolean;
+}
+
+/**
+ * 分批读取GeoJSON时推送的消息
+ */
+export type GeoJsonStreamEvent =
+  | { event: 'batch'; index: number; features: any[]; read: number; total: number }
+  | { event: 'finished'; features: number; batches: number };
+
+export interface GeoJsonStreamOptions {
+  layerIndex?: number;
+  dimension?: DimensionMode;
+  decodeDomains?: boolean;
+  /** 虚拟属性连接，连接字段附加到要素属性 */
+  joins?: AttributeJoin[];
+  /** 每批要素数（100-100000，默认5000） */
+  batchSize?: number;
+  /** 任务ID，可用于 cancelJob 取消读取 */
+  jobId?: string;
+}
+
+/**
+ * 矢量瓦片源选项
+ */
+export interface TileSourceOptions {
+  /** 写入瓦片的属性字段，为空时写入全部字段 */
+  fields?: string[] | null;
+  where_clause?: string | null;
+  min_zoom?: number;
+  max_zoom?: number;
+  /** 简化容差（屏幕像素），0 表示不简化，默认1 */
+  simplify?: number;
+  /** web_mercator：EPSG:3857 标准瓦片；geographic：经纬度瓦片（EPSG:4326/4490 地图使用） */
+  grid?: 'web_mercator' | 'geographic';
+  cache?: 'none' | 'memory' | 'disk';
+}
+
+/**
+ * 已注册的矢量瓦片源
+ */
+export interface SpatialIndexInfo {
+  path: string;
+  layer_index: number;
+  /** 已索引的要素数（不含空几何） */
+  features: number;
+  /** 图层坐标系下的范围 */
+  extent: { min_x: number; min_y: number; max_x: number; max_y: number } | null;
+  build_ms: number;
+}
+
+export type LengthUnit = 'm' | 'km';
+/** m2、km2、ha（公顷）、mu（亩） */
+export type AreaUnit = 'm2' | 'km2' | 'ha' | 'mu';
+
+export interface MeasureRequest {
+  /** GeoJSON 几何 */
+  geometry: unknown;
+  /** 几何的坐标系（如 EPSG:4527），默认 WGS84 经纬度 */
+  crs?: string;
+  /** 平面量算使用的投影坐标系，为空时只做椭球面量算 */
+  planar_crs?: string;
+  length_unit?: LengthUnit;
+  area_unit?: AreaUnit;
+}
+
+/** 线的长度、面的面积和周长（含内环），多部件几何为各部件之和 */
+export interface MeasureValues {
+  length: number;
+  area: number;
+  perimeter: number;
+}
+
+export interface MeasureResult {
+  /** CGCS2000/WGS84 椭球面测地线量算 */
+  geodesic: MeasureValues;
+  /** planar_crs 投影平面量算 */
+  planar: MeasureValues | null;
+  length_unit: LengthUnit;
+  area_unit: AreaUnit;
+}
+
+export interface IdentifyRequest {
+  /** 地图坐标 */
+  x: number;
+  y: number;
+  /** 地图坐标系，如 EPSG:4490 */
+  crs: string;
+  /** 地图分辨率（地图单位/像素） */
+  resolution: number;
+  /** 像素容差，默认 3 */
+  tolerance?: number;
+  /** 按图层从上到下的顺序 */
+  layers: { layer_id: string; path: string; layer_index?: number }[];
+  /** 每个图层最多返回的要素数，默认 50 */
+  max_features?: number;
+  decode_domains?: boolean;
+}
+
+export interface IdentifyLayerResult {
+  layer_id: string;
+  layer_name: string;
+  /** WGS84 GeoJSON 要素，按与识别点的距离排序 */
+  features: any[];
+  truncated: boolean;
+  error: string | null;
+}
+
+export type SelectionMode = 'new' | 'add' | 'remove' | 'intersect';
+
+export type SpatialRelation = 'intersects' | 'within' | 'contains' | 'touches' | 'crosses' | 'overlaps';
+
+type Extent = { min_x: number; min_y: number; max_x: number; max_y: number };
+
+/** 选择条件：矩形/多边形的 crs 默认为 WGS84 */
+export type SelectionQuery =
+  | { type: 'rectangle'; extent: Extent; crs?: string; relation?: SpatialRelation }
+  | { type: 'polygon'; geometry: any; crs?: string; relation?: SpatialRelation }
+  | { type: 'expression'; where_clause: string }
+  | {
+      type: 'spatial';
+      path: string;
+      layer_index?: number;
+      /** 参照图层ID，只使用其选择集中的要素 */
+      selection?: string;
+      relation?: SpatialRelation;
+    };
+
+export interface SelectionSummary {
+  layer_id: string;
+  path: string;
+  layer_index: number;
+  count: number;
+}
+
+export interface SelectionSet {
+  layer_id: string;
+  path: string;
+  layer_index: number;
+  fids: number[];
+}
+
+export interface FieldStatistics {
+  field: string;
+  /** 非空值个数 */
+  count: number;
+  null_count: number;
+  min: number | null;
+  max: number | null;
+  sum: number | null;
+  mean: number | null;
+}
+
+/** 分析工具的输入图层，指定 selection（图层ID）时只处理其选择集中的要素 */
+export interface AnalysisInput {
+  path: string;
+  layer_index?: number;
+  selection?: string;
+}
+
+/** 分析工具的输出，format 为空时按扩展名选择驱动，layer_name 为空时使用文件名 */
+export interface AnalysisOutput {
+  path: string;
+  format?: string;
+  layer_name?: string;
+}
+
+export type JoinPredicate = 'intersects' | 'within' | 'contains' | 'nearest';
+
+export type JoinCardinality = 'one_to_one' | 'one_to_many';
+
+export type FieldAggregation = 'first' | 'count' | 'sum' | 'mean' | 'min' | 'max';
+
+export interface AggregationRule {
+  field: string;
+  aggregation?: FieldAggregation;
+  output_name?: string;
+}
+
+/** 裁剪、擦除请求：mask 为面图层 */
+export interface OverlayRequest {
+  input: AnalysisInput;
+  mask: AnalysisInput;
+  output: AnalysisOutput;
+}
+
+/** 按掩膜面分割：每个掩膜面（name_field 值相同的合并）输出一个以字段值命名的文件 */
+export interface SplitRequest {
+  input: AnalysisInput;
+  mask: AnalysisInput;
+  name_field: string;
+  output_dir: string;
+  format: string;
+}
+
+/** 融合：融合字段值相同的要素合并为一个，其他字段按汇总方式统计 */
+export interface DissolveRequest {
+  input: AnalysisInput;
+  /** 为空时全部要素合并为一个 */
+  fields?: string[];
+  statistics?: AggregationRule[];
+  /** false 时每个部件输出为单独的要素，默认 true */
+  multipart?: boolean;
+  output: AnalysisOutput;
+}
+
+export interface MergeInput extends AnalysisInput {
+  /** 源字段名 -> 输出字段名，映射为空字符串时不输出该字段 */
+  field_map?: Record<string, string>;
+}
+
+/** 合并：多个图层合并为一个输出，同名字段合并，类型不一致时放宽 */
+export interface MergeRequest {
+  inputs: MergeInput[];
+  /** 记录来源图层名的字段 */
+  source_field?: string;
+  output: AnalysisOutput;
+}
+
+export type GeometryIssueKind =
+  | 'self_intersection'
+  | 'ring_orientation'
+  | 'duplicate_vertices'
+  | 'unclosed_ring'
+  | 'too_few_points'
+  | 'spike'
+  | 'empty_geometry'
+  | 'invalid';
+
+export interface GeometryIssue {
+  fid: number | null;
+  kind: GeometryIssueKind;
+  reason: string;
+  /** 问题位置（经纬度） */
+  location: [number, number] | null;
+}
+
+export interface ValidationRequest {
+  input: AnalysisInput;
+  /** 最多返回的问题数，默认 10000 */
+  max_issues?: number;
+}
+
+export interface ValidationReport {
+  features_checked: number;
+  invalid_features: number;
+  issues: GeometryIssue[];
+  /** 问题数超过 max_issues，未全部返回 */
+  truncated: boolean;
+}
+
+/** make_valid：保留全部顶点，自相交处拆分；buffer：零距离缓冲 */
+export type RepairMethod = 'make_valid' | 'buffer';
+
+export interface RepairRequest {
+  input: AnalysisInput;
+  method?: RepairMethod;
+  /** 去掉尖刺顶点，默认 true */
+  remove_spikes?: boolean;
+  /** 为空时原位修复，写回输入图层（支持事务的数据源在事务中提交） */
+  output?: AnalysisOutput;
+}
+
+export interface RepairResult {
+  features_checked: number;
+  repaired: number;
+  /** 修复失败、几何为空或原位修复单部件图层时拆分为多个部件、保持原样的要素 */
+  unrepaired: number[];
+  /** 原位修复时为空 */
+  output: ExportResult | null;
+}
+
+export type TopologyRuleKind =
+  | 'must_not_overlap'
+  | 'must_not_have_gaps'
+  | 'must_be_covered_by'
+  | 'must_not_have_dangles'
+  | 'must_not_self_intersect';
+
+export interface TopologyRule {
+  /** 规则ID，写入错误图层，默认 rule_序号 */
+  id?: string;
+  rule: TopologyRuleKind;
+  layer: AnalysisInput;
+  /** must_be_covered_by 的覆盖图层 */
+  cover_layer?: AnalysisInput;
+  /** 悬挂点判断的容差（错误图层坐标系单位） */
+  tolerance?: number;
+}
+
+/** 拓扑检查：错误图层包含 rule_id、rule、layer、fid、fid2、detail 字段，几何类型混合，需输出为 GeoPackage 等格式（不支持 Shapefile、FileGDB） */
+export interface TopologyRequest {
+  rules: TopologyRule[];
+  output: AnalysisOutput;
+}
+
+export interface TopologyRuleSummary {
+  id: string;
+  rule: TopologyRuleKind;
+  features_checked: number;
+  errors: number;
+}
+
+export type GeometryAttribute =
+  | 'area'
+  | 'perimeter'
+  | 'length'
+  | 'centroid_x'
+  | 'centroid_y'
+  | 'vertex_count'
+  | 'min_x'
+  | 'min_y'
+  | 'max_x'
+  | 'max_y';
+
+/** geodesic：椭球面量算，中心点和外包矩形为经纬度；planar：投影平面量算 */
+export type MeasureMethod = 'geodesic' | 'planar';
+
+export interface GeometryAttributeField {
+  attribute: GeometryAttribute;
+  /** 字段名，默认为属性名 */
+  name?: string | null;
+}
+
+export interface GeometryAttributesRequest {
+  input: AnalysisInput;
+  fields: GeometryAttributeField[];
+  method?: MeasureMethod;
+  /** 平面量算使用的投影坐标系，默认为图层坐标系 */
+  planar_crs?: string | null;
+  length_unit?: LengthUnit;
+  area_unit?: AreaUnit;
+  /** 字段已存在时覆盖其值 */
+  overwrite?: boolean;
+  /** 为空时直接写入输入图层 */
+  output?: AnalysisOutput | null;
+}
+
+/** nearest：输入要素附加最近的 k 个目标要素；matrix：起点-终点距离表（无几何） */
+export type NearMode = 'nearest' | 'matrix';
+
+export interface NearRequest {
+  /** 起点图层 */
+  input: AnalysisInput;
+  /** 目标图层 */
+  near: AnalysisInput;
+  mode?: NearMode;
+  /** 每个输入要素的邻近要素数，nearest 默认 1，matrix 为空时输出全部组合 */
+  k?: number | null;
+  /** 搜索距离（length_unit 单位），为空时不限距离 */
+  max_distance?: number | null;
+  length_unit?: LengthUnit;
+  /** 输出的起点标识字段（matrix） */
+  input_id_field?: string | null;
+  /** 输出的目标标识字段 */
+  near_id_field?: string | null;
+  output: AnalysisOutput;
+}
+
+/** hexagon 为尖顶六边形，cell_size 为相邻格网中心的距离 */
+export type GridShape = 'rectangle' | 'hexagon';
+
+export interface GridRequest {
+  /** 格网坐标系下的范围，为空时取 layer 的范围 */
+  extent?: Extent | null;
+  layer?: AnalysisInput | null;
+  /** 格网坐标系，默认为 layer 的坐标系，都为空时为 WGS84 */
+  crs?: string | null;
+  shape?: GridShape;
+  /** 格网宽度（格网坐标系单位） */
+  cell_size: number;
+  /** 矩形格网高度，默认与宽度相同 */
+  cell_height?: number | null;
+  /** 统计每个格网内的点数 */
+  count_points?: AnalysisInput | null;
+  /** 不输出没有点的格网 */
+  skip_empty?: boolean;
+  output: AnalysisOutput;
+}
+
+/** random：每个面内随机 count 个点；regular：按 spacing 间距的规则点阵 */
+export type SampleMethod = 'random' | 'regular';
+
+export interface SamplePointsRequest {
+  /** 面图层 */
+  input: AnalysisInput;
+  method?: SampleMethod;
+  count?: number | null;
+  /** 点间距（图层坐标系单位） */
+  spacing?: number | null;
+  /** 随机种子，相同种子生成相同的点 */
+  seed?: number | null;
+  output: AnalysisOutput;
+}
+
+/** 属性连接：按关键字段将表格（CSV/DBF/XLSX）字段连接到图层 */
+export interface AttributeJoin {
+  table_path: string;
+  /** 表格图层序号（如 XLSX 工作表），默认第一个 */
+  table_layer?: number;
+  table_key: string;
+  layer_key: string;
+  /** 数值与文本关键字按值比较，默认 true */
+  coerce_types?: boolean;
+  ignore_case?: boolean;
+  /** 连接的表格字段，为空时连接除关键字段外的全部字段 */
+  fields?: string[];
+  prefix?: string;
+}
+
+export interface AttributeJoinReport {
+  layer_features: number;
+  table_rows: number;
+  matched_features: number;
+  unmatched_features: number;
+  unmatched_layer_keys: string[];
+  unmatched_table_keys: string[];
+  duplicate_table_keys: string[];
+  fields: AttributeField[];
+}
+
+export interface AttributeJoinRequest {
+  input: AnalysisInput;
+  join: AttributeJoin;
+  /** 保留没有匹配的要素，默认 true */
+  keep_all?: boolean;
+  output: AnalysisOutput;
+}
+
+export interface SpatialJoinRequest {
+  /** 待连接图层，输出几何和属性来自该图层 */
+  target: AnalysisInput;
+  join: AnalysisInput;
+  predicate?: JoinPredicate;
+  /** 最近要素的搜索距离（连接图层坐标系单位） */
+  distance?: number;
+  cardinality?: JoinCardinality;
+  /** 为空时连接全部字段 */
+  fields?: AggregationRule[];
+  /** 保留没有匹配的待连接要素，默认 true */
+  keep_all?: boolean;
+  output: AnalysisOutput;
+}
+
+export interface TileSourceInfo {
+  id: string;
+  path: string;
+  layer_index: number;
+  layer_name: string;
+  geometry_type: string;
+  fields: string[];
+  /** MVT 瓦片 URL 模板（{z}/{x}/{y}） */
+  tile_url: string;
+  options: Required<TileSourceOptions>;
+}
+
+/**
+ * GDAL工作线程池状态
+ */
+export interface WorkerPoolStatus {
+  /** 最多同时执行的GDAL任务数 */
+  concurrency: number;
+  active: number;
+  queued: number;
+}
+
+/**
+ * 导出结果
+ */
+export interface ExportResult {
+  output_path: string;
+  driver: string;
+  layer_name: string;
+  features_written: number;
+  features_failed: number;
+}
+
+export interface AttributeJoinResult extends ExportResult {
+  report: AttributeJoinReport;
+}
+
+export interface SplitOutput extends ExportResult {
+  /** 掩膜字段值 */
+  name: string;
+}
+
+export interface SplitResult {
+  output_dir: string;
+  outputs: SplitOutput[];
+  /** 范围内没有要素的掩膜字段值（不生成文件） */
+  empty: string[];
+}
+
+export interface MergeResult extends ExportResult {
+  fields: TableFieldSchema[];
+  /** 各输入类型不一致、已放宽类型的字段 */
+  widened: string[];
+}
+
+export interface TopologyResult extends ExportResult {
+  rules: TopologyRuleSummary[];
+}
+
+export interface GeometryAttributesResult {
+  features_updated: number;
+  /** 写入的字段名（部分格式会截断字段名） */
+  fields: string[];
+  output: ExportResult | null;
+}
+
+export interface NearResult extends ExportResult {
+  /** 找到邻近要素的输入要素数 */
+  matched: number;
+  unmatched: number;
+  /** 目标图层为地理坐标系时按椭球面计算距离 */
+  geodesic: boolean;
+}
+
+export interface GridResult extends ExportResult {
+  rows: number;
+  cols: number;
+  /** 落在格网内的点数 */
+  points_counted: number;
+}
+
+export interface SamplePointsResult extends ExportResult {
+  /** 实际使用的随机种子，再次传入可生成相同的点 */
+  seed: number | null;
+}
+
+export interface SpatialJoinResult extends ExportResult {
+  matched: number;
+  unmatched: number;
+}
+
+/**
+ * 可写入的矢量驱动
+ */
+export interface ExportDriver {
+  name: string;
+  long_name: string;
+  extensions: string[];
+}
+
+/**
+ * 批量转换请求
+ */
+export interface BatchConvertRequest {
+  /** 文件夹或多图层数据源（GDB、GPKG、KML等） */
+  source: string;
+  /** 输出目录；container 为 true 时为输出文件 */
+  output: string;
+  format: string;
+  /** 所有图层写入同一个数据源（如一个 GPKG） */
+  container?: boolean;
+  /** 遍历文件夹时包含子文件夹 */
+  recursive?: boolean;
+  /** 只转换指定名称的图层 */
+  layers?: string[] | null;
+  options?: ExportOptions;
+}
+
+/**
+ * 批量转换中单个图层的结果
+ */
+export interface BatchItemResult {
+  source: string;
+  layer_index?: number | null;
+  layer_name?: string | null;
+  output_path: string;
+  output_layer?: string | null;
+  success: boolean;
+  features_written: number;
+  features_failed: number;
+  error?: string | null;
+}
+
+/**
+ * 批量转换汇总
+ */
+export interface BatchConvertReport {
+  output: string;
+  total: number;
+  succeeded: number;
+  failed: number;
+  features_written: number;
+  items: BatchItemResult[];
 }
 
 /**
@@ -53,13 +799,15 @@ class GDALService {
   async getAttributeTable(
     path: string,
     offset?: number,
-    limit?: number
+    limit?: number,
+    joins?: AttributeJoin[]
   ): Promise<Array<Record<string, any>>> {
     try {
       const result = await invoke<any>('gdal_get_attribute_table', {
         path,
         offset,
-        limit
+        limit,
+        joins
       });
       // 后端返回的是 { features: [...], total: ... }
       return result.features || [];
@@ -69,12 +817,320 @@ class GDALService {
     }
   }
 
+  /**
+   * 读取文件地理数据库元数据（要素集、属性域、子类型、关系类）
+   */
+  async getGdbMetadata(path: string): Promise<GdbMetadata> {
+    try {
+      return await invoke<GdbMetadata>('gdal_get_gdb_metadata', { path });
+    } catch (error) {
+      console.error('[GDAL] 读取GDB元数据失败:', error);
+      throw new Error(`无法读取GDB元数据: ${error}`);
+    }
+  }
+
+  /**
+   * 导出矢量数据
+   * @param format 导出格式（KML、KMZ、GEOJSON、SHAPEFILE、GPKG 或任意 GDAL 矢量驱动名，AUTO 按扩展名识别）
+   */
+  async exportVector(
+    inputPath: string,
+    outputPath: string,
+    format: string,
+    layerIndex?: number,
+    options?: ExportOptions,
+    jobId?: string
+  ): Promise<ExportResult> {
+    return invoke<ExportResult>('gdal_export_vector', { inputPath, outputPath, format, layerIndex, options, jobId });
+  }
+
+  /**
+   * 批量转换文件夹或多图层数据源
+   */
+  async batchConvert(request: BatchConvertRequest, jobId?: string): Promise<BatchConvertReport> {
+    return invoke<BatchConvertReport>('gdal_batch_convert', { request, jobId });
+  }
+
+  /**
+   * 监听批量转换进度（每完成一个图层触发一次）
+   */
+  onBatchProgress(
+    handler: (progress: { completed: number; total: number; item: BatchItemResult }) => void
+  ): Promise<UnlistenFn> {
+    return listen<{ completed: number; total: number; item: BatchItemResult }>('batch-progress', (event) =>
+      handler(event.payload)
+    );
+  }
+
+  /**
+   * 获取可用于导出的矢量驱动
+   */
+  async getExportDrivers(): Promise<ExportDriver[]> {
+    return invoke<ExportDriver[]>('gdal_get_export_drivers');
+  }
+
+  /**
+   * 监听任务进度（导出、批量转换、读取图层）
+   */
+  onJobProgress(handler: (progress: JobProgress) => void): Promise<UnlistenFn> {
+    return listen<JobProgress>('job-progress', (event) => handler(event.payload));
+  }
+
+  /**
+   * 取消任务，任务已结束时返回 false
+   */
+  async cancelJob(jobId: string): Promise<boolean> {
+    return invoke<boolean>('job_cancel', { jobId });
+  }
+
+  /**
+   * 列出正在运行的任务
+   */
+  async listJobs(): Promise<JobInfo[]> {
+    return invoke<JobInfo[]>('job_list');
+  }
+
+  /**
+   * 注册矢量瓦片源（大图层按需生成 MVT 瓦片，配合 createMvtSource 使用）
+   */
+  async registerTileSource(path: string, layerIndex?: number, options?: TileSourceOptions): Promise<TileSourceInfo> {
+    return invoke<TileSourceInfo>('tiles_register_source', { path, layerIndex, options });
+  }
+
+  /**
+   * 注销矢量瓦片源
+   */
+  async unregisterTileSource(sourceId: string): Promise<void> {
+    return invoke('tiles_unregister_source', { sourceId });
+  }
+
+  /**
+   * 清除瓦片缓存（数据编辑后调用），不指定瓦片源时清除全部
+   */
+  async clearTileCache(sourceId?: string): Promise<void> {
+    return invoke('tiles_clear_cache', { sourceId });
+  }
+
+  /**
+   * 构建图层空间索引（首次查询时也会自动构建，打开大图层后可提前调用）
+   */
+  async buildSpatialIndex(path: string, layerIndex?: number): Promise<SpatialIndexInfo> {
+    return invoke<SpatialIndexInfo>('spatial_index_build', { path, layerIndex });
+  }
+
+  /**
+   * 丢弃空间索引（图层移除或外部修改数据后调用），不指定图层时丢弃数据源的全部图层索引
+   */
+  async invalidateSpatialIndex(path: string, layerIndex?: number): Promise<void> {
+    return invoke('spatial_index_invalidate', { path, layerIndex });
+  }
+
+  /**
+   * 按空间范围（经纬度）查询要素 FID，exact 为 false 时只比较外包矩形
+   */
+  async queryExtent(
+    path: string,
+    extent: { min_x: number; min_y: number; max_x: number; max_y: number },
+    layerIndex?: number,
+    exact?: boolean,
+  ): Promise<number[]> {
+    return invoke<number[]>('spatial_query_extent', { path, layerIndex, extent, exact });
+  }
+
+  /**
+   * 要素识别：查询多个图层在地图坐标处（含像素容差）的要素及全部属性
+   */
+  async identify(request: IdentifyRequest): Promise<IdentifyLayerResult[]> {
+    return invoke<IdentifyLayerResult[]>('spatial_identify', { request });
+  }
+
+  /**
+   * 量算几何的长度、面积和周长（椭球面，可选投影平面）
+   */
+  async measure(request: MeasureRequest): Promise<MeasureResult> {
+    return invoke<MeasureResult>('spatial_measure', { request });
+  }
+
+  /**
+   * 按条件选择要素并更新图层选择集
+   */
+  async selectFeatures(
+    layerId: string,
+    path: string,
+    query: SelectionQuery,
+    mode: SelectionMode = 'new',
+    layerIndex?: number,
+  ): Promise<SelectionSummary> {
+    return invoke<SelectionSummary>('selection_select', { layerId, path, layerIndex, query, mode });
+  }
+
+  /**
+   * 用指定要素更新选择集（如地图点选、属性表勾选）
+   */
+  async setSelectionFids(
+    layerId: string,
+    path: string,
+    fids: number[],
+    mode: SelectionMode = 'new',
+    layerIndex?: number,
+  ): Promise<SelectionSummary> {
+    return invoke<SelectionSummary>('selection_set_fids', { layerId, path, layerIndex, fids, mode });
+  }
+
+  /**
+   * 获取图层选择集中的要素 FID
+   */
+  async getSelectionFids(layerId: string): Promise<number[]> {
+    return invoke<number[]>('selection_get_fids', { layerId });
+  }
+
+  /**
+   * 列出所有非空选择集
+   */
+  async listSelections(): Promise<SelectionSummary[]> {
+    return invoke<SelectionSummary[]>('selection_list');
+  }
+
+  /**
+   * 清除选择集，不指定图层时清除全部
+   */
+  async clearSelection(layerId?: string): Promise<void> {
+    return invoke('selection_clear', { layerId });
+  }
+
+  /**
+   * 统计字段值，指定 selection（图层ID）时只统计该图层选择集中的要素
+   */
+  async fieldStatistics(path: string, field: string, layerIndex?: number, selection?: string): Promise<FieldStatistics> {
+    return invoke<FieldStatistics>('gdal_field_statistics', { path, layerIndex, field, selection });
+  }
+
+  /**
+   * 空间连接，进度通过 job-progress 事件推送
+   */
+  async spatialJoin(request: SpatialJoinRequest, jobId?: string): Promise<SpatialJoinResult> {
+    return invoke<SpatialJoinResult>('analysis_spatial_join', { request, jobId });
+  }
+
+  /**
+   * 裁剪：保留掩膜面内的部分，属性不变
+   */
+  async clip(request: OverlayRequest, jobId?: string): Promise<ExportResult> {
+    return invoke<ExportResult>('analysis_clip', { request, jobId });
+  }
+
+  /**
+   * 擦除：去掉掩膜面内的部分，属性不变
+   */
+  async erase(request: OverlayRequest, jobId?: string): Promise<ExportResult> {
+    return invoke<ExportResult>('analysis_erase', { request, jobId });
+  }
+
+  /**
+   * 按掩膜面分割图层，每个掩膜面输出一个文件
+   */
+  async split(request: SplitRequest, jobId?: string): Promise<SplitResult> {
+    return invoke<SplitResult>('analysis_split', { request, jobId });
+  }
+
+  /**
+   * 融合：按融合字段合并要素并汇总其他字段
+   */
+  async dissolve(request: DissolveRequest, jobId?: string): Promise<ExportResult> {
+    return invoke<ExportResult>('analysis_dissolve', { request, jobId });
+  }
+
+  /**
+   * 合并多个图层为一个输出
+   */
+  async merge(request: MergeRequest, jobId?: string): Promise<MergeResult> {
+    return invoke<MergeResult>('analysis_merge', { request, jobId });
+  }
+
+  /**
+   * 检查图层几何，逐要素报告问题和位置
+   */
+  async validateGeometries(request: ValidationRequest, jobId?: string): Promise<ValidationReport> {
+    return invoke<ValidationReport>('analysis_validate_geometries', { request, jobId });
+  }
+
+  /**
+   * 修复图层几何：指定输出时写入新图层，否则原位写回输入图层
+   */
+  async repairGeometries(request: RepairRequest, jobId?: string): Promise<RepairResult> {
+    return invoke<RepairResult>('analysis_repair_geometries', { request, jobId });
+  }
+
+  /**
+   * 拓扑检查，错误几何写入错误图层
+   */
+  async checkTopology(request: TopologyRequest, jobId?: string): Promise<TopologyResult> {
+    return invoke<TopologyResult>('analysis_check_topology', { request, jobId });
+  }
+
+  /**
+   * 计算几何属性（面积、周长、长度、中心点、顶点数、外包矩形）并写入字段
+   */
+  async addGeometryAttributes(request: GeometryAttributesRequest, jobId?: string): Promise<GeometryAttributesResult> {
+    return invoke<GeometryAttributesResult>('analysis_add_geometry_attributes', { request, jobId });
+  }
+
+  /**
+   * 邻近分析：查找最近的 k 个目标要素，或输出起点-终点距离表
+   */
+  async near(request: NearRequest, jobId?: string): Promise<NearResult> {
+    return invoke<NearResult>('analysis_near', { request, jobId });
+  }
+
+  /**
+   * 生成矩形或六边形格网，可统计每个格网内的点数
+   */
+  async createGrid(request: GridRequest, jobId?: string): Promise<GridResult> {
+    return invoke<GridResult>('analysis_create_grid', { request, jobId });
+  }
+
+  /**
+   * 在面要素内生成随机或规则采样点
+   */
+  async samplePoints(request: SamplePointsRequest, jobId?: string): Promise<SamplePointsResult> {
+    return invoke<SamplePointsResult>('analysis_sample_points', { request, jobId });
+  }
+
+  /**
+   * 检查属性连接的匹配情况（添加虚拟连接前预览）
+   */
+  async checkAttributeJoin(path: string, join: AttributeJoin, layerIndex?: number): Promise<AttributeJoinReport> {
+    return invoke<AttributeJoinReport>('analysis_attribute_join_check', { path, layerIndex, join });
+  }
+
+  /**
+   * 属性连接并导出为新图层，进度通过 job-progress 事件推送
+   */
+  async attributeJoin(request: AttributeJoinRequest, jobId?: string): Promise<AttributeJoinResult> {
+    return invoke<AttributeJoinResult>('analysis_attribute_join', { request, jobId });
+  }
+
+  /**
+   * 获取GDAL工作线程池状态
+   */
+  async getWorkerStatus(): Promise<WorkerPoolStatus> {
+    return invoke<WorkerPoolStatus>('worker_get_status');
+  }
+
+  /**
+   * 设置GDAL工作线程池并发数（1-32），同时加载多个图层时降低并发可保持界面流畅
+   */
+  async setWorkerConcurrency(concurrency: number): Promise<WorkerPoolStatus> {
+    return invoke<WorkerPoolStatus>('worker_set_concurrency', { concurrency });
+  }
+
   /**
    * 获取 GeoJSON
+   * @param dimension Z值/M值处理方式，默认保留
    */
-  async getGeoJSON(path: string): Promise<any> {
+  async getGeoJSON(path: string, dimension?: DimensionMode): Promise<any> {
     try {
-      const geojson = await invoke('gdal_get_geojson', { path });
+      const geojson = await invoke('gdal_get_geojson', { path, dimension });
       return geojson;
     } catch (error) {
       console.error('[GDAL] 读取 GeoJSON 失败:', error);
@@ -82,6 +1138,41 @@ class GDALService {
     }
   }
 
+  /**
+   * 分批读取图层GeoJSON要素（大图层使用，避免一次性传输整个 FeatureCollection）
+   * onBatch 每收到一批要素调用一次，可边接收边渲染；全部接收完成后 Promise 才返回
+   */
+  async streamLayerGeoJSON(
+    path: string,
+    onBatch: (features: any[], read: number, total: number) => void,
+    options: GeoJsonStreamOptions = {}
+  ): Promise<{ features: number; batches: number }> {
+    let finished: (() => void) | undefined;
+    const done = new Promise<void>(resolve => (finished = resolve));
+    const onEvent = new Channel<GeoJsonStreamEvent>();
+    onEvent.onmessage = (message) => {
+      if (message.event === 'batch') {
+        onBatch(message.features, message.read, message.total);
+      } else if (message.event === 'finished') {
+        finished?.();
+      }
+    };
+
+    const summary = await invoke<{ features: number; batches: number }>('gdal_stream_layer_geojson', {
+      path,
+      layerIndex: options.layerIndex,
+      dimension: options.dimension,
+      decodeDomains: options.decodeDomains,
+      joins: options.joins,
+      batchSize: options.batchSize,
+      onEvent,
+      jobId: options.jobId,
+    });
+    // 通道消息可能晚于命令返回，等待完成消息确保所有批次已交付
+    await done;
+    return summary;
+  }
+
   /**
    * 获取投影后的GeoJSON
    * @param path 文件路径
@@ -106,15 +1197,15 @@ class GDALService {
    * 坐标转换
    * @param fromSrs 源坐标系 (EPSG代码、WKT或PROJ.4)
    * @param toSrs 目标坐标系 (EPSG代码、WKT或PROJ.4)
-   * @param coordinates 坐标数组 [[x1, y1], [x2, y2], ...]
+   * @param coordinates 坐标数组 [[x1, y1], [x2, y2, z2], [x3, y3, z3, m3], ...]，Z值参与转换，M值原样保留
    */
-  async transformCoordinates(
+  async transformCoordinates<T extends number[]>(
     fromSrs: string,
     toSrs: string,
-    coordinates: Array<[number, number]>
-  ): Promise<Array<[number, number]>> {
+    coordinates: Array<T>
+  ): Promise<Array<T>> {
     try {
-      const transformed = await invoke<Array<[number, number]>>(
+      const transformed = await invoke<Array<T>>(
         'gdal_transform_coordinates',
         { fromSrs, toSrs, coordinates }
       );
@@ -0,0 +1,122 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { CoordinateDimension } from './gdalService';
+
+/**
+ * PostGIS 连接配置
+ * 密码仅在 save_password 为 true 时保存到系统钥匙串（配置文件不含密码），列表接口不返回密码
+ */
+export interface PostgisConnection {
+  id: string;
+  name: string;
+  host: string;
+  port: number;
+  database: string;
+  username: string;
+  password?: string | null;
+  save_password: boolean;
+  ssl_mode?: string | null;
+}
+
+/**
+ * PostGIS 空间表/视图
+ * path 为图层引用（postgis:连接ID/模式.表名(几何列)），可直接传给 gdal_* 读取命令
+ */
+export interface PostgisTableInfo {
+  schema: string;
+  table: string;
+  geometry_column: string;
+  geometry_type: string;
+  coordinate_dimension: CoordinateDimension;
+  srid: number;
+  is_view: boolean;
+  path: string;
+}
+
+/**
+ * 要素编辑（几何为 WGS84 GeoJSON）
+ */
+export interface FeatureEdits {
+  inserts?: any[];
+  updates?: any[];
+  deletes?: number[];
+}
+
+export interface EditResult {
+  inserted: number;
+  updated: number;
+  deleted: number;
+}
+
+/**
+ * PostGIS 服务类
+ */
+class PostgisService {
+  async listConnections(): Promise<PostgisConnection[]> {
+    return invoke<PostgisConnection[]>('postgis_list_connections');
+  }
+
+  async saveConnection(connection: PostgisConnection): Promise<PostgisConnection> {
+    return invoke<PostgisConnection>('postgis_save_connection', { connection });
+  }
+
+  async deleteConnection(connectionId: string): Promise<void> {
+    return invoke('postgis_delete_connection', { connectionId });
+  }
+
+  /**
+   * 测试连接，返回数据库版本
+   */
+  async testConnection(connection: PostgisConnection): Promise<string> {
+    try {
+      return await invoke<string>('postgis_test_connection', { connection });
+    } catch (error) {
+      console.error('[PostGIS] 连接测试失败:', error);
+      throw new Error(`连接失败: ${error}`);
+    }
+  }
+
+  async listSchemas(connectionId: string): Promise<string[]> {
+    return invoke<string[]>('postgis_list_schemas', { connectionId });
+  }
+
+  async listTables(connectionId: string, schema?: string): Promise<PostgisTableInfo[]> {
+    return invoke<PostgisTableInfo[]>('postgis_list_tables', { connectionId, schema });
+  }
+
+  /**
+   * 将编辑写回表（在事务中提交）
+   */
+  async applyEdits(
+    connectionId: string,
+    schema: string,
+    table: string,
+    edits: FeatureEdits
+  ): Promise<EditResult> {
+    return invoke<EditResult>('postgis_apply_edits', { connectionId, schema, table, edits });
+  }
+
+  /**
+   * 导出矢量图层到 PostGIS，返回新表的图层引用
+   */
+  async exportLayer(
+    inputPath: string,
+    connectionId: string,
+    schema: string,
+    table: string,
+    options?: { layerIndex?: number; overwrite?: boolean }
+  ): Promise<string> {
+    return invoke<string>('postgis_export_layer', {
+      inputPath,
+      layerIndex: options?.layerIndex,
+      connectionId,
+      schema,
+      table,
+      overwrite: options?.overwrite
+    });
+  }
+}
+
+// 导出单例
+export const postgisService = new PostgisService();
+
+export default PostgisService;
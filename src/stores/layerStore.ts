@@ -5,6 +5,7 @@ import { saveProjectState } from '../services/storageService';
 import { useMapStore } from './mapStore';
 import { useProjectStore } from './projectStore';
 import type { CRSInfo } from './crsStore';
+import type { AttributeJoin } from '../services/gdalService';
 import type { UiStore } from './uiStore';
 
 export interface LabelConfig {
@@ -52,6 +53,7 @@ export interface Layer {
   children?: Layer[]; // 子图层(用于分组显示)
   expanded?: boolean; // 分组是否展开
   deferredLoad?: boolean; // 延迟加载标记（用于会话恢复优化）
+  joins?: AttributeJoin[]; // 虚拟属性连接（读取图层时附加表格字段）
 }
 
 interface LayerStore {
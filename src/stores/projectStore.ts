@@ -1,5 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
+import type { SelectionSet } from '../services/gdalService';
 
 interface Project {
   id: string;
@@ -14,6 +15,8 @@ interface Project {
   };
   createdAt: string;
   updatedAt: string;
+  /** 各图层的选择集（保存项目时由后端写入） */
+  selections?: SelectionSet[];
 }
 
 interface ProjectStore {
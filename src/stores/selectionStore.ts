@@ -1,4 +1,5 @@
 import { create } from 'zustand';
+import type { SelectionSummary } from '../services/gdalService';
 
 // 识别到的要素信息
 export interface InspectedFeatureInfo {
@@ -16,6 +17,7 @@ interface SelectionStore {
   inspectedFeatures: InspectedFeatureInfo[]; // 浏览模式下识别到的所有要素
   currentInspectedIndex: number; // 当前选中的识别要素索引
   selectedFeatureId: string | null; // 当前选中的要素ID
+  selectionSets: Record<string, SelectionSummary>; // 后端选择集概要（按图层ID）
   setSelectedFeatures: (features: any[]) => void;
   addSelectedFeature: (feature: any) => void;
   clearSelection: () => void;
@@ -25,6 +27,8 @@ interface SelectionStore {
   setInspectedFeatures: (features: InspectedFeatureInfo[]) => void;
   setCurrentInspectedIndex: (index: number) => void;
   setSelectedFeatureId: (id: string | null) => void;
+  setSelectionSummary: (summary: SelectionSummary) => void;
+  removeSelectionSet: (layerId?: string) => void;
 }
 
 export const useSelectionStore = create<SelectionStore>((set) => ({
@@ -35,6 +39,7 @@ export const useSelectionStore = create<SelectionStore>((set) => ({
   inspectedFeatures: [],
   currentInspectedIndex: 0,
   selectedFeatureId: null,
+  selectionSets: {},
 
   setSelectedFeatures: (features) => set({ selectedFeatures: features }),
   
@@ -63,5 +68,18 @@ export const useSelectionStore = create<SelectionStore>((set) => ({
     inspectedFeature: state.inspectedFeatures[index]?.feature || null
   })),
   
-  setSelectedFeatureId: (id) => set({ selectedFeatureId: id })
+  setSelectedFeatureId: (id) => set({ selectedFeatureId: id }),
+
+  setSelectionSummary: (summary) => set((state) => {
+    const { [summary.layer_id]: _, ...rest } = state.selectionSets;
+    return {
+      selectionSets: summary.count > 0 ? { ...rest, [summary.layer_id]: summary } : rest
+    };
+  }),
+
+  removeSelectionSet: (layerId) => set((state) => {
+    if (!layerId) return { selectionSets: {} };
+    const { [layerId]: _, ...rest } = state.selectionSets;
+    return { selectionSets: rest };
+  })
 }));
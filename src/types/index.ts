@@ -1,3 +1,5 @@
+import type { AttributeJoin } from '../services/gdalService';
+
 export interface Project {
   id: string;
   name: string;
@@ -18,6 +20,7 @@ export interface Layer {
   style?: LayerStyle;
   extent?: Extent;
   attributes?: AttributeField[];
+  joins?: AttributeJoin[]; // 虚拟属性连接（读取图层时附加表格字段）
   groupId?: string; // 所属分组ID
   isGroup?: boolean; // 是否为分组图层
   children?: Layer[]; // 子图层(用于分组显示)
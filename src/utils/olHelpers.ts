@@ -1,5 +1,9 @@
 import type { Coordinate } from 'ol/coordinate';
 import type { Extent } from 'ol/extent';
+import VectorTileSource from 'ol/source/VectorTile';
+import MVT from 'ol/format/MVT';
+import TileGrid from 'ol/tilegrid/TileGrid';
+import type { TileSourceInfo } from '../services/gdalService';
 
 /**
  * OpenLayers辅助工具函数
@@ -67,3 +71,33 @@ export const createXYZUrl = (template: string): string => {
   }
   return template;
 };
+
+/**
+ * 根据已注册的瓦片源创建 OpenLayers MVT 数据源
+ * 经纬度瓦片（grid = geographic）使用 0 级为东西两个瓦片的切片方案，projection 为地图坐标系（如 EPSG:4490）
+ */
+export const createMvtSource = (info: TileSourceInfo, projection: string): VectorTileSource => {
+  const format = new MVT();
+  if (info.options.grid !== 'geographic') {
+    return new VectorTileSource({
+      format,
+      url: info.tile_url,
+      minZoom: info.options.min_zoom,
+      maxZoom: info.options.max_zoom,
+    });
+  }
+
+  const resolutions = Array.from({ length: info.options.max_zoom + 1 }, (_, z) => 180 / 256 / 2 ** z);
+  return new VectorTileSource({
+    format,
+    url: info.tile_url,
+    projection,
+    tileGrid: new TileGrid({
+      extent: [-180, -90, 180, 90],
+      origin: [-180, 90],
+      resolutions,
+      minZoom: info.options.min_zoom,
+      tileSize: 256,
+    }),
+  });
+};